@@ -0,0 +1,544 @@
+//! Configuration for the components
+//!
+//! # Examples
+//! ```
+//! use onlyati_datastore::config::Builder;
+//!
+//! let config = Builder::new()
+//!     .max_hooks_per_prefix(10)
+//!     .build();
+//!
+//! assert_eq!(Some(10), config.max_hooks_per_prefix());
+//! ```
+
+use std::{
+    path::PathBuf,
+    sync::{mpsc::Sender, Arc},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use crate::{
+    datastore::{
+        enums::{DatabaseAction, KeyValidation},
+        types::Loader,
+        utilities::start_datastore_with_config,
+    },
+    hook::{enums::HookManagerAction, utilities::start_hook_manager_with_config, RetryPolicy},
+    logger::{
+        enums::{FlushPolicy, LogFilter, LogFormat, LogRotation, LoggerAction},
+        utilities::start_logger_with_config,
+        LoggerManager,
+        DEFAULT_TIMESTAMP_FORMAT,
+    },
+};
+
+/// Configuration of the datastore components, it can be created by `Builder`
+#[derive(Clone, Default)]
+pub struct Config {
+    /// Maximum number of links that can be registered under a single hook prefix
+    max_hooks_per_prefix: Option<usize>,
+
+    /// Count the reads per key, so hot keys can be found
+    access_counters: bool,
+
+    /// Retry policy of failed hook deliveries
+    hook_retry_policy: RetryPolicy,
+
+    /// Loader that is called when a key is missing
+    loader: Option<Loader>,
+
+    /// Match hook prefixes without case sensitivity
+    hook_case_folding: bool,
+
+    /// Maximum time that a hook request can take
+    hook_timeout: Option<Duration>,
+
+    /// Maximum number of hook deliveries that can be in progress
+    max_pending_hooks: Option<usize>,
+
+    /// Alert the hook manager on every write
+    auto_hooks: Option<bool>,
+
+    /// Maximum number of log items that are sent to logger together
+    log_batch_size: usize,
+
+    /// Maximum time while log items are collected before they are sent to logger
+    log_batch_window: Duration,
+
+    /// Maximum number of entries that a list can return
+    max_list_results: Option<usize>,
+
+    /// Maximum size of a record or a queue in bytes
+    max_value_bytes: Option<usize>,
+
+    /// Time between two scans of expired records
+    ttl_sweep_interval: Option<Duration>,
+
+    /// File where the database is saved
+    persist_path: Option<PathBuf>,
+
+    /// File where the hook definitions are saved
+    hook_persist_path: Option<PathBuf>,
+
+    /// Shared secret of hook request signatures
+    hook_signing_secret: Option<String>,
+
+    /// How the log file is rotated
+    log_rotation: LogRotation,
+
+    /// Format of the log lines
+    log_format: LogFormat,
+
+    /// Log items that are written
+    log_filter: LogFilter,
+
+    /// When the log lines are flushed into the file
+    log_flush_policy: FlushPolicy,
+
+    /// Compress the rotated log files
+    log_compression: bool,
+
+    /// Format of the timestamps in log lines
+    log_timestamp_format: Option<String>,
+
+    /// How the keys of new records, queues and tables are checked
+    key_validation: KeyValidation,
+
+    /// Keys are converted to lowercase before routing
+    case_insensitive_keys: bool,
+
+    /// Start hook manager by `Config::start`
+    hook_manager: bool,
+
+    /// Log file, logger is started by `Config::start` if it is set
+    logger_path: Option<String>,
+}
+
+/// Threads that are started by `Config::start`
+pub struct Handles {
+    /// Thread of datastore
+    pub datastore: JoinHandle<()>,
+
+    /// Sender and thread of hook manager, if it is enabled by `Builder::hook_manager`.
+    /// Hook manager stops when all of its senders are dropped, including this one.
+    pub hook_manager: Option<(Sender<HookManagerAction>, JoinHandle<()>)>,
+
+    /// Sender and thread of logger, if `Builder::logger_path` is set
+    pub logger: Option<(Sender<LoggerAction>, JoinHandle<()>)>,
+}
+
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f
+            .debug_struct("Config")
+            .field("max_hooks_per_prefix", &self.max_hooks_per_prefix)
+            .field("access_counters", &self.access_counters)
+            .field("hook_retry_policy", &self.hook_retry_policy)
+            .field("loader", &self.loader.is_some())
+            .field("hook_case_folding", &self.hook_case_folding)
+            .field("hook_timeout", &self.hook_timeout)
+            .field("max_pending_hooks", &self.max_pending_hooks)
+            .field("auto_hooks", &self.auto_hooks())
+            .field("log_batch_size", &self.log_batch_size)
+            .field("log_batch_window", &self.log_batch_window)
+            .field("max_list_results", &self.max_list_results)
+            .field("max_value_bytes", &self.max_value_bytes)
+            .field("ttl_sweep_interval", &self.ttl_sweep_interval())
+            .field("persist_path", &self.persist_path)
+            .field("hook_persist_path", &self.hook_persist_path)
+            .field("hook_signing_secret", &self.hook_signing_secret.is_some())
+            .field("log_rotation", &self.log_rotation)
+            .field("log_format", &self.log_format)
+            .field("log_filter", &self.log_filter)
+            .field("log_flush_policy", &self.log_flush_policy)
+            .field("log_compression", &self.log_compression)
+            .field("log_timestamp_format", &self.log_timestamp_format())
+            .field("key_validation", &self.key_validation)
+            .field("case_insensitive_keys", &self.case_insensitive_keys)
+            .field("hook_manager", &self.hook_manager)
+            .field("logger_path", &self.logger_path)
+            .finish();
+    }
+}
+
+impl Config {
+    /// Maximum number of links that can be registered under a single hook prefix, `None` means unlimited
+    pub fn max_hooks_per_prefix(&self) -> Option<usize> {
+        return self.max_hooks_per_prefix;
+    }
+
+    /// Tells that reads are counted per key
+    pub fn access_counters(&self) -> bool {
+        return self.access_counters;
+    }
+
+    /// Retry policy of failed hook deliveries
+    pub fn hook_retry_policy(&self) -> &RetryPolicy {
+        return &self.hook_retry_policy;
+    }
+
+    /// Read-through loader of missing keys
+    pub fn loader(&self) -> Option<&Loader> {
+        return self.loader.as_ref();
+    }
+
+    /// Tells that hook prefixes are matched without case sensitivity
+    pub fn hook_case_folding(&self) -> bool {
+        return self.hook_case_folding;
+    }
+
+    /// Maximum time that a hook request can take, `None` means no limit
+    pub fn hook_timeout(&self) -> Option<Duration> {
+        return self.hook_timeout;
+    }
+
+    /// Maximum number of hook deliveries that can be in progress, `None` means unlimited
+    pub fn max_pending_hooks(&self) -> Option<usize> {
+        return self.max_pending_hooks;
+    }
+
+    /// Tells that writes alert the hook manager automatically, default is true
+    pub fn auto_hooks(&self) -> bool {
+        return self.auto_hooks.unwrap_or(true);
+    }
+
+    /// Maximum number of log items that are sent to logger together, less than 2 means immediate send
+    pub fn log_batch_size(&self) -> usize {
+        return self.log_batch_size;
+    }
+
+    /// Maximum time while log items are collected, zero means immediate send
+    pub fn log_batch_window(&self) -> Duration {
+        return self.log_batch_window;
+    }
+
+    /// Maximum number of entries that a list or a JSON export can return, `None` means unlimited
+    pub fn max_list_results(&self) -> Option<usize> {
+        return self.max_list_results;
+    }
+
+    /// Maximum size of a record or a queue in bytes, `None` means unlimited
+    pub fn max_value_bytes(&self) -> Option<usize> {
+        return self.max_value_bytes;
+    }
+
+    /// Time between two scans of expired records, default is 1 second
+    pub fn ttl_sweep_interval(&self) -> Duration {
+        return self.ttl_sweep_interval.unwrap_or(Duration::from_secs(1));
+    }
+
+    /// File where the database is saved
+    pub fn persist_path(&self) -> Option<&PathBuf> {
+        return self.persist_path.as_ref();
+    }
+
+    /// File where the hook definitions are saved
+    pub fn hook_persist_path(&self) -> Option<&PathBuf> {
+        return self.hook_persist_path.as_ref();
+    }
+
+    /// Shared secret of hook request signatures, `None` means requests are not signed
+    pub fn hook_signing_secret(&self) -> Option<&String> {
+        return self.hook_signing_secret.as_ref();
+    }
+
+    /// How the log file is rotated, default is no rotation
+    pub fn log_rotation(&self) -> LogRotation {
+        return self.log_rotation;
+    }
+
+    /// Format of the log lines, default is text
+    pub fn log_format(&self) -> LogFormat {
+        return self.log_format;
+    }
+
+    /// Log items that are written, default is every item
+    pub fn log_filter(&self) -> &LogFilter {
+        return &self.log_filter;
+    }
+
+    /// When the log lines are flushed into the file, default is at stop
+    pub fn log_flush_policy(&self) -> FlushPolicy {
+        return self.log_flush_policy;
+    }
+
+    /// Tells that rotated log files are compressed
+    pub fn log_compression(&self) -> bool {
+        return self.log_compression;
+    }
+
+    /// Format of the timestamps in log lines, default is RFC3339
+    pub fn log_timestamp_format(&self) -> &str {
+        return self.log_timestamp_format.as_deref().unwrap_or(DEFAULT_TIMESTAMP_FORMAT);
+    }
+
+    /// How the keys of new records, queues and tables are checked
+    pub fn key_validation(&self) -> KeyValidation {
+        return self.key_validation;
+    }
+
+    /// Keys are converted to lowercase before routing
+    pub fn case_insensitive_keys(&self) -> bool {
+        return self.case_insensitive_keys;
+    }
+
+    /// Start hook manager by `Config::start`
+    pub fn hook_manager(&self) -> bool {
+        return self.hook_manager;
+    }
+
+    /// Log file that the logger of `Config::start` writes
+    pub fn logger_path(&self) -> Option<&String> {
+        return self.logger_path.as_ref();
+    }
+
+    /// Start the datastore with the enabled hook manager and logger, they are wired into the datastore
+    ///
+    /// # Arguments
+    /// 1. `name` - Name of root table
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::config::Builder;
+    /// use onlyati_datastore::datastore::client::DatastoreClient;
+    ///
+    /// let config = Builder::new()
+    ///     .hook_manager(true)
+    ///     .logger_path("/tmp/datastore-config-start.txt".to_string())
+    ///     .build();
+    /// let (sender, handles) = config.start("root".to_string());
+    /// assert_eq!(true, handles.hook_manager.is_some());
+    /// assert_eq!(true, handles.logger.is_some());
+    ///
+    /// let client = DatastoreClient::new(sender);
+    /// client.set("/root/status".to_string(), "ok".to_string()).expect("Failed to set");
+    /// ```
+    pub fn start(&self, name: String) -> (Sender<DatabaseAction>, Handles) {
+        let hook_manager = match self.hook_manager {
+            true => Some(start_hook_manager_with_config(self)),
+            false => None,
+        };
+        let logger = self.logger_path.as_ref().map(|path| start_logger_with_config(path, self));
+
+        let (sender, datastore) = start_datastore_with_config(
+            name,
+            hook_manager.as_ref().map(|(sender, _)| sender.clone()),
+            logger.as_ref().map(|(sender, _)| sender.clone()),
+            self,
+        );
+
+        return (
+            sender,
+            Handles {
+                datastore,
+                hook_manager,
+                logger,
+            },
+        );
+    }
+}
+
+/// Builder for `Config`
+#[derive(Default)]
+pub struct Builder {
+    config: Config,
+}
+
+impl Builder {
+    /// Start a new builder with default values
+    pub fn new() -> Self {
+        return Builder {
+            config: Config::default(),
+        };
+    }
+
+    /// Set the maximum number of links that can be registered under a single hook prefix
+    pub fn max_hooks_per_prefix(mut self, limit: usize) -> Self {
+        self.config.max_hooks_per_prefix = Some(limit);
+        return self;
+    }
+
+    /// Enable or disable counting of reads per key
+    pub fn access_counters(mut self, enabled: bool) -> Self {
+        self.config.access_counters = enabled;
+        return self;
+    }
+
+    /// Set the retry policy of failed hook deliveries
+    pub fn hook_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.config.hook_retry_policy = policy;
+        return self;
+    }
+
+    /// Set a read-through loader that is called when a key is missing, see `Database::set_loader`
+    pub fn read_through_loader<F>(mut self, loader: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.config.loader = Some(Arc::new(loader));
+        return self;
+    }
+
+    /// Enable or disable case insensitive matching of hook prefixes
+    pub fn hook_case_folding(mut self, enabled: bool) -> Self {
+        self.config.hook_case_folding = enabled;
+        return self;
+    }
+
+    /// Set the maximum time that a hook request can take, timed out requests are counted as failed
+    pub fn hook_timeout(mut self, timeout: Duration) -> Self {
+        self.config.hook_timeout = Some(timeout);
+        return self;
+    }
+
+    /// Set the maximum number of hook deliveries that can be in progress, further ones are dropped,
+    /// so a slow endpoint cannot pile up the requests in memory
+    pub fn max_pending_hooks(mut self, limit: usize) -> Self {
+        self.config.max_pending_hooks = Some(limit);
+        return self;
+    }
+
+    /// Enable or disable that writes (e.g. `Set`, `Push`) alert the hook manager automatically.
+    /// If it is disabled, hooks are executed only by `DatabaseAction::Trigger`.
+    pub fn auto_hooks(mut self, enabled: bool) -> Self {
+        self.config.auto_hooks = Some(enabled);
+        return self;
+    }
+
+    /// Collect log items in the datastore thread and send them to the logger together,
+    /// when `size` items are collected or `window` time elapsed since the first one.
+    /// By default every item is sent immediately.
+    pub fn log_batch(mut self, size: usize, window: Duration) -> Self {
+        self.config.log_batch_size = size;
+        self.config.log_batch_window = window;
+        return self;
+    }
+
+    /// Set the maximum number of entries that a list or a JSON export can return
+    pub fn max_list_results(mut self, limit: usize) -> Self {
+        self.config.max_list_results = Some(limit);
+        return self;
+    }
+
+    /// Set the maximum size of a record or a queue in bytes, see `Database::set_max_value_bytes`
+    pub fn max_value_bytes(mut self, max: usize) -> Self {
+        self.config.max_value_bytes = Some(max);
+        return self;
+    }
+
+    /// Set the time between two scans of expired records, see `Database::insert_with_ttl`
+    pub fn ttl_sweep_interval(mut self, interval: Duration) -> Self {
+        self.config.ttl_sweep_interval = Some(interval);
+        return self;
+    }
+
+    /// Set the file where the database is saved by `DatabaseAction::Flush`,
+    /// if it exists then it is loaded when the datastore is started. If it cannot be loaded,
+    /// the error is logged and the datastore starts empty.
+    pub fn persist_path(mut self, path: PathBuf) -> Self {
+        self.config.persist_path = Some(path);
+        return self;
+    }
+
+    /// Set the file where the hook definitions are saved by `HookManagerAction::Persist`,
+    /// if it exists then it is loaded when the hook manager is started. If it cannot be loaded,
+    /// the error is logged and the hook manager starts without hooks.
+    pub fn hook_persist_path(mut self, path: PathBuf) -> Self {
+        self.config.hook_persist_path = Some(path);
+        return self;
+    }
+
+    /// Set the shared secret that hook requests are signed with, see `HookManager::set_signing_secret`
+    pub fn hook_signing_secret(mut self, secret: String) -> Self {
+        self.config.hook_signing_secret = Some(secret);
+        return self;
+    }
+
+    /// Set the maximum size of log file in bytes, it is the same like `log_rotation(LogRotation::Size(size))`
+    pub fn log_max_file_size(mut self, size: u64) -> Self {
+        self.config.log_rotation = LogRotation::Size(size);
+        return self;
+    }
+
+    /// Set how the log file is rotated, see `LogRotation`
+    pub fn log_rotation(mut self, rotation: LogRotation) -> Self {
+        self.config.log_rotation = rotation;
+        return self;
+    }
+
+    /// Set the format of the log lines, see `LogFormat`
+    pub fn log_format(mut self, format: LogFormat) -> Self {
+        self.config.log_format = format;
+        return self;
+    }
+
+    /// Set which log items are written, see `LogFilter`. Datastore does not send the dropped items to the logger.
+    pub fn log_filter(mut self, filter: LogFilter) -> Self {
+        self.config.log_filter = filter;
+        return self;
+    }
+
+    /// Set when the log lines are flushed into the file, see `FlushPolicy`
+    pub fn log_flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.config.log_flush_policy = policy;
+        return self;
+    }
+
+    /// Enable or disable gzip compression of rotated log files, see `LoggerManager::set_compression`
+    pub fn log_compression(mut self, enabled: bool) -> Self {
+        self.config.log_compression = enabled;
+        return self;
+    }
+
+    /// Set the format of the timestamps in log lines, see `LoggerManager::set_timestamp_format`.
+    /// Invalid formats and formats whose timestamps contain space or delimiter are rejected, the former one is kept.
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::config::Builder;
+    ///
+    /// let config = Builder::new().log_timestamp_format("%Y%m%d-%H%M%S".to_string()).build();
+    /// assert_eq!("%Y%m%d-%H%M%S", config.log_timestamp_format());
+    ///
+    /// let config = Builder::new().log_timestamp_format("%Y-%m-%d %H:%M:%S".to_string()).build();
+    /// assert_eq!("%+", config.log_timestamp_format());
+    /// ```
+    pub fn log_timestamp_format(mut self, format: String) -> Self {
+        match LoggerManager::check_timestamp_format(&format) {
+            Ok(_) => self.config.log_timestamp_format = Some(format),
+            Err(e) => tracing::error!("log timestamp format is rejected: {}", e),
+        }
+        return self;
+    }
+
+    /// Set how the keys of new records, queues and tables are checked, see `KeyValidation`
+    pub fn key_validation(mut self, validation: KeyValidation) -> Self {
+        self.config.key_validation = validation;
+        return self;
+    }
+
+    /// Enable or disable case-insensitive keys, see `Database::set_case_insensitive_keys`.
+    /// It is recommended to enable it only for a new dataset, because existing keys are not converted.
+    pub fn case_insensitive_keys(mut self, enabled: bool) -> Self {
+        self.config.case_insensitive_keys = enabled;
+        return self;
+    }
+
+    /// Enable or disable the hook manager that is started by `Config::start`
+    pub fn hook_manager(mut self, enabled: bool) -> Self {
+        self.config.hook_manager = enabled;
+        return self;
+    }
+
+    /// Set the log file, then `Config::start` starts a logger too
+    pub fn logger_path(mut self, path: String) -> Self {
+        self.config.logger_path = Some(path);
+        return self;
+    }
+
+    /// Return with the built configuration
+    pub fn build(self) -> Config {
+        return self.config;
+    }
+}
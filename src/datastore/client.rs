@@ -0,0 +1,165 @@
+//! Blocking client of the datastore thread that is started by `utilities::start_datastore`
+
+use std::sync::mpsc::{channel, Sender};
+
+use super::{
+    enums::{error::ErrorKind, pair::KeyType, pair::ValueType, DatabaseAction, ListType, TxnOp},
+    types::{ChangeEvent, DatastoreStats, SizeReport},
+};
+
+/// Wrapper of the datastore sender: every method creates the response channel, sends the action
+/// and waits for the answer. If the datastore thread is stopped, `ErrorKind::InternalError` is returned.
+/// Clones send to the same datastore thread.
+///
+/// # Examples
+/// ```
+/// use onlyati_datastore::datastore::{client::DatastoreClient, enums::pair::ValueType, utilities::start_datastore};
+///
+/// let (sender, _) = start_datastore("root".to_string(), None, None);
+/// let client = DatastoreClient::new(sender);
+///
+/// client.set("/root/status".to_string(), "ok".to_string()).expect("Failed to set");
+/// assert_eq!(ValueType::RecordPointer("ok".to_string()), client.get("/root/status".to_string()).unwrap());
+/// ```
+#[derive(Clone)]
+pub struct DatastoreClient {
+    sender: Sender<DatabaseAction>,
+}
+
+impl DatastoreClient {
+    /// Create client
+    ///
+    /// # Arguments
+    /// 1. `sender` - Sender to datastore thread
+    pub fn new(sender: Sender<DatabaseAction>) -> Self {
+        return DatastoreClient { sender };
+    }
+
+    /// Return with the sender, for the actions that have no own method here
+    pub fn sender(&self) -> &Sender<DatabaseAction> {
+        return &self.sender;
+    }
+
+    /// Send the action that is made with the response sender and wait for the answer
+    fn request<T>(&self, action: impl FnOnce(Sender<T>) -> DatabaseAction) -> Result<T, ErrorKind> {
+        let (tx, rx) = channel();
+
+        if let Err(e) = self.sender.send(action(tx)) {
+            tracing::error!("Error during send: {}", e);
+            return Err(ErrorKind::InternalError("Datastore is not running".to_string()));
+        }
+
+        match rx.recv() {
+            Ok(response) => return Ok(response),
+            Err(e) => {
+                tracing::error!("Error during receive: {}", e);
+                return Err(ErrorKind::InternalError("Datastore did not answer".to_string()));
+            }
+        }
+    }
+
+    /// Get the value of a key, see `DatabaseAction::Get`
+    pub fn get(&self, key: String) -> Result<ValueType, ErrorKind> {
+        return self.request(|tx| DatabaseAction::Get(tx, key))?;
+    }
+
+    /// Set or update a key-value pair, see `DatabaseAction::Set`
+    pub fn set(&self, key: String, value: String) -> Result<(), ErrorKind> {
+        return self.request(|tx| DatabaseAction::Set(tx, key, value))?;
+    }
+
+    /// Check that key exists, see `DatabaseAction::Exists`
+    pub fn exists(&self, key: KeyType) -> Result<bool, ErrorKind> {
+        return self.request(|tx| DatabaseAction::Exists(tx, key))?;
+    }
+
+    /// Set the value if the current one is the expected, see `DatabaseAction::CompareAndSwap`
+    pub fn compare_and_swap(&self, key: String, expected: Option<String>, value: String) -> Result<bool, ErrorKind> {
+        return self.request(|tx| DatabaseAction::CompareAndSwap(tx, key, expected, value))?;
+    }
+
+    /// Add delta to a numeric record, see `DatabaseAction::Increment`
+    pub fn increment(&self, key: String, delta: i64) -> Result<i64, ErrorKind> {
+        return self.request(|tx| DatabaseAction::Increment(tx, key, delta))?;
+    }
+
+    /// Delete a record, see `DatabaseAction::DeleteKey`
+    pub fn delete_key(&self, key: String) -> Result<(), ErrorKind> {
+        return self.request(|tx| DatabaseAction::DeleteKey(tx, key))?;
+    }
+
+    /// Delete a record and return with its value, see `DatabaseAction::Remove`
+    pub fn remove(&self, key: String) -> Result<ValueType, ErrorKind> {
+        return self.request(|tx| DatabaseAction::Remove(tx, key))?;
+    }
+
+    /// Delete everything under a prefix, see `DatabaseAction::DeletePrefix`
+    pub fn delete_prefix(&self, prefix: String) -> Result<usize, ErrorKind> {
+        return self.request(|tx| DatabaseAction::DeletePrefix(tx, prefix))?;
+    }
+
+    /// Create an empty table, see `DatabaseAction::CreateTable`
+    pub fn create_table(&self, key: String) -> Result<(), ErrorKind> {
+        return self.request(|tx| DatabaseAction::CreateTable(tx, key))?;
+    }
+
+    /// Delete a table with its content, see `DatabaseAction::DeleteTable`
+    pub fn delete_table(&self, key: String) -> Result<(), ErrorKind> {
+        return self.request(|tx| DatabaseAction::DeleteTable(tx, key))?;
+    }
+
+    /// List keys under a table, see `DatabaseAction::ListKeys`
+    pub fn list(&self, prefix: String, level: ListType) -> Result<Vec<KeyType>, ErrorKind> {
+        return self.request(|tx| DatabaseAction::ListKeys(tx, prefix, level))?;
+    }
+
+    /// List tables under a table, see `DatabaseAction::ListTables`
+    pub fn list_tables(&self, prefix: String, level: ListType) -> Result<Vec<KeyType>, ErrorKind> {
+        return self.request(|tx| DatabaseAction::ListTables(tx, prefix, level))?;
+    }
+
+    /// List keys with their values under a table, see `DatabaseAction::ListValues`
+    pub fn list_values(&self, prefix: String, level: ListType) -> Result<Vec<(KeyType, ValueType)>, ErrorKind> {
+        return self.request(|tx| DatabaseAction::ListValues(tx, prefix, level))?;
+    }
+
+    /// Push a value into a queue, see `DatabaseAction::Push`
+    pub fn push(&self, key: String, value: String) -> Result<(), ErrorKind> {
+        return self.request(|tx| DatabaseAction::Push(tx, key, value))?;
+    }
+
+    /// Pop the first value of a queue, see `DatabaseAction::Pop`
+    pub fn pop(&self, key: String) -> Result<Option<String>, ErrorKind> {
+        return self.request(|tx| DatabaseAction::Pop(tx, key))?;
+    }
+
+    /// Remove every table, record and queue, see `DatabaseAction::Clear`
+    pub fn clear(&self) -> Result<(), ErrorKind> {
+        return self.request(DatabaseAction::Clear)?;
+    }
+
+    /// Apply more operations all or nothing, see `DatabaseAction::Transaction`
+    pub fn transaction(&self, ops: Vec<TxnOp>) -> Result<(), ErrorKind> {
+        return self.request(|tx| DatabaseAction::Transaction(tx, ops))?;
+    }
+
+    /// Return with the counters of requests, see `DatabaseAction::Stats`
+    pub fn stats(&self) -> Result<DatastoreStats, ErrorKind> {
+        return self.request(DatabaseAction::Stats);
+    }
+
+    /// Return with the estimated size of database, see `DatabaseAction::SizeEstimate`
+    pub fn size_estimate(&self) -> Result<SizeReport, ErrorKind> {
+        return self.request(DatabaseAction::SizeEstimate);
+    }
+
+    /// Subscribe to the changes of keys under a prefix, see `DatabaseAction::Watch`.
+    /// It does not wait, the changes arrive on the sender.
+    pub fn watch(&self, prefix: String, sender: Sender<ChangeEvent>) -> Result<(), ErrorKind> {
+        if let Err(e) = self.sender.send(DatabaseAction::Watch(sender, prefix)) {
+            tracing::error!("Error during send: {}", e);
+            return Err(ErrorKind::InternalError("Datastore is not running".to_string()));
+        }
+        return Ok(());
+    }
+}
@@ -2,6 +2,7 @@
 /// Possible error types that database can return
 ///
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ErrorKind {
     /// The root name in the key does not match with the root table name
     InvalidRoot(String),
@@ -9,6 +10,9 @@ pub enum ErrorKind {
     /// Wrong key is specified, reason in the message
     InvalidKey(String),
 
+    /// Key is well-formed but it does not exist, reason in the message
+    NotFound(String),
+
     /// Oops, it should not happen
     InternalError(String),
 
@@ -17,16 +21,53 @@ pub enum ErrorKind {
 
     /// Send log errors back
     LogError(String),
+
+    /// Database is paused, modifications are not allowed until resume
+    Paused,
+
+    /// Expected version of record does not match, current version is attached
+    VersionConflict(u64),
+
+    /// Value has different type than the operation expects, e.g. it is not a number
+    TypeError(String),
+
+    /// Queue reached its capacity and the new item is rejected
+    QueueFull,
+
+    /// Current value of record is not the expected one, key is attached
+    CompareFailed(String),
+
+    /// One item of a batch is failed, index of the item and its error are attached
+    BatchFailed { index: usize, error: Box<ErrorKind> },
+
+    /// Result would contain more entries than the configured limit
+    ResultTooLarge { count: usize, limit: usize },
+
+    /// Value would be larger than the configured maximum, size is in bytes
+    ValueTooLarge { size: usize, max: usize },
 }
 
 impl std::fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let response = match self {
             Self::InvalidKey(message) => format!("Invalid key: {message}"),
+            Self::NotFound(message) => format!("Not found: {message}"),
             Self::InvalidRoot(message) => format!("Invalid root: {message}"),
             Self::InternalError(message) => format!("Internal error: {message}"),
-            Self::InactiveHookManager => format!("Inacvite hook manager: database is not subscried"),
+            Self::InactiveHookManager => "Inacvite hook manager: database is not subscried".to_string(),
             Self::LogError(message) => format!("LogError: {}", message),
+            Self::Paused => "Paused: database does not accept modifications".to_string(),
+            Self::VersionConflict(current) => format!("Version conflict: current version is {}", current),
+            Self::TypeError(message) => format!("Type error: {}", message),
+            Self::QueueFull => "Queue full: queue reached its capacity".to_string(),
+            Self::CompareFailed(key) => format!("Compare failed: value of '{}' is not the expected one", key),
+            Self::BatchFailed { index, error } => format!("Batch failed at item {}: {}", index, error),
+            Self::ResultTooLarge { count, limit } => {
+                format!("Result too large: {} entries found but limit is {}", count, limit)
+            }
+            Self::ValueTooLarge { size, max } => {
+                format!("Value too large: value is {} bytes but maximum is {}", size, max)
+            }
         };
         return write!(f, "{}", response);
     }
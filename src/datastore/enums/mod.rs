@@ -1,9 +1,17 @@
 //! Enum for datastore
 
-use crate::hook::types::{Link, Prefix};
+use crate::hook::{
+    enums::HookEvent,
+    types::{Headers, Link, Prefix},
+};
+use reqwest::Method;
+
+use self::pair::KeyType;
 
 use super::types::{
-    ResultWithHook, ResultWithHooks, ResultWithList, ResultWithResult, ResultWithoutResult, Table,
+    ChangeEvent, DatabaseSnapshot, DatastoreStats, SizeReport, Predicate, QueueLengths, ResultWithBool, ResultWithMeta, ResultWithSnapshot, ResultWithJson, ResultWithPairs, ResultWithPrevious, ResultWithOptional, ResultWithCount, ResultWithHook, ResultWithHooks, ResultWithHotKeys, ResultWithKeyResults,
+    ResultWithLinks, ResultWithList, ResultWithNumber, ResultWithResult, ResultWithSource,
+    ResultWithoutResult, Table, ValueResults,
 };
 use std::sync::mpsc::Sender;
 
@@ -32,6 +40,172 @@ impl std::fmt::Display for ListType {
     }
 }
 
+///
+/// Specify how key collisions are handled during merge
+///
+#[derive(PartialEq, Clone, Debug)]
+pub enum MergePolicy {
+    /// Existing value remains, the other one is ignored
+    KeepExisting,
+
+    /// Existing value is overwritten by the other one
+    Overwrite,
+
+    /// Merge fails and nothing is merged
+    Error,
+}
+
+///
+/// Specify how insert behaves if the key exists or not
+///
+#[derive(PartialEq, Clone, Debug, Default)]
+pub enum InsertMode {
+    /// Insert the key or update if it already exists
+    #[default]
+    Upsert,
+
+    /// Insert only if the key does not exist yet
+    CreateOnly,
+
+    /// Update only if the key already exists
+    UpdateOnly,
+}
+
+impl std::fmt::Display for InsertMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Upsert => "Upsert",
+            Self::CreateOnly => "CreateOnly",
+            Self::UpdateOnly => "UpdateOnly",
+        };
+        return write!(f, "{}", text);
+    }
+}
+
+///
+/// Specify how strictly the keys of new records, queues and tables are checked
+///
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum KeyValidation {
+    /// Empty segments are skipped, e.g. `/root//status` is the same like `/root/status`
+    #[default]
+    Lenient,
+
+    /// Empty segments are rejected, e.g. `/root//status` or `/root/status/`
+    NoEmptySegments,
+
+    /// Empty segments are rejected and segments can contain only ASCII letters, digits and `-`, `_`, `.`, `:`
+    Restricted,
+}
+
+impl std::fmt::Display for KeyValidation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Lenient => "Lenient",
+            Self::NoEmptySegments => "NoEmptySegments",
+            Self::Restricted => "Restricted",
+        };
+        return write!(f, "{}", text);
+    }
+}
+
+///
+/// Specify what happens if an item is pushed into a full queue
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DropPolicy {
+    /// New item is rejected with `ErrorKind::QueueFull`
+    RejectNew,
+
+    /// Oldest items are dropped from the front of queue to make room for the new one
+    DropOldest,
+}
+
+impl std::fmt::Display for DropPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::RejectNew => "RejectNew",
+            Self::DropOldest => "DropOldest",
+        };
+        return write!(f, "{}", text);
+    }
+}
+
+///
+/// Tells where the value is coming from at read-through reads
+///
+#[derive(PartialEq, Clone, Debug)]
+pub enum Source {
+    /// Value was already in the database
+    Cache,
+
+    /// Value was missing and it was fetched by the loader
+    Loader,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Cache => "Cache",
+            Self::Loader => "Loader",
+        };
+        return write!(f, "{}", text);
+    }
+}
+
+///
+/// Operation of a transaction, see `Database::transaction`
+///
+#[derive(PartialEq, Clone, Debug)]
+pub enum TxnOp {
+    /// Set or update a key-value pair
+    Set(String, String),
+
+    /// Delete a pair
+    DeleteKey(String),
+
+    /// Delete a table with its content
+    DeleteTable(String),
+
+    /// Set the value if the current one is the expected, `None` means that the key must not exist.
+    /// Transaction is failed with `ErrorKind::CompareFailed` if it is different.
+    CompareAndSwap(String, Option<String>, String),
+}
+
+impl std::fmt::Display for TxnOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Set(key, _) => format!("Set[{}]", key),
+            Self::DeleteKey(key) => format!("DeleteKey[{}]", key),
+            Self::DeleteTable(key) => format!("DeleteTable[{}]", key),
+            Self::CompareAndSwap(key, _, _) => format!("CompareAndSwap[{}]", key),
+        };
+        return write!(f, "{}", text);
+    }
+}
+
+///
+/// Kind of change that is sent to watchers, see `DatabaseAction::Watch`
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ChangeKind {
+    /// Record is inserted or updated
+    Set,
+
+    /// Record or table is deleted
+    Delete,
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Set => "Set",
+            Self::Delete => "Delete",
+        };
+        return write!(f, "{}", text);
+    }
+}
+
 ///
 /// Actions for built-in server
 ///
@@ -39,24 +213,92 @@ pub enum DatabaseAction {
     /// Set or update a key-value pair
     Set(Sender<ResultWithoutResult>, String, String),
 
+    /// Set a key-value pair according to the insert mode
+    SetWithMode(Sender<ResultWithoutResult>, String, String, InsertMode),
+
+    /// Set a key-value pair that expires after the specified seconds
+    SetWithTtl(Sender<ResultWithoutResult>, String, String, u64),
+
+    /// Set more key and value pairs at once
+    SetBatch(Sender<ResultWithoutResult>, Vec<(String, String)>),
+
     /// Get a value for a key
     Get(Sender<ResultWithResult>, String),
 
+    /// Get the creation and modification time of a record
+    GetMeta(Sender<ResultWithMeta>, String),
+
+    /// Get the value of more keys, response contains the result of each key
+    GetBatch(Sender<ValueResults>, Vec<String>),
+
+    /// Move a record, queue or table to a new key
+    Rename(Sender<ResultWithoutResult>, KeyType, KeyType),
+
+    /// List keys with their values under a prefix
+    ListValues(Sender<ResultWithPairs>, String, ListType),
+
+    /// Count keys under a prefix
+    CountKeys(Sender<ResultWithCount>, String, ListType),
+
+    /// Tells that a key exists or not
+    Exists(Sender<ResultWithBool>, KeyType),
+
+    /// Get a value for a key, if it does not exist then insert the specified value and return with it
+    GetOrInsert(Sender<ResultWithResult>, String, String),
+
+    /// Set the value only if the current value is the expected one, `None` means that the key must not exist.
+    /// Response tells that the value was written or not.
+    CompareAndSwap(Sender<ResultWithBool>, String, Option<String>, String),
+
+    /// Set the value and return with the previous one if it existed
+    GetSet(Sender<ResultWithPrevious>, String, String),
+
+    /// Get a value for a key and where it is coming from
+    GetWithSource(Sender<ResultWithSource>, String),
+
     /// Delete a pair
     DeleteKey(Sender<ResultWithoutResult>, String),
 
+    /// Delete a pair and return with its value
+    Remove(Sender<ResultWithResult>, String),
+
+    /// Delete a pair, if the flag is true then the tables on its route that became empty are removed too,
+    /// see `Database::delete_key_pruning`
+    DeleteKeyWithPruning(Sender<ResultWithoutResult>, String, bool),
+
+    /// Delete more pairs, response contains the result of each key
+    DeleteKeys(Sender<ResultWithKeyResults>, Vec<String>),
+
     /// Delete a whole table
     DeleteTable(Sender<ResultWithoutResult>, String),
 
+    /// Delete everything whose path begins with the prefix, see `Database::delete_prefix`
+    DeletePrefix(Sender<ResultWithCount>, String),
+
+    /// Create an empty table
+    CreateTable(Sender<ResultWithoutResult>, String),
+
     /// List keys from a route
     ListKeys(Sender<ResultWithList>, String, ListType),
 
+    /// List the tables under a route, see `Database::list_tables`
+    ListTables(Sender<ResultWithList>, String, ListType),
+
     /// Send trigger to HookManager
     Trigger(Sender<ResultWithoutResult>, String, String),
 
     /// Set new hook
     HookSet(Sender<ResultWithoutResult>, Prefix, Link),
 
+    /// Set new hook that is called with the specified HTTP method instead of POST
+    HookSetWithMethod(Sender<ResultWithoutResult>, Prefix, Link, Method),
+
+    /// Set new hook with static headers that are sent with every request, method is POST if it is `None`
+    HookSetWithHeaders(Sender<ResultWithoutResult>, Prefix, Link, Option<Method>, Headers),
+
+    /// Set which events an existing hook is executed for, see `HookManager::set_events`
+    HookSetEvents(Sender<ResultWithoutResult>, Prefix, Link, Vec<HookEvent>),
+
     /// Check that hook exist
     HookGet(Sender<ResultWithHook>, Prefix),
 
@@ -66,6 +308,9 @@ pub enum DatabaseAction {
     /// List hooks
     HookList(Sender<ResultWithHooks>, Prefix),
 
+    /// List hooks grouped by links
+    HookListByLink(Sender<ResultWithLinks>),
+
     /// Command to suspend the logging
     SuspendLog(Sender<ResultWithoutResult>),
 
@@ -75,27 +320,173 @@ pub enum DatabaseAction {
     /// Push to a queue
     Push(Sender<ResultWithoutResult>, String, String),
 
-    /// Pop from queue
-    Pop(Sender<ResultWithResult>, String),
+    /// Push to a queue with maximum capacity, response is the number of dropped items
+    PushBounded(Sender<ResultWithCount>, String, String, usize, DropPolicy),
+
+    /// Pop from queue, `None` if the queue is empty or does not exist
+    Pop(Sender<ResultWithOptional>, String),
+
+    /// Return with the front item of a queue without removing it
+    Peek(Sender<ResultWithOptional>, String),
+
+    /// Return with the last item of a queue without removing it
+    PeekBack(Sender<ResultWithOptional>, String),
+
+    /// Pop from queue only if the front value satisfies the predicate
+    PopIf(Sender<ResultWithOptional>, String, Predicate),
+
+    /// Append text to the end of a record
+    Append(Sender<ResultWithoutResult>, String, String),
+
+    /// Add a number to a numeric record, response is the new value
+    Increment(Sender<ResultWithNumber>, String, i64),
+
+    /// Add a number to a random shard of a counter, parameters are the base key, number of shards and the delta.
+    /// Response is the new value of the updated shard.
+    ShardedIncrement(Sender<ResultWithNumber>, String, usize, i64),
+
+    /// Sum of every shard of a counter
+    ShardedSum(Sender<ResultWithNumber>, String),
+
+    /// Count items in every queue
+    TotalQueued(Sender<ResultWithCount>),
+
+    /// Length of a queue, it fails if the queue does not exist
+    QueueLen(Sender<ResultWithCount>, String),
+
+    /// Length of more queues, response contains the result of each key
+    QueueLenMulti(Sender<QueueLengths>, Vec<String>),
+
+    /// List the most read keys
+    HotKeys(Sender<ResultWithHotKeys>, usize),
+
+    /// Pop maximum the specified number of items from queue and send them to the link,
+    /// failed items are put back to the queue. Response is the number of delivered items.
+    QueueForward(Sender<ResultWithCount>, String, Link, usize),
+
+    /// Create an owned copy of the whole database
+    Snapshot(Sender<ResultWithSnapshot>),
+
+    /// Export a table as nested JSON object, see `Database::to_json`
+    ExportJson(Sender<ResultWithJson>, String),
+
+    /// Import a JSON object under a table, see `Database::from_json`
+    ImportJson(Sender<ResultWithCount>, String, serde_json::Value),
+
+    /// Replace the content of database with a snapshot, it is not written to the log
+    Restore(Sender<ResultWithoutResult>, DatabaseSnapshot),
+
+    /// Save the database into the file that is set by `Builder::persist_path`
+    Flush(Sender<ResultWithoutResult>),
+
+    /// Remove every table, record and queue, see `Database::clear`
+    Clear(Sender<ResultWithoutResult>),
+
+    /// Return with the counters of requests
+    Stats(Sender<DatastoreStats>),
+
+    /// Return with the estimated size of database, see `Database::size_estimate`
+    SizeEstimate(Sender<SizeReport>),
+
+    /// Write the pending log items and the persisted file then stop the datastore thread.
+    /// Response is sent before the thread exits, so its handle can be joined.
+    Shutdown(Sender<ResultWithoutResult>),
+
+    /// Pause the database, modifications are rejected with `ErrorKind::Paused` until resume,
+    /// reads are still served. It can be used to get a consistent point-in-time state.
+    Pause(Sender<ResultWithoutResult>),
+
+    /// Resume the paused database
+    Resume(Sender<ResultWithoutResult>),
+
+    /// Apply more operations atomically, see `Database::transaction`
+    Transaction(Sender<ResultWithoutResult>, Vec<TxnOp>),
+
+    /// Subscribe to the changes of keys under a prefix, a `ChangeEvent` is sent after every
+    /// successful set and delete. Subscriber is dropped when its receiver is dropped.
+    Watch(Sender<ChangeEvent>, String),
 }
 
 impl std::fmt::Display for DatabaseAction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let text = match self {
             Self::Set(_, key, _) => format!("Set[{}]", key),
+            Self::SetWithMode(_, key, _, mode) => format!("SetWithMode[{}, {}]", key, mode),
+            Self::SetWithTtl(_, key, _, secs) => format!("SetWithTtl[{}, {}]", key, secs),
+            Self::SetBatch(_, pairs) => format!("SetBatch[{}]", pairs.len()),
             Self::Get(_, key) => format!("Get[{}]", key),
+            Self::GetWithSource(_, key) => format!("GetWithSource[{}]", key),
+            Self::GetMeta(_, key) => format!("GetMeta[{}]", key),
+            Self::GetBatch(_, keys) => format!("GetBatch[{}]", keys.join(", ")),
+            Self::Rename(_, from, to) => format!("Rename[{}, {}]", from.get_key(), to.get_key()),
+            Self::ListValues(_, key, r#type) => format!("ListValues[{}, {}]", key, r#type),
+            Self::CountKeys(_, key, r#type) => format!("CountKeys[{}, {}]", key, r#type),
+            Self::Exists(_, key) => format!("Exists[{}]", key.get_key()),
+            Self::GetOrInsert(_, key, _) => format!("GetOrInsert[{}]", key),
+            Self::CompareAndSwap(_, key, _, _) => format!("CompareAndSwap[{}]", key),
+            Self::GetSet(_, key, _) => format!("GetSet[{}]", key),
             Self::DeleteKey(_, key) => format!("RemKey[{}]", key),
+            Self::Remove(_, key) => format!("Remove[{}]", key),
+            Self::DeleteKeyWithPruning(_, key, prune) => format!("RemKeyWithPruning[{}, {}]", key, prune),
+            Self::DeleteKeys(_, keys) => format!("RemKeys[{}]", keys.join(", ")),
             Self::DeleteTable(_, key) => format!("RemPath[{}]", key),
+            Self::DeletePrefix(_, key) => format!("DeletePrefix[{}]", key),
+            Self::CreateTable(_, key) => format!("CreatePath[{}]", key),
             Self::ListKeys(_, key, r#type) => format!("ListKeys[{}, {}]", key, r#type),
+            Self::ListTables(_, key, r#type) => format!("ListTables[{}, {}]", key, r#type),
             Self::Trigger(_, key, value) => format!("Trigger[{}, {}]", key, value),
             Self::HookSet(_, prefix, link) => format!("HookSet[{}, {}]", prefix, link),
+            Self::HookSetWithMethod(_, prefix, link, method) => {
+                format!("HookSetWithMethod[{}, {}, {}]", prefix, link, method)
+            }
+            Self::HookSetWithHeaders(_, prefix, link, method, headers) => {
+                let names: Vec<&String> = headers.keys().collect();
+                format!("HookSetWithHeaders[{}, {}, {:?}, {:?}]", prefix, link, method, names)
+            }
+            Self::HookSetEvents(_, prefix, link, events) => {
+                let events: Vec<String> = events.iter().map(|x| x.to_string()).collect();
+                format!("HookSetEvents[{}, {}, {:?}]", prefix, link, events)
+            }
             Self::HookGet(_, prefix) => format!("HookGet[{}]", prefix),
             Self::HookRemove(_, prefix, link) => format!("HookRemove[{}, {}]", prefix, link),
             Self::HookList(_, prefix) => format!("HookList[{}]", prefix),
-            Self::SuspendLog(_) => format!("SuspendLog"),
-            Self::ResumeLog(_) => format!("ResumeLog"),
+            Self::HookListByLink(_) => "HookListByLink".to_string(),
+            Self::SuspendLog(_) => "SuspendLog".to_string(),
+            Self::ResumeLog(_) => "ResumeLog".to_string(),
             Self::Push(_, key, _) => format!("Push[{}]", key),
+            Self::PushBounded(_, key, _, max_len, policy) => {
+                format!("PushBounded[{}, {}, {}]", key, max_len, policy)
+            }
             Self::Pop(_, key) => format!("Pop[{}]", key),
+            Self::Peek(_, key) => format!("Peek[{}]", key),
+            Self::PeekBack(_, key) => format!("PeekBack[{}]", key),
+            Self::PopIf(_, key, _) => format!("PopIf[{}]", key),
+            Self::Append(_, key, _) => format!("Append[{}]", key),
+            Self::Increment(_, key, delta) => format!("Increment[{}, {}]", key, delta),
+            Self::ShardedIncrement(_, key, shards, delta) => {
+                format!("ShardedIncrement[{}, {}, {}]", key, shards, delta)
+            }
+            Self::ShardedSum(_, key) => format!("ShardedSum[{}]", key),
+            Self::TotalQueued(_) => "TotalQueued".to_string(),
+            Self::QueueLen(_, key) => format!("QueueLen[{}]", key),
+            Self::QueueLenMulti(_, keys) => format!("QueueLenMulti[{}]", keys.join(", ")),
+            Self::HotKeys(_, n) => format!("HotKeys[{}]", n),
+            Self::QueueForward(_, key, link, batch) => {
+                format!("QueueForward[{}, {}, {}]", key, link, batch)
+            }
+            Self::Snapshot(_) => "Snapshot".to_string(),
+            Self::ExportJson(_, key) => format!("ExportJson[{}]", key),
+            Self::ImportJson(_, key, _) => format!("ImportJson[{}]", key),
+            Self::Restore(_, snapshot) => format!("Restore[{}]", snapshot.name),
+            Self::Flush(_) => "Flush".to_string(),
+            Self::Clear(_) => "Clear".to_string(),
+            Self::Stats(_) => "Stats".to_string(),
+            Self::SizeEstimate(_) => "SizeEstimate".to_string(),
+            Self::Shutdown(_) => "Shutdown".to_string(),
+            Self::Pause(_) => "Pause".to_string(),
+            Self::Resume(_) => "Resume".to_string(),
+            Self::Watch(_, prefix) => format!("Watch[{}]", prefix),
+            Self::Transaction(_, ops) => format!("Transaction[{}]", ops.len()),
         };
         return write!(f, "{}", text);
     }
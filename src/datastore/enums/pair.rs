@@ -1,11 +1,14 @@
-use std::cmp::Ordering;
+use std::borrow::Cow;
 use std::collections::VecDeque;
 use std::fmt::Display;
 
 ///
 /// Key type that database accept, it can be record or another table
 ///
-#[derive(Eq, Ord, Debug, Clone)]
+/// With `serde` feature it is serialized as string like its `Display` output, e.g. `r /root/status`,
+/// so a `Table` can be used as map key in formats like JSON. Comparison traits are not affected.
+///
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
 pub enum KeyType {
     /// Value will be a pointer to another table
     Table(String),
@@ -18,28 +21,55 @@ pub enum KeyType {
 }
 
 impl KeyType {
+    /// Create `KeyType::Record` key
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::enums::pair::KeyType;
+    ///
+    /// assert_eq!(KeyType::Record("/root/status".to_string()), KeyType::record("/root/status"));
+    /// ```
+    pub fn record(key: impl Into<String>) -> Self {
+        return KeyType::Record(key.into());
+    }
+
+    /// Create `KeyType::Table` key
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::enums::pair::KeyType;
+    ///
+    /// assert_eq!(KeyType::Table("/root/status".to_string()), KeyType::table("/root/status"));
+    /// ```
+    pub fn table(key: impl Into<String>) -> Self {
+        return KeyType::Table(key.into());
+    }
+
+    /// Create `KeyType::Queue` key
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::enums::pair::KeyType;
+    ///
+    /// assert_eq!(KeyType::Queue("/root/jobs".to_string()), KeyType::queue("/root/jobs"));
+    /// ```
+    pub fn queue(key: impl Into<String>) -> Self {
+        return KeyType::Queue(key.into());
+    }
+
     /// Tells that key type is `KeyType::Table`
     pub fn is_table(&self) -> bool {
-        return match self {
-            KeyType::Table(_) => true,
-            _ => false,
-        };
+        return matches!(self, KeyType::Table(_));
     }
 
     /// Tells that key type is `KeyType::Record`
     pub fn is_record(&self) -> bool {
-        return match self {
-            KeyType::Record(_) => true,
-            _ => false,
-        };
+        return matches!(self, KeyType::Record(_));
     }
 
     /// Tells that key type is `KeyType::Queue`
     pub fn is_queue(&self) -> bool {
-        return match self {
-            KeyType::Queue(_) => true,
-            _ => false,
-        };
+        return matches!(self, KeyType::Queue(_));
     }
 
     /// Return with the record name or the table name
@@ -72,40 +102,56 @@ impl Display for KeyType {
     }
 }
 
-impl PartialOrd for KeyType {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let self_key = self.get_key();
-        let other_key = self.get_key();
-
-        if self_key == other_key {
-            if self.is_table() && other.is_record() {
-                return Some(Ordering::Greater);
-            } else if self.is_record() && other.is_table() {
-                return Some(Ordering::Less);
-            } else {
-                return Some(Ordering::Equal);
-            }
-        }
+#[cfg(feature = "serde")]
+impl serde::Serialize for KeyType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(&self.to_string());
+    }
+}
 
-        return Some(self_key.cmp(other_key));
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for KeyType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        return match text.split_once(' ') {
+            Some(("t", key)) => Ok(KeyType::Table(key.to_string())),
+            Some(("r", key)) => Ok(KeyType::Record(key.to_string())),
+            Some(("q", key)) => Ok(KeyType::Queue(key.to_string())),
+            _ => Err(serde::de::Error::custom(format!("Invalid key: {}", text))),
+        };
     }
 }
 
-impl<'a> PartialEq for KeyType {
-    fn eq(&self, other: &Self) -> bool {
-        if (self.is_record() && other.is_record()) || (self.is_table() && other.is_table() || (self.is_queue() && other.is_queue())) {
-            if self.get_key() == other.get_key() {
-                return true;
-            }
-        }
-        return false;
+/// Key from text is always `KeyType::Record`, use `KeyType::table` or `KeyType::queue` for the others
+///
+/// # Example
+/// ```
+/// use onlyati_datastore::datastore::enums::pair::KeyType;
+///
+/// let key: KeyType = "/root/status".into();
+/// assert_eq!(KeyType::Record("/root/status".to_string()), key);
+/// ```
+impl From<&str> for KeyType {
+    fn from(key: &str) -> Self {
+        return KeyType::Record(key.to_string());
+    }
+}
+
+/// Key from text is always `KeyType::Record`, use `KeyType::table` or `KeyType::queue` for the others
+impl From<String> for KeyType {
+    fn from(key: String) -> Self {
+        return KeyType::Record(key);
     }
 }
 
 ///
 /// Type of the value
 ///
+/// With `serde` feature the variants are tagged by their names, so `IntPointer(5)` and `RecordPointer("5")`
+/// remain different after deserialization.
+///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ValueType {
     /// This is a table pointer, belongs to `KeyType::Table`
     TablePointer(super::Table),
@@ -115,38 +161,162 @@ pub enum ValueType {
 
     /// This is a queue pointer, belongs to `KeyType::Queue`
     QueuePointer(VecDeque<String>),
+
+    /// This is a numeric record, belongs to `KeyType::Record`.
+    /// Logger writes it as `SetInt` item, so replay restores it as number.
+    IntPointer(i64),
+
+    /// This is a binary record, belongs to `KeyType::Record`.
+    /// It is not sent to the hooks, logger writes it as `SetBytes` item with base64 value.
+    BytesPointer(Vec<u8>),
 }
 
 impl ValueType {
     /// Tells that it is a `ValueType::TablePointer`
     pub fn is_table(&self) -> bool {
-        return match self {
-            ValueType::TablePointer(_) => true,
-            _ => false,
-        };
+        return matches!(self, ValueType::TablePointer(_));
     }
 
     /// Tells that it is a `ValueType::RecordPointer`
     pub fn is_record(&self) -> bool {
-        return match self {
-            ValueType::RecordPointer(_) => true,
-            _ => false,
-        };
+        return matches!(self, ValueType::RecordPointer(_));
     }
 
     /// Tells that it is a `ValueType::QueuePointer`
     pub fn is_queue(&self) -> bool {
+        return matches!(self, ValueType::QueuePointer(_));
+    }
+
+    /// Tells that it is a `ValueType::IntPointer`
+    pub fn is_int(&self) -> bool {
+        return matches!(self, ValueType::IntPointer(_));
+    }
+
+    /// Tells that it is a `ValueType::BytesPointer`
+    pub fn is_bytes(&self) -> bool {
+        return matches!(self, ValueType::BytesPointer(_));
+    }
+
+    /// Tells that value belongs to `KeyType::Record`: it is a text, numeric or binary record
+    pub fn belongs_to_record(&self) -> bool {
+        return matches!(self, ValueType::RecordPointer(_) | ValueType::IntPointer(_) | ValueType::BytesPointer(_));
+    }
+
+    /// Return with the text of record or the number of numeric record, other types return with their type name.
+    /// Use `to_string` to display them.
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::enums::pair::ValueType;
+    ///
+    /// assert_eq!("ok", ValueType::RecordPointer("ok".to_string()).get_value());
+    /// assert_eq!("-5", ValueType::IntPointer(-5).get_value());
+    /// assert_eq!("BytesPointer", ValueType::BytesPointer(vec![1, 2]).get_value());
+    /// ```
+    pub fn get_value(&self) -> Cow<'_, str> {
+        return match self {
+            Self::TablePointer(_) => Cow::Borrowed("TablePointer"),
+            Self::RecordPointer(key) => Cow::Borrowed(&key[..]),
+            Self::QueuePointer(_) => Cow::Borrowed("QueuePointer"),
+            Self::IntPointer(value) => Cow::Owned(value.to_string()),
+            Self::BytesPointer(_) => Cow::Borrowed("BytesPointer"),
+        };
+    }
+
+    /// Return with the number if it is a `ValueType::IntPointer`
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::enums::pair::ValueType;
+    ///
+    /// assert_eq!(Some(5), ValueType::IntPointer(5).as_int());
+    /// assert_eq!(None, ValueType::RecordPointer("5".to_string()).as_int());
+    /// ```
+    pub fn as_int(&self) -> Option<i64> {
+        return match self {
+            Self::IntPointer(value) => Some(*value),
+            _ => None,
+        };
+    }
+
+    /// Return with the bytes if it is a `ValueType::BytesPointer`
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::enums::pair::ValueType;
+    ///
+    /// assert_eq!(Some(&[0x01, 0xff][..]), ValueType::BytesPointer(vec![0x01, 0xff]).as_bytes());
+    /// assert_eq!(None, ValueType::IntPointer(1).as_bytes());
+    /// ```
+    pub fn as_bytes(&self) -> Option<&[u8]> {
         return match self {
-            ValueType::QueuePointer(_) => true,
-            _ => false,
+            Self::BytesPointer(value) => Some(&value[..]),
+            _ => None,
         };
     }
 
-    pub fn get_value(&self) -> &str {
+    /// Return with the text if it is a `ValueType::RecordPointer`
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::enums::pair::ValueType;
+    ///
+    /// assert_eq!(Some("ok"), ValueType::RecordPointer("ok".to_string()).as_record());
+    /// assert_eq!(None, ValueType::QueuePointer(Default::default()).as_record());
+    /// ```
+    pub fn as_record(&self) -> Option<&str> {
         return match self {
-            Self::TablePointer(_) => "TablePointer",
-            Self::RecordPointer(key) => &key[..],
-            Self::QueuePointer(_) => "QueuePointer",
+            Self::RecordPointer(value) => Some(&value[..]),
+            _ => None,
+        };
+    }
+
+    /// Return with the items if it is a `ValueType::QueuePointer`
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::VecDeque;
+    /// use onlyati_datastore::datastore::enums::pair::ValueType;
+    ///
+    /// let queue = VecDeque::from(vec!["job1".to_string()]);
+    /// assert_eq!(Some(&queue), ValueType::QueuePointer(queue.clone()).as_queue());
+    /// assert_eq!(None, ValueType::RecordPointer("ok".to_string()).as_queue());
+    /// ```
+    pub fn as_queue(&self) -> Option<&VecDeque<String>> {
+        return match self {
+            Self::QueuePointer(queue) => Some(queue),
+            _ => None,
+        };
+    }
+}
+
+/// Records are shown as their text, numbers as decimal, queues as `[item1, item2]`, bytes as `<bytes: 4 bytes>`
+/// and tables as `<table: 2 entries>`
+///
+/// # Example
+/// ```
+/// use std::collections::VecDeque;
+/// use onlyati_datastore::datastore::enums::pair::ValueType;
+///
+/// assert_eq!("ok", ValueType::RecordPointer("ok".to_string()).to_string());
+///
+/// let queue = VecDeque::from(vec!["job1".to_string(), "job2".to_string()]);
+/// assert_eq!("[job1, job2]", ValueType::QueuePointer(queue).to_string());
+///
+/// assert_eq!("-5", ValueType::IntPointer(-5).to_string());
+/// assert_eq!("<bytes: 2 bytes>", ValueType::BytesPointer(vec![0x01, 0xff]).to_string());
+/// ```
+impl Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Self::RecordPointer(value) => write!(f, "{}", value),
+            Self::QueuePointer(queue) => {
+                let items: Vec<&str> = queue.iter().map(|x| &x[..]).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Self::TablePointer(table) => write!(f, "<table: {} entries>", table.len()),
+            Self::IntPointer(value) => write!(f, "{}", value),
+            Self::BytesPointer(value) => write!(f, "<bytes: {} bytes>", value.len()),
         };
     }
 }
@@ -1,16 +1,46 @@
 //! Main component
+//!
+//! ## Tracing fields
+//!
+//! Beside the message, the key events of the database operations are emitted with structured fields,
+//! so they are machine-parseable with a JSON tracing subscriber. The field names are stable:
+//! - `op` - name of operation: `set`, `get`, `push`, `pop`, `delete_key`, `delete_table`, `create_table`, `rename`, `trigger`, `list_keys`, `count_keys`, `transaction`
+//! - `key` - key of the operation
+//! - `result` - `ok` if the operation was successful, else `error`
+//!
+//! `Database` methods emit them on trace level, the built-in thread server (`utilities::start_datastore`)
+//! emits one debug event with the same fields for every processed request.
 
-use std::{collections::VecDeque, sync::mpsc::Sender};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    io::Write,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::Sender,
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
+pub mod client;
 pub mod enums;
+pub mod shared;
 pub mod types;
 pub mod utilities;
 
-use crate::{hook::enums::HookManagerAction, logger::enums::LoggerAction};
+use crate::{
+    config::Config,
+    hook::enums::{HookEvent, HookManagerAction},
+    logger::enums::{LogItem, LoggerAction},
+};
 
 use self::{
-    enums::{error::ErrorKind, pair::KeyType, pair::ValueType, ListType},
-    types::Table,
+    enums::{
+        error::ErrorKind, pair::KeyType, pair::ValueType, DropPolicy, InsertMode, ListType,
+        MergePolicy, Source, ChangeKind, KeyValidation, TxnOp,
+    },
+    types::{ChangeEvent, DatabaseSnapshot, SizeReport, Loader, RecordMeta, SnapshotTable, Table},
 };
 
 /// Database struct
@@ -26,6 +56,45 @@ pub struct Database {
 
     /// Logger function
     logger_sender: Option<Sender<LoggerAction>>,
+
+    /// Number of reads per key, only allocated if it is enabled
+    access_counters: Option<Mutex<HashMap<String, u64>>>,
+
+    /// Read-through loader for missing keys
+    loader: Option<Loader>,
+
+    /// Version of records that were written by `insert_versioned`, every later insert increase it
+    versions: HashMap<String, u64>,
+
+    /// Maximum number of entries that a list can return
+    max_list_results: Option<usize>,
+
+    /// Maximum size of a record or a queue in bytes
+    max_value_bytes: Option<usize>,
+
+    /// Capacity and drop policy of queues that were created by `push_bounded`
+    queue_limits: HashMap<String, (usize, DropPolicy)>,
+
+    /// Expiry time of records that were written by `insert_with_ttl`
+    expirations: HashMap<String, Instant>,
+
+    /// Metadata of records, it is kept beside the tables so the values are compared on the same way like before
+    metadata: HashMap<String, RecordMeta>,
+
+    /// Number of requests that were sent to the hook manager
+    hook_sends: AtomicU64,
+
+    /// Alert the hook manager on every write, else only `trigger` does
+    auto_hooks: bool,
+
+    /// Subscribers of key changes with their prefix
+    watchers: Vec<(String, Sender<ChangeEvent>)>,
+
+    /// How the keys of new records, queues and tables are checked
+    key_validation: KeyValidation,
+
+    /// Keys are converted to lowercase before routing
+    case_insensitive_keys: bool,
 }
 
 impl Database {
@@ -55,9 +124,174 @@ impl Database {
             root: Table::new(),
             hook_sender: None,
             logger_sender: None,
+            access_counters: None,
+            loader: None,
+            versions: HashMap::new(),
+            max_list_results: None,
+            max_value_bytes: None,
+            queue_limits: HashMap::new(),
+            expirations: HashMap::new(),
+            metadata: HashMap::new(),
+            hook_sends: AtomicU64::new(0),
+            auto_hooks: true,
+            watchers: Vec::new(),
+            key_validation: KeyValidation::Lenient,
+            case_insensitive_keys: false,
         });
     }
 
+    /// Create new database based on configuration and return with the struct.
+    ///
+    /// # Arguments
+    /// 1. `root_name` - Name of database
+    /// 1. `config` - Configuration of database
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::config::Builder;
+    /// use onlyati_datastore::datastore::Database;
+    ///
+    /// let config = Builder::new().access_counters(true).build();
+    /// let db = Database::from_config("root".to_string(), &config).unwrap();
+    /// ```
+    pub fn from_config(root_name: String, config: &Config) -> Result<Self, ErrorKind> {
+        let mut db = Database::new(root_name)?;
+
+        if config.access_counters() {
+            db.enable_access_counters();
+        }
+
+        if let Some(loader) = config.loader() {
+            db.loader = Some(loader.clone());
+        }
+
+        db.set_max_list_results(config.max_list_results());
+        db.set_max_value_bytes(config.max_value_bytes());
+        db.set_auto_hooks(config.auto_hooks());
+        db.set_key_validation(config.key_validation());
+        db.set_case_insensitive_keys(config.case_insensitive_keys());
+
+        return Ok(db);
+    }
+
+    /// Start to count the reads per key, it is needed for `hot_keys` function
+    pub fn enable_access_counters(&mut self) {
+        tracing::trace!("access counters are enabled");
+        if self.access_counters.is_none() {
+            self.access_counters = Some(Mutex::new(HashMap::new()));
+        }
+    }
+
+    /// Stop counting the reads per key and drop the collected counters
+    pub fn disable_access_counters(&mut self) {
+        tracing::trace!("access counters are disabled");
+        self.access_counters = None;
+    }
+
+    /// Set the maximum number of entries that `list_keys`, `list_tables`, `list_pairs` and `to_json` can return,
+    /// `None` means unlimited. If the result would be larger, `ErrorKind::ResultTooLarge` is returned
+    /// without collecting the whole result.
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::KeyType, pair::ValueType, ListType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.set_max_list_results(Some(1));
+    ///
+    /// db.insert(KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("OK".to_string())).unwrap();
+    /// db.insert(KeyType::Record("/root/status/sub2".to_string()), ValueType::RecordPointer("OK".to_string())).unwrap();
+    ///
+    /// let result = db.list_keys(KeyType::Record("/root".to_string()), ListType::All);
+    /// assert_eq!(true, result.is_err());
+    /// ```
+    pub fn set_max_list_results(&mut self, limit: Option<usize>) {
+        tracing::trace!("maximum list results is set to {:?}", limit);
+        self.max_list_results = limit;
+    }
+
+    /// Set the maximum size of values in bytes, `None` means unlimited and it is the default.
+    /// Text and binary records are checked at `insert`, queues are checked at `push` with the items
+    /// that are already in the queue. If the value would be larger, `ErrorKind::ValueTooLarge` is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.set_max_value_bytes(Some(4));
+    ///
+    /// let result = db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("OK".to_string()));
+    /// assert_eq!(true, result.is_ok());
+    ///
+    /// let result = db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("NOT OK".to_string()));
+    /// assert_eq!(true, result.is_err());
+    ///
+    /// db.push(KeyType::Record("/root/jobs".to_string()), "ab".to_string()).unwrap();
+    /// db.push(KeyType::Record("/root/jobs".to_string()), "cd".to_string()).unwrap();
+    /// assert_eq!(true, db.push(KeyType::Record("/root/jobs".to_string()), "e".to_string()).is_err());
+    /// ```
+    pub fn set_max_value_bytes(&mut self, max: Option<usize>) {
+        tracing::trace!("maximum value bytes is set to {:?}", max);
+        self.max_value_bytes = max;
+    }
+
+    /// Check the size of value against `max_value_bytes`, tables and numbers are not limited
+    fn check_value_size(&self, value: &ValueType) -> Result<(), ErrorKind> {
+        let max = match self.max_value_bytes {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+
+        let size = match value {
+            ValueType::RecordPointer(value) => value.len(),
+            ValueType::BytesPointer(value) => value.len(),
+            ValueType::QueuePointer(queue) => queue.iter().map(|x| x.len()).sum(),
+            ValueType::TablePointer(_) | ValueType::IntPointer(_) => return Ok(()),
+        };
+
+        if size > max {
+            tracing::trace!("value with {} bytes is larger than the maximum {}", size, max);
+            return Err(ErrorKind::ValueTooLarge { size, max });
+        }
+
+        return Ok(());
+    }
+
+    /// Set a read-through loader. When `get_or_load` does not find the key, the loader is called
+    /// and the returned value is stored and returned. With the built-in thread server the loader runs
+    /// inside the database thread and blocks every other request, so it should be fast.
+    ///
+    /// # Arguments
+    /// 1. `loader` - Function that return with the value of missing key or `None`
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.set_loader(|key| Some(format!("loaded {}", key)));
+    ///
+    /// let value = db.get_or_load(KeyType::Record("/root/status".to_string())).unwrap();
+    /// assert_eq!(ValueType::RecordPointer("loaded /root/status".to_string()), value);
+    /// ```
+    pub fn set_loader<F>(&mut self, loader: F)
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        tracing::trace!("read-through loader is set");
+        self.loader = Some(Arc::new(loader));
+    }
+
+    /// Remove the read-through loader
+    pub fn clear_loader(&mut self) {
+        tracing::trace!("read-through loader is removed");
+        self.loader = None;
+    }
+
     /// Subscribe to HookManager
     ///
     /// # Arguments
@@ -74,6 +308,191 @@ impl Database {
         self.hook_sender = Some(sender);
     }
 
+    /// Set that writes (`insert`, `push`) alert the hook manager automatically, it is enabled by default.
+    /// If it is disabled, hooks are executed only by `trigger`.
+    ///
+    /// # Examples
+    /// ```
+    /// let (sender, _) = onlyati_datastore::hook::utilities::start_hook_manager();
+    /// let mut db = onlyati_datastore::datastore::Database::new("root".to_string()).unwrap();
+    /// db.subscribe_to_hook_manager(sender);
+    /// db.set_auto_hooks(false);
+    /// ```
+    pub fn set_auto_hooks(&mut self, enabled: bool) {
+        tracing::trace!("automatic hook alerts are set to {}", enabled);
+        self.auto_hooks = enabled;
+    }
+
+    /// Set how the keys of new records, queues and tables are checked, see `KeyValidation`.
+    /// Existing keys are not checked, they can be read and deleted.
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::{KeyType, ValueType}, KeyValidation};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.set_key_validation(KeyValidation::NoEmptySegments);
+    ///
+    /// let result = db.insert(KeyType::Record("/root//status".to_string()), ValueType::RecordPointer("ok".to_string()));
+    /// assert_eq!(true, result.is_err());
+    /// ```
+    pub fn set_key_validation(&mut self, validation: KeyValidation) {
+        tracing::trace!("key validation is set to {}", validation);
+        self.key_validation = validation;
+    }
+
+    /// Set that keys are converted to lowercase before routing, so `/root/Status` and `/root/status` are the same key.
+    /// It is applied on every read, write, delete and list. The root name is converted too, so the keys are still
+    /// matched with it.
+    ///
+    /// Existing keys are not converted: if it is enabled on a database that already has keys with uppercase
+    /// characters, those keys cannot be reached anymore and different keys can collide, e.g. `/root/A` and `/root/a`.
+    /// Enable it on an empty database, typically by `Builder::case_insensitive_keys`.
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.set_case_insensitive_keys(true);
+    ///
+    /// db.insert(KeyType::Record("/root/Status".to_string()), ValueType::RecordPointer("ok".to_string())).unwrap();
+    /// assert_eq!(ValueType::RecordPointer("ok".to_string()), db.get(KeyType::Record("/ROOT/status".to_string())).unwrap());
+    /// ```
+    pub fn set_case_insensitive_keys(&mut self, enabled: bool) {
+        tracing::trace!("case-insensitive keys are set to {}", enabled);
+        self.case_insensitive_keys = enabled;
+
+        let name = self.name.to_lowercase();
+        if enabled && name != self.name {
+            if let Some(table) = self.root.remove(&KeyType::Table(self.name.clone())) {
+                self.root.insert(KeyType::Table(name.clone()), table);
+            }
+            self.name = name;
+        }
+    }
+
+    /// Normalize the key before it is routed: it is converted to lowercase if keys are case-insensitive,
+    /// see `set_case_insensitive_keys`. Every entry point that receives a key calls it first.
+    pub(crate) fn normalize_key(&self, key: KeyType) -> KeyType {
+        if !self.case_insensitive_keys {
+            return key;
+        }
+        let path = self.normalize_path(key.get_key());
+        return utilities::internal::with_key(&key, path);
+    }
+
+    /// Normalize a path or prefix that is not a `KeyType` on the same way like `normalize_key`
+    pub(crate) fn normalize_path(&self, path: &str) -> String {
+        if !self.case_insensitive_keys {
+            return path.to_string();
+        }
+        return path.to_lowercase();
+    }
+
+    /// Validate the key of a new record, queue or table, including the configured key validation
+    fn validate_new_key<'a>(&self, key: &'a str) -> Result<Vec<&'a str>, ErrorKind> {
+        let key_routes = utilities::internal::validate_key(key, &self.name)?;
+        utilities::internal::check_key_segments(key, self.key_validation)?;
+        return Ok(key_routes);
+    }
+
+    /// Return with the hook manager sender if writes have to alert it
+    fn auto_hook_sender(&self) -> Option<&Sender<HookManagerAction>> {
+        if !self.auto_hooks {
+            return None;
+        }
+        return self.hook_sender.as_ref();
+    }
+
+    /// Tell the hook manager that a key is written, if writes alert it automatically
+    pub(crate) fn alert_set(&self, key: &str, value: &str) {
+        let key = &self.normalize_path(key);
+        if let Some(sender) = self.auto_hook_sender() {
+            tracing::trace!("send alert to hook manager about '{}' key", key);
+            let action = HookManagerAction::Send(None, key.to_string(), value.to_string());
+            self.hook_sends.fetch_add(1, Ordering::Relaxed);
+
+            sender
+                .send(action)
+                .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
+        }
+    }
+
+    /// Tell the hook manager that a key is deleted, if writes alert it automatically
+    pub(crate) fn alert_delete(&self, key: &str) {
+        let key = &self.normalize_path(key);
+        if let Some(sender) = self.auto_hook_sender() {
+            tracing::trace!("send delete alert to hook manager about '{}' key", key);
+            let action = HookManagerAction::SendEvent(None, key.to_string(), None, HookEvent::Delete);
+            self.hook_sends.fetch_add(1, Ordering::Relaxed);
+
+            sender
+                .send(action)
+                .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
+        }
+    }
+
+    /// Subscribe to the changes of keys under a prefix, see `notify_watchers`
+    ///
+    /// # Arguments
+    /// 1. `prefix` - Table or key that is watched, e.g. `/root/status`
+    /// 1. `sender` - Channel where the changes are sent
+    ///
+    /// # Examples
+    /// ```
+    /// let (tx, rx) = std::sync::mpsc::channel();
+    /// let mut db = onlyati_datastore::datastore::Database::new("root".to_string()).unwrap();
+    /// db.watch("/root/status".to_string(), tx);
+    /// ```
+    pub fn watch(&mut self, prefix: String, sender: Sender<ChangeEvent>) {
+        tracing::trace!("subscribe watcher to '{}' prefix", prefix);
+        let prefix = self.normalize_path(&prefix);
+        self.watchers.push((prefix, sender));
+    }
+
+    /// Send the change to the watchers whose prefix covers the key, watchers that are gone are dropped
+    pub(crate) fn notify_watchers(&mut self, key: &str, kind: ChangeKind, value: Option<String>) {
+        let key = &self.normalize_path(key);
+        self.watchers.retain(|(prefix, sender)| {
+            let covered = match key.strip_prefix(prefix.as_str()) {
+                Some(rest) => rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/'),
+                None => false,
+            };
+            if !covered {
+                return true;
+            }
+
+            let event = ChangeEvent {
+                key: key.to_string(),
+                kind,
+                value: value.clone(),
+            };
+            match sender.send(event) {
+                Ok(_) => return true,
+                Err(_) => {
+                    tracing::trace!("watcher of '{}' prefix is gone, drop it", prefix);
+                    return false;
+                }
+            }
+        });
+    }
+
+    /// Tell the hook manager to remove the hooks under a deleted table, so they do not remain registered.
+    /// Return with the log item of the removal if the database is subscribed to a hook manager.
+    pub(crate) fn clear_hooks_under(&self, key: &str) -> Option<LogItem> {
+        let key = self.normalize_path(key);
+        let sender = self.hook_sender.as_ref()?;
+
+        tracing::trace!("send hook removal to hook manager under '{}' table", key);
+        sender
+            .send(HookManagerAction::RemoveUnderPrefix(None, key.clone()))
+            .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
+        return Some(LogItem::RemHooksUnder(key));
+    }
+
     /// Subscribe to Logger
     ///
     /// # Arguments
@@ -107,9 +526,11 @@ impl Database {
     /// let result = db.insert(KeyType::Record("/root/network/dns-stats".to_string()), ValueType::RecordPointer("ok".to_string()));
     /// ```
     pub fn insert(&mut self, key: KeyType, value: ValueType) -> Result<(), ErrorKind> {
+        let key = self.normalize_key(key);
         tracing::trace!("set request is performed for '{}'", key.get_key());
 
-        let key_routes = utilities::internal::validate_key(key.get_key(), &self.name)?;
+        let key_routes = self.validate_new_key(key.get_key())?;
+        self.check_value_size(&value)?;
 
         let mut table = Box::new(&mut self.root);
         let last_route = key_routes[key_routes.len() - 1];
@@ -146,156 +567,2419 @@ impl Database {
 
         let record_key = KeyType::Record(last_route.to_string());
         table.insert(record_key, value.clone());
-        tracing::trace!("set request is done for '{}'", key.get_key());
 
-        if let Some(sender) = &self.hook_sender {
-            tracing::trace!("send alert to hook manager about '{}' key", key.get_key());
-            if let ValueType::RecordPointer(value) = &value {
-                let action = HookManagerAction::Send(key.get_key().to_string(), value.to_string());
+        if let Some(version) = self.versions.get_mut(&format!("/{}", key_routes.join("/"))) {
+            *version += 1;
+        }
+        self.expirations.remove(&format!("/{}", key_routes.join("/")));
+        self.touch_meta(format!("/{}", key_routes.join("/")));
+
+        tracing::trace!(op = "set", key = key.get_key(), result = "ok", "set request is done for '{}'", key.get_key());
 
-                sender
-                    .send(action)
-                    .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
-            }
+        // Binary records have no text form for the hooks
+        match &value {
+            ValueType::RecordPointer(value) => self.alert_set(key.get_key(), value),
+            ValueType::IntPointer(value) => self.alert_set(key.get_key(), &value.to_string()),
+            _ => (),
         }
 
         return Ok(());
     }
 
-    /// Push a value into a queue. Return with nothing if the insert was successful. Else with an error code.
+    /// Insert or update key into database according to the mode. Return with nothing if the insert was successful. Else with an error code.
     ///
     /// # Arguments
     /// 1. `key` - Unique key for data
-    /// 1. `value` - Value that will be pushed to queue
+    /// 1. `value` - Value that is assigned for the key
+    /// 1. `mode` - `InsertMode::CreateOnly` fails if key exists, `InsertMode::UpdateOnly` fails if key does not exist
     ///
     /// # Example
+    ///
     /// ```
     /// use onlyati_datastore::datastore::Database;
-    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    /// use onlyati_datastore::datastore::enums::{pair::KeyType, pair::ValueType, InsertMode};
     ///
     /// let mut db = Database::new("root".to_string()).unwrap();
     ///
-    /// let result = db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC100".to_string()).expect("Failed to push");
-    /// let result = db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC101".to_string()).expect("Failed to push");
+    /// let key = KeyType::Record("/root/network/dns-stats".to_string());
+    /// let result = db.insert_with_mode(key.clone(), ValueType::RecordPointer("ok".to_string()), InsertMode::UpdateOnly);
+    /// assert_eq!(true, result.is_err());
+    ///
+    /// let result = db.insert_with_mode(key.clone(), ValueType::RecordPointer("ok".to_string()), InsertMode::CreateOnly);
+    /// assert_eq!(true, result.is_ok());
+    ///
+    /// let result = db.insert_with_mode(key.clone(), ValueType::RecordPointer("ok".to_string()), InsertMode::CreateOnly);
+    /// assert_eq!(true, result.is_err());
     /// ```
-    pub fn push(&mut self, key: KeyType, value: String) -> Result<(), ErrorKind> {
-        tracing::trace!("push request is performed for '{}'", key.get_key());
-        let key = match key {
-            KeyType::Record(key) => key,
-            _ => {
-                return Err(ErrorKind::InvalidKey(
-                    "Parameter must be a Record type".to_string(),
-                ));
-            }
-        };
-
-        let key_routes = utilities::internal::validate_key(&key[..], &self.name)?;
-
-        let mut table = Box::new(&mut self.root);
-        let last_route = key_routes[key_routes.len() - 1];
-        let mut route_index: usize = 0;
-        let mut current_route = key_routes[route_index].to_string();
-
-        while last_route != current_route {
-            let temp_key = KeyType::Table(current_route);
-            table
-                .entry(temp_key.clone())
-                .or_insert(ValueType::TablePointer(Table::new()));
+    pub fn insert_with_mode(
+        &mut self,
+        key: KeyType,
+        value: ValueType,
+        mode: InsertMode,
+    ) -> Result<(), ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("set request with {} mode is performed for '{}'", mode, key.get_key());
 
-            *table = match table.get_mut(&temp_key) {
-                Some(item) => match item {
-                    ValueType::TablePointer(sub_table) => sub_table,
-                    _ => {
-                        tracing::error!("wow, this should not happen a table pointer should be here not a record pointer");
-                        return Err(ErrorKind::InternalError(
-                            "This should not have happen".to_string(),
-                        ));
-                    }
-                },
-                _ => {
-                    tracing::error!("wow, this should not happen table must exist");
-                    return Err(ErrorKind::InternalError(
-                        "This should not have happen".to_string(),
+        match mode {
+            InsertMode::Upsert => (),
+            InsertMode::CreateOnly => {
+                if self.lookup(&key).is_some() {
+                    tracing::trace!("key '{}' already exists", key.get_key());
+                    return Err(ErrorKind::InvalidKey(
+                        "Specified key already exists".to_string(),
                     ));
                 }
-            };
-
-            route_index += 1;
-            current_route = key_routes[route_index].to_string();
-        }
-
-        match table.get_mut(&KeyType::Queue(last_route.to_string())) {
-            Some(elem) => match elem {
-                ValueType::QueuePointer(queue) => {
-                    queue.push_back(value.clone());
-                    tracing::trace!("push request is done for '{}'", key);
-
-                    if let Some(sender) = &self.hook_sender {
-                        tracing::trace!("send alert to hook manager about '{}' key", key);
-                        let action = HookManagerAction::Send(key, value.clone());
-
-                        sender
-                            .send(action)
-                            .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
-                    }
-                }
-                _ => {
-                    tracing::trace!("queue '{}' does not exist", key);
-                    return Err(ErrorKind::InvalidKey(
+            }
+            InsertMode::UpdateOnly => {
+                if self.lookup(&key).is_none() {
+                    tracing::trace!("key '{}' does not exist", key.get_key());
+                    return Err(ErrorKind::NotFound(
                         "Specified key does not exist".to_string(),
                     ));
                 }
-            },
-            None => {
-                let new_qeue = KeyType::Queue(last_route.to_string());
-                let mut queue = VecDeque::new();
-                queue.push_back(value);
-                table.insert(new_qeue, ValueType::QueuePointer(queue));
             }
         }
 
-        return Ok(());
+        return self.insert(key, value);
     }
 
-    /// Send a trigger to HookManager, record is not created like at `insert` but it can trigger and send some hooks out
+    /// Insert or update a record only if its current version matches with the expected one. Return with the new version.
     ///
-    /// # Arguments
+    /// Version of a missing record is 0, a record that was never written on versioned way has version 1.
+    /// Every successful write, including the plain `insert`, increases the version of the record.
+    /// Versions are kept after delete, so they never go backward.
     ///
+    /// # Arguments
     /// 1. `key` - Unique key for data
     /// 1. `value` - Value that is assigned for the key
+    /// 1. `expected_version` - Version that the client knows, `ErrorKind::VersionConflict` is returned if it is stale
     ///
-    /// # Examples
+    /// # Example
     ///
     /// ```
     /// use onlyati_datastore::datastore::Database;
     /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
     ///
     /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/counter".to_string());
     ///
-    /// let result = db.trigger(KeyType::Record("/root/network/dns-stats".to_string()), ValueType::RecordPointer("ok".to_string()));
+    /// let version = db.insert_versioned(key.clone(), ValueType::RecordPointer("1".to_string()), 0).unwrap();
+    /// assert_eq!(1, version);
+    ///
+    /// let version = db.insert_versioned(key.clone(), ValueType::RecordPointer("2".to_string()), version).unwrap();
+    /// assert_eq!(2, version);
+    ///
+    /// let result = db.insert_versioned(key.clone(), ValueType::RecordPointer("3".to_string()), 1);
+    /// assert_eq!(true, result.is_err());
     /// ```
-    pub fn trigger(&self, key: KeyType, value: ValueType) -> Result<(), ErrorKind> {
-        match &self.hook_sender {
-            Some(sender) => {
-                tracing::trace!("send trigger to hook manager about '{}' key", key.get_key());
-                if let ValueType::RecordPointer(value) = &value {
-                    let action =
-                        HookManagerAction::Send(key.get_key().to_string(), value.to_string());
+    pub fn insert_versioned(
+        &mut self,
+        key: KeyType,
+        value: ValueType,
+        expected_version: u64,
+    ) -> Result<u64, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!(
+            "versioned set request is performed for '{}' with version {}",
+            key.get_key(),
+            expected_version
+        );
+        let key_routes = utilities::internal::validate_key(key.get_key(), &self.name)?;
+        let version_key = format!("/{}", key_routes.join("/"));
+
+        let current_version = self.version(key.clone())?;
+        if current_version != expected_version {
+            tracing::trace!(
+                "versioned set request is failed due to version {} is expected but it is {}",
+                expected_version,
+                current_version
+            );
+            return Err(ErrorKind::VersionConflict(current_version));
+        }
+
+        // From now the version of record is tracked, so insert increases it
+        self.versions.entry(version_key.clone()).or_insert(current_version);
+        self.insert(key, value)?;
+
+        let new_version = self.versions[&version_key];
+        tracing::trace!("versioned set request is done, new version is {}", new_version);
+        return Ok(new_version);
+    }
+
+    /// Return with the current version of a record, it is 0 if the record does not exist.
+    /// See `insert_versioned` for details.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key of record
+    pub fn version(&self, key: KeyType) -> Result<u64, ErrorKind> {
+        let key = self.normalize_key(key);
+        let key_routes = utilities::internal::validate_key(key.get_key(), &self.name)?;
+        let version_key = format!("/{}", key_routes.join("/"));
+        let record_key = KeyType::Record(key.get_key().to_string());
+
+        match self.lookup(&record_key) {
+            Some(value) if value.belongs_to_record() => {
+                return Ok(*self.versions.get(&version_key).unwrap_or(&1));
+            }
+            _ => return Ok(0),
+        }
+    }
+
+    /// Insert a record that expires after the specified time. Expired record is handled as it would not exist,
+    /// it is physically removed by `remove_expired`, that is called periodically by `utilities::start_datastore`.
+    /// Later `insert` on the same key removes the expiry time, `rename` and `copy_prefix` keep it.
+    /// If `ttl` is too large to compute the expiry time, `ErrorKind::InvalidKey` is returned.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key for data
+    /// 1. `value` - Value of record
+    /// 1. `ttl` - Time while the record is alive
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/session/abc".to_string());
+    ///
+    /// db.insert_with_ttl(key.clone(), ValueType::RecordPointer("user1".to_string()), Duration::from_millis(50)).expect("Failed to insert");
+    /// assert_eq!(true, db.get(key.clone()).is_ok());
+    ///
+    /// std::thread::sleep(Duration::from_millis(100));
+    /// assert_eq!(true, db.get(key.clone()).is_err());
+    /// assert_eq!(vec![key], db.remove_expired());
+    /// ```
+    pub fn insert_with_ttl(&mut self, key: KeyType, value: ValueType, ttl: Duration) -> Result<(), ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("insert with ttl request is performed for '{}'", key.get_key());
+        if !matches!(key, KeyType::Record(_)) {
+            return Err(ErrorKind::InvalidKey(
+                "Parameter must be a Record type".to_string(),
+            ));
+        }
+        let key_routes = utilities::internal::validate_key(key.get_key(), &self.name)?;
+        let expiry_key = format!("/{}", key_routes.join("/"));
+        let expiry = match Instant::now().checked_add(ttl) {
+            Some(expiry) => expiry,
+            None => {
+                return Err(ErrorKind::InvalidKey(
+                    "Time to live is too large".to_string(),
+                ));
+            }
+        };
+
+        self.insert(key, value)?;
+        self.expirations.insert(expiry_key, expiry);
+
+        return Ok(());
+    }
+
+    /// Delete the expired records and return with their keys.
+    pub fn remove_expired(&mut self) -> Vec<KeyType> {
+        let now = Instant::now();
+        let expired: Vec<String> = self
+            .expirations
+            .iter()
+            .filter(|(_, expiry)| **expiry <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        let mut removed = Vec::new();
+        for key in expired {
+            self.expirations.remove(&key);
+            let key = KeyType::Record(key);
+            if self.delete_key(key.clone()).is_ok() {
+                removed.push(key);
+            }
+        }
+
+        if !removed.is_empty() {
+            tracing::trace!("{} expired record removed", removed.len());
+        }
+        return removed;
+    }
+
+    /// Tells that the record on the route has expiry time that already passed
+    fn is_expired(&self, key_routes: &[&str]) -> bool {
+        if self.expirations.is_empty() {
+            return false;
+        }
+
+        return match self.expirations.get(&format!("/{}", key_routes.join("/"))) {
+            Some(expiry) => *expiry <= Instant::now(),
+            None => false,
+        };
+    }
+
+    /// Return with the creation and last modification time of a record.
+    ///
+    /// # Arguments
+    /// 1. `key` - Key of record
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/status".to_string());
+    ///
+    /// db.insert(key.clone(), ValueType::RecordPointer("starting".to_string())).expect("Failed to insert");
+    /// db.insert(key.clone(), ValueType::RecordPointer("running".to_string())).expect("Failed to insert");
+    ///
+    /// let meta = db.get_meta(key).expect("Metadata not found");
+    /// assert_eq!(true, meta.created_at <= meta.updated_at);
+    /// ```
+    pub fn get_meta(&self, key: KeyType) -> Result<RecordMeta, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("get meta request is performed for '{}'", key.get_key());
+        if !matches!(key, KeyType::Record(_)) {
+            return Err(ErrorKind::InvalidKey(
+                "Parameter must be a Record type".to_string(),
+            ));
+        }
+        let key_routes = utilities::internal::validate_key(key.get_key(), &self.name)?;
+
+        if self.lookup(&key).is_none() {
+            tracing::trace!("get meta request is failed, '{}' does not exist", key.get_key());
+            return Err(ErrorKind::NotFound(
+                "Specified key does not exist".to_string(),
+            ));
+        }
+
+        match self.metadata.get(&format!("/{}", key_routes.join("/"))) {
+            Some(meta) => return Ok(meta.clone()),
+            None => {
+                tracing::error!("wow, this should not happen metadata must exist for '{}'", key.get_key());
+                return Err(ErrorKind::InternalError(
+                    "This should not have happen".to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Insert more records at once. Every key is validated before the first insert, so an invalid key does not
+    /// leave a partial result. If an insert fails anyway then the earlier pairs remain inserted.
+    /// Error is `ErrorKind::BatchFailed` that contains the index of failed pair.
+    ///
+    /// # Arguments
+    /// 1. `pairs` - Key and value pairs that have to be inserted
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// let pairs = vec![
+    ///     (KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("ok".to_string())),
+    ///     (KeyType::Record("/root/status/sub2".to_string()), ValueType::RecordPointer("nok".to_string())),
+    /// ];
+    /// db.insert_many(pairs).expect("Failed to insert");
+    ///
+    /// let pairs = vec![
+    ///     (KeyType::Record("/root/status/sub3".to_string()), ValueType::RecordPointer("ok".to_string())),
+    ///     (KeyType::Record("/other/status".to_string()), ValueType::RecordPointer("ok".to_string())),
+    /// ];
+    /// assert_eq!(true, db.insert_many(pairs).is_err());
+    /// assert_eq!(true, db.get(KeyType::Record("/root/status/sub3".to_string())).is_err());
+    /// ```
+    pub fn insert_many(&mut self, pairs: Vec<(KeyType, ValueType)>) -> Result<(), ErrorKind> {
+        tracing::trace!("insert many request is performed for {} pairs", pairs.len());
+        let pairs: Vec<(KeyType, ValueType)> = pairs
+            .into_iter()
+            .map(|(key, value)| (self.normalize_key(key), value))
+            .collect();
+        for (index, (key, value)) in pairs.iter().enumerate() {
+            if let Err(e) = self.validate_new_key(key.get_key()) {
+                return Err(ErrorKind::BatchFailed { index, error: Box::new(e) });
+            }
+            if let Err(e) = self.check_value_size(value) {
+                return Err(ErrorKind::BatchFailed { index, error: Box::new(e) });
+            }
+        }
+
+        for (index, (key, value)) in pairs.into_iter().enumerate() {
+            if let Err(e) = self.insert(key, value) {
+                tracing::trace!("insert many request is failed at {}", index);
+                return Err(ErrorKind::BatchFailed { index, error: Box::new(e) });
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Push a value into a queue. Return with nothing if the insert was successful. Else with an error code.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key for data
+    /// 1. `value` - Value that will be pushed to queue
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// let result = db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC100".to_string()).expect("Failed to push");
+    /// let result = db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC101".to_string()).expect("Failed to push");
+    /// ```
+    pub fn push(&mut self, key: KeyType, value: String) -> Result<(), ErrorKind> {
+        self.push_limited(key, value, None)?;
+        return Ok(());
+    }
+
+    /// Push a value into a queue which has a maximum capacity. Return with the number of items that were dropped
+    /// from the front of queue to make room for the new one.
+    ///
+    /// The capacity and the policy are stored if the queue is created by this call, later `push` calls on the
+    /// same queue honor them too, until the queue is emptied and removed. If the queue already exists then
+    /// the specified capacity and policy are enforced for this push only:
+    /// an existing unbounded queue remains unbounded for `push` and a bounded one keeps its original settings.
+    /// If the queue already holds more items than the capacity then `DropPolicy::DropOldest` drops the surplus
+    /// and `DropPolicy::RejectNew` rejects the value.
+    ///
+    /// The capacity itself is not written to the log, only the dropped items.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key for data
+    /// 1. `value` - Value that will be pushed to queue
+    /// 1. `max_len` - Maximum number of items in the queue
+    /// 1. `policy` - What happens if the queue is full
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::KeyType, DropPolicy};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/events".to_string());
+    ///
+    /// db.push_bounded(key.clone(), "event1".to_string(), 2, DropPolicy::DropOldest).expect("Failed to push");
+    /// db.push(key.clone(), "event2".to_string()).expect("Failed to push");
+    ///
+    /// let dropped = db.push_bounded(key.clone(), "event3".to_string(), 2, DropPolicy::DropOldest).expect("Failed to push");
+    /// assert_eq!(1, dropped);
+    ///
+    /// let result = db.push_bounded(key.clone(), "event4".to_string(), 2, DropPolicy::RejectNew);
+    /// assert_eq!(true, result.is_err());
+    ///
+    /// assert_eq!(Some("event2".to_string()), db.pop(key.clone()).unwrap());
+    /// ```
+    pub fn push_bounded(
+        &mut self,
+        key: KeyType,
+        value: String,
+        max_len: usize,
+        policy: DropPolicy,
+    ) -> Result<usize, ErrorKind> {
+        return self.push_limited(key, value, Some((max_len, policy)));
+    }
+
+    /// Push a value into queue by honoring the specified or the stored capacity, return with the number of dropped items
+    pub(crate) fn push_limited(
+        &mut self,
+        key: KeyType,
+        value: String,
+        limit: Option<(usize, DropPolicy)>,
+    ) -> Result<usize, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("push request is performed for '{}'", key.get_key());
+        let key = match key {
+            KeyType::Record(key) => key,
+            _ => {
+                return Err(ErrorKind::InvalidKey(
+                    "Parameter must be a Record type".to_string(),
+                ));
+            }
+        };
+
+        let key_routes = self.validate_new_key(&key[..])?;
+        let limit_key = format!("/{}", key_routes.join("/"));
+        let stored_limit = self.queue_limits.get(&limit_key).cloned();
+        let max_value_bytes = self.max_value_bytes;
+
+        // Item alone is too large, tables of the path are not created
+        if let Some(max) = max_value_bytes {
+            if value.len() > max {
+                tracing::trace!(op = "push", key = %key, result = "error", "queue '{}' would be {} bytes", key, value.len());
+                return Err(ErrorKind::ValueTooLarge { size: value.len(), max });
+            }
+        }
+
+        let mut table = Box::new(&mut self.root);
+        let last_route = key_routes[key_routes.len() - 1];
+        let mut route_index: usize = 0;
+        let mut current_route = key_routes[route_index].to_string();
+
+        while last_route != current_route {
+            let temp_key = KeyType::Table(current_route);
+            table
+                .entry(temp_key.clone())
+                .or_insert(ValueType::TablePointer(Table::new()));
+
+            *table = match table.get_mut(&temp_key) {
+                Some(item) => match item {
+                    ValueType::TablePointer(sub_table) => sub_table,
+                    _ => {
+                        tracing::error!("wow, this should not happen a table pointer should be here not a record pointer");
+                        return Err(ErrorKind::InternalError(
+                            "This should not have happen".to_string(),
+                        ));
+                    }
+                },
+                _ => {
+                    tracing::error!("wow, this should not happen table must exist");
+                    return Err(ErrorKind::InternalError(
+                        "This should not have happen".to_string(),
+                    ));
+                }
+            };
+
+            route_index += 1;
+            current_route = key_routes[route_index].to_string();
+        }
+
+        if table.contains_key(&KeyType::Record(last_route.to_string())) {
+            tracing::trace!(op = "push", key = %key, result = "error", "'{}' already holds a record", key);
+            return Err(ErrorKind::InvalidKey(
+                "Specified key already holds a record".to_string(),
+            ));
+        }
+
+        if let Some((max_len, _)) = limit {
+            if max_len == 0 {
+                tracing::trace!(op = "push", key = %key, result = "error", "queue '{}' has zero capacity", key);
+                return Err(ErrorKind::QueueFull);
+            }
+        }
+
+        let mut dropped: usize = 0;
+        match table.get_mut(&KeyType::Queue(last_route.to_string())) {
+            Some(elem) => match elem {
+                ValueType::QueuePointer(queue) => {
+                    let mut to_drop: usize = 0;
+                    if let Some((max_len, policy)) = limit.or(stored_limit) {
+                        if queue.len() >= max_len {
+                            if policy == DropPolicy::RejectNew {
+                                tracing::trace!(op = "push", key = %key, result = "error", "queue '{}' is full", key);
+                                return Err(ErrorKind::QueueFull);
+                            }
+                            to_drop = queue.len() + 1 - max_len;
+                        }
+                    }
+
+                    // Items that would be dropped do not count, queue is not changed if the push is rejected
+                    if let Some(max) = max_value_bytes {
+                        let size = queue.iter().skip(to_drop).map(|x| x.len()).sum::<usize>() + value.len();
+                        if size > max {
+                            tracing::trace!(op = "push", key = %key, result = "error", "queue '{}' would be {} bytes", key, size);
+                            return Err(ErrorKind::ValueTooLarge { size, max });
+                        }
+                    }
+
+                    if to_drop > 0 {
+                        dropped = to_drop;
+                        queue.drain(..dropped);
+                        tracing::trace!("{} item dropped from '{}' queue", dropped, key);
+                    }
+
+                    queue.push_back(value.clone());
+                    tracing::trace!(op = "push", key = %key, result = "ok", "push request is done for '{}'", key);
+
+                    if let Some(sender) = self.auto_hook_sender() {
+                        tracing::trace!("send alert to hook manager about '{}' key", key);
+                        let action = HookManagerAction::Send(None, key, value.clone());
+                        self.hook_sends.fetch_add(1, Ordering::Relaxed);
+
+                        sender
+                            .send(action)
+                            .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
+                    }
+                }
+                _ => {
+                    tracing::trace!(op = "push", key = %key, result = "error", "queue '{}' does not exist", key);
+                    return Err(ErrorKind::NotFound(
+                        "Specified key does not exist".to_string(),
+                    ));
+                }
+            },
+            None => {
+                let new_qeue = KeyType::Queue(last_route.to_string());
+                let mut queue = VecDeque::new();
+                queue.push_back(value);
+                table.insert(new_qeue, ValueType::QueuePointer(queue));
+
+                // Capacity belongs to the queue that is created now, not to a former one on the same key
+                match limit {
+                    Some(limit) => self.queue_limits.insert(limit_key, limit),
+                    None => self.queue_limits.remove(&limit_key),
+                };
+            }
+        }
+
+        return Ok(dropped);
+    }
+
+    /// Send a trigger to HookManager, record is not created like at `insert` but it can trigger and send some hooks out
+    ///
+    /// # Arguments
+    ///
+    /// 1. `key` - Unique key for data
+    /// 1. `value` - Value that is assigned for the key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// let result = db.trigger(KeyType::Record("/root/network/dns-stats".to_string()), ValueType::RecordPointer("ok".to_string()));
+    /// ```
+    pub fn trigger(&self, key: KeyType, value: ValueType) -> Result<(), ErrorKind> {
+        let key = self.normalize_key(key);
+        match &self.hook_sender {
+            Some(sender) => {
+                tracing::trace!("send trigger to hook manager about '{}' key", key.get_key());
+                let value = match &value {
+                    ValueType::RecordPointer(value) => Some(value.to_string()),
+                    ValueType::IntPointer(value) => Some(value.to_string()),
+                    _ => None,
+                };
+                if let Some(value) = value {
+                    let action = HookManagerAction::Send(None, key.get_key().to_string(), value);
+                    self.hook_sends.fetch_add(1, Ordering::Relaxed);
 
                     sender
                         .send(action)
                         .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
                 }
-                return Ok(());
+                return Ok(());
+            }
+            None => return Err(ErrorKind::InactiveHookManager),
+        }
+    }
+
+    /// Get the value of a key and return with a copy of it. If not found return with error.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key that has to be found
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("Having a great time".to_string())).expect("Failed to insert");
+    /// let value = db.get(KeyType::Record("/root/status".to_string())).expect("Key not found");
+    /// ```
+    pub fn get(&self, key: KeyType) -> Result<ValueType, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("get request is performed for '{}'", key.get_key());
+        let key = match key {
+            KeyType::Record(key) => key,
+            _ => {
+                return Err(ErrorKind::InvalidKey(
+                    "Parameter must be a Record type".to_string(),
+                ));
+            }
+        };
+
+        let key_routes = utilities::internal::validate_key(&key[..], &self.name)?;
+        if self.is_expired(&key_routes) {
+            tracing::trace!(op = "get", key = %key, result = "error", "key '{}' is expired", key);
+            return Err(ErrorKind::NotFound(
+                "Specified key does not exist".to_string(),
+            ));
+        }
+
+        let table = match utilities::internal::find_table(&self.root,
+            key_routes[..key_routes.len() - 1].to_vec(),
+        ) {
+            Some(table) => table,
+            None => {
+                tracing::trace!(op = "get", key = %key, result = "error", "key '{}' does not exist", key);
+                return Err(ErrorKind::NotFound(
+                    "Specified key does not exist".to_string(),
+                ));
+            }
+        };
+
+        let find_key = KeyType::Record(key_routes[key_routes.len() - 1].to_string());
+
+        match table.get(&find_key) {
+            Some(value) => {
+                tracing::trace!(op = "get", key = %key, result = "ok", "get request is done for '{}'", key);
+                if let Some(counters) = &self.access_counters {
+                    match counters.lock() {
+                        Ok(mut counters) => *counters.entry(key.clone()).or_insert(0) += 1,
+                        Err(e) => tracing::error!("failed to lock access counters: {}", e),
+                    }
+                }
+                return Ok(value.clone());
+            }
+            None => {
+                tracing::trace!(op = "get", key = %key, result = "error", "key '{}' does not exist", key);
+                return Err(ErrorKind::NotFound(
+                    "Specified key does not exist".to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Get the value of a key like `get`, but if it is missing and read-through loader is set
+    /// then the loader is called and the loaded value is stored and returned.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key that has to be found
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// // Without loader it works like get
+    /// let value = db.get_or_load(KeyType::Record("/root/status".to_string()));
+    /// assert_eq!(true, value.is_err());
+    /// ```
+    pub fn get_or_load(&mut self, key: KeyType) -> Result<ValueType, ErrorKind> {
+        return self.get_or_load_with_source(key).map(|(value, _)| value);
+    }
+
+    /// Get the value of more keys at once. Every key is handled like at `get_or_load` and independently,
+    /// so a missing key does not affect the others.
+    ///
+    /// # Arguments
+    /// 1. `keys` - Keys that have to be found
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    ///
+    /// let results = db.get_many(vec![
+    ///     KeyType::Record("/root/status".to_string()),
+    ///     KeyType::Record("/root/missing".to_string()),
+    /// ]);
+    ///
+    /// assert_eq!(ValueType::RecordPointer("ok".to_string()), *results[0].1.as_ref().unwrap());
+    /// assert_eq!(true, results[1].1.is_err());
+    /// ```
+    pub fn get_many(&mut self, keys: Vec<KeyType>) -> Vec<(KeyType, Result<ValueType, ErrorKind>)> {
+        tracing::trace!("get many request is performed for {} keys", keys.len());
+        return keys
+            .into_iter()
+            .map(|key| {
+                let result = self.get_or_load(key.clone());
+                return (key, result);
+            })
+            .collect();
+    }
+
+    /// Same like `get_or_load` but it also tells that the value was found in database or it was loaded.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key that has to be found
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::KeyType, Source};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.set_loader(|_| Some("loaded".to_string()));
+    ///
+    /// let (_, source) = db.get_or_load_with_source(KeyType::Record("/root/status".to_string())).unwrap();
+    /// assert_eq!(Source::Loader, source);
+    ///
+    /// let (_, source) = db.get_or_load_with_source(KeyType::Record("/root/status".to_string())).unwrap();
+    /// assert_eq!(Source::Cache, source);
+    /// ```
+    pub fn get_or_load_with_source(&mut self, key: KeyType) -> Result<(ValueType, Source), ErrorKind> {
+        return self.fetch_with_source(key, true);
+    }
+
+    /// Same like `get_or_load_with_source`, but the loaded value is only stored if `store` is true
+    pub(crate) fn fetch_with_source(&mut self, key: KeyType, store: bool) -> Result<(ValueType, Source), ErrorKind> {
+        let key = self.normalize_key(key);
+        let error = match self.get(key.clone()) {
+            Ok(value) => return Ok((value, Source::Cache)),
+            Err(e) => e,
+        };
+
+        let loader = match (&self.loader, &key, &error) {
+            (Some(loader), KeyType::Record(_), ErrorKind::NotFound(_)) => loader.clone(),
+            _ => return Err(error),
+        };
+
+        tracing::trace!("call read-through loader for '{}'", key.get_key());
+        match loader(key.get_key()) {
+            Some(value) => {
+                let value = ValueType::RecordPointer(value);
+                if store {
+                    self.insert(key, value.clone())?;
+                }
+                return Ok((value, Source::Loader));
+            }
+            None => {
+                tracing::trace!("loader did not find value for '{}'", key.get_key());
+                return Err(error);
+            }
+        }
+    }
+
+    /// Return with the value of key, if it does not exist then insert the default value and return with it.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key that has to be found
+    /// 1. `default` - Value that is inserted if key does not exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/status".to_string());
+    ///
+    /// let value = db.get_or_insert(key.clone(), ValueType::RecordPointer("new".to_string())).unwrap();
+    /// assert_eq!(ValueType::RecordPointer("new".to_string()), value);
+    ///
+    /// let value = db.get_or_insert(key.clone(), ValueType::RecordPointer("other".to_string())).unwrap();
+    /// assert_eq!(ValueType::RecordPointer("new".to_string()), value);
+    /// ```
+    pub fn get_or_insert(&mut self, key: KeyType, default: ValueType) -> Result<ValueType, ErrorKind> {
+        tracing::trace!("get or insert request is performed for '{}'", key.get_key());
+        if !default.belongs_to_record() {
+            return Err(ErrorKind::InvalidKey(
+                "Default value must be a RecordPointer, IntPointer or BytesPointer type".to_string(),
+            ));
+        }
+
+        match self.get(key.clone()) {
+            Ok(value) => return Ok(value),
+            Err(ErrorKind::NotFound(_)) => (),
+            Err(e) => return Err(e),
+        }
+
+        self.insert(key, default.clone())?;
+        return Ok(default);
+    }
+
+    /// Write the record only if its current value is the expected one. Return with `true` if the value was written,
+    /// `false` if the precondition failed.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key of record
+    /// 1. `expected` - Expected current value, `None` means that the key must not exist, numeric records are compared by their text
+    /// 1. `new` - New value of record, it stays numeric if the record was numeric and the new value is an integer
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/lock/owner".to_string());
+    ///
+    /// assert_eq!(true, db.compare_and_swap(key.clone(), None, "node1".to_string()).unwrap());
+    /// assert_eq!(false, db.compare_and_swap(key.clone(), None, "node2".to_string()).unwrap());
+    /// assert_eq!(true, db.compare_and_swap(key.clone(), Some("node1".to_string()), "node2".to_string()).unwrap());
+    ///
+    /// assert_eq!(ValueType::RecordPointer("node2".to_string()), db.get(key).unwrap());
+    /// ```
+    pub fn compare_and_swap(&mut self, key: KeyType, expected: Option<String>, new: String) -> Result<bool, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("compare and swap request is performed for '{}'", key.get_key());
+        if !matches!(key, KeyType::Record(_)) {
+            return Err(ErrorKind::InvalidKey(
+                "Parameter must be a Record type".to_string(),
+            ));
+        }
+        utilities::internal::validate_key(key.get_key(), &self.name)?;
+
+        // Numeric record is compared by its text and remains numeric if the new value is a number
+        let (matched, native) = match (self.lookup(&key), &expected) {
+            (None, None) => (true, false),
+            (Some(ValueType::RecordPointer(current)), Some(expected)) => (current == expected, false),
+            (Some(ValueType::IntPointer(current)), Some(expected)) => (&current.to_string() == expected, true),
+            _ => (false, false),
+        };
+
+        if !matched {
+            tracing::trace!("compare and swap precondition is failed for '{}'", key.get_key());
+            return Ok(false);
+        }
+
+        let value = match (native, new.parse::<i64>()) {
+            (true, Ok(number)) => ValueType::IntPointer(number),
+            _ => ValueType::RecordPointer(new),
+        };
+        self.insert(key, value)?;
+        return Ok(true);
+    }
+
+    /// Insert the value and return with the previous value of key if it existed.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key of record
+    /// 1. `value` - New value of record
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/cluster/leader".to_string());
+    ///
+    /// let previous = db.get_set(key.clone(), ValueType::RecordPointer("node1".to_string())).unwrap();
+    /// assert_eq!(None, previous);
+    ///
+    /// let previous = db.get_set(key.clone(), ValueType::RecordPointer("node2".to_string())).unwrap();
+    /// assert_eq!(Some(ValueType::RecordPointer("node1".to_string())), previous);
+    /// ```
+    pub fn get_set(&mut self, key: KeyType, value: ValueType) -> Result<Option<ValueType>, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("get set request is performed for '{}'", key.get_key());
+        utilities::internal::validate_key(key.get_key(), &self.name)?;
+
+        let previous = self.lookup(&key).cloned();
+        self.insert(key, value)?;
+
+        return Ok(previous);
+    }
+
+    /// Pop value from queue. If queue is empty or not found return with `None`.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key that has to be found
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// let result = db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC100".to_string()).expect("Failed to push");
+    /// let result = db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC101".to_string()).expect("Failed to push");
+    ///
+    /// let ticket = db.pop(KeyType::Record("/root/ticket/open".to_string())).expect("Failed to pop");
+    /// assert_eq!(Some("SINC100".to_string()), ticket);
+    ///
+    /// let ticket = db.pop(KeyType::Record("/root/ticket/open".to_string())).expect("Failed to pop");
+    /// assert_eq!(Some("SINC101".to_string()), ticket);
+    ///
+    /// let ticket = db.pop(KeyType::Record("/root/ticket/open".to_string())).expect("Failed to pop");
+    /// assert_eq!(None, ticket);
+    /// ```
+    pub fn pop(&mut self, key: KeyType) -> Result<Option<String>, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("get request is performed for '{}'", key.get_key());
+        let key = match key {
+            KeyType::Record(key) => key,
+            _ => {
+                return Err(ErrorKind::InvalidKey(
+                    "Parameter must be a Record type".to_string(),
+                ));
+            }
+        };
+
+        let key_routes = utilities::internal::validate_key(&key[..], &self.name)?;
+        let table = match utilities::internal::find_table_mut(&mut self.root,
+            key_routes[..key_routes.len() - 1].to_vec(),
+        ) {
+            Some(table) => table,
+            None => {
+                tracing::trace!(op = "pop", key = %key, result = "empty", "key '{}' does not exist", key);
+                return Ok(None);
+            }
+        };
+
+        let find_key = KeyType::Queue(key_routes[key_routes.len() - 1].to_string());
+
+        match table.get_mut(&find_key) {
+            Some(ValueType::QueuePointer(queue)) => {
+                let ret_value = queue.pop_front();
+                if queue.is_empty() {
+                    table.remove(&find_key);
+                }
+
+                tracing::trace!(op = "pop", key = %key, result = "ok", "pop request is done for '{}'", key);
+                return Ok(ret_value);
+            }
+            Some(_) => {
+                tracing::error!("this should not be happen, search was to a Queue but something else was found");
+                return Err(ErrorKind::NotFound(
+                    "Specified key does not exist".to_string(),
+                ));
+            }
+            None => {
+                tracing::trace!(op = "pop", key = %key, result = "empty", "key '{}' does not exist", key);
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Pop the front value of queue only if it satisfies the predicate, else it remains in the queue.
+    /// Return with `None` if the value did not match. If queue is not found return with error.
+    ///
+    /// # Arguments
+    /// 1. `key` - Key of queue
+    /// 1. `predicate` - Function that decides about the front value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::KeyType;
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/ticket/open".to_string());
+    ///
+    /// db.push(key.clone(), "SINC100".to_string()).expect("Failed to push");
+    ///
+    /// let ticket = db.pop_front_if(key.clone(), |x| x.starts_with("CHG")).expect("Failed to pop");
+    /// assert_eq!(None, ticket);
+    ///
+    /// let ticket = db.pop_front_if(key.clone(), |x| x.starts_with("SINC")).expect("Failed to pop");
+    /// assert_eq!(Some("SINC100".to_string()), ticket);
+    /// ```
+    pub fn pop_front_if<F: Fn(&str) -> bool>(
+        &mut self,
+        key: KeyType,
+        predicate: F,
+    ) -> Result<Option<String>, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("conditional pop request is performed for '{}'", key.get_key());
+        let key_routes = utilities::internal::validate_key(key.get_key(), &self.name)?;
+        let table = match utilities::internal::find_table_mut(&mut self.root,
+            key_routes[..key_routes.len() - 1].to_vec(),
+        ) {
+            Some(table) => table,
+            None => {
+                tracing::trace!("queue '{}' does not exist", key.get_key());
+                return Err(ErrorKind::NotFound(
+                    "Specified key does not exist".to_string(),
+                ));
+            }
+        };
+
+        let find_key = KeyType::Queue(key_routes[key_routes.len() - 1].to_string());
+        let queue = match table.get_mut(&find_key) {
+            Some(ValueType::QueuePointer(queue)) => queue,
+            _ => {
+                tracing::trace!("queue '{}' does not exist", key.get_key());
+                return Err(ErrorKind::NotFound(
+                    "Specified key does not exist".to_string(),
+                ));
+            }
+        };
+
+        let matched = match queue.front() {
+            Some(value) => predicate(value),
+            None => false,
+        };
+        if !matched {
+            tracing::trace!("conditional pop request is done, front value did not match");
+            return Ok(None);
+        }
+
+        let value = queue.pop_front();
+        if queue.is_empty() {
+            table.remove(&find_key);
+        }
+
+        tracing::trace!("conditional pop request is done for '{}'", key.get_key());
+        return Ok(value);
+    }
+
+    /// Add `delta` to one of the `shard_count` counters under `base_key`, the shard is selected randomly.
+    /// Shards are records with `shard<index>` name, missing shard starts from 0.
+    /// Return with the key of the updated shard and its new value.
+    ///
+    /// # Arguments
+    /// 1. `base_key` - Key of the table that contains the shards
+    /// 1. `shard_count` - Number of shards
+    /// 1. `delta` - Value that is added to the counter
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::KeyType;
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/visits".to_string());
+    ///
+    /// for _ in 0..10 {
+    ///     db.sharded_increment(key.clone(), 4, 1).expect("Failed to increment");
+    /// }
+    ///
+    /// assert_eq!(10, db.sharded_sum(key).unwrap());
+    /// ```
+    pub fn sharded_increment(
+        &mut self,
+        base_key: KeyType,
+        shard_count: usize,
+        delta: i64,
+    ) -> Result<(KeyType, i64), ErrorKind> {
+        let base_key = self.normalize_key(base_key);
+        tracing::trace!("sharded increment request is performed for '{}'", base_key.get_key());
+        if shard_count == 0 {
+            return Err(ErrorKind::InvalidKey(
+                "Number of shards must be at least 1".to_string(),
+            ));
+        }
+
+        let shard_key = KeyType::Record(format!(
+            "{}/shard{}",
+            base_key.get_key(),
+            fastrand::usize(0..shard_count)
+        ));
+
+        let value = self.increment(shard_key.clone(), delta)?;
+
+        tracing::trace!("sharded increment request is done for '{}'", shard_key.get_key());
+        return Ok((shard_key, value));
+    }
+
+    /// Append text to the end of a record, if record does not exist then it is created with the text.
+    /// If the key is a table or queue then return with error.
+    ///
+    /// # Arguments
+    /// 1. `key` - Key of record
+    /// 1. `suffix` - Text that is appended
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/build/output".to_string());
+    ///
+    /// db.append(key.clone(), "step1;".to_string()).expect("Failed to append");
+    /// db.append(key.clone(), "step2;".to_string()).expect("Failed to append");
+    ///
+    /// assert_eq!(ValueType::RecordPointer("step1;step2;".to_string()), db.get(key).unwrap());
+    /// ```
+    pub fn append(&mut self, key: KeyType, suffix: String) -> Result<(), ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("append request is performed for '{}'", key.get_key());
+        let key = match key {
+            KeyType::Record(key) => key,
+            _ => {
+                return Err(ErrorKind::InvalidKey(
+                    "Parameter must be a Record type".to_string(),
+                ));
+            }
+        };
+        utilities::internal::validate_key(&key[..], &self.name)?;
+
+        if self.lookup(&KeyType::Queue(key.clone())).is_some()
+            || self.lookup(&KeyType::Table(key.clone())).is_some()
+        {
+            tracing::trace!("append request is failed, '{}' is not a record", key);
+            return Err(ErrorKind::InvalidKey(
+                "Specified key is not a record".to_string(),
+            ));
+        }
+
+        let record_key = KeyType::Record(key.clone());
+        let value = match self.lookup(&record_key) {
+            Some(ValueType::RecordPointer(value)) => format!("{}{}", value, suffix),
+            Some(ValueType::IntPointer(value)) => format!("{}{}", value, suffix),
+            Some(ValueType::BytesPointer(_)) => {
+                tracing::trace!("append request is failed, '{}' is a binary record", key);
+                return Err(ErrorKind::TypeError(format!("Value of '{}' is binary", key)));
+            }
+            _ => suffix,
+        };
+        self.insert(record_key, ValueType::RecordPointer(value))?;
+
+        tracing::trace!("append request is done for '{}'", key);
+        return Ok(());
+    }
+
+    /// Add a number to a numeric record and return with the new value. Missing record is handled as 0.
+    /// `ValueType::IntPointer` record remains `IntPointer`, text record and missing record is stored as text.
+    ///
+    /// # Arguments
+    /// 1. `key` - Key of record
+    /// 1. `delta` - Number that is added, it can be negative
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let key = KeyType::Record("/root/visitors".to_string());
+    ///
+    /// assert_eq!(5, db.increment(key.clone(), 5).unwrap());
+    /// assert_eq!(3, db.increment(key.clone(), -2).unwrap());
+    /// assert_eq!(ValueType::RecordPointer("3".to_string()), db.get(key).unwrap());
+    ///
+    /// let key = KeyType::Record("/root/errors".to_string());
+    /// db.insert(key.clone(), ValueType::IntPointer(10)).unwrap();
+    /// assert_eq!(11, db.increment(key.clone(), 1).unwrap());
+    /// assert_eq!(ValueType::IntPointer(11), db.get(key).unwrap());
+    ///
+    /// db.insert(KeyType::Record("/root/name".to_string()), ValueType::RecordPointer("demo".to_string())).unwrap();
+    /// assert_eq!(true, db.increment(KeyType::Record("/root/name".to_string()), 1).is_err());
+    /// ```
+    pub fn increment(&mut self, key: KeyType, delta: i64) -> Result<i64, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("increment request is performed for '{}'", key.get_key());
+        if !matches!(key, KeyType::Record(_)) {
+            return Err(ErrorKind::InvalidKey(
+                "Parameter must be a Record type".to_string(),
+            ));
+        }
+        utilities::internal::validate_key(key.get_key(), &self.name)?;
+
+        let (current, native) = match self.lookup(&key) {
+            Some(ValueType::RecordPointer(value)) => {
+                let value = value.parse::<i64>().map_err(|_| {
+                    ErrorKind::TypeError(format!("Value of '{}' is not a number", key.get_key()))
+                })?;
+                (value, false)
+            }
+            Some(ValueType::IntPointer(value)) => (*value, true),
+            Some(ValueType::BytesPointer(_)) => {
+                return Err(ErrorKind::TypeError(format!("Value of '{}' is not a number", key.get_key())));
+            }
+            _ => (0, false),
+        };
+
+        let value = match current.checked_add(delta) {
+            Some(value) => value,
+            None => {
+                return Err(ErrorKind::TypeError(
+                    "Result does not fit into 64-bit integer".to_string(),
+                ));
+            }
+        };
+        let new_value = match native {
+            true => ValueType::IntPointer(value),
+            false => ValueType::RecordPointer(value.to_string()),
+        };
+        self.insert(key.clone(), new_value)?;
+
+        tracing::trace!("increment request is done for '{}'", key.get_key());
+        return Ok(value);
+    }
+
+    /// Return with the sum of shard counters under `base_key`, see `sharded_increment`. Only the `shard<N>` records are counted.
+    /// It is 0 if no shard exists yet. If a shard is not a number or the sum does not fit into `i64`, `ErrorKind::TypeError` is returned.
+    ///
+    /// # Arguments
+    /// 1. `base_key` - Key of the table that contains the shards
+    pub fn sharded_sum(&self, base_key: KeyType) -> Result<i64, ErrorKind> {
+        let base_key = self.normalize_key(base_key);
+        tracing::trace!("sharded sum request is performed for '{}'", base_key.get_key());
+        let key_routes = utilities::internal::validate_key(base_key.get_key(), &self.name)?;
+
+        let table = match utilities::internal::find_table(&self.root, key_routes) {
+            Some(table) => table,
+            None => return Ok(0),
+        };
+
+        // Only `shard<N>` records are counters, other records of the table are skipped
+        let is_shard = |name: &str| match name.strip_prefix("shard") {
+            Some(index) => !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()),
+            None => false,
+        };
+
+        let mut sum: i64 = 0;
+        for (key, value) in table.iter() {
+            let name = match key {
+                KeyType::Record(name) if is_shard(name) => name,
+                _ => continue,
+            };
+
+            let value = match value {
+                ValueType::IntPointer(value) => *value,
+                ValueType::RecordPointer(value) => value.parse::<i64>().map_err(|_| {
+                    ErrorKind::TypeError(format!("Value of '{}/{}' is not a number", base_key.get_key(), name))
+                })?,
+                _ => {
+                    return Err(ErrorKind::TypeError(format!(
+                        "Value of '{}/{}' is not a number",
+                        base_key.get_key(),
+                        name
+                    )));
+                }
+            };
+
+            sum = match sum.checked_add(value) {
+                Some(sum) => sum,
+                None => {
+                    return Err(ErrorKind::TypeError(
+                        "Sum does not fit into 64-bit integer".to_string(),
+                    ));
+                }
+            };
+        }
+
+        tracing::trace!("sharded sum request is done for '{}'", base_key.get_key());
+        return Ok(sum);
+    }
+
+    /// Return with the number of items in a queue. If queue is not found return with error.
+    ///
+    /// # Arguments
+    /// 1. `key` - Key of queue
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::KeyType;
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC100".to_string()).expect("Failed to push");
+    /// db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC101".to_string()).expect("Failed to push");
+    ///
+    /// assert_eq!(2, db.queue_len(KeyType::Record("/root/ticket/open".to_string())).unwrap());
+    /// assert_eq!(true, db.queue_len(KeyType::Record("/root/ticket/closed".to_string())).is_err());
+    /// ```
+    pub fn queue_len(&self, key: KeyType) -> Result<usize, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("queue length request is performed for '{}'", key.get_key());
+        utilities::internal::validate_key(key.get_key(), &self.name)?;
+
+        let queue_key = KeyType::Queue(key.get_key().to_string());
+        match self.lookup(&queue_key) {
+            Some(ValueType::QueuePointer(queue)) => {
+                tracing::trace!("queue length request is done for '{}'", key.get_key());
+                return Ok(queue.len());
+            }
+            _ => {
+                tracing::trace!("queue '{}' does not exist", key.get_key());
+                return Err(ErrorKind::NotFound(
+                    "Specified key does not exist".to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Return with a copy of the front item of a queue without removing it.
+    /// If the queue does not exist return with `None`, if the key is a record or table then with error.
+    ///
+    /// # Arguments
+    /// 1. `key` - Key of queue
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::KeyType;
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC100".to_string()).expect("Failed to push");
+    /// db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC101".to_string()).expect("Failed to push");
+    ///
+    /// let front = db.peek(KeyType::Record("/root/ticket/open".to_string())).expect("Failed to peek");
+    /// assert_eq!(Some("SINC100".to_string()), front);
+    ///
+    /// let back = db.peek_back(KeyType::Record("/root/ticket/open".to_string())).expect("Failed to peek");
+    /// assert_eq!(Some("SINC101".to_string()), back);
+    ///
+    /// assert_eq!(2, db.queue_len(KeyType::Record("/root/ticket/open".to_string())).unwrap());
+    /// ```
+    pub fn peek(&self, key: KeyType) -> Result<Option<String>, ErrorKind> {
+        tracing::trace!("peek request is performed for '{}'", key.get_key());
+        return self.peek_end(key, false);
+    }
+
+    /// Return with a copy of the last item of a queue without removing it.
+    /// It works on the same way like `peek`.
+    ///
+    /// # Arguments
+    /// 1. `key` - Key of queue
+    pub fn peek_back(&self, key: KeyType) -> Result<Option<String>, ErrorKind> {
+        tracing::trace!("peek back request is performed for '{}'", key.get_key());
+        return self.peek_end(key, true);
+    }
+
+    fn peek_end(&self, key: KeyType, back: bool) -> Result<Option<String>, ErrorKind> {
+        let key = self.normalize_key(key);
+        let key = match key {
+            KeyType::Record(key) => key,
+            _ => {
+                return Err(ErrorKind::InvalidKey(
+                    "Parameter must be a Record type".to_string(),
+                ));
+            }
+        };
+        utilities::internal::validate_key(&key[..], &self.name)?;
+
+        if let Some(ValueType::QueuePointer(queue)) = self.lookup(&KeyType::Queue(key.clone())) {
+            let item = match back {
+                true => queue.back(),
+                false => queue.front(),
+            };
+            tracing::trace!("peek request is done for '{}'", key);
+            return Ok(item.cloned());
+        }
+
+        if self.lookup(&KeyType::Record(key.clone())).is_some()
+            || self.lookup(&KeyType::Table(key.clone())).is_some()
+        {
+            tracing::trace!("peek request is failed, '{}' is not a queue", key);
+            return Err(ErrorKind::InvalidKey(
+                "Specified key is not a queue".to_string(),
+            ));
+        }
+
+        tracing::trace!("queue '{}' does not exist", key);
+        return Ok(None);
+    }
+
+    /// Tells that the key exists or not without cloning its value.
+    /// If the key does not exist but there is an other type of key on the same path (e.g. record is requested but it is
+    /// a table) then return with error.
+    ///
+    /// # Arguments
+    /// 1. `key` - Key that has to be checked
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("OK".to_string())).expect("Failed to insert");
+    ///
+    /// assert_eq!(true, db.exists(KeyType::Record("/root/status/sub1".to_string())).unwrap());
+    /// assert_eq!(true, db.exists(KeyType::Table("/root/status".to_string())).unwrap());
+    /// assert_eq!(false, db.exists(KeyType::Record("/root/status/sub2".to_string())).unwrap());
+    /// assert_eq!(true, db.exists(KeyType::Record("/root/status".to_string())).is_err());
+    /// ```
+    pub fn exists(&self, key: KeyType) -> Result<bool, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("exists request is performed for '{}'", key.get_key());
+        utilities::internal::validate_key(key.get_key(), &self.name)?;
+
+        if self.lookup(&key).is_some() {
+            return Ok(true);
+        }
+
+        let others = [
+            KeyType::Record(key.get_key().to_string()),
+            KeyType::Queue(key.get_key().to_string()),
+            KeyType::Table(key.get_key().to_string()),
+        ];
+        for other in others {
+            if std::mem::discriminant(&other) != std::mem::discriminant(&key) && self.lookup(&other).is_some() {
+                tracing::trace!("'{}' exists with different type", key.get_key());
+                return Err(ErrorKind::InvalidKey(
+                    "Specified key exists with different type".to_string(),
+                ));
+            }
+        }
+
+        return Ok(false);
+    }
+
+    /// Find a record or queue value, the path is validated on the same way like at `get`
+    pub(crate) fn lookup(&self, key: &KeyType) -> Option<&ValueType> {
+        let key = &self.normalize_key(key.clone());
+        let key_routes = utilities::internal::validate_key(key.get_key(), &self.name).ok()?;
+        if let KeyType::Record(_) = key {
+            if self.is_expired(&key_routes) {
+                return None;
+            }
+        }
+        let table = utilities::internal::find_table(&self.root,
+            key_routes[..key_routes.len() - 1].to_vec(),
+        )?;
+
+        let last_route = key_routes[key_routes.len() - 1].to_string();
+        let find_key = utilities::internal::with_key(key, last_route);
+
+        return table.get(&find_key);
+    }
+
+    /// Place a record or queue value on the path with the same key type, missing tables are created
+    pub(crate) fn place(&mut self, key: KeyType, value: ValueType) -> Result<(), ErrorKind> {
+        let key = self.normalize_key(key);
+        let key_routes = self.validate_new_key(key.get_key())?;
+        let table = utilities::internal::find_or_create_table_mut(
+            &mut self.root,
+            key_routes[..key_routes.len() - 1].to_vec(),
+        )?;
+
+        let last_route = key_routes[key_routes.len() - 1].to_string();
+        let place_key = utilities::internal::with_key(&key, last_route);
+
+        table.insert(place_key, value);
+
+        if let KeyType::Record(_) = key {
+            self.expirations.remove(&format!("/{}", key_routes.join("/")));
+            self.touch_meta(format!("/{}", key_routes.join("/")));
+        }
+        return Ok(());
+    }
+
+    /// Set the modification time of record metadata, it is created if it does not exist yet
+    fn touch_meta(&mut self, meta_key: String) {
+        let now = chrono::Utc::now();
+        self.metadata
+            .entry(meta_key)
+            .and_modify(|meta| meta.updated_at = now)
+            .or_insert(RecordMeta {
+                created_at: now,
+                updated_at: now,
+            });
+    }
+
+    /// Move the metadata, versions, expiry times and queue limits of `from` key to `to` key.
+    /// For a table the entries of its whole subtree are moved, for a record or queue only its own entries.
+    fn move_key_state(&mut self, from: &KeyType, to: &KeyType) -> Result<(), ErrorKind> {
+        let old = format!("/{}", utilities::internal::validate_key(from.get_key(), &self.name)?.join("/"));
+        let new = format!("/{}", utilities::internal::validate_key(to.get_key(), &self.name)?.join("/"));
+
+        let is_table = matches!(from, KeyType::Table(_));
+        let filter = |key: &str| match is_table {
+            true => key != old && utilities::internal::is_under_prefix(key, &old),
+            false => key == old,
+        };
+
+        if !matches!(from, KeyType::Queue(_)) {
+            utilities::internal::move_entries(&mut self.metadata, &old, &new, filter);
+            utilities::internal::move_entries(&mut self.versions, &old, &new, filter);
+            utilities::internal::move_entries(&mut self.expirations, &old, &new, filter);
+        }
+        if !matches!(from, KeyType::Record(_)) {
+            utilities::internal::move_entries(&mut self.queue_limits, &old, &new, filter);
+        }
+
+        return Ok(());
+    }
+
+    /// Move the metadata, versions, expiry times and queue limits of every key under `old` prefix to be under `new` prefix
+    fn move_prefix_state(&mut self, old: &str, new: &str) -> Result<(), ErrorKind> {
+        let old = format!("/{}", utilities::internal::validate_key(old, &self.name)?.join("/"));
+        let new = format!("/{}", utilities::internal::validate_key(new, &self.name)?.join("/"));
+        let under = |key: &str| utilities::internal::is_under_prefix(key, &old);
+
+        utilities::internal::move_entries(&mut self.metadata, &old, &new, under);
+        utilities::internal::move_entries(&mut self.versions, &old, &new, under);
+        utilities::internal::move_entries(&mut self.expirations, &old, &new, under);
+        utilities::internal::move_entries(&mut self.queue_limits, &old, &new, under);
+
+        return Ok(());
+    }
+
+    /// Rename every record and queue whose key is under `old` prefix to be under `new` prefix, empty tables are moved too.
+    /// Prefix is checked by routes, so `/root/v1` does not cover `/root/v10`.
+    /// Return with the number of moved records and queues.
+    ///
+    /// It is rejected if the prefixes overlap, any destination key already exists or it is not a valid new key,
+    /// in this case nothing is moved.
+    ///
+    /// # Arguments
+    /// 1. `old` - Prefix that has to be renamed
+    /// 1. `new` - New prefix of the keys
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/v1/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    /// db.insert(KeyType::Record("/root/v1/network/dns".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    ///
+    /// let moved = db.rename_prefix("/root/v1", "/root/v2").expect("Failed to rename");
+    /// assert_eq!(2, moved);
+    ///
+    /// let value = db.get(KeyType::Record("/root/v2/network/dns".to_string())).expect("Key not found");
+    /// assert_eq!(ValueType::RecordPointer("ok".to_string()), value);
+    /// ```
+    pub fn rename_prefix(&mut self, old: &str, new: &str) -> Result<usize, ErrorKind> {
+        let (old, new) = (self.normalize_path(old), self.normalize_path(new));
+        let (old, new) = (old.as_str(), new.as_str());
+        tracing::trace!("rename prefix request is performed from '{}' to '{}'", old, new);
+        let pairs = self.pairs_with_new_prefix(old, new)?;
+
+        // Remove the old entries then place them to the new location
+        let old_routes = utilities::internal::validate_key(old, &self.name)?;
+        if let Some(table) = utilities::internal::find_table_mut(&mut self.root,
+            old_routes[..old_routes.len() - 1].to_vec(),
+        ) {
+            let last_route = old_routes[old_routes.len() - 1];
+            table.remove(&KeyType::Record(last_route.to_string()));
+            table.remove(&KeyType::Queue(last_route.to_string()));
+            table.remove(&KeyType::Table(last_route.to_string()));
+        }
+
+        let count = pairs.iter().filter(|(key, _)| !matches!(key, KeyType::Table(_))).count();
+        for (key, value) in pairs {
+            self.place(key, value)?;
+        }
+        self.move_prefix_state(old, new)?;
+
+        tracing::trace!("rename prefix request is done, {} key moved", count);
+        return Ok(count);
+    }
+
+    /// Move a record, queue or table to a new key, missing tables of the new key are created.
+    /// Table is moved with its whole subtree. Both key must have the same type.
+    ///
+    /// It fails if the source does not exist, the destination already exists or a table would be moved under itself,
+    /// in this case the source remains untouched.
+    ///
+    /// # Arguments
+    /// 1. `from` - Current key
+    /// 1. `to` - New key
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    ///
+    /// db.rename(KeyType::Record("/root/status/sub1".to_string()), KeyType::Record("/root/archive/sub1".to_string())).expect("Failed to rename");
+    /// assert_eq!(true, db.get(KeyType::Record("/root/status/sub1".to_string())).is_err());
+    ///
+    /// db.rename(KeyType::Table("/root/archive".to_string()), KeyType::Table("/root/old".to_string())).expect("Failed to rename");
+    /// let value = db.get(KeyType::Record("/root/old/sub1".to_string())).expect("Key not found");
+    /// assert_eq!(ValueType::RecordPointer("ok".to_string()), value);
+    /// ```
+    pub fn rename(&mut self, from: KeyType, to: KeyType) -> Result<(), ErrorKind> {
+        let (from, to) = (self.normalize_key(from), self.normalize_key(to));
+        tracing::trace!("rename request is performed from '{}' to '{}'", from.get_key(), to.get_key());
+        if std::mem::discriminant(&from) != std::mem::discriminant(&to) {
+            return Err(ErrorKind::InvalidKey(
+                "Source and destination must have the same type".to_string(),
+            ));
+        }
+
+        let from_routes = utilities::internal::validate_key(from.get_key(), &self.name)?;
+        let to_routes = self.validate_new_key(to.get_key())?;
+        if from_routes.len() < 2 || to_routes.len() < 2 {
+            return Err(ErrorKind::InvalidKey(
+                "Root table cannot be renamed".to_string(),
+            ));
+        }
+
+        if let KeyType::Table(_) = from {
+            let from_path = format!("/{}", from_routes.join("/"));
+            let to_path = format!("/{}", to_routes.join("/"));
+            if utilities::internal::is_under_prefix(&to_path, &from_path) {
+                tracing::trace!("rename request is failed, table cannot be moved under itself");
+                return Err(ErrorKind::InvalidKey(
+                    "Table cannot be moved under itself".to_string(),
+                ));
+            }
+        }
+
+        if self.lookup(&from).is_none() {
+            tracing::trace!("rename request is failed, '{}' does not exist", from.get_key());
+            return Err(ErrorKind::NotFound(
+                "Specified key does not exist".to_string(),
+            ));
+        }
+        if self.lookup(&to).is_some() {
+            tracing::trace!("rename request is failed, '{}' already exists", to.get_key());
+            return Err(ErrorKind::InvalidKey(format!(
+                "Destination key already exists: {}",
+                to.get_key()
+            )));
+        }
+
+        // Destination tables are created before anything is removed from the source
+        let to_last = utilities::internal::with_key(&to, to_routes[to_routes.len() - 1].to_string());
+        utilities::internal::find_or_create_table_mut(
+            &mut self.root,
+            to_routes[..to_routes.len() - 1].to_vec(),
+        )?;
+
+        let from_last = utilities::internal::with_key(&from, from_routes[from_routes.len() - 1].to_string());
+        let value = match utilities::internal::find_table_mut(&mut self.root,
+            from_routes[..from_routes.len() - 1].to_vec(),
+        ) {
+            Some(table) => table.remove(&from_last),
+            None => None,
+        };
+        let value = match value {
+            Some(value) => value,
+            None => {
+                tracing::error!("wow, this should not happen source was found before");
+                return Err(ErrorKind::InternalError(
+                    "This should not have happen".to_string(),
+                ));
+            }
+        };
+
+        let table = utilities::internal::find_or_create_table_mut(
+            &mut self.root,
+            to_routes[..to_routes.len() - 1].to_vec(),
+        )?;
+        table.insert(to_last, value);
+
+        self.move_key_state(&from, &to)?;
+
+        tracing::trace!("rename request is done from '{}' to '{}'", from.get_key(), to.get_key());
+        return Ok(());
+    }
+
+    /// Copy every record and queue whose key is under `src` prefix to be under `dst` prefix too.
+    /// It can be used to provision a new namespace from a template one.
+    /// Return with the number of copied keys.
+    ///
+    /// It is rejected if the prefixes overlap or any destination key already exists,
+    /// in this case nothing is copied.
+    ///
+    /// # Arguments
+    /// 1. `src` - Prefix that has to be copied
+    /// 1. `dst` - Prefix of the new keys
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/template/limits/cpu".to_string()), ValueType::RecordPointer("2".to_string())).expect("Failed to insert");
+    ///
+    /// let copied = db.copy_prefix("/root/template", "/root/tenant1").expect("Failed to copy");
+    /// assert_eq!(1, copied);
+    ///
+    /// let value = db.get(KeyType::Record("/root/tenant1/limits/cpu".to_string())).expect("Key not found");
+    /// assert_eq!(ValueType::RecordPointer("2".to_string()), value);
+    /// ```
+    pub fn copy_prefix(&mut self, src: &str, dst: &str) -> Result<usize, ErrorKind> {
+        let (src, dst) = (self.normalize_path(src), self.normalize_path(dst));
+        let (src, dst) = (src.as_str(), dst.as_str());
+        tracing::trace!("copy prefix request is performed from '{}' to '{}'", src, dst);
+        let pairs = self.pairs_with_new_prefix(src, dst)?;
+
+        let count = pairs.iter().filter(|(key, _)| !matches!(key, KeyType::Table(_))).count();
+        for (key, value) in pairs {
+            self.place(key, value)?;
+        }
+
+        // Copies expire together with their source
+        let src = format!("/{}", utilities::internal::validate_key(src, &self.name)?.join("/"));
+        let dst = format!("/{}", utilities::internal::validate_key(dst, &self.name)?.join("/"));
+        utilities::internal::copy_entries(&mut self.expirations, &src, &dst);
+
+        tracing::trace!("copy prefix request is done, {} key copied", count);
+        return Ok(count);
+    }
+
+    /// Collect every record, queue and empty table under `old` prefix with their keys rewritten to `new` prefix.
+    /// Return with error if the prefixes overlap, any new key already exists or it is not valid as a new key.
+    fn pairs_with_new_prefix(&self, old: &str, new: &str) -> Result<Vec<(KeyType, ValueType)>, ErrorKind> {
+        let old_routes = utilities::internal::validate_key(old, &self.name)?;
+        let new_routes = self.validate_new_key(new)?;
+        let old = format!("/{}", old_routes.join("/"));
+        let new = format!("/{}", new_routes.join("/"));
+
+        if utilities::internal::is_under_prefix(&old, &new)
+            || utilities::internal::is_under_prefix(&new, &old)
+        {
+            tracing::trace!("prefix request is failed due to overlapping prefixes");
+            return Err(ErrorKind::InvalidKey(
+                "Source and destination prefixes overlap".to_string(),
+            ));
+        }
+
+        // Collect everything that has to be moved
+        let mut pairs: Vec<(KeyType, ValueType)> = Vec::new();
+        for key in [KeyType::Record(old.clone()), KeyType::Queue(old.clone())] {
+            if let Some(value) = self.lookup(&key) {
+                pairs.push((key, value.clone()));
+            }
+        }
+
+        if let Some(table) =
+            utilities::internal::find_table(&self.root, old_routes.clone())
+        {
+            pairs.append(&mut utilities::internal::collect_pairs(table, &old, &ListType::All));
+            pairs.append(&mut utilities::internal::collect_empty_tables(table, &old));
+        }
+
+        let pairs: Vec<(KeyType, ValueType)> = pairs
+            .into_iter()
+            .map(|(key, value)| {
+                let new_key = format!("{}{}", new, &key.get_key()[old.len()..]);
+                (utilities::internal::with_key(&key, new_key), value)
+            })
+            .collect();
+
+        for (key, _) in &pairs {
+            self.validate_new_key(key.get_key())?;
+            if self.lookup(key).is_some() {
+                tracing::trace!("prefix request is failed due to '{}' already exists", key.get_key());
+                return Err(ErrorKind::InvalidKey(format!(
+                    "Destination key already exists: {}",
+                    key.get_key()
+                )));
+            }
+        }
+
+        return Ok(pairs);
+    }
+
+    /// Merge every record and queue of other database into this one under the same paths.
+    /// If the root name of the other database differs, its keys are remapped to this root.
+    /// Return with the number of merged keys. If any key is not valid as a new key, nothing is merged.
+    ///
+    /// # Arguments
+    /// 1. `other` - Database that has to be merged
+    /// 1. `on_conflict` - What to do if a key exists in both database
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::KeyType, pair::ValueType, MergePolicy};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    ///
+    /// let mut other = Database::new("other".to_string()).unwrap();
+    /// other.insert(KeyType::Record("/other/status".to_string()), ValueType::RecordPointer("nok".to_string())).expect("Failed to insert");
+    /// other.insert(KeyType::Record("/other/network".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    ///
+    /// let merged = db.merge(other, MergePolicy::KeepExisting).expect("Failed to merge");
+    /// assert_eq!(1, merged);
+    /// ```
+    pub fn merge(&mut self, other: Database, on_conflict: MergePolicy) -> Result<usize, ErrorKind> {
+        tracing::trace!(
+            "merge request is performed from '{}' database with {:?} policy",
+            other.name,
+            on_conflict
+        );
+        let other_root = format!("/{}", other.name);
+        let pairs: Vec<(KeyType, ValueType)> =
+            utilities::internal::collect_pairs(&other.root, &String::new(), &ListType::All)
+                .into_iter()
+                .map(|(key, value)| {
+                    let new_key = format!("/{}{}", self.name, &key.get_key()[other_root.len()..]);
+                    (utilities::internal::with_key(&key, new_key), value)
+                })
+                .collect();
+
+        // Every key is checked before the first write, so a rejected merge does not leave anything behind
+        for (key, _) in &pairs {
+            self.validate_new_key(self.normalize_key(key.clone()).get_key())?;
+        }
+
+        if on_conflict == MergePolicy::Error {
+            for (key, _) in &pairs {
+                if self.lookup(key).is_some() {
+                    tracing::trace!("merge request is failed due to '{}' exists in both database", key.get_key());
+                    return Err(ErrorKind::InvalidKey(format!(
+                        "Key already exists: {}",
+                        key.get_key()
+                    )));
+                }
+            }
+        }
+
+        let mut counter: usize = 0;
+        for (key, value) in pairs {
+            if on_conflict == MergePolicy::KeepExisting && self.lookup(&key).is_some() {
+                continue;
+            }
+
+            self.place(key, value)?;
+            counter += 1;
+        }
+
+        tracing::trace!("merge request is done, {} key merged", counter);
+        return Ok(counter);
+    }
+
+    /// Count the records per depth. Depth is the number of routes after the root, so `/root/status` is on depth 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    /// db.insert(KeyType::Record("/root/network/dns".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    ///
+    /// let histogram = db.key_depth_histogram();
+    /// assert_eq!(Some(&1), histogram.get(&1));
+    /// assert_eq!(Some(&1), histogram.get(&2));
+    /// ```
+    pub fn key_depth_histogram(&self) -> BTreeMap<usize, usize> {
+        tracing::trace!("key depth histogram request is performed");
+        let mut histogram: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut tables: Vec<(&Table, usize)> = vec![(&self.root, 0)];
+
+        while let Some((table, depth)) = tables.pop() {
+            for value in table.values() {
+                match value {
+                    ValueType::TablePointer(sub_table) => tables.push((sub_table, depth + 1)),
+                    ValueType::RecordPointer(_) | ValueType::IntPointer(_) | ValueType::BytesPointer(_) => {
+                        *histogram.entry(depth).or_insert(0) += 1
+                    }
+                    ValueType::QueuePointer(_) => (),
+                }
+            }
+        }
+
+        tracing::trace!("key depth histogram request is done");
+        return histogram;
+    }
+
+    /// Estimate the memory usage of the tables. It is an approximation: the lengths of key names, record values
+    /// and queue items are summed, and the fixed size of every table entry (`KeyType` and `ValueType`) and queue item
+    /// is added. The spare capacity of strings and collections, tree nodes of tables and the side data
+    /// (metadata, versions, expiry times) are not counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/network/dns".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    /// db.push(KeyType::Record("/root/jobs".to_string()), "job1".to_string()).expect("Failed to push");
+    ///
+    /// let report = db.size_estimate();
+    /// assert_eq!(1, report.records);
+    /// assert_eq!(1, report.tables);
+    /// assert_eq!(1, report.queues);
+    /// assert_eq!(true, report.total_bytes > report.key_bytes + report.value_bytes);
+    /// ```
+    pub fn size_estimate(&self) -> SizeReport {
+        tracing::trace!("size estimate request is performed");
+        let entry_size = std::mem::size_of::<KeyType>() + std::mem::size_of::<ValueType>();
+        let item_size = std::mem::size_of::<String>();
+
+        let mut report = SizeReport::default();
+        let mut entries = 0;
+        let root = match self.root.get(&KeyType::Table(self.name.clone())) {
+            Some(ValueType::TablePointer(table)) => table,
+            _ => return report,
+        };
+        let mut tables: Vec<&Table> = vec![root];
+
+        while let Some(table) = tables.pop() {
+            for (key, value) in table.iter() {
+                entries += 1;
+                report.key_bytes += key.get_key().len();
+                match value {
+                    ValueType::TablePointer(sub_table) => {
+                        report.tables += 1;
+                        tables.push(sub_table);
+                    }
+                    ValueType::RecordPointer(value) => {
+                        report.records += 1;
+                        report.value_bytes += value.len();
+                    }
+                    ValueType::IntPointer(_) => report.records += 1,
+                    ValueType::BytesPointer(value) => {
+                        report.records += 1;
+                        report.value_bytes += value.len();
+                    }
+                    ValueType::QueuePointer(queue) => {
+                        report.queues += 1;
+                        report.queue_items += queue.len();
+                        report.value_bytes += queue.iter().map(|x| x.len()).sum::<usize>();
+                    }
+                }
+            }
+        }
+
+        report.total_bytes = report.key_bytes + report.value_bytes + entries * entry_size + report.queue_items * item_size;
+
+        tracing::trace!("size estimate request is done, {} bytes", report.total_bytes);
+        return report;
+    }
+
+    /// Return with the number of requests that were sent to the hook manager
+    pub fn hook_sends(&self) -> u64 {
+        return self.hook_sends.load(Ordering::Relaxed);
+    }
+
+    /// Return with an owned copy of the whole database
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    ///
+    /// let snapshot = db.snapshot();
+    /// assert_eq!(Some(&"ok".to_string()), snapshot.root.records.get("status"));
+    ///
+    /// let mut other = Database::new("root".to_string()).unwrap();
+    /// other.restore(snapshot).expect("Failed to restore");
+    /// assert_eq!(ValueType::RecordPointer("ok".to_string()), other.get(KeyType::Record("/root/status".to_string())).unwrap());
+    /// ```
+    pub fn snapshot(&self) -> DatabaseSnapshot {
+        tracing::trace!("snapshot request is performed");
+        let root = match self.root.get(&KeyType::Table(self.name.clone())) {
+            Some(ValueType::TablePointer(table)) => utilities::internal::table_to_snapshot(table),
+            _ => SnapshotTable::default(),
+        };
+
+        return DatabaseSnapshot {
+            name: self.name.clone(),
+            root,
+        };
+    }
+
+    /// Replace the content of database with the snapshot. Snapshot must be created from a database with the same name.
+    /// Expiry times, versions and queue capacities are dropped, records get new metadata.
+    ///
+    /// # Arguments
+    /// 1. `snapshot` - Snapshot that is restored
+    pub fn restore(&mut self, snapshot: DatabaseSnapshot) -> Result<(), ErrorKind> {
+        tracing::trace!("restore request is performed");
+        if snapshot.name != self.name {
+            tracing::trace!("restore request is failed, snapshot belongs to '{}'", snapshot.name);
+            return Err(ErrorKind::InvalidRoot(format!(
+                "Snapshot belongs to a different root: {}",
+                snapshot.name
+            )));
+        }
+
+        let table = utilities::internal::snapshot_to_table(snapshot.root);
+        self.root = Table::new();
+        self.root.insert(KeyType::Table(self.name.clone()), ValueType::TablePointer(table));
+
+        self.versions.clear();
+        self.expirations.clear();
+        self.queue_limits.clear();
+        self.metadata.clear();
+        for (key, _) in utilities::internal::collect_pairs(&self.root, &String::new(), &ListType::All) {
+            if let KeyType::Record(key) = key {
+                self.touch_meta(key);
+            }
+        }
+
+        tracing::trace!("restore request is done");
+
+        return Ok(());
+    }
+
+    /// Remove every table, record and queue, only the empty root table remains.
+    /// Expiry times, versions, queue capacities and metadata are dropped too, the settings of database are kept.
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::{KeyType, ValueType}, ListType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).unwrap();
+    ///
+    /// db.clear();
+    /// assert_eq!(0, db.list_keys(KeyType::Record("/root".to_string()), ListType::All).unwrap().len());
+    /// ```
+    pub fn clear(&mut self) {
+        tracing::trace!("clear request is performed");
+        self.root = Table::new();
+        self.root.insert(KeyType::Table(self.name.clone()), ValueType::TablePointer(Table::new()));
+
+        self.versions.clear();
+        self.expirations.clear();
+        self.queue_limits.clear();
+        self.metadata.clear();
+        tracing::trace!("clear request is done");
+    }
+
+    /// Apply the operations in order, all or nothing. If an operation fails, the database is restored to its state
+    /// before the transaction and the error is returned as `ErrorKind::BatchFailed` with the index of operation.
+    /// Hook manager is alerted about the writes only after all operations are applied.
+    ///
+    /// Before the first operation the whole database (tables, metadata, versions, expiry times) is cloned,
+    /// so every transaction costs as much memory and time as a copy of the database, regardless how many
+    /// keys it touches. Keep it for small databases or rare multi-key updates.
+    ///
+    /// # Arguments
+    /// 1. `ops` - Operations that are applied
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::{KeyType, ValueType}, TxnOp};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.insert(KeyType::Record("/root/account/a".to_string()), ValueType::RecordPointer("100".to_string())).unwrap();
+    ///
+    /// let ops = vec![
+    ///     TxnOp::Set("/root/account/b".to_string(), "50".to_string()),
+    ///     TxnOp::CompareAndSwap("/root/account/a".to_string(), Some("90".to_string()), "50".to_string()),
+    /// ];
+    /// assert_eq!(true, db.transaction(ops).is_err());
+    /// assert_eq!(true, db.get(KeyType::Record("/root/account/b".to_string())).is_err());
+    /// ```
+    pub fn transaction(&mut self, ops: Vec<TxnOp>) -> Result<(), ErrorKind> {
+        tracing::trace!("transaction request is performed for {} operations", ops.len());
+        let root = self.root.clone();
+        let versions = self.versions.clone();
+        let expirations = self.expirations.clone();
+        let queue_limits = self.queue_limits.clone();
+        let metadata = self.metadata.clone();
+
+        let auto_hooks = self.auto_hooks;
+        self.auto_hooks = false;
+
+        let mut result = Ok(());
+        for (index, op) in ops.iter().enumerate() {
+            let op_result = match op {
+                TxnOp::Set(key, value) => {
+                    self.insert(KeyType::Record(key.clone()), ValueType::RecordPointer(value.clone()))
+                }
+                TxnOp::DeleteKey(key) => self.delete_key(KeyType::Record(key.clone())),
+                TxnOp::DeleteTable(key) => self.delete_table(KeyType::Table(key.clone())),
+                TxnOp::CompareAndSwap(key, expected, value) => {
+                    match self.compare_and_swap(KeyType::Record(key.clone()), expected.clone(), value.clone()) {
+                        Ok(true) => Ok(()),
+                        Ok(false) => Err(ErrorKind::CompareFailed(key.clone())),
+                        Err(e) => Err(e),
+                    }
+                }
+            };
+
+            if let Err(e) = op_result {
+                tracing::trace!("transaction request is failed at {}: {}", index, op);
+                result = Err(ErrorKind::BatchFailed { index, error: Box::new(e) });
+                break;
+            }
+        }
+
+        self.auto_hooks = auto_hooks;
+
+        if result.is_err() {
+            self.root = root;
+            self.versions = versions;
+            self.expirations = expirations;
+            self.queue_limits = queue_limits;
+            self.metadata = metadata;
+            return result;
+        }
+
+        for op in &ops {
+            match op {
+                TxnOp::Set(key, value) | TxnOp::CompareAndSwap(key, _, value) => self.alert_set(key, value),
+                _ => (),
+            }
+        }
+
+        tracing::trace!("transaction request is done");
+
+        return Ok(());
+    }
+
+    /// Save the whole database into a file as JSON snapshot. The file is written next to the target
+    /// then renamed, so a crash during write does not destroy the former file.
+    ///
+    /// # Arguments
+    /// 1. `path` - Path of the file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let path = Path::new("/tmp/datastore-doc-persist.json");
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    /// db.save_to_file(path).expect("Failed to save");
+    ///
+    /// let mut other = Database::new("root".to_string()).unwrap();
+    /// other.load_from_file(path).expect("Failed to load");
+    /// assert_eq!(ValueType::RecordPointer("ok".to_string()), other.get(KeyType::Record("/root/status".to_string())).unwrap());
+    /// ```
+    pub fn save_to_file(&self, path: &Path) -> Result<(), ErrorKind> {
+        tracing::trace!("save request is performed to '{}'", path.display());
+        let temp_path = path.with_extension("tmp");
+
+        let file = std::fs::File::create(&temp_path).map_err(|e| {
+            ErrorKind::InternalError(format!("Failed to create '{}': {}", temp_path.display(), e))
+        })?;
+        let mut writer = std::io::BufWriter::new(file);
+        self.write_snapshot(&mut writer)?;
+        writer.flush().map_err(|e| {
+            ErrorKind::InternalError(format!("Failed to write '{}': {}", temp_path.display(), e))
+        })?;
+
+        std::fs::rename(&temp_path, path).map_err(|e| {
+            ErrorKind::InternalError(format!("Failed to replace '{}': {}", path.display(), e))
+        })?;
+
+        tracing::trace!("save request is done to '{}'", path.display());
+        return Ok(());
+    }
+
+    /// Replace the content of database with the file that was written by `save_to_file`, see `restore`.
+    ///
+    /// # Arguments
+    /// 1. `path` - Path of the file
+    pub fn load_from_file(&mut self, path: &Path) -> Result<(), ErrorKind> {
+        tracing::trace!("load request is performed from '{}'", path.display());
+        let file = std::fs::File::open(path).map_err(|e| {
+            ErrorKind::InternalError(format!("Failed to open '{}': {}", path.display(), e))
+        })?;
+
+        let snapshot = serde_json::from_reader::<_, serde_json::Value>(std::io::BufReader::new(file))
+            .map_err(|e| e.to_string())
+            .and_then(|value| DatabaseSnapshot::from_json(&value))
+            .map_err(|e| {
+                ErrorKind::InternalError(format!("Failed to parse '{}': {}", path.display(), e))
+            })?;
+        self.restore(snapshot)?;
+
+        tracing::trace!("load request is done from '{}'", path.display());
+        return Ok(());
+    }
+
+    /// Convert a subtree into nested JSON object: tables become objects, text records become strings,
+    /// numeric records become integers, binary records become `{"$bytes": "<base64>"}` objects
+    /// and queues become arrays of strings.
+    ///
+    /// A record, a table and a queue can have the same name on one level. In this case the record
+    /// keeps its name, the table is written with `/` suffix (e.g. `"status/"`) and the queue
+    /// with `[]` suffix (e.g. `"status[]"`). Names that are not shared are written without suffix.
+    /// If the table has more entries than `set_max_list_results` allows, `ErrorKind::ResultTooLarge` is returned.
+    ///
+    /// # Arguments
+    /// 1. `key_prefix` - Table that is exported, e.g. `KeyType::Table("/root".to_string())`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.insert(KeyType::Record("/root/network/dns".to_string()), ValueType::RecordPointer("up".to_string())).expect("Failed to insert");
+    /// db.push(KeyType::Record("/root/jobs".to_string()), "backup".to_string()).expect("Failed to push");
+    ///
+    /// let json = db.to_json(KeyType::Table("/root".to_string())).unwrap();
+    /// assert_eq!(serde_json::json!({ "network": { "dns": "up" }, "jobs": ["backup"] }), json);
+    /// ```
+    pub fn to_json(&self, key_prefix: KeyType) -> Result<serde_json::Value, ErrorKind> {
+        let key_prefix = self.normalize_key(key_prefix);
+        let key_prefix = match key_prefix {
+            KeyType::Table(key) => key,
+            _ => {
+                return Err(ErrorKind::InvalidKey(
+                    "Parameter must be a Table type".to_string(),
+                ));
+            }
+        };
+        tracing::trace!("export json request is performed for '{}'", key_prefix);
+
+        let key_routes = utilities::internal::validate_key(&key_prefix[..], &self.name)?;
+        let table = match utilities::internal::find_table(&self.root, key_routes) {
+            Some(table) => table,
+            None => {
+                tracing::trace!("export json request is failed due to no '{}' key exist", key_prefix);
+                return Err(ErrorKind::NotFound(
+                    "Specified route does not exist".to_string(),
+                ));
+            }
+        };
+
+        if let Some(limit) = self.max_list_results {
+            let count = utilities::internal::count_entries(table);
+            if count > limit {
+                tracing::trace!("export json request is failed due to {} entries found", count);
+                return Err(ErrorKind::ResultTooLarge { count, limit });
+            }
+        }
+
+        let json = utilities::internal::table_to_json(table);
+
+        tracing::trace!("export json request is done for '{}'", key_prefix);
+        return Ok(json);
+    }
+
+    /// Import a nested JSON object under a table, this is the inverse of `to_json`. Objects become tables,
+    /// integers become `IntPointer` records, `{"$bytes": "<base64>"}` objects become `BytesPointer` records,
+    /// strings, other numbers and booleans become text records and arrays of strings become queues.
+    /// The `/` and `[]` suffixes that `to_json` uses for shared names are removed.
+    /// Existing records are overwritten, items are appended to the existing queues.
+    /// Return with the number of records that are written.
+    ///
+    /// The import is atomic: the whole object is checked and tried before any change, so nothing is written
+    /// if any of its values fails. Other types (e.g. null or array of numbers) cause `ErrorKind::TypeError`.
+    ///
+    /// # Arguments
+    /// 1. `key_prefix` - Table where the object is imported, e.g. `KeyType::Table("/root".to_string())`
+    /// 1. `value` - JSON object
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// let json = serde_json::json!({ "network": { "dns": "up", "port": 53 }, "jobs": ["backup"] });
+    /// assert_eq!(2, db.from_json(KeyType::Table("/root".to_string()), json).unwrap());
+    ///
+    /// assert_eq!(ValueType::IntPointer(53), db.get(KeyType::Record("/root/network/port".to_string())).unwrap());
+    /// assert_eq!(1, db.queue_len(KeyType::Record("/root/jobs".to_string())).unwrap());
+    /// ```
+    pub fn from_json(&mut self, key_prefix: KeyType, value: serde_json::Value) -> Result<usize, ErrorKind> {
+        let pairs = self.import_json(key_prefix, value)?;
+        return Ok(pairs.iter().filter(|(key, _)| key.is_record()).count());
+    }
+
+    /// Import a nested JSON object like `from_json` and return with the pairs that were applied.
+    /// Pairs are applied on a copy of the data first, so nothing is changed if any of them fails.
+    pub(crate) fn import_json(&mut self, key_prefix: KeyType, value: serde_json::Value) -> Result<Vec<(KeyType, ValueType)>, ErrorKind> {
+        let key_prefix = self.normalize_key(key_prefix);
+        let key_prefix = match key_prefix {
+            KeyType::Table(key) => key,
+            _ => {
+                return Err(ErrorKind::InvalidKey(
+                    "Parameter must be a Table type".to_string(),
+                ));
+            }
+        };
+        tracing::trace!("import json request is performed for '{}'", key_prefix);
+
+        let key_routes = utilities::internal::validate_key(&key_prefix[..], &self.name)?;
+        let key_prefix = format!("/{}", key_routes.join("/"));
+
+        let mut pairs = Vec::new();
+        utilities::internal::json_to_pairs(&key_prefix, &value, &mut pairs)?;
+
+        let mut staging = self.staging_copy();
+        if let Err(e) = staging.apply_pairs(&pairs) {
+            tracing::trace!("import json request is failed, nothing is written: {}", e);
+            return Err(e);
+        }
+        let counter = self.apply_pairs(&pairs)?;
+
+        tracing::trace!("import json request is done, {} records are written", counter);
+        return Ok(pairs);
+    }
+
+    /// Write the pairs of `json_to_pairs` into the database, return with the number of written records
+    fn apply_pairs(&mut self, pairs: &[(KeyType, ValueType)]) -> Result<usize, ErrorKind> {
+        let mut counter: usize = 0;
+        for (key, value) in pairs {
+            match (key, value) {
+                (KeyType::Record(key), value) => {
+                    self.insert(KeyType::Record(key.clone()), value.clone())?;
+                    counter += 1;
+                }
+                (KeyType::Queue(key), ValueType::QueuePointer(queue)) => {
+                    for item in queue {
+                        self.push(KeyType::Record(key.clone()), item.clone())?;
+                    }
+                }
+                (KeyType::Table(key), _) => {
+                    let key_routes = utilities::internal::validate_key(&key[..], &self.name)?;
+                    utilities::internal::find_or_create_table_mut(&mut self.root, key_routes)?;
+                }
+                (key, _) => {
+                    return Err(ErrorKind::InternalError(format!(
+                        "Unexpected pair for '{}' during import",
+                        key.get_key()
+                    )));
+                }
             }
-            None => return Err(ErrorKind::InactiveHookManager),
         }
+
+        return Ok(counter);
     }
 
-    /// Get the value of a key and return with a copy of it. If not found return with error.
-    ///
-    /// # Arguments
-    /// 1. `key` - Unique key that has to be found
+    /// Copy of the data and the write rules without hooks, logger and watchers, changes can be tried on it
+    fn staging_copy(&self) -> Database {
+        return Database {
+            name: self.name.clone(),
+            root: self.root.clone(),
+            hook_sender: None,
+            logger_sender: None,
+            access_counters: None,
+            loader: None,
+            versions: self.versions.clone(),
+            max_list_results: self.max_list_results,
+            max_value_bytes: self.max_value_bytes,
+            queue_limits: self.queue_limits.clone(),
+            expirations: self.expirations.clone(),
+            metadata: self.metadata.clone(),
+            hook_sends: AtomicU64::new(0),
+            auto_hooks: false,
+            watchers: Vec::new(),
+            key_validation: self.key_validation,
+            case_insensitive_keys: self.case_insensitive_keys,
+        };
+    }
+
+    /// Serialize the whole database as snapshot into the writer
+    pub(crate) fn write_snapshot<W: Write>(&self, writer: W) -> Result<(), ErrorKind> {
+        if let Err(e) = serde_json::to_writer(writer, &self.snapshot().to_json()) {
+            tracing::error!("failed to serialize database: {}", e);
+            return Err(ErrorKind::InternalError(format!(
+                "Failed to serialize database: {}",
+                e
+            )));
+        }
+
+        return Ok(());
+    }
+
+    /// Return with the number of bytes that a snapshot would occupy, nothing is written.
     ///
     /// # Example
     ///
@@ -304,12 +2988,49 @@ impl Database {
     /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
     ///
     /// let mut db = Database::new("root".to_string()).unwrap();
+    /// let empty_size = db.snapshot_size_estimate();
     ///
-    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("Having a great time".to_string())).expect("Failed to insert");
-    /// let value = db.get(KeyType::Record("/root/status".to_string())).expect("Key not found");
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    /// assert_eq!(true, db.snapshot_size_estimate() > empty_size);
     /// ```
-    pub fn get(&self, key: KeyType) -> Result<ValueType, ErrorKind> {
-        tracing::trace!("get request is performed for '{}'", key.get_key());
+    pub fn snapshot_size_estimate(&self) -> usize {
+        tracing::trace!("snapshot size estimate request is performed");
+        let mut counter = utilities::internal::ByteCounter::default();
+
+        if let Err(e) = self.write_snapshot(&mut counter) {
+            tracing::error!("failed to estimate snapshot size: {}", e);
+        }
+
+        tracing::trace!("snapshot size estimate request is done, {} bytes", counter.count);
+        return counter.count;
+    }
+
+    /// Pop maximum `n` values from queue. If queue does not exist return with error.
+    pub(crate) fn pop_batch(&mut self, key: KeyType, n: usize) -> Result<Vec<String>, ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("pop batch request is performed for '{}'", key.get_key());
+        let mut values = Vec::with_capacity(n);
+
+        while values.len() < n {
+            match self.pop(key.clone())? {
+                Some(value) => values.push(value),
+                None if values.is_empty() => {
+                    return Err(ErrorKind::NotFound(
+                        "Specified key does not exist".to_string(),
+                    ));
+                }
+                None => break,
+            }
+        }
+
+        tracing::trace!("pop batch request is done, {} value popped", values.len());
+        return Ok(values);
+    }
+
+    /// Put values back to the front of a queue in their original order, used if forward of the values failed.
+    pub(crate) fn requeue(&mut self, key: KeyType, values: Vec<String>) -> Result<(), ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("requeue request is performed for '{}'", key.get_key());
         let key = match key {
             KeyType::Record(key) => key,
             _ => {
@@ -320,39 +3041,373 @@ impl Database {
         };
 
         let key_routes = utilities::internal::validate_key(&key[..], &self.name)?;
-        let table = match utilities::internal::find_table(
-            Box::new(&self.root),
+        let table = match utilities::internal::find_table_mut(&mut self.root,
             key_routes[..key_routes.len() - 1].to_vec(),
         ) {
             Some(table) => table,
             None => {
                 tracing::trace!("key '{}' does not exist", key);
-                return Err(ErrorKind::InvalidKey(
+                return Err(ErrorKind::NotFound(
                     "Specified key does not exist".to_string(),
                 ));
             }
         };
 
-        let find_key = KeyType::Record(key_routes[key_routes.len() - 1].to_string());
+        let queue_key = KeyType::Queue(key_routes[key_routes.len() - 1].to_string());
+        let queue = table
+            .entry(queue_key)
+            .or_insert(ValueType::QueuePointer(VecDeque::new()));
 
-        match table.get(&find_key) {
-            Some(value) => {
-                tracing::trace!("get request is done for '{}'", key);
-                return Ok(value.clone());
+        match queue {
+            ValueType::QueuePointer(queue) => {
+                for value in values.into_iter().rev() {
+                    queue.push_front(value);
+                }
+            }
+            _ => {
+                tracing::error!("this should not be happen, search was to a Queue but something else was found");
+                return Err(ErrorKind::InternalError(
+                    "This should not have happen".to_string(),
+                ));
+            }
+        }
+
+        tracing::trace!("requeue request is done for '{}'", key);
+        return Ok(());
+    }
+
+    /// List keys from a specific entry point and return with a key list. If failed return with error.
+    ///
+    /// # Arguments
+    /// 1. `key_prefix` - Path where the keys has to be collected
+    /// 1. `level` - Need all inner level (`ListType::All`) or just current level (`ListType::OneLevel`)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::KeyType, pair::ValueType, ListType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("PING OK".to_string())).expect("Failed to insert");
+    /// db.insert(KeyType::Record("/root/status/sub2".to_string()), ValueType::RecordPointer("PING NOK".to_string())).expect("Failed to insert");
+    /// db.insert(KeyType::Record("/root/status/sub3".to_string()), ValueType::RecordPointer("PING OK".to_string())).expect("Failed to insert");
+    /// let list = db.list_keys(KeyType::Record("/root/status".to_string()), ListType::All).expect("Key not found");
+    ///
+    /// println!("{:?}", list);
+    /// ```
+    pub fn list_keys(
+        &mut self,
+        key_prefix: KeyType,
+        level: ListType,
+    ) -> Result<Vec<KeyType>, ErrorKind> {
+        let key_prefix = self.normalize_key(key_prefix);
+        tracing::trace!(
+            "list keys request is performed for '{}'",
+            key_prefix.get_key()
+        );
+        let key_prefix = match key_prefix {
+            KeyType::Record(key) => key,
+            _ => {
+                return Err(ErrorKind::InvalidKey(
+                    "Parameter must be a Record type".to_string(),
+                ));
             }
+        };
+
+        // Find the base table
+        let key_routes = utilities::internal::validate_key(&key_prefix[..], &self.name)?;
+        let table = match utilities::internal::find_table(&self.root, key_routes) {
+            Some(table) => table,
             None => {
-                tracing::trace!("key '{}' does not exist", key);
+                tracing::trace!("get request is failed due to no '{}' key exist", key_prefix);
+                return Err(ErrorKind::NotFound(
+                    "Specified route does not exist".to_string(),
+                ));
+            }
+        };
+
+        // Get the information
+        let result = utilities::internal::display_tables(table, &key_prefix, &level, self.max_list_results);
+        if let Err(e) = &result {
+            tracing::trace!("list keys request is failed: {}", e);
+        }
+        let result = result?;
+
+        tracing::trace!("list keys request is done for '{}'", key_prefix);
+        return Ok(result);
+    }
+
+    /// List the tables from a specific entry point, records and queues are not part of the result.
+    /// With `ListType::OneLevel` only the direct subtables are returned, with `ListType::All` every table
+    /// of the subtree is returned and the children of a table follow it.
+    ///
+    /// # Arguments
+    /// 1. `key_prefix` - Table where the tables has to be collected
+    /// 1. `level` - Need all inner level (`ListType::All`) or just current level (`ListType::OneLevel`)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::KeyType, pair::ValueType, ListType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("PING OK".to_string())).expect("Failed to insert");
+    /// db.insert(KeyType::Record("/root/status/server/sub2".to_string()), ValueType::RecordPointer("PING NOK".to_string())).expect("Failed to insert");
+    ///
+    /// let list = db.list_tables(KeyType::Table("/root".to_string()), ListType::OneLevel).expect("Key not found");
+    /// assert_eq!(vec![KeyType::Table("/root/status".to_string())], list);
+    ///
+    /// let list = db.list_tables(KeyType::Table("/root".to_string()), ListType::All).expect("Key not found");
+    /// assert_eq!(2, list.len());
+    /// ```
+    pub fn list_tables(&mut self, key_prefix: KeyType, level: ListType) -> Result<Vec<KeyType>, ErrorKind> {
+        let key_prefix = self.normalize_key(key_prefix);
+        tracing::trace!("list tables request is performed for '{}'", key_prefix.get_key());
+        let key_prefix = match key_prefix {
+            KeyType::Table(key) => key,
+            _ => {
                 return Err(ErrorKind::InvalidKey(
-                    "Specified key does not exist".to_string(),
+                    "Parameter must be a Table type".to_string(),
+                ));
+            }
+        };
+
+        // Find the base table
+        let key_routes = utilities::internal::validate_key(&key_prefix[..], &self.name)?;
+        let table = match utilities::internal::find_table(&self.root, key_routes) {
+            Some(table) => table,
+            None => {
+                tracing::trace!("list tables request is failed due to no '{}' key exist", key_prefix);
+                return Err(ErrorKind::NotFound(
+                    "Specified route does not exist".to_string(),
+                ));
+            }
+        };
+
+        let result = utilities::internal::display_subtables(table, &key_prefix, &level, self.max_list_results);
+        if let Err(e) = &result {
+            tracing::trace!("list tables request is failed: {}", e);
+        }
+        let result = result?;
+
+        tracing::trace!("list tables request is done for '{}'", key_prefix);
+        return Ok(result);
+    }
+
+    /// List the keys under a prefix with their values, keys are listed on the same way like at `list_keys`.
+    /// Queues are returned with all of their items.
+    ///
+    /// # Arguments
+    /// 1. `key_prefix` - Prefix of the keys
+    /// 1. `level` - List only the direct keys or the whole subtree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::{KeyType, ValueType}, ListType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    ///
+    /// let pairs = db.list_pairs(KeyType::Record("/root/status".to_string()), ListType::All).unwrap();
+    /// assert_eq!(
+    ///     vec![(KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("ok".to_string()))],
+    ///     pairs
+    /// );
+    /// ```
+    pub fn list_pairs(&self, key_prefix: KeyType, level: ListType) -> Result<Vec<(KeyType, ValueType)>, ErrorKind> {
+        tracing::trace!("list pairs request is performed for '{}'", key_prefix.get_key());
+        let table = self.find_list_table(&key_prefix)?;
+
+        if let Some(limit) = self.max_list_results {
+            let count = utilities::internal::count_tables(table, &level);
+            if count > limit {
+                tracing::trace!("list pairs request is failed due to {} entries found", count);
+                return Err(ErrorKind::ResultTooLarge { count, limit });
+            }
+        }
+
+        let result = utilities::internal::collect_pairs(table, &key_prefix.get_key().to_string(), &level);
+
+        tracing::trace!("list pairs request is done for '{}'", key_prefix.get_key());
+        return Ok(result);
+    }
+
+    /// Count the keys under a prefix on the same way like `list_keys` list them, but the list is not allocated.
+    ///
+    /// # Arguments
+    /// 1. `key_prefix` - Prefix of the keys
+    /// 1. `level` - Count only the direct keys or the whole subtree
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::{KeyType, ValueType}, ListType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    /// db.insert(KeyType::Record("/root/status/deep/sub2".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    ///
+    /// assert_eq!(1, db.count_keys(KeyType::Record("/root/status".to_string()), ListType::OneLevel).unwrap());
+    /// assert_eq!(2, db.count_keys(KeyType::Record("/root/status".to_string()), ListType::All).unwrap());
+    /// ```
+    pub fn count_keys(&self, key_prefix: KeyType, level: ListType) -> Result<usize, ErrorKind> {
+        tracing::trace!("count keys request is performed for '{}'", key_prefix.get_key());
+        let table = self.find_list_table(&key_prefix)?;
+
+        let count = utilities::internal::count_tables(table, &level);
+
+        tracing::trace!("count keys request is done for '{}'", key_prefix.get_key());
+        return Ok(count);
+    }
+
+    /// Find the base table of list requests, prefix must be a record type
+    fn find_list_table(&self, key_prefix: &KeyType) -> Result<&Table, ErrorKind> {
+        let key_prefix = &self.normalize_key(key_prefix.clone());
+        let key_prefix = match key_prefix {
+            KeyType::Record(key) => key,
+            _ => {
+                return Err(ErrorKind::InvalidKey(
+                    "Parameter must be a Record type".to_string(),
+                ));
+            }
+        };
+
+        let key_routes = utilities::internal::validate_key(&key_prefix[..], &self.name)?;
+        match utilities::internal::find_table(&self.root, key_routes) {
+            Some(table) => return Ok(table),
+            None => {
+                tracing::trace!("list request is failed due to no '{}' key exist", key_prefix);
+                return Err(ErrorKind::NotFound(
+                    "Specified route does not exist".to_string(),
                 ));
             }
         }
     }
 
-    /// Pop value from queue. If not found return with error.
+    /// Return with the most read keys in descending order. Empty list is returned if access counters are not enabled.
+    ///
+    /// # Arguments
+    /// 1. `n` - Maximum number of keys in the list
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.enable_access_counters();
+    ///
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    /// db.get(KeyType::Record("/root/status".to_string())).expect("Key not found");
+    ///
+    /// let hot_keys = db.hot_keys(10);
+    /// assert_eq!(vec![(KeyType::Record("/root/status".to_string()), 1)], hot_keys);
+    /// ```
+    pub fn hot_keys(&self, n: usize) -> Vec<(KeyType, u64)> {
+        tracing::trace!("hot keys request is performed for top {} keys", n);
+        let counters = match &self.access_counters {
+            Some(counters) => counters,
+            None => return Vec::new(),
+        };
+
+        let mut list: Vec<(String, u64)> = match counters.lock() {
+            Ok(counters) => counters.iter().map(|x| (x.0.clone(), *x.1)).collect(),
+            Err(e) => {
+                tracing::error!("failed to lock access counters: {}", e);
+                return Vec::new();
+            }
+        };
+
+        list.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        return list
+            .into_iter()
+            .take(n)
+            .map(|x| (KeyType::Record(x.0), x.1))
+            .collect();
+    }
+
+    /// Iterate over the entries of the root table, it is read only.
+    /// Iterator does not go into the inner tables, recursion can be done by the caller via `ValueType::TablePointer`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    /// db.insert(KeyType::Record("/root/node_name".to_string()), ValueType::RecordPointer("vps01".to_string())).expect("Failed to insert");
+    ///
+    /// for (key, value) in db.root_iter() {
+    ///     match value {
+    ///         ValueType::TablePointer(table) => println!("{} has {} entries", key, table.len()),
+    ///         _ => println!("{} -> {}", key, value.get_value()),
+    ///     }
+    /// }
+    /// assert_eq!(2, db.root_iter().count());
+    /// ```
+    pub fn root_iter(&self) -> impl Iterator<Item = (&KeyType, &ValueType)> {
+        let root = match self.root.get(&KeyType::Table(self.name.clone())) {
+            Some(ValueType::TablePointer(table)) => Some(table.iter()),
+            _ => None,
+        };
+
+        return root.into_iter().flatten();
+    }
+
+    /// Count the items in every queue of the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::KeyType;
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC100".to_string()).expect("Failed to push");
+    /// db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC101".to_string()).expect("Failed to push");
+    /// db.push(KeyType::Record("/root/jobs".to_string()), "backup".to_string()).expect("Failed to push");
+    ///
+    /// assert_eq!(3, db.total_queued());
+    /// ```
+    pub fn total_queued(&self) -> usize {
+        tracing::trace!("total queued request is performed");
+        let mut counter: usize = 0;
+        let mut tables: Vec<&Table> = vec![&self.root];
+
+        while let Some(table) = tables.pop() {
+            for value in table.values() {
+                match value {
+                    ValueType::TablePointer(sub_table) => tables.push(sub_table),
+                    ValueType::QueuePointer(queue) => counter += queue.len(),
+                    _ => (),
+                }
+            }
+        }
+
+        tracing::trace!("total queued request is done, found {} items", counter);
+        return counter;
+    }
+
+    /// Parse a line of the log file and apply it on the database. Read and hook related lines are skipped.
+    /// It can be used to apply the log of a primary database on a replica incrementally.
     ///
     /// # Arguments
-    /// 1. `key` - Unique key that has to be found
+    /// 1. `line` - A line from the log file
     ///
     /// # Example
     ///
@@ -362,143 +3417,185 @@ impl Database {
     ///
     /// let mut db = Database::new("root".to_string()).unwrap();
     ///
-    /// let result = db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC100".to_string()).expect("Failed to push");
-    /// let result = db.push(KeyType::Record("/root/ticket/open".to_string()), "SINC101".to_string()).expect("Failed to push");
+    /// db.apply_log_line("2023-05-20 10:00:00.000000000 UTC SetKey [ '/root/status', 'ok' ]").expect("Failed to apply");
+    /// db.apply_log_line("2023-05-20 10:00:01.000000000 UTC GetKey [ '/root/status' ]").expect("Failed to apply");
     ///
-    /// let ticket = db.pop(KeyType::Record("/root/ticket/open".to_string())).expect("Failed to pop");
-    /// assert_eq!("SINC100".to_string(), ticket);
+    /// let value = db.get(KeyType::Record("/root/status".to_string())).unwrap();
+    /// assert_eq!(ValueType::RecordPointer("ok".to_string()), value);
+    /// ```
+    pub fn apply_log_line(&mut self, line: &str) -> Result<(), ErrorKind> {
+        tracing::trace!("apply log line request is performed");
+        let item = line.parse::<LogItem>().map_err(ErrorKind::LogError)?;
+        self.apply_log_item(item)?;
+
+        tracing::trace!("apply log line request is done");
+        return Ok(());
+    }
+
+    /// Apply log items on the database in order, e.g. the output of `LoggerManager::replay`.
+    /// Items that do not modify the database are skipped. If an item cannot be applied,
+    /// return with an error that contains its position.
     ///
-    /// let ticket = db.pop(KeyType::Record("/root/ticket/open".to_string())).expect("Failed to pop");
-    /// assert_eq!("SINC101".to_string(), ticket);
+    /// # Arguments
+    /// 1. `items` - Log items to be applied
+    ///
+    /// # Example
     ///
-    /// let ticket = db.pop(KeyType::Record("/root/ticket/open".to_string()));
-    /// assert_eq!(true, ticket.is_err());
     /// ```
-    pub fn pop(&mut self, key: KeyType) -> Result<String, ErrorKind> {
-        tracing::trace!("get request is performed for '{}'", key.get_key());
-        let key = match key {
-            KeyType::Record(key) => key,
-            _ => {
-                return Err(ErrorKind::InvalidKey(
-                    "Parameter must be a Record type".to_string(),
-                ));
-            }
-        };
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    /// use onlyati_datastore::logger::enums::LogItem;
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// let items = vec![
+    ///     LogItem::SetKey("/root/status".to_string(), "ok".to_string()),
+    ///     LogItem::GetKey("/root/status".to_string()),
+    ///     LogItem::SetKey("/root/network".to_string(), "up".to_string()),
+    ///     LogItem::RemKey("/root/network".to_string()),
+    /// ];
+    /// db.apply_log(items).expect("Failed to apply");
+    ///
+    /// let value = db.get(KeyType::Record("/root/status".to_string())).unwrap();
+    /// assert_eq!(ValueType::RecordPointer("ok".to_string()), value);
+    /// assert_eq!(true, db.get(KeyType::Record("/root/network".to_string())).is_err());
+    /// ```
+    pub fn apply_log(&mut self, items: Vec<LogItem>) -> Result<(), ErrorKind> {
+        tracing::trace!("apply log request is performed with {} items", items.len());
 
-        let key_routes = utilities::internal::validate_key(&key[..], &self.name)?;
-        let table = match utilities::internal::find_table_mut(
-            Box::new(&mut self.root),
-            key_routes[..key_routes.len() - 1].to_vec(),
-        ) {
-            Some(table) => table,
-            None => {
-                tracing::trace!("key '{}' does not exist", key);
-                return Err(ErrorKind::InvalidKey(
-                    "Specified key does not exist".to_string(),
-                ));
+        for (index, item) in items.into_iter().enumerate() {
+            if let Err(e) = self.apply_log_item(item) {
+                tracing::error!("failed to apply item {} of log: {}", index + 1, e);
+                return Err(ErrorKind::LogError(format!("Item {}: {}", index + 1, e)));
             }
-        };
-
-        let find_key = KeyType::Queue(key_routes[key_routes.len() - 1].to_string());
-
-        match table.get_mut(&find_key) {
-            Some(value) => {
-                tracing::trace!("get request is done for '{}'", key);
-                match value {
-                    ValueType::QueuePointer(queue) => {
-                        let ret_value = match queue.pop_front() {
-                            Some(v) => v,
-                            None => {
-                                tracing::error!("queue was not cleanup before, try now");
-                                table.remove(&find_key);
-                                return Err(ErrorKind::InvalidKey(
-                                    "Specified key does not exist".to_string(),
-                                ));
-                            }
-                        };
+        }
 
-                        if queue.len() == 0 {
-                            table.remove(&find_key);
-                        }
+        tracing::trace!("apply log request is done");
+        return Ok(());
+    }
 
-                        return Ok(ret_value);
-                    }
-                    _ => {
-                        tracing::error!("this should not be happen, search was to a Queue but something else was found");
-                        return Err(ErrorKind::InvalidKey(
-                            "Specified key does not exist".to_string(),
-                        ));
-                    }
-                }
+    /// Apply a single log item on the database
+    fn apply_log_item(&mut self, item: LogItem) -> Result<(), ErrorKind> {
+        match item {
+            LogItem::SetKey(key, value) => {
+                self.insert(KeyType::Record(key), ValueType::RecordPointer(value))?
             }
-            None => {
-                tracing::trace!("key '{}' does not exist", key);
-                return Err(ErrorKind::InvalidKey(
-                    "Specified key does not exist".to_string(),
-                ));
+            LogItem::SetInt(key, value) => self.insert(KeyType::Record(key), ValueType::IntPointer(value))?,
+            LogItem::SetBytes(key, value) => self.insert(KeyType::Record(key), ValueType::BytesPointer(value))?,
+            LogItem::RemKey(key) => self.delete_key(KeyType::Record(key))?,
+            LogItem::RemPath(key) => self.delete_table(KeyType::Table(key))?,
+            LogItem::RemPrefix(prefix) => {
+                self.delete_prefix(prefix)?;
+            }
+            LogItem::CreatePath(key) => self.create_table(KeyType::Table(key))?,
+            LogItem::RenameKey(from, to) => self.rename(KeyType::Record(from), KeyType::Record(to))?,
+            LogItem::RenameQueue(from, to) => self.rename(KeyType::Queue(from), KeyType::Queue(to))?,
+            LogItem::RenamePath(from, to) => self.rename(KeyType::Table(from), KeyType::Table(to))?,
+            LogItem::QueuePush(key, value) => self.push(KeyType::Record(key), value)?,
+            LogItem::QueuePop(key) => {
+                self.pop(KeyType::Record(key))?;
+            }
+            LogItem::QueueDrain(key, count) => {
+                for _ in 0..count {
+                    self.pop(KeyType::Record(key.clone()))?;
+                }
             }
+            LogItem::ClearAll => self.clear(),
+            _ => tracing::trace!("log item does not modify the database, skipped"),
         }
+
+        return Ok(());
     }
 
-    /// List keys from a specific entry point and return with a key list. If failed return with error.
+    /// Read a log file and apply every line on the database by `apply_log_line`. Return with the number of lines.
+    /// If a line cannot be applied, return with an error that contains the line number.
     ///
     /// # Arguments
-    /// 1. `key_prefix` - Path where the keys has to be collected
-    /// 1. `level` - Need all inner level (`ListType::All`) or just current level (`ListType::OneLevel`)
+    /// 1. `path` - Path of the log file
+    pub fn replay_log(&mut self, path: &str) -> Result<usize, ErrorKind> {
+        tracing::trace!("replay log request is performed for '{}'", path);
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ErrorKind::LogError(format!("Failed to read log file: {}", e)))?;
+
+        let mut counter: usize = 0;
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Err(e) = self.apply_log_line(line) {
+                tracing::error!("failed to apply line {} of log: {}", index + 1, e);
+                return Err(ErrorKind::LogError(format!("Line {}: {}", index + 1, e)));
+            }
+            counter += 1;
+        }
+
+        tracing::trace!("replay log request is done, {} line applied", counter);
+        return Ok(counter);
+    }
+
+    /// Walk the whole tree and check its invariants: every table key points to a table,
+    /// every record key to a record, every queue key to a queue and the root
+    /// only contains the table of database name. Return with the list of violations if any found.
     ///
     /// # Example
     ///
     /// ```
     /// use onlyati_datastore::datastore::Database;
-    /// use onlyati_datastore::datastore::enums::{pair::KeyType, pair::ValueType, ListType};
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
     ///
     /// let mut db = Database::new("root".to_string()).unwrap();
     ///
-    /// db.insert(KeyType::Record("/root/status/sub1".to_string()), ValueType::RecordPointer("PING OK".to_string())).expect("Failed to insert");
-    /// db.insert(KeyType::Record("/root/status/sub2".to_string()), ValueType::RecordPointer("PING NOK".to_string())).expect("Failed to insert");
-    /// db.insert(KeyType::Record("/root/status/sub3".to_string()), ValueType::RecordPointer("PING OK".to_string())).expect("Failed to insert");
-    /// let list = db.list_keys(KeyType::Record("/root/status".to_string()), ListType::All).expect("Key not found");
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    /// db.push(KeyType::Record("/root/jobs".to_string()), "backup".to_string()).expect("Failed to push");
     ///
-    /// println!("{:?}", list);
+    /// assert_eq!(true, db.verify().is_ok());
     /// ```
-    pub fn list_keys(
-        &mut self,
-        key_prefix: KeyType,
-        level: ListType,
-    ) -> Result<Vec<KeyType>, ErrorKind> {
-        tracing::trace!(
-            "list keys request is performed for '{}'",
-            key_prefix.get_key()
-        );
-        let key_prefix = match key_prefix {
-            KeyType::Record(key) => key,
-            _ => {
-                return Err(ErrorKind::InvalidKey(
-                    "Parameter must be a Record type".to_string(),
-                ));
+    pub fn verify(&self) -> Result<(), Vec<String>> {
+        tracing::trace!("verify request is performed");
+        let mut violations = Vec::new();
+
+        for key in self.root.keys() {
+            if key != &KeyType::Table(self.name.clone()) {
+                violations.push(format!("root contains unexpected '{}' key", key.get_key()));
             }
-        };
+        }
 
-        // Find the base table
-        let key_routes = utilities::internal::validate_key(&key_prefix[..], &self.name)?;
-        let table = match utilities::internal::find_table(Box::new(&self.root), key_routes) {
-            Some(table) => table,
-            None => {
-                tracing::trace!("get request is failed due to no '{}' key exist", key_prefix);
-                return Err(ErrorKind::InvalidKey(
-                    "Specified route does not exist".to_string(),
-                ));
+        let mut tables: Vec<(String, &Table)> = vec![(String::new(), &self.root)];
+
+        while let Some((path, table)) = tables.pop() {
+            for (key, value) in table {
+                let key_path = format!("{}/{}", path, key.get_key());
+                match (key, value) {
+                    (KeyType::Table(_), ValueType::TablePointer(sub_table)) => {
+                        tables.push((key_path, sub_table));
+                    }
+                    (KeyType::Record(_), value) if value.belongs_to_record() => (),
+                    (KeyType::Queue(_), ValueType::QueuePointer(_)) => (),
+                    (KeyType::Table(_), _) => {
+                        violations.push(format!("table '{}' does not point to a table", key_path));
+                    }
+                    (KeyType::Record(_), _) => {
+                        violations.push(format!("record '{}' does not point to a record", key_path));
+                    }
+                    (KeyType::Queue(_), _) => {
+                        violations.push(format!("queue '{}' does not point to a queue", key_path));
+                    }
+                }
             }
-        };
+        }
 
-        // Get the information
-        let result = utilities::internal::display_tables(table, &key_prefix, &level)?;
+        if violations.is_empty() {
+            tracing::trace!("verify request is done, no violation found");
+            return Ok(());
+        }
 
-        tracing::trace!("list keys request is done for '{}'", key_prefix);
-        return Ok(result);
+        tracing::error!("verify request is done, {} violation found", violations.len());
+        return Err(violations);
     }
 
     /// Delete specific key, return with nothig if successful, else with error message.
+    /// Record or queue is deleted based on the key type, expired record is reported as not found.
     ///
     /// # Arguments
     /// 1. `key` - Unique key that has to be deleted
@@ -516,6 +3613,31 @@ impl Database {
     /// db.delete_key(key).expect("Could not delete the key");
     /// ```
     pub fn delete_key(&mut self, key: KeyType) -> Result<(), ErrorKind> {
+        self.remove(key)?;
+        return Ok(());
+    }
+
+    /// Delete specific key like `delete_key` and return with the removed value, like `BTreeMap::remove`
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key that has to be deleted
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// let key = KeyType::Record("/root/jobs/job1".to_string());
+    /// db.insert(key.clone(), ValueType::RecordPointer("backup".to_string())).expect("Failed to insert");
+    ///
+    /// assert_eq!(ValueType::RecordPointer("backup".to_string()), db.remove(key.clone()).unwrap());
+    /// assert_eq!(true, db.remove(key).is_err());
+    /// ```
+    pub fn remove(&mut self, key: KeyType) -> Result<ValueType, ErrorKind> {
+        let key = self.normalize_key(key);
         tracing::trace!("delete key request is performed for '{}'", key.get_key());
         if let KeyType::Table(_) = key {
             tracing::trace!("delete request is failed due to wrong key type");
@@ -525,41 +3647,192 @@ impl Database {
         }
 
         let key_routes = utilities::internal::validate_key(key.get_key(), &self.name)?;
-        let table = match utilities::internal::find_table_mut(
-            Box::new(&mut self.root),
+
+        // Expired record is already invisible for get, it is removed by the sweep
+        let expired = matches!(key, KeyType::Record(_)) && self.is_expired(&key_routes);
+        let table = match utilities::internal::find_table_mut(&mut self.root,
             key_routes[..key_routes.len() - 1].to_vec(),
         ) {
             Some(table) => table,
             None => {
                 tracing::trace!(
+                    op = "delete_key",
+                    key = key.get_key(),
+                    result = "error",
                     "delete request is failed because no '{}' key exist",
                     key.get_key()
                 );
-                return Err(ErrorKind::InvalidKey(
+                return Err(ErrorKind::NotFound(
                     "Specified key does not exist".to_string(),
                 ));
             }
         };
 
-        let delete_key = KeyType::Record(key_routes[key_routes.len() - 1].to_string());
+        let delete_key = utilities::internal::with_key(&key, key_routes[key_routes.len() - 1].to_string());
 
-        match table.remove(&delete_key) {
-            Some(_) => {
-                tracing::trace!("delete request is done for '{}'", key.get_key());
-                return Ok(());
+        let removed = match expired {
+            true => None,
+            false => table.remove(&delete_key),
+        };
+
+        match removed {
+            Some(value) => {
+                if let KeyType::Record(_) = key {
+                    self.expirations.remove(&format!("/{}", key_routes.join("/")));
+                    self.metadata.remove(&format!("/{}", key_routes.join("/")));
+                }
+                tracing::trace!(op = "delete_key", key = key.get_key(), result = "ok", "delete request is done for '{}'", key.get_key());
+                return Ok(value);
             }
             None => {
                 tracing::trace!(
+                    op = "delete_key",
+                    key = key.get_key(),
+                    result = "error",
                     "delete request is failed because no '{}' key exist",
                     key.get_key()
                 );
-                return Err(ErrorKind::InvalidKey(
+                return Err(ErrorKind::NotFound(
                     "Specified key does not exist".to_string(),
                 ));
             }
         };
     }
 
+    /// Delete specific key like `delete_key`, then remove the tables on its route that became empty.
+    /// Pruning stops at the first table that is not empty, the root table is never removed.
+    /// Return with the removed tables, the deepest is the first.
+    ///
+    /// # Arguments
+    /// 1. `key` - Unique key that has to be deleted
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    /// db.insert(KeyType::Record("/root/a/b/c".to_string()), ValueType::RecordPointer("ok".to_string())).unwrap();
+    /// db.insert(KeyType::Record("/root/a/x".to_string()), ValueType::RecordPointer("ok".to_string())).unwrap();
+    ///
+    /// let pruned = db.delete_key_pruning(KeyType::Record("/root/a/b/c".to_string())).unwrap();
+    /// assert_eq!(vec!["/root/a/b".to_string()], pruned);
+    /// ```
+    pub fn delete_key_pruning(&mut self, key: KeyType) -> Result<Vec<String>, ErrorKind> {
+        let key = self.normalize_key(key);
+        self.remove(key.clone())?;
+
+        let key_routes = utilities::internal::validate_key(key.get_key(), &self.name)?;
+        let mut pruned = Vec::new();
+
+        // Tables are between the root (index 0) and the deleted key (last index)
+        for index in (1..key_routes.len() - 1).rev() {
+            let parent = match utilities::internal::find_table_mut(&mut self.root,
+                key_routes[..index].to_vec(),
+            ) {
+                Some(parent) => parent,
+                None => break,
+            };
+
+            let table_key = KeyType::Table(key_routes[index].to_string());
+            match parent.get(&table_key) {
+                Some(ValueType::TablePointer(table)) if table.is_empty() => {
+                    parent.remove(&table_key);
+                    pruned.push(format!("/{}", key_routes[..=index].join("/")));
+                }
+                _ => break,
+            }
+        }
+
+        tracing::trace!("{} empty table is pruned after '{}' is deleted", pruned.len(), key.get_key());
+        return Ok(pruned);
+    }
+
+    /// Delete more keys in one call. Return with the result of each key in the same order.
+    ///
+    /// # Arguments
+    /// 1. `keys` - Keys that have to be deleted
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// let key = KeyType::Record("/root/status".to_string());
+    /// db.insert(key.clone(), ValueType::RecordPointer("Having a great time".to_string())).expect("Failed to insert");
+    ///
+    /// let results = db.delete_keys(vec![key, KeyType::Record("/root/missing".to_string())]);
+    /// assert_eq!(true, results[0].1.is_ok());
+    /// assert_eq!(true, results[1].1.is_err());
+    /// ```
+    pub fn delete_keys(&mut self, keys: Vec<KeyType>) -> Vec<(KeyType, Result<(), ErrorKind>)> {
+        tracing::trace!("delete keys request is performed for {} keys", keys.len());
+        let mut results = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            let result = self.delete_key(key.clone());
+            results.push((key, result));
+        }
+
+        tracing::trace!("delete keys request is done");
+        return results;
+    }
+
+    /// Create an empty table, so a namespace can exist before it has any record. Missing parent tables are created too.
+    /// If a table, record or queue already exists on the path, return with error.
+    ///
+    /// # Arguments
+    /// 1. `key` - Key of the new table
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::{pair::KeyType, ListType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.create_table(KeyType::Table("/root/status".to_string())).expect("Failed to create table");
+    ///
+    /// let list = db.list_keys(KeyType::Record("/root/status".to_string()), ListType::All).expect("Table not found");
+    /// assert_eq!(0, list.len());
+    /// ```
+    pub fn create_table(&mut self, key: KeyType) -> Result<(), ErrorKind> {
+        let key = self.normalize_key(key);
+        tracing::trace!("create table request is performed for '{}'", key.get_key());
+        if let KeyType::Record(_) = key {
+            tracing::trace!("create table request is failed due to wrong key type is specified");
+            return Err(ErrorKind::InvalidKey(
+                "Parameter must be a Table type".to_string(),
+            ));
+        }
+
+        let key_routes = self.validate_new_key(key.get_key())?;
+        let table = utilities::internal::find_or_create_table_mut(
+            &mut self.root,
+            key_routes[..key_routes.len() - 1].to_vec(),
+        )?;
+
+        let last_route = key_routes[key_routes.len() - 1].to_string();
+        let exists = table.contains_key(&KeyType::Table(last_route.clone()))
+            || table.contains_key(&KeyType::Record(last_route.clone()))
+            || table.contains_key(&KeyType::Queue(last_route.clone()));
+        if exists {
+            tracing::trace!("create table request is failed due to '{}' already exists", key.get_key());
+            return Err(ErrorKind::InvalidKey(
+                "Specified key already exists".to_string(),
+            ));
+        }
+
+        table.insert(KeyType::Table(last_route), ValueType::TablePointer(Table::new()));
+        tracing::trace!("create table request is done for '{}'", key.get_key());
+        return Ok(());
+    }
+
     /// Drop the whole table. If successful return with nothing else with error message.
     ///
     /// # Arguments
@@ -585,6 +3858,7 @@ impl Database {
     /// println!("{:?}", list);
     /// ```
     pub fn delete_table(&mut self, key: KeyType) -> Result<(), ErrorKind> {
+        let key = self.normalize_key(key);
         tracing::trace!("delete table request is performed for '{}'", key.get_key());
         if let KeyType::Record(_) = key {
             tracing::trace!("delete table request is failed due to wrong key type is specified");
@@ -594,17 +3868,19 @@ impl Database {
         }
 
         let key_routes = utilities::internal::validate_key(key.get_key(), &self.name)?;
-        let table = match utilities::internal::find_table_mut(
-            Box::new(&mut self.root),
+        let table = match utilities::internal::find_table_mut(&mut self.root,
             key_routes[..key_routes.len() - 1].to_vec(),
         ) {
             Some(table) => table,
             None => {
                 tracing::trace!(
+                    op = "delete_table",
+                    key = key.get_key(),
+                    result = "error",
                     "delete table request is failed because no '{}' key exist",
                     key.get_key()
                 );
-                return Err(ErrorKind::InvalidKey(
+                return Err(ErrorKind::NotFound(
                     "Specified key does not exist".to_string(),
                 ));
             }
@@ -614,18 +3890,114 @@ impl Database {
 
         match table.remove(&delete_key) {
             Some(_) => {
-                tracing::trace!("delete table request is performed for '{}'", key.get_key());
+                // Record or queue with the same name as the table is not affected
+                let prefix = format!("/{}/", key_routes.join("/"));
+                self.metadata.retain(|key, _| !key.starts_with(&prefix));
+                self.versions.retain(|key, _| !key.starts_with(&prefix));
+                self.expirations.retain(|key, _| !key.starts_with(&prefix));
+                self.queue_limits.retain(|key, _| !key.starts_with(&prefix));
+                tracing::trace!(op = "delete_table", key = key.get_key(), result = "ok", "delete table request is done for '{}'", key.get_key());
                 return Ok(());
             }
             None => {
                 tracing::trace!(
+                    op = "delete_table",
+                    key = key.get_key(),
+                    result = "error",
                     "delete table request is failed because no '{}' key exist",
                     key.get_key()
                 );
-                return Err(ErrorKind::InvalidKey(
+                return Err(ErrorKind::NotFound(
                     "Specified key does not exist".to_string(),
                 ));
             }
         };
     }
+
+    /// Delete every record, queue and table whose full path begins with the prefix. Prefix is compared by characters,
+    /// so it does not have to be an existing table: `/root/session/` removes the content of the session table,
+    /// `/root/sess` removes `/root/session` and `/root/sessions` too. Return with the number of removed entries,
+    /// content of the removed tables is counted too.
+    ///
+    /// # Arguments
+    /// 1. `prefix` - Beginning of the paths, it must be under the root table
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use onlyati_datastore::datastore::Database;
+    /// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+    ///
+    /// let mut db = Database::new("root".to_string()).unwrap();
+    ///
+    /// db.insert(KeyType::Record("/root/session/a1/user".to_string()), ValueType::RecordPointer("joe".to_string())).expect("Failed to insert");
+    /// db.insert(KeyType::Record("/root/sessions".to_string()), ValueType::RecordPointer("1".to_string())).expect("Failed to insert");
+    /// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+    ///
+    /// // Table "session", its table "a1" with record "user" and record "sessions"
+    /// assert_eq!(4, db.delete_prefix("/root/sess".to_string()).unwrap());
+    /// assert_eq!(true, db.get(KeyType::Record("/root/status".to_string())).is_ok());
+    /// ```
+    pub fn delete_prefix(&mut self, prefix: String) -> Result<usize, ErrorKind> {
+        let (count, _) = self.delete_prefix_keys(&prefix)?;
+        return Ok(count);
+    }
+
+    /// Perform `delete_prefix` and return with the removed entries of the deepest existing table too
+    pub(crate) fn delete_prefix_keys(&mut self, prefix: &str) -> Result<(usize, Vec<KeyType>), ErrorKind> {
+        let prefix = self.normalize_path(prefix);
+        tracing::trace!("delete prefix request is performed for '{}'", prefix);
+        if !prefix.starts_with(&format!("/{}/", self.name)) {
+            tracing::trace!("delete prefix request is failed, '{}' is not under the root table", prefix);
+            return Err(ErrorKind::InvalidKey(
+                "Prefix must be under the root table".to_string(),
+            ));
+        }
+
+        // Only the last table of prefix can contain partially matching names
+        let (parent, partial) = match prefix.rsplit_once('/') {
+            Some(parts) => parts,
+            None => ("", &prefix[..]),
+        };
+        let key_routes = utilities::internal::validate_key(parent, &self.name)?;
+        let table = match utilities::internal::find_table_mut(&mut self.root, key_routes) {
+            Some(table) => table,
+            None => {
+                tracing::trace!("delete prefix request is done for '{}', no table found", prefix);
+                return Ok((0, Vec::new()));
+            }
+        };
+
+        let keys: Vec<KeyType> = table
+            .keys()
+            .filter(|key| key.get_key().starts_with(partial))
+            .cloned()
+            .collect();
+
+        let mut count: usize = 0;
+        let mut removed = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(value) = table.remove(&key) {
+                count += 1;
+                if let ValueType::TablePointer(sub_table) = &value {
+                    count += utilities::internal::count_entries(sub_table);
+                }
+
+                let path = format!("{}/{}", parent, key.get_key());
+                removed.push(match key {
+                    KeyType::Table(_) => KeyType::Table(path),
+                    KeyType::Record(_) => KeyType::Record(path),
+                    KeyType::Queue(_) => KeyType::Queue(path),
+                });
+            }
+        }
+
+        self.versions.retain(|key, _| !key.starts_with(&prefix));
+        self.expirations.retain(|key, _| !key.starts_with(&prefix));
+        self.queue_limits.retain(|key, _| !key.starts_with(&prefix));
+        self.metadata.retain(|key, _| !key.starts_with(&prefix));
+
+        tracing::trace!("delete prefix request is done for '{}', {} entries removed", prefix, count);
+        return Ok((count, removed));
+    }
 }
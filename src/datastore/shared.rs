@@ -0,0 +1,162 @@
+//! Database that can be used from more threads directly, without the channel of `utilities::start_datastore`
+
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use super::{
+    enums::{error::ErrorKind, pair::KeyType, pair::ValueType, ListType},
+    Database,
+};
+
+/// Database behind `Arc<RwLock>`: reads take a shared lock, so they run in parallel,
+/// only the modifications take the exclusive lock. Clones point to the same database.
+///
+/// Writes alert the hook manager like `Database` does, but nothing is written into the log,
+/// so use `utilities::start_datastore` if the log is needed.
+///
+/// # Examples
+/// ```
+/// use onlyati_datastore::datastore::shared::SharedDatabase;
+/// use onlyati_datastore::datastore::enums::pair::{KeyType, ValueType};
+///
+/// let db = SharedDatabase::new("root".to_string()).unwrap();
+/// db.insert(KeyType::Record("/root/status".to_string()), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+///
+/// let reader = db.clone();
+/// let value = std::thread::spawn(move || reader.get(KeyType::Record("/root/status".to_string())))
+///     .join()
+///     .unwrap()
+///     .expect("Key not found");
+/// assert_eq!(ValueType::RecordPointer("ok".to_string()), value);
+/// ```
+#[derive(Clone)]
+pub struct SharedDatabase {
+    inner: Arc<RwLock<Database>>,
+}
+
+impl SharedDatabase {
+    /// Create new database, see `Database::new`
+    ///
+    /// # Arguments
+    /// 1. `root_name` - Name of database
+    pub fn new(root_name: String) -> Result<Self, ErrorKind> {
+        return Ok(SharedDatabase::from_database(Database::new(root_name)?));
+    }
+
+    /// Share an existing database, e.g. that is subscribed to the hook manager
+    pub fn from_database(db: Database) -> Self {
+        return SharedDatabase {
+            inner: Arc::new(RwLock::new(db)),
+        };
+    }
+
+    /// Shared lock of database, a panic of another thread does not make it unusable
+    fn read_lock(&self) -> RwLockReadGuard<'_, Database> {
+        return self.inner.read().unwrap_or_else(|e| e.into_inner());
+    }
+
+    /// Exclusive lock of database, a panic of another thread does not make it unusable
+    fn write_lock(&self) -> RwLockWriteGuard<'_, Database> {
+        return self.inner.write().unwrap_or_else(|e| e.into_inner());
+    }
+
+    /// Run a function with shared lock, for the reads that have no own method here
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::datastore::shared::SharedDatabase;
+    ///
+    /// let db = SharedDatabase::new("root".to_string()).unwrap();
+    /// assert_eq!(0, db.read(|db| db.total_queued()));
+    /// ```
+    pub fn read<R>(&self, f: impl FnOnce(&Database) -> R) -> R {
+        return f(&self.read_lock());
+    }
+
+    /// Run a function with exclusive lock, for the modifications that have no own method here
+    pub fn write<R>(&self, f: impl FnOnce(&mut Database) -> R) -> R {
+        return f(&mut self.write_lock());
+    }
+
+    /// Get the value of a key, see `Database::get`
+    pub fn get(&self, key: KeyType) -> Result<ValueType, ErrorKind> {
+        return self.read_lock().get(key);
+    }
+
+    /// Check that key exists, see `Database::exists`
+    pub fn exists(&self, key: KeyType) -> Result<bool, ErrorKind> {
+        return self.read_lock().exists(key);
+    }
+
+    /// List keys with their values, see `Database::list_pairs`
+    pub fn list_pairs(&self, key_prefix: KeyType, level: ListType) -> Result<Vec<(KeyType, ValueType)>, ErrorKind> {
+        return self.read_lock().list_pairs(key_prefix, level);
+    }
+
+    /// Count keys under a table, see `Database::count_keys`
+    pub fn count_keys(&self, key_prefix: KeyType, level: ListType) -> Result<usize, ErrorKind> {
+        return self.read_lock().count_keys(key_prefix, level);
+    }
+
+    /// Length of a queue, see `Database::queue_len`
+    pub fn queue_len(&self, key: KeyType) -> Result<usize, ErrorKind> {
+        return self.read_lock().queue_len(key);
+    }
+
+    /// First value of a queue without removing it, see `Database::peek`
+    pub fn peek(&self, key: KeyType) -> Result<Option<String>, ErrorKind> {
+        return self.read_lock().peek(key);
+    }
+
+    /// Insert or update a record, see `Database::insert`
+    pub fn insert(&self, key: KeyType, value: ValueType) -> Result<(), ErrorKind> {
+        return self.write_lock().insert(key, value);
+    }
+
+    /// Push a value into a queue, see `Database::push`
+    pub fn push(&self, key: KeyType, value: String) -> Result<(), ErrorKind> {
+        return self.write_lock().push(key, value);
+    }
+
+    /// Pop a value from a queue, see `Database::pop`
+    pub fn pop(&self, key: KeyType) -> Result<Option<String>, ErrorKind> {
+        return self.write_lock().pop(key);
+    }
+
+    /// Add delta to a numeric record, see `Database::increment`
+    pub fn increment(&self, key: KeyType, delta: i64) -> Result<i64, ErrorKind> {
+        return self.write_lock().increment(key, delta);
+    }
+
+    /// Delete a record, see `Database::delete_key`
+    pub fn delete_key(&self, key: KeyType) -> Result<(), ErrorKind> {
+        let mut db = self.write_lock();
+        let name = key.get_key().to_string();
+        db.delete_key(key)?;
+        db.alert_delete(&name);
+        return Ok(());
+    }
+
+    /// Delete a record and return with its value, see `Database::remove`
+    pub fn remove(&self, key: KeyType) -> Result<ValueType, ErrorKind> {
+        let mut db = self.write_lock();
+        let name = key.get_key().to_string();
+        let value = db.remove(key)?;
+        db.alert_delete(&name);
+        return Ok(value);
+    }
+
+    /// Create a table, see `Database::create_table`
+    pub fn create_table(&self, key: KeyType) -> Result<(), ErrorKind> {
+        return self.write_lock().create_table(key);
+    }
+
+    /// Delete a table with its content, see `Database::delete_table`
+    pub fn delete_table(&self, key: KeyType) -> Result<(), ErrorKind> {
+        let mut db = self.write_lock();
+        let name = key.get_key().to_string();
+        db.delete_table(key)?;
+        db.alert_delete(&name);
+        db.clear_hooks_under(&name);
+        return Ok(());
+    }
+}
@@ -1,14 +1,239 @@
 //! Custom types
 
 use std::collections::BTreeMap;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
 use super::enums::error::ErrorKind;
 
 use super::enums::pair::{KeyType, ValueType};
+use super::enums::{ChangeKind, Source};
 
 pub type Table = BTreeMap<KeyType, ValueType>;
 
 pub type ResultWithResult = Result<ValueType, ErrorKind>;
 pub type ResultWithoutResult = Result<(), ErrorKind>;
 pub type ResultWithList = Result<Vec<KeyType>, ErrorKind>;
+pub type ResultWithPairs = Result<Vec<(KeyType, ValueType)>, ErrorKind>;
+pub type ResultWithMeta = Result<RecordMeta, ErrorKind>;
+pub type ResultWithSnapshot = Result<DatabaseSnapshot, ErrorKind>;
+pub type ResultWithJson = Result<serde_json::Value, ErrorKind>;
 pub type ResultWithHook = Result<(String, Vec<String>), ErrorKind>;
 pub type ResultWithHooks = Result<BTreeMap<String, Vec<String>>, ErrorKind>;
+pub type ResultWithCount = Result<usize, ErrorKind>;
+pub type ResultWithHotKeys = Result<Vec<(KeyType, u64)>, ErrorKind>;
+pub type ResultWithLinks = Result<BTreeMap<String, Vec<String>>, ErrorKind>;
+pub type ResultWithKeyResults = Result<Vec<(KeyType, ResultWithoutResult)>, ErrorKind>;
+pub type QueueLengths = Vec<(String, ResultWithCount)>;
+pub type ValueResults = Vec<(String, ResultWithResult)>;
+pub type ResultWithOptional = Result<Option<String>, ErrorKind>;
+pub type ResultWithSource = Result<(ValueType, Source), ErrorKind>;
+pub type ResultWithNumber = Result<i64, ErrorKind>;
+pub type ResultWithBool = Result<bool, ErrorKind>;
+pub type ResultWithPrevious = Result<Option<ValueType>, ErrorKind>;
+
+/// Metadata of a record
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordMeta {
+    /// Time when the record was created
+    pub created_at: DateTime<Utc>,
+
+    /// Time when the record was written last time
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Change of a key that is sent to watchers, see `DatabaseAction::Watch`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangeEvent {
+    /// Full name of the changed key
+    pub key: String,
+
+    /// What happened with the key
+    pub kind: ChangeKind,
+
+    /// New value at set, `None` at delete
+    pub value: Option<String>,
+}
+
+/// Estimated size of the database, see `Database::size_estimate`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SizeReport {
+    /// Number of records
+    pub records: usize,
+
+    /// Number of tables, without the root table
+    pub tables: usize,
+
+    /// Number of queues
+    pub queues: usize,
+
+    /// Number of items in all queues
+    pub queue_items: usize,
+
+    /// Bytes of key names
+    pub key_bytes: usize,
+
+    /// Bytes of record values and queue items
+    pub value_bytes: usize,
+
+    /// Approximate heap usage: key and value bytes plus the fixed size of every entry and queue item
+    pub total_bytes: usize,
+}
+
+/// Counters of the datastore thread, see `DatabaseAction::Stats`
+#[derive(Clone, Debug, Default)]
+pub struct DatastoreStats {
+    /// Number of read requests of records, batch requests are counted per key
+    pub gets: u64,
+
+    /// Number of write requests of records and tables, batch requests are counted per key
+    pub sets: u64,
+
+    /// Number of delete requests of records and tables, batch requests are counted per key
+    pub deletes: u64,
+
+    /// Number of list, count and export requests
+    pub lists: u64,
+
+    /// Number of requests that were sent to the hook manager
+    pub hook_executions: u64,
+
+    /// Current number of records
+    pub keys: usize,
+
+    /// Current number of items in all queues
+    pub total_queued: usize,
+}
+
+/// Owned copy of the database, it can be serialized to persist it
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DatabaseSnapshot {
+    /// Name of the root table
+    pub name: String,
+
+    /// Content of the root table
+    pub root: SnapshotTable,
+}
+
+impl DatabaseSnapshot {
+    /// Convert snapshot into JSON, layout is the same like with `serde` feature
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        return serde_json::json!({
+            "name": self.name,
+            "root": self.root.to_json(),
+        });
+    }
+
+    /// Parse snapshot from JSON that was created by `to_json`
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let name = match value.get("name").and_then(|x| x.as_str()) {
+            Some(name) => name.to_string(),
+            None => return Err("Missing 'name' field".to_string()),
+        };
+        let root = match value.get("root") {
+            Some(root) => SnapshotTable::from_json(root)?,
+            None => return Err("Missing 'root' field".to_string()),
+        };
+
+        return Ok(DatabaseSnapshot { name, root });
+    }
+}
+
+/// Owned copy of a table, records, queues and sub tables are separated because they can have the same name
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotTable {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub records: BTreeMap<String, String>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ints: BTreeMap<String, i64>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bytes: BTreeMap<String, Vec<u8>>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub queues: BTreeMap<String, Vec<String>>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tables: BTreeMap<String, SnapshotTable>,
+}
+
+impl SnapshotTable {
+    /// Convert table into JSON, layout is the same like with `serde` feature
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let tables: serde_json::Map<String, serde_json::Value> = self
+            .tables
+            .iter()
+            .map(|(name, table)| (name.clone(), table.to_json()))
+            .collect();
+
+        return serde_json::json!({
+            "records": self.records,
+            "ints": self.ints,
+            "bytes": self.bytes,
+            "queues": self.queues,
+            "tables": tables,
+        });
+    }
+
+    /// Parse table from JSON that was created by `to_json`, missing fields are empty
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let mut table = SnapshotTable::default();
+
+        for (name, value) in snapshot_field(value, "records")? {
+            let value = value.as_str().ok_or(format!("Record '{}' is not a string", name))?;
+            table.records.insert(name.clone(), value.to_string());
+        }
+
+        for (name, value) in snapshot_field(value, "ints")? {
+            let value = value.as_i64().ok_or(format!("Record '{}' is not an integer", name))?;
+            table.ints.insert(name.clone(), value);
+        }
+
+        for (name, value) in snapshot_field(value, "bytes")? {
+            let bytes = value
+                .as_array()
+                .and_then(|list| {
+                    list.iter()
+                        .map(|x| x.as_u64().and_then(|x| u8::try_from(x).ok()))
+                        .collect::<Option<Vec<u8>>>()
+                })
+                .ok_or(format!("Record '{}' is not a byte array", name))?;
+            table.bytes.insert(name.clone(), bytes);
+        }
+
+        for (name, value) in snapshot_field(value, "queues")? {
+            let queue = value
+                .as_array()
+                .and_then(|list| {
+                    list.iter()
+                        .map(|x| x.as_str().map(|x| x.to_string()))
+                        .collect::<Option<Vec<String>>>()
+                })
+                .ok_or(format!("Queue '{}' is not a string array", name))?;
+            table.queues.insert(name.clone(), queue);
+        }
+
+        for (name, value) in snapshot_field(value, "tables")? {
+            table.tables.insert(name.clone(), SnapshotTable::from_json(value)?);
+        }
+
+        return Ok(table);
+    }
+}
+
+/// Return with the object field of snapshot table, missing field is empty
+fn snapshot_field<'a>(value: &'a serde_json::Value, field: &str) -> Result<Vec<(&'a String, &'a serde_json::Value)>, String> {
+    return match value.get(field) {
+        Some(serde_json::Value::Object(map)) => Ok(map.iter().collect()),
+        Some(_) => Err(format!("Field '{}' is not an object", field)),
+        None => Ok(Vec::new()),
+    };
+}
+
+/// Predicate that can be sent to the database thread
+pub type Predicate = Box<dyn Fn(&str) -> bool + Send>;
+
+/// Read-through loader, it is called with the key if it is missing and return with its value if any
+pub type Loader = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
@@ -1,3 +1,19 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::{
+    collections::{HashMap, VecDeque},
+    io::Write,
+    sync::mpsc::{Receiver, Sender, TryRecvError},
+    time::{Duration, Instant},
+};
+
+use crate::hook::enums::HookManagerResponse;
+use crate::logger::enums::{LogFilter, LogItem, LoggerAction};
+
+use crate::datastore::{
+    enums::{DatabaseAction, KeyValidation, TxnOp},
+    types::{DatastoreStats, ResultWithCount, SnapshotTable},
+};
+
 use super::{
     Table, {ErrorKind, KeyType, ListType, ValueType},
 };
@@ -15,12 +31,11 @@ pub(crate) fn validate_key<'a>(
     }
 
     let key_routes = key_string
-        .split("/")
-        .into_iter()
+        .split('/')
         .filter(|x| !x.is_empty())
         .collect::<Vec<&str>>();
 
-    if key_routes.len() < 1 {
+    if key_routes.is_empty() {
         return Err(ErrorKind::InvalidKey(
             "Key must contain at least 1 items, e.g.: /root/status".to_string(),
         ));
@@ -35,84 +50,725 @@ pub(crate) fn validate_key<'a>(
     return Ok(key_routes);
 }
 
+/// Check the segments of key according to the validation, the offending segment is named in the error.
+/// It does not check the root, that is done by `validate_key`.
+pub(crate) fn check_key_segments(key_string: &str, validation: KeyValidation) -> Result<(), ErrorKind> {
+    if validation == KeyValidation::Lenient {
+        return Ok(());
+    }
+
+    let segments = key_string.split('/').skip(1).collect::<Vec<&str>>();
+    for (index, segment) in segments.iter().enumerate() {
+        if segment.is_empty() {
+            let before = format!("/{}", segments[..index].join("/"));
+            return Err(ErrorKind::InvalidKey(format!(
+                "Key contains empty segment after '{}'",
+                before
+            )));
+        }
+
+        if validation == KeyValidation::Restricted {
+            let allowed = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':');
+            if let Some(c) = segment.chars().find(|c| !allowed(*c)) {
+                return Err(ErrorKind::InvalidKey(format!(
+                    "Segment '{}' contains not allowed character {:?}",
+                    segment, c
+                )));
+            }
+        }
+    }
+
+    return Ok(());
+}
+
 /// Recursive algoritm to find a table
-pub(crate) fn find_table<'a>(db: Box<&'a Table>, routes: Vec<&'a str>) -> Option<Box<&'a Table>> {
-    if routes.len() == 0 {
+pub(crate) fn find_table<'a>(db: &'a Table, routes: Vec<&str>) -> Option<&'a Table> {
+    if routes.is_empty() {
         return Some(db);
     }
 
     let current_table = KeyType::Table(routes[0].to_string());
     match db.get(&current_table) {
-        Some(table) => match table {
-            ValueType::TablePointer(table_pointer) => {
-                return find_table(Box::new(table_pointer), routes[1..].to_vec());
-            }
-            _ => return None,
-        },
+        Some(ValueType::TablePointer(table_pointer)) => {
+            return find_table(table_pointer, routes[1..].to_vec());
+        }
         _ => return None,
     }
 }
 
 /// Recursive algoritm the find a table and return as mutable reference
-pub(crate) fn find_table_mut<'a>(
-    db: Box<&'a mut Table>,
-    routes: Vec<&'a str>,
-) -> Option<Box<&'a mut Table>> {
-    if routes.len() == 0 {
+pub(crate) fn find_table_mut<'a>(db: &'a mut Table, routes: Vec<&str>) -> Option<&'a mut Table> {
+    if routes.is_empty() {
         return Some(db);
     }
 
     let current_table = KeyType::Table(routes[0].to_string());
     match db.get_mut(&current_table) {
-        Some(table) => match table {
-            ValueType::TablePointer(table_pointer) => {
-                return find_table_mut(Box::new(table_pointer), routes[1..].to_vec());
-            }
-            _ => return None,
-        },
+        Some(ValueType::TablePointer(table_pointer)) => {
+            return find_table_mut(table_pointer, routes[1..].to_vec());
+        }
         _ => return None,
     }
 }
 
-/// Display all items from a table
-pub(crate) fn display_tables<'a>(
-    db: Box<&Table>,
-    key_prefix: &String,
+/// Display all items from a table. If more than `limit` keys are found, collecting is stopped
+/// and `ErrorKind::ResultTooLarge` is returned.
+pub(crate) fn display_tables(
+    db: &Table,
+    key_prefix: &str,
     level: &ListType,
+    limit: Option<usize>,
 ) -> Result<Vec<KeyType>, ErrorKind> {
-    let mut result: Vec<KeyType> = Vec::with_capacity(std::mem::size_of::<KeyType>() * db.len());
+    let mut result: Vec<KeyType> = Vec::new();
+
+    if let Some(limit) = limit {
+        if !collect_keys(db, key_prefix, level, limit, &mut result) {
+            return Err(ErrorKind::ResultTooLarge {
+                count: count_tables(db, level),
+                limit,
+            });
+        }
+    } else {
+        collect_keys(db, key_prefix, level, usize::MAX, &mut result);
+    }
 
+    return Ok(result);
+}
+
+/// Collect the records and queues into `result`, return with false as soon as it has more than `limit` keys
+fn collect_keys(db: &Table, key_prefix: &str, level: &ListType, limit: usize, result: &mut Vec<KeyType>) -> bool {
     for (key, value) in db.iter() {
         match key {
             KeyType::Record(key) => {
-                let new_key = format!("{}/{}", key_prefix.clone(), key);
-                let new_key = KeyType::Record(new_key);
-                result.push(new_key);
+                let new_key = format!("{}/{}", key_prefix, key);
+                result.push(KeyType::Record(new_key));
             }
             KeyType::Table(key) => {
                 if *level == ListType::OneLevel {
                     continue;
                 }
 
-                let table_name = match value {
+                let table = match value {
                     ValueType::TablePointer(table) => table,
                     _ => continue,
                 };
-                let mut temp = display_tables(
-                    Box::new(table_name),
-                    &format!("{}/{}", key_prefix, key),
-                    level,
-                )?;
-
-                result.append(&mut temp);
+                if !collect_keys(table, &format!("{}/{}", key_prefix, key), level, limit, result) {
+                    return false;
+                }
             }
             KeyType::Queue(key) => {
-                let new_key = format!("{}/{}", key_prefix.clone(), key);
-                let new_key = KeyType::Queue(new_key);
-                result.push(new_key);
+                let new_key = format!("{}/{}", key_prefix, key);
+                result.push(KeyType::Queue(new_key));
             }
         }
+
+        if result.len() > limit {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+/// Collect the tables under the prefix, children of a table come right after it.
+/// If more than `limit` tables are found, collecting is stopped and `ErrorKind::ResultTooLarge` is returned.
+pub(crate) fn display_subtables(
+    db: &Table,
+    key_prefix: &str,
+    level: &ListType,
+    limit: Option<usize>,
+) -> Result<Vec<KeyType>, ErrorKind> {
+    let mut result: Vec<KeyType> = Vec::new();
+
+    if let Some(limit) = limit {
+        if !collect_subtables(db, key_prefix, level, limit, &mut result) {
+            return Err(ErrorKind::ResultTooLarge {
+                count: count_subtables(db, level),
+                limit,
+            });
+        }
+    } else {
+        collect_subtables(db, key_prefix, level, usize::MAX, &mut result);
     }
 
     return Ok(result);
 }
+
+/// Collect the tables into `result`, return with false as soon as it has more than `limit` tables
+fn collect_subtables(db: &Table, key_prefix: &str, level: &ListType, limit: usize, result: &mut Vec<KeyType>) -> bool {
+    for (key, value) in db.iter() {
+        if let (KeyType::Table(key), ValueType::TablePointer(table)) = (key, value) {
+            let new_key = format!("{}/{}", key_prefix, key);
+            result.push(KeyType::Table(new_key.clone()));
+
+            if result.len() > limit {
+                return false;
+            }
+
+            if *level == ListType::All && !collect_subtables(table, &new_key, level, limit, result) {
+                return false;
+            }
+        }
+    }
+
+    return true;
+}
+
+/// Count the tables like `display_subtables` lists them, but without collecting the keys
+pub(crate) fn count_subtables(db: &Table, level: &ListType) -> usize {
+    let mut count: usize = 0;
+
+    for (key, value) in db.iter() {
+        if let (KeyType::Table(_), ValueType::TablePointer(table)) = (key, value) {
+            count += 1;
+            if *level == ListType::All {
+                count += count_subtables(table, level);
+            }
+        }
+    }
+
+    return count;
+}
+
+/// Count every record, queue and table under the table on recursive way
+pub(crate) fn count_entries(db: &Table) -> usize {
+    let mut count: usize = 0;
+
+    for value in db.values() {
+        count += 1;
+        if let ValueType::TablePointer(table) = value {
+            count += count_entries(table);
+        }
+    }
+
+    return count;
+}
+
+/// Count records and queues like `display_tables` lists them, but without collecting the keys
+pub(crate) fn count_tables(db: &Table, level: &ListType) -> usize {
+    let mut count: usize = 0;
+
+    for (key, value) in db.iter() {
+        match key {
+            KeyType::Record(_) | KeyType::Queue(_) => count += 1,
+            KeyType::Table(_) => {
+                if *level == ListType::OneLevel {
+                    continue;
+                }
+
+                if let ValueType::TablePointer(table) = value {
+                    count += count_tables(table, level);
+                }
+            }
+        }
+    }
+
+    return count;
+}
+
+/// Find a table and create the missing tables on the route
+pub(crate) fn find_or_create_table_mut<'a>(
+    db: &'a mut Table,
+    routes: Vec<&str>,
+) -> Result<&'a mut Table, ErrorKind> {
+    let mut table = db;
+
+    for route in routes {
+        table = match table
+            .entry(KeyType::Table(route.to_string()))
+            .or_insert(ValueType::TablePointer(Table::new()))
+        {
+            ValueType::TablePointer(sub_table) => sub_table,
+            _ => {
+                tracing::error!("wow, this should not happen a table pointer should be here");
+                return Err(ErrorKind::InternalError(
+                    "This should not have happen".to_string(),
+                ));
+            }
+        };
+    }
+
+    return Ok(table);
+}
+
+/// Collect every record and queue with their full key from a table, sub tables are walked if level is `ListType::All`
+pub(crate) fn collect_pairs(db: &Table, key_prefix: &String, level: &ListType) -> Vec<(KeyType, ValueType)> {
+    let mut result: Vec<(KeyType, ValueType)> = Vec::new();
+
+    for (key, value) in db.iter() {
+        match key {
+            KeyType::Record(key) => {
+                let new_key = KeyType::Record(format!("{}/{}", key_prefix, key));
+                result.push((new_key, value.clone()));
+            }
+            KeyType::Queue(key) => {
+                let new_key = KeyType::Queue(format!("{}/{}", key_prefix, key));
+                result.push((new_key, value.clone()));
+            }
+            KeyType::Table(key) => {
+                if *level == ListType::OneLevel {
+                    continue;
+                }
+
+                if let ValueType::TablePointer(table) = value {
+                    let mut temp = collect_pairs(table, &format!("{}/{}", key_prefix, key), level);
+                    result.append(&mut temp);
+                }
+            }
+        }
+    }
+
+    return result;
+}
+
+/// Collect the empty tables on recursive way, the table itself is included if it is empty
+pub(crate) fn collect_empty_tables(db: &Table, key_prefix: &String) -> Vec<(KeyType, ValueType)> {
+    if db.is_empty() {
+        return vec![(KeyType::Table(key_prefix.clone()), ValueType::TablePointer(Table::new()))];
+    }
+
+    let mut result: Vec<(KeyType, ValueType)> = Vec::new();
+    for (key, value) in db.iter() {
+        if let (KeyType::Table(key), ValueType::TablePointer(table)) = (key, value) {
+            result.append(&mut collect_empty_tables(table, &format!("{}/{}", key_prefix, key)));
+        }
+    }
+
+    return result;
+}
+
+/// Return with a new key that has the same type than the original one
+pub(crate) fn with_key(key: &KeyType, new_key: String) -> KeyType {
+    return match key {
+        KeyType::Record(_) => KeyType::Record(new_key),
+        KeyType::Queue(_) => KeyType::Queue(new_key),
+        KeyType::Table(_) => KeyType::Table(new_key),
+    };
+}
+
+/// Tells that a key is the same or it is under the prefix, checked by routes not by characters
+pub(crate) fn is_under_prefix(key: &str, prefix: &str) -> bool {
+    if key == prefix {
+        return true;
+    }
+
+    return key.starts_with(prefix) && key[prefix.len()..].starts_with('/');
+}
+
+/// Move the entries of `map` whose key is accepted by `filter` from `old` prefix to `new` prefix
+pub(crate) fn move_entries<V, F: Fn(&str) -> bool>(map: &mut HashMap<String, V>, old: &str, new: &str, filter: F) {
+    let keys: Vec<String> = map.keys().filter(|key| filter(key)).cloned().collect();
+
+    for key in keys {
+        if let Some(value) = map.remove(&key) {
+            map.insert(format!("{}{}", new, &key[old.len()..]), value);
+        }
+    }
+}
+
+/// Copy the entries of `map` whose key is under `old` prefix to be under `new` prefix too
+pub(crate) fn copy_entries<V: Clone>(map: &mut HashMap<String, V>, old: &str, new: &str) {
+    let entries: Vec<(String, V)> = map
+        .iter()
+        .filter(|(key, _)| is_under_prefix(key, old))
+        .map(|(key, value)| (format!("{}{}", new, &key[old.len()..]), value.clone()))
+        .collect();
+
+    map.extend(entries);
+}
+
+/// Copy a table into snapshot on recursive way
+pub(crate) fn table_to_snapshot(db: &Table) -> SnapshotTable {
+    let mut snapshot = SnapshotTable::default();
+
+    for (key, value) in db.iter() {
+        match value {
+            ValueType::TablePointer(table) => {
+                snapshot.tables.insert(key.get_key().to_string(), table_to_snapshot(table));
+            }
+            ValueType::RecordPointer(record) => {
+                snapshot.records.insert(key.get_key().to_string(), record.clone());
+            }
+            ValueType::IntPointer(record) => {
+                snapshot.ints.insert(key.get_key().to_string(), *record);
+            }
+            ValueType::BytesPointer(record) => {
+                snapshot.bytes.insert(key.get_key().to_string(), record.clone());
+            }
+            ValueType::QueuePointer(queue) => {
+                snapshot.queues.insert(key.get_key().to_string(), queue.iter().cloned().collect());
+            }
+        }
+    }
+
+    return snapshot;
+}
+
+/// Build a table from snapshot on recursive way
+pub(crate) fn snapshot_to_table(snapshot: SnapshotTable) -> Table {
+    let mut table = Table::new();
+
+    for (name, record) in snapshot.records {
+        table.insert(KeyType::Record(name), ValueType::RecordPointer(record));
+    }
+
+    for (name, record) in snapshot.ints {
+        table.insert(KeyType::Record(name), ValueType::IntPointer(record));
+    }
+
+    for (name, record) in snapshot.bytes {
+        table.insert(KeyType::Record(name), ValueType::BytesPointer(record));
+    }
+
+    for (name, queue) in snapshot.queues {
+        // Empty queues are not kept in the database
+        if !queue.is_empty() {
+            table.insert(KeyType::Queue(name), ValueType::QueuePointer(queue.into()));
+        }
+    }
+
+    for (name, sub_table) in snapshot.tables {
+        table.insert(KeyType::Table(name), ValueType::TablePointer(snapshot_to_table(sub_table)));
+    }
+
+    return table;
+}
+
+/// Binary record is represented as `{"$bytes": "<base64>"}` in JSON.
+const BYTES_TAG: &str = "$bytes";
+
+/// Convert binary record into its JSON representation.
+fn bytes_to_json(bytes: &[u8]) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    object.insert(BYTES_TAG.to_string(), serde_json::Value::String(STANDARD.encode(bytes)));
+    return serde_json::Value::Object(object);
+}
+
+/// Return with the encoded content if the object is a binary record representation.
+fn json_bytes_tag(object: &serde_json::Map<String, serde_json::Value>) -> Option<&str> {
+    if object.len() != 1 {
+        return None;
+    }
+
+    return object.get(BYTES_TAG).and_then(|x| x.as_str());
+}
+
+/// Create the log item of a record based on its value type, tables and queues are not logged this way.
+pub(crate) fn record_log_item(key: &KeyType, value: &ValueType) -> Option<LogItem> {
+    let key = key.get_key().to_string();
+    return match value {
+        ValueType::RecordPointer(value) => Some(LogItem::SetKey(key, value.clone())),
+        ValueType::IntPointer(value) => Some(LogItem::SetInt(key, *value)),
+        ValueType::BytesPointer(value) => Some(LogItem::SetBytes(key, value.clone())),
+        _ => None,
+    };
+}
+
+/// Convert a table into nested JSON object on recursive way. If names are shared on the level,
+/// record keeps its name, table gets `/` suffix and queue gets `[]` suffix.
+pub(crate) fn table_to_json(db: &Table) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+
+    for (key, value) in db.iter() {
+        let name = key.get_key();
+        let has_record = db.contains_key(&KeyType::Record(name.to_string()));
+        let has_table = db.contains_key(&KeyType::Table(name.to_string()));
+        let has_queue = db.contains_key(&KeyType::Queue(name.to_string()));
+
+        match value {
+            ValueType::RecordPointer(record) => {
+                object.insert(name.to_string(), serde_json::Value::String(record.clone()));
+            }
+            ValueType::IntPointer(record) => {
+                object.insert(name.to_string(), serde_json::Value::from(*record));
+            }
+            ValueType::BytesPointer(record) => {
+                object.insert(name.to_string(), bytes_to_json(record));
+            }
+            ValueType::TablePointer(table) => {
+                let name = match has_record || has_queue {
+                    true => format!("{}/", name),
+                    false => name.to_string(),
+                };
+                object.insert(name, table_to_json(table));
+            }
+            ValueType::QueuePointer(queue) => {
+                let name = match has_record || has_table {
+                    true => format!("{}[]", name),
+                    false => name.to_string(),
+                };
+                let items = queue.iter().map(|x| serde_json::Value::String(x.clone())).collect();
+                object.insert(name, serde_json::Value::Array(items));
+            }
+        }
+    }
+
+    return serde_json::Value::Object(object);
+}
+
+/// Walk a JSON object on recursive way and collect the records, queues and empty tables
+/// with their full key. Suffixes of `table_to_json` are removed from the names.
+pub(crate) fn json_to_pairs(
+    prefix: &str,
+    value: &serde_json::Value,
+    pairs: &mut Vec<(KeyType, ValueType)>,
+) -> Result<(), ErrorKind> {
+    let object = match value {
+        serde_json::Value::Object(object) => object,
+        _ => {
+            return Err(ErrorKind::TypeError(format!(
+                "Value of '{}' must be an object",
+                prefix
+            )));
+        }
+    };
+
+    if object.is_empty() {
+        pairs.push((KeyType::Table(prefix.to_string()), ValueType::TablePointer(Table::new())));
+    }
+
+    for (name, value) in object {
+        let record_name = |name: &str| -> Result<String, ErrorKind> {
+            if name.is_empty() || name.contains('/') {
+                return Err(ErrorKind::InvalidKey(format!("Invalid name '{}' under '{}'", name, prefix)));
+            }
+            return Ok(format!("{}/{}", prefix, name));
+        };
+
+        match value {
+            serde_json::Value::Object(object) if json_bytes_tag(object).is_some() => {
+                let key = record_name(name)?;
+                let bytes = STANDARD.decode(json_bytes_tag(object).unwrap_or_default()).map_err(|e| {
+                    ErrorKind::TypeError(format!("Value of '{}' is not valid base64: {}", key, e))
+                })?;
+                pairs.push((KeyType::Record(key), ValueType::BytesPointer(bytes)));
+            }
+            serde_json::Value::Object(_) => {
+                let key = record_name(name.strip_suffix('/').unwrap_or(name))?;
+                json_to_pairs(&key, value, pairs)?;
+            }
+            serde_json::Value::Array(items) => {
+                let key = record_name(name.strip_suffix("[]").unwrap_or(name))?;
+                let mut queue = VecDeque::with_capacity(items.len());
+                for item in items {
+                    match item {
+                        serde_json::Value::String(item) => queue.push_back(item.clone()),
+                        _ => {
+                            return Err(ErrorKind::TypeError(format!(
+                                "Queue '{}' can contain only strings",
+                                key
+                            )));
+                        }
+                    }
+                }
+                pairs.push((KeyType::Queue(key), ValueType::QueuePointer(queue)));
+            }
+            serde_json::Value::String(record) => {
+                pairs.push((KeyType::Record(record_name(name)?), ValueType::RecordPointer(record.clone())));
+            }
+            serde_json::Value::Number(record) => {
+                let value = match record.as_i64() {
+                    Some(number) => ValueType::IntPointer(number),
+                    None => ValueType::RecordPointer(record.to_string()),
+                };
+                pairs.push((KeyType::Record(record_name(name)?), value));
+            }
+            serde_json::Value::Bool(record) => {
+                pairs.push((KeyType::Record(record_name(name)?), ValueType::RecordPointer(record.to_string())));
+            }
+            serde_json::Value::Null => {
+                return Err(ErrorKind::TypeError(format!(
+                    "Value of '{}' cannot be null",
+                    record_name(name)?
+                )));
+            }
+        }
+    }
+
+    return Ok(());
+}
+
+/// Count the request in the matching counter of statistics
+pub(crate) fn count_request(stats: &mut DatastoreStats, action: &DatabaseAction) {
+    match action {
+        DatabaseAction::Get(..)
+        | DatabaseAction::GetMeta(..)
+        | DatabaseAction::GetOrInsert(..)
+        | DatabaseAction::GetWithSource(..)
+        | DatabaseAction::Exists(..) => stats.gets += 1,
+        DatabaseAction::GetBatch(_, keys) => stats.gets += keys.len() as u64,
+        DatabaseAction::Set(..)
+        | DatabaseAction::SetWithTtl(..)
+        | DatabaseAction::SetWithMode(..)
+        | DatabaseAction::GetSet(..)
+        | DatabaseAction::CompareAndSwap(..)
+        | DatabaseAction::Append(..)
+        | DatabaseAction::Increment(..)
+        | DatabaseAction::ShardedIncrement(..)
+        | DatabaseAction::Rename(..)
+        | DatabaseAction::CreateTable(..)
+        | DatabaseAction::ImportJson(..) => stats.sets += 1,
+        DatabaseAction::SetBatch(_, pairs) => stats.sets += pairs.len() as u64,
+        DatabaseAction::Transaction(_, ops) => {
+            for op in ops {
+                match op {
+                    TxnOp::DeleteKey(_) | TxnOp::DeleteTable(_) => stats.deletes += 1,
+                    _ => stats.sets += 1,
+                }
+            }
+        }
+        DatabaseAction::DeleteKey(..)
+        | DatabaseAction::Remove(..)
+        | DatabaseAction::DeleteKeyWithPruning(..)
+        | DatabaseAction::Clear(..)
+        | DatabaseAction::DeletePrefix(..)
+        | DatabaseAction::DeleteTable(..) => stats.deletes += 1,
+        DatabaseAction::DeleteKeys(_, keys) => stats.deletes += keys.len() as u64,
+        DatabaseAction::ListKeys(..)
+        | DatabaseAction::ListTables(..)
+        | DatabaseAction::ListValues(..)
+        | DatabaseAction::CountKeys(..)
+        | DatabaseAction::ExportJson(..) => stats.lists += 1,
+        // Queue, hook and control requests are not counted
+        DatabaseAction::Trigger(..)
+        | DatabaseAction::HookSet(..)
+        | DatabaseAction::HookSetWithMethod(..)
+        | DatabaseAction::HookSetWithHeaders(..)
+        | DatabaseAction::HookSetEvents(..)
+        | DatabaseAction::HookGet(..)
+        | DatabaseAction::HookRemove(..)
+        | DatabaseAction::HookList(..)
+        | DatabaseAction::HookListByLink(..)
+        | DatabaseAction::SuspendLog(..)
+        | DatabaseAction::ResumeLog(..)
+        | DatabaseAction::Push(..)
+        | DatabaseAction::PushBounded(..)
+        | DatabaseAction::Pop(..)
+        | DatabaseAction::Peek(..)
+        | DatabaseAction::PeekBack(..)
+        | DatabaseAction::PopIf(..)
+        | DatabaseAction::ShardedSum(..)
+        | DatabaseAction::TotalQueued(..)
+        | DatabaseAction::QueueLen(..)
+        | DatabaseAction::QueueLenMulti(..)
+        | DatabaseAction::HotKeys(..)
+        | DatabaseAction::QueueForward(..)
+        | DatabaseAction::Snapshot(..)
+        | DatabaseAction::Restore(..)
+        | DatabaseAction::Flush(..)
+        | DatabaseAction::Stats(..)
+        | DatabaseAction::SizeEstimate(..)
+        | DatabaseAction::Shutdown(..)
+        | DatabaseAction::Pause(..)
+        | DatabaseAction::Resume(..)
+        | DatabaseAction::Watch(..) => (),
+    }
+}
+
+/// Writer that does not write anything just count the bytes
+#[derive(Default)]
+pub(crate) struct ByteCounter {
+    pub(crate) count: usize,
+}
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.count += buf.len();
+        return Ok(buf.len());
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        return Ok(());
+    }
+}
+
+/// Collect log items, so more of them can be sent to the logger in one `WriteAsync` message
+pub(crate) struct LogBatch {
+    items: Vec<LogItem>,
+    started: Option<Instant>,
+    max_items: usize,
+    window: Duration,
+    filter: LogFilter,
+}
+
+impl LogBatch {
+    /// Batch is sent if it has `max_items` items or the `window` elapsed since its first item,
+    /// `max_items` less than 2 or zero `window` means immediate send. Items that are not allowed by the filter are dropped.
+    pub(crate) fn new(max_items: usize, window: Duration, filter: LogFilter) -> Self {
+        return LogBatch {
+            items: Vec::new(),
+            started: None,
+            max_items,
+            window,
+            filter,
+        };
+    }
+
+    /// Add items to the batch and send it if it is full
+    pub(crate) fn push(&mut self, sender: &Sender<LoggerAction>, items: Vec<LogItem>) {
+        let items: Vec<LogItem> = items.into_iter().filter(|x| self.filter.allows(x)).collect();
+        if items.is_empty() {
+            return;
+        }
+
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+        self.items.extend(items);
+
+        if self.items.len() >= self.max_items || self.window.is_zero() {
+            self.flush(sender);
+        }
+    }
+
+    /// Send the collected items to the logger
+    pub(crate) fn flush(&mut self, sender: &Sender<LoggerAction>) {
+        self.started = None;
+        if self.items.is_empty() {
+            return;
+        }
+
+        let items = std::mem::take(&mut self.items);
+        sender
+            .send(LoggerAction::WriteAsync(items))
+            .unwrap_or_else(|e| tracing::error!("{}", e));
+    }
+
+    /// Remaining time until the batch has to be sent, `None` if the batch is empty
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        return self
+            .started
+            .map(|started| self.window.saturating_sub(started.elapsed()));
+    }
+}
+
+/// Queue items handed over to the hook manager by `QueueForward`, whose delivery is not finished yet
+pub(crate) struct PendingForward {
+    pub(crate) sender: Sender<ResultWithCount>,
+    pub(crate) key: String,
+    pub(crate) values: Vec<String>,
+    pub(crate) receiver: Receiver<HookManagerResponse>,
+}
+
+impl PendingForward {
+    /// Items whose delivery failed, `None` if the hook manager has not answered yet.
+    /// If `wait` is true, it blocks until the answer. Without a valid answer every item is handled as failed.
+    pub(crate) fn poll(&self, wait: bool) -> Option<Vec<String>> {
+        let response = match wait {
+            true => self.receiver.recv().map_err(|e| e.to_string()),
+            false => match self.receiver.try_recv() {
+                Err(TryRecvError::Empty) => return None,
+                response => response.map_err(|e| e.to_string()),
+            },
+        };
+
+        return match response {
+            Ok(HookManagerResponse::Forwarded(failed)) => Some(failed),
+            Ok(_) => Some(self.values.clone()),
+            Err(e) => {
+                tracing::error!("Failed to receive from hook manager: {}", e);
+                Some(self.values.clone())
+            }
+        };
+    }
+}
@@ -1,15 +1,20 @@
 //! Built-in utilities
 
 use std::{
-    sync::mpsc::{Receiver, Sender},
+    sync::mpsc::{Receiver, RecvTimeoutError, Sender},
     thread::JoinHandle,
+    time::{Duration, Instant},
 };
 
 pub(crate) mod internal;
 
+use reqwest::Method;
+
 use crate::{
+    config::Config,
     hook::{
         enums::{HookManagerAction, HookManagerResponse},
+        types::{Headers, Link, Prefix},
         utilities::get_channel,
     },
     logger::{
@@ -19,10 +24,11 @@ use crate::{
 };
 
 use super::{
-    enums::{error::ErrorKind, pair::KeyType, pair::ValueType, DatabaseAction, ListType},
+    enums::{error::ErrorKind, pair::KeyType, pair::ValueType, ChangeKind, DatabaseAction, ListType, Source, TxnOp},
     types::{
-        ResultWithHook, ResultWithHooks, ResultWithList, ResultWithResult, ResultWithoutResult,
-        Table,
+        QueueLengths, ResultWithBool, ResultWithMeta, DatastoreStats, SizeReport, ResultWithSnapshot, ResultWithJson, ResultWithPairs, ResultWithPrevious, ResultWithNumber, ResultWithOptional, ResultWithSource, ResultWithCount, ResultWithHook, ResultWithHooks, ResultWithHotKeys,
+        ResultWithKeyResults, ResultWithLinks, ResultWithList, ResultWithResult, ValueResults,
+        ResultWithoutResult, Table,
     },
     Database,
 };
@@ -30,6 +36,27 @@ use super::{
 /// Initialize database on another thread, create a channel and return with it
 /// For all possible action check `onlyati::datastore::enums::mod::DatabaseAction` enum.
 ///
+/// # Hook manager calls
+///
+/// Writes that alert the hook manager (e.g. `Set`, `Push`) and `Trigger` hand over the key and value
+/// by `HookManagerAction::Send` without waiting, so a slow endpoint does not block the database.
+/// Successful `DeleteKey`, `Remove`, `DeleteKeys` and `DeleteTable` send `HookEvent::Delete` on the same way,
+/// for a table the hooks whose prefix matches the table key are executed.
+/// After a successful `DeleteTable`, the hooks whose prefix is under the table are removed by
+/// `HookManagerAction::RemoveUnderPrefix`, also if alerts of writes are disabled.
+/// Alerts of writes can be disabled by `Builder::auto_hooks`, then only `Trigger` executes the hooks.
+/// The number of deliveries in progress can be limited by `Builder::max_pending_hooks`.
+///
+/// The hook administration actions (`HookSet`, `HookSetWithMethod`, `HookSetEvents`, `HookGet`, `HookList`, `HookListByLink`,
+/// `HookRemove`) wait for the answer of hook manager, they do not send HTTP requests.
+/// `QueueForward` does not wait for the delivery, the thread checks the answer of hook manager between requests
+/// and then puts the failed items back into the queue and answers the caller.
+///
+/// # Watchers
+///
+/// Subscribers of `Watch` get a `ChangeEvent` after every successful `Set`, `DeleteKey`, `Remove`, `DeleteKeys`
+/// and `DeleteTable` whose key is under their prefix. A subscriber is removed when its receiver is dropped.
+///
 /// # Example for call
 ///
 /// ```
@@ -59,12 +86,45 @@ pub fn start_datastore(
     name: String,
     hook_sender: Option<Sender<HookManagerAction>>,
     logger_sender: Option<Sender<LoggerAction>>,
+) -> (Sender<DatabaseAction>, JoinHandle<()>) {
+    return start_datastore_with_config(name, hook_sender, logger_sender, &Config::default());
+}
+
+/// How often the answers of hook manager are checked while a `QueueForward` is in progress
+const FORWARD_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Initialize database based on configuration on another thread, create a channel and return with it
+///
+/// # Example for call
+///
+/// ```
+/// use onlyati_datastore::config::Builder;
+/// use onlyati_datastore::datastore::{
+///     enums::{DatabaseAction, pair::ValueType},
+///     utilities::{start_datastore_with_config, self},
+/// };
+///
+/// let config = Builder::new().access_counters(true).build();
+/// let (sender, _) = start_datastore_with_config("root".to_string(), None, None, &config);
+///
+/// let (tx, rx) = utilities::get_channel_for_set();
+/// let set_action = DatabaseAction::Set(tx, "/root/network".to_string(), "ok".to_string());
+///
+/// sender.send(set_action).expect("Failed to send the request");
+/// rx.recv().unwrap().unwrap();
+/// ```
+pub fn start_datastore_with_config(
+    name: String,
+    hook_sender: Option<Sender<HookManagerAction>>,
+    logger_sender: Option<Sender<LoggerAction>>,
+    config: &Config,
 ) -> (Sender<DatabaseAction>, JoinHandle<()>) {
     tracing::debug!("root element of database is '{}'", name);
     let (tx, rx) = std::sync::mpsc::channel::<DatabaseAction>();
+    let config = config.clone();
 
     let thread = std::thread::spawn(move || {
-        let mut db = Database::new(name).expect("Failed to allocate database");
+        let mut db = Database::from_config(name, &config).expect("Failed to allocate database");
 
         if let Some(sender) = hook_sender {
             tracing::debug!("subscribed to a hook manager");
@@ -76,97 +136,687 @@ pub fn start_datastore(
             db.subscribe_to_logger(sender);
         }
 
-        while let Ok(data) = rx.recv() {
+        if let Some(path) = config.persist_path() {
+            if path.exists() {
+                tracing::debug!("load database from '{}'", path.display());
+                if let Err(e) = db.load_from_file(path) {
+                    tracing::error!("failed to load persisted database, start with empty one: {}", e);
+                }
+            }
+        }
+
+        let mut paused = false;
+        let mut log_batch = internal::LogBatch::new(
+            config.log_batch_size(),
+            config.log_batch_window(),
+            config.log_filter().clone(),
+        );
+        let mut last_sweep = Instant::now();
+        let mut stats = DatastoreStats::default();
+        let mut pending_forwards: Vec<internal::PendingForward> = Vec::new();
+
+        loop {
+            // Deliveries of `QueueForward` are finished here, so the thread does not wait for the endpoint
+            if !pending_forwards.is_empty() {
+                let mut still_pending = Vec::new();
+                for forward in pending_forwards.drain(..) {
+                    match forward.poll(false) {
+                        Some(failed) => finish_forward(&mut db, &mut log_batch, forward, failed),
+                        None => still_pending.push(forward),
+                    }
+                }
+                pending_forwards = still_pending;
+            }
+
+            // Expired records are removed periodically, while the database is paused they are only hidden
+            let sweep_timeout = match db.expirations.is_empty() || paused {
+                true => None,
+                false => Some(config.ttl_sweep_interval().saturating_sub(last_sweep.elapsed())),
+            };
+            if sweep_timeout == Some(Duration::ZERO) {
+                let removed = db.remove_expired();
+                last_sweep = Instant::now();
+
+                if !removed.is_empty() {
+                    if let Some(sender) = &db.logger_sender {
+                        let items = removed
+                            .into_iter()
+                            .map(|key| LogItem::RemKey(key.get_key().to_string()))
+                            .collect();
+                        write_log!(log_batch, sender, items);
+                    }
+                }
+                continue;
+            }
+
+            let timeout = match (log_batch.timeout(), sweep_timeout) {
+                (Some(log_timeout), Some(sweep_timeout)) => Some(log_timeout.min(sweep_timeout)),
+                (log_timeout, sweep_timeout) => log_timeout.or(sweep_timeout),
+            };
+            let timeout = match pending_forwards.is_empty() {
+                true => timeout,
+                false => Some(timeout.map_or(FORWARD_POLL_INTERVAL, |x| x.min(FORWARD_POLL_INTERVAL))),
+            };
+
+            let data = match timeout {
+                None => match rx.recv() {
+                    Ok(data) => data,
+                    Err(_) => break,
+                },
+                Some(timeout) => match rx.recv_timeout(timeout) {
+                    Ok(data) => data,
+                    Err(RecvTimeoutError::Timeout) => {
+                        if log_batch.timeout() == Some(Duration::ZERO) {
+                            if let Some(sender) = &db.logger_sender {
+                                log_batch.flush(sender);
+                            }
+                        }
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                },
+            };
+
             tracing::trace!("received request: {}", data);
+            internal::count_request(&mut stats, &data);
             match data {
                 // Handle Get actions
                 DatabaseAction::Get(sender, key) => {
-                    match db.get(KeyType::Record(key.clone())) {
-                        Ok(value) => send_response!(sender, Ok(value)),
-                        Err(e) => send_response!(sender, Err(e)),
+                    let result = db.fetch_with_source(KeyType::Record(key.clone()), !paused);
+                    trace_result!("get", key, result);
+
+                    let items = loaded_log_items(&key, &result, paused);
+                    send_response!(sender, result.map(|(value, _)| value));
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, items);
+                    }
+                }
+                // Handle GetMeta actions
+                DatabaseAction::GetMeta(sender, key) => {
+                    let result = db.get_meta(KeyType::Record(key.clone()));
+                    trace_result!("get", key, result);
+                    send_response!(sender, result);
+                }
+                // Handle GetBatch actions
+                DatabaseAction::GetBatch(sender, keys) => {
+                    // Every key is fetched like a single get, so loaded values are stored and logged the same way
+                    let mut results: ValueResults = Vec::with_capacity(keys.len());
+                    let mut log_items = Vec::new();
+                    for key in keys {
+                        let result = db.fetch_with_source(KeyType::Record(key.clone()), !paused);
+                        trace_result!("get", key, result);
+
+                        log_items.append(&mut loaded_log_items(&key, &result, paused));
+                        results.push((key, result.map(|(value, _)| value)));
+                    }
+
+                    send_response!(sender, results);
+
+                    if !log_items.is_empty() {
+                        if let Some(sender) = &db.logger_sender {
+                            write_log!(log_batch, sender, log_items);
+                        }
+                    }
+                }
+                // Handle GetOrInsert actions
+                DatabaseAction::GetOrInsert(sender, key, value) => {
+                    let record_key = KeyType::Record(key.clone());
+                    let inserted = db.lookup(&record_key).is_none();
+                    if inserted {
+                        reject_if_paused!(paused, sender);
+                    }
+
+                    let result = db.get_or_insert(record_key, ValueType::RecordPointer(value.clone()));
+                    trace_result!("get", key, result);
+
+                    let failed = result.is_err();
+                    send_response!(sender, result);
+
+                    if let Some(sender) = &db.logger_sender {
+                        let item = match inserted && !failed {
+                            true => LogItem::SetKey(key, value),
+                            false => LogItem::GetKey(key),
+                        };
+                        write_log!(log_batch, sender, vec![item]);
+                    }
+                }
+                // Handle Rename actions
+                DatabaseAction::Rename(sender, from, to) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.rename(from.clone(), to.clone());
+                    trace_result!("rename", from.get_key(), result);
+
+                    match result {
+                        Ok(_) => send_response!(sender, Ok(())),
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
                     }
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::GetKey(key)]);
+                        let to = to.get_key().to_string();
+                        let item = match from {
+                            KeyType::Record(from) => LogItem::RenameKey(from, to),
+                            KeyType::Queue(from) => LogItem::RenameQueue(from, to),
+                            KeyType::Table(from) => LogItem::RenamePath(from, to),
+                        };
+                        write_log!(log_batch, sender, vec![item]);
+                    }
+                }
+                // Handle ListValues actions
+                DatabaseAction::ListValues(sender, key, level) => {
+                    let result = db.list_pairs(KeyType::Record(key.clone()), level);
+                    trace_result!("list_keys", key, result);
+                    send_response!(sender, result);
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, vec![LogItem::ListKeys(key)]);
+                    }
+                }
+                // Handle CountKeys actions
+                DatabaseAction::CountKeys(sender, key, level) => {
+                    let result = db.count_keys(KeyType::Record(key.clone()), level);
+                    trace_result!("count_keys", key, result);
+                    send_response!(sender, result);
+                }
+                // Handle Exists actions
+                DatabaseAction::Exists(sender, key) => {
+                    send_response!(sender, db.exists(key));
+                }
+                // Handle CompareAndSwap actions
+                DatabaseAction::CompareAndSwap(sender, key, expected, value) => {
+                    reject_if_paused!(paused, sender);
+
+                    let record_key = KeyType::Record(key.clone());
+                    let result = db.compare_and_swap(record_key.clone(), expected, value);
+                    trace_result!("set", key, result);
+
+                    let swapped = matches!(result, Ok(true));
+                    send_response!(sender, result);
+
+                    if swapped {
+                        if let Some(sender) = &db.logger_sender {
+                            if let Some(item) = db.lookup(&record_key).and_then(|x| internal::record_log_item(&record_key, x)) {
+                                write_log!(log_batch, sender, vec![item]);
+                            }
+                        }
+                    }
+                }
+                // Handle GetSet actions
+                DatabaseAction::GetSet(sender, key, value) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.get_set(KeyType::Record(key.clone()), ValueType::RecordPointer(value.clone()));
+                    trace_result!("set", key, result);
+
+                    match result {
+                        Ok(previous) => send_response!(sender, Ok(previous)),
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    }
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, vec![LogItem::SetKey(key, value)]);
+                    }
+                }
+                // Handle GetWithSource actions
+                DatabaseAction::GetWithSource(sender, key) => {
+                    let result = db.fetch_with_source(KeyType::Record(key.clone()), !paused);
+                    trace_result!("get", key, result);
+
+                    let items = loaded_log_items(&key, &result, paused);
+                    send_response!(sender, result);
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, items);
                     }
                 }
                 // Handle Set actions
                 DatabaseAction::Set(sender, key, value) => {
-                    match db.insert(
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.insert(
+                        KeyType::Record(key.clone()),
+                        ValueType::RecordPointer(value.clone()),
+                    );
+                    trace_result!("set", key, result);
+
+                    match result {
+                        Ok(_) => send_response!(sender, Ok(())),
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    }
+
+                    db.notify_watchers(&key, ChangeKind::Set, Some(value.clone()));
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, vec![LogItem::SetKey(key, value)]);
+                    }
+                }
+                // Handle SetWithTtl actions, expiry time is not logged only the removal of expired record
+                DatabaseAction::SetWithTtl(sender, key, value, secs) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.insert_with_ttl(
                         KeyType::Record(key.clone()),
                         ValueType::RecordPointer(value.clone()),
-                    ) {
+                        Duration::from_secs(secs),
+                    );
+                    trace_result!("set", key, result);
+
+                    match result {
                         Ok(_) => send_response!(sender, Ok(())),
-                        Err(e) => send_response!(sender, Err(e)),
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
                     }
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::SetKey(key, value)]);
+                        write_log!(log_batch, sender, vec![LogItem::SetKey(key, value)]);
+                    }
+                }
+                // Handle SetBatch actions
+                DatabaseAction::SetBatch(sender, pairs) => {
+                    reject_if_paused!(paused, sender);
+
+                    let records = pairs
+                        .iter()
+                        .map(|(key, value)| {
+                            (KeyType::Record(key.clone()), ValueType::RecordPointer(value.clone()))
+                        })
+                        .collect();
+                    let result = db.insert_many(records);
+
+                    // Pairs before the failed one are inserted
+                    let inserted = match &result {
+                        Ok(_) => pairs.len(),
+                        Err(ErrorKind::BatchFailed { index, .. }) => *index,
+                        Err(_) => 0,
+                    };
+                    for (index, (key, _)) in pairs.iter().enumerate() {
+                        if index < inserted {
+                            trace_result!("set", key, Ok::<(), ErrorKind>(()));
+                        } else {
+                            trace_result!("set", key, result);
+                            break;
+                        }
+                    }
+
+                    send_response!(sender, result);
+
+                    if inserted > 0 {
+                        if let Some(sender) = &db.logger_sender {
+                            let items = pairs
+                                .into_iter()
+                                .take(inserted)
+                                .map(|(key, value)| LogItem::SetKey(key, value))
+                                .collect();
+                            write_log!(log_batch, sender, items);
+                        }
+                    }
+                }
+                // Handle Transaction actions
+                DatabaseAction::Transaction(sender, ops) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.transaction(ops.clone());
+                    for op in &ops {
+                        trace_result!("transaction", op, result);
+                    }
+
+                    if let Err(e) = result {
+                        send_response!(sender, Err(e));
+                        continue;
+                    }
+
+                    // Hooks are cleared before the response, so the caller cannot see them after the delete
+                    let mut log_items = Vec::new();
+                    for op in ops {
+                        match op {
+                            TxnOp::Set(key, value) | TxnOp::CompareAndSwap(key, _, value) => {
+                                db.notify_watchers(&key, ChangeKind::Set, Some(value.clone()));
+                                log_items.push(LogItem::SetKey(key, value));
+                            }
+                            TxnOp::DeleteKey(key) => {
+                                db.alert_delete(&key);
+                                db.notify_watchers(&key, ChangeKind::Delete, None);
+                                log_items.push(LogItem::RemKey(key));
+                            }
+                            TxnOp::DeleteTable(key) => {
+                                db.alert_delete(&key);
+                                let hook_item = db.clear_hooks_under(&key);
+                                db.notify_watchers(&key, ChangeKind::Delete, None);
+                                log_items.push(LogItem::RemPath(key));
+                                log_items.extend(hook_item);
+                            }
+                        }
+                    }
+                    send_response!(sender, Ok(()));
+
+                    if !log_items.is_empty() {
+                        if let Some(sender) = &db.logger_sender {
+                            write_log!(log_batch, sender, log_items);
+                        }
+                    }
+                }
+                // Handle SetWithMode actions
+                DatabaseAction::SetWithMode(sender, key, value, mode) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.insert_with_mode(
+                        KeyType::Record(key.clone()),
+                        ValueType::RecordPointer(value.clone()),
+                        mode,
+                    );
+                    trace_result!("set", key, result);
+
+                    match result {
+                        Ok(_) => send_response!(sender, Ok(())),
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    }
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, vec![LogItem::SetKey(key, value)]);
                     }
                 }
                 // Handle DeleteKey actions
                 DatabaseAction::DeleteKey(sender, key) => {
-                    match db.delete_key(KeyType::Record(key.clone())) {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.delete_key(KeyType::Record(key.clone()));
+                    trace_result!("delete_key", key, result);
+
+                    match result {
                         Ok(_) => send_response!(sender, Ok(())),
-                        Err(e) => send_response!(sender, Err(e)),
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    }
+                    db.alert_delete(&key);
+                    db.notify_watchers(&key, ChangeKind::Delete, None);
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, vec![LogItem::RemKey(key)]);
+                    }
+                }
+                // Handle DeleteKeyWithPruning actions, pruned tables are logged like deleted tables
+                DatabaseAction::DeleteKeyWithPruning(sender, key, prune) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = match prune {
+                        true => db.delete_key_pruning(KeyType::Record(key.clone())),
+                        false => db.delete_key(KeyType::Record(key.clone())).map(|_| Vec::new()),
+                    };
+                    trace_result!("delete_key", key, result);
+
+                    let pruned = match result {
+                        Ok(pruned) => {
+                            send_response!(sender, Ok(()));
+                            pruned
+                        }
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    };
+                    db.alert_delete(&key);
+                    db.notify_watchers(&key, ChangeKind::Delete, None);
+
+                    if let Some(sender) = &db.logger_sender {
+                        let mut log_items = vec![LogItem::RemKey(key)];
+                        log_items.extend(pruned.into_iter().map(LogItem::RemPath));
+                        write_log!(log_batch, sender, log_items);
+                    }
+                }
+                // Handle Remove actions
+                DatabaseAction::Remove(sender, key) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.remove(KeyType::Record(key.clone()));
+                    trace_result!("delete_key", key, result);
+
+                    match result {
+                        Ok(value) => send_response!(sender, Ok(value)),
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
                     }
+                    db.alert_delete(&key);
+                    db.notify_watchers(&key, ChangeKind::Delete, None);
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::RemKey(key)]);
+                        write_log!(log_batch, sender, vec![LogItem::RemKey(key)]);
+                    }
+                }
+                // Handle DeleteKeys actions
+                DatabaseAction::DeleteKeys(sender, keys) => {
+                    reject_if_paused!(paused, sender);
+
+                    let keys = keys.into_iter().map(KeyType::Record).collect();
+                    let results = db.delete_keys(keys);
+
+                    let mut log_items = Vec::new();
+                    for (key, result) in &results {
+                        trace_result!("delete_key", key.get_key(), result);
+                        if result.is_ok() {
+                            db.alert_delete(key.get_key());
+                            db.notify_watchers(key.get_key(), ChangeKind::Delete, None);
+                            log_items.push(LogItem::RemKey(key.get_key().to_string()));
+                        }
+                    }
+
+                    send_response!(sender, Ok(results));
+
+                    if !log_items.is_empty() {
+                        if let Some(sender) = &db.logger_sender {
+                            write_log!(log_batch, sender, log_items);
+                        }
                     }
                 }
                 // Handle DeleteTable actions
                 DatabaseAction::DeleteTable(sender, key) => {
-                    match db.delete_table(KeyType::Table(key.clone())) {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.delete_table(KeyType::Table(key.clone()));
+                    trace_result!("delete_table", key, result);
+
+                    if let Err(e) = result {
+                        send_response!(sender, Err(e));
+                        continue;
+                    }
+
+                    // Hooks are cleared before the response, so the caller cannot see them after the delete
+                    db.alert_delete(&key);
+                    let hook_item = db.clear_hooks_under(&key);
+                    db.notify_watchers(&key, ChangeKind::Delete, None);
+                    send_response!(sender, Ok(()));
+
+                    if let Some(sender) = &db.logger_sender {
+                        let mut items = vec![LogItem::RemPath(key)];
+                        items.extend(hook_item);
+                        write_log!(log_batch, sender, items);
+                    }
+                }
+                // Handle DeletePrefix actions
+                DatabaseAction::DeletePrefix(sender, prefix) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.delete_prefix_keys(&prefix);
+                    trace_result!("delete_prefix", prefix, result);
+
+                    let (count, removed) = match result {
+                        Ok(result) => result,
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    };
+
+                    // Hooks are cleared before the response, so the caller cannot see them after the delete
+                    let mut items = vec![LogItem::RemPrefix(prefix)];
+                    for key in removed {
+                        db.alert_delete(key.get_key());
+                        if key.is_table() {
+                            items.extend(db.clear_hooks_under(key.get_key()));
+                        }
+                        db.notify_watchers(key.get_key(), ChangeKind::Delete, None);
+                    }
+                    send_response!(sender, Ok(count));
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, items);
+                    }
+                }
+                // Handle CreateTable actions
+                DatabaseAction::CreateTable(sender, key) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.create_table(KeyType::Table(key.clone()));
+                    trace_result!("create_table", key, result);
+
+                    match result {
                         Ok(_) => send_response!(sender, Ok(())),
-                        Err(e) => send_response!(sender, Err(e)),
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
                     }
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::RemPath(key)]);
+                        write_log!(log_batch, sender, vec![LogItem::CreatePath(key)]);
                     }
                 }
                 // Handle ListKeys action
                 DatabaseAction::ListKeys(sender, key, level) => {
-                    match db.list_keys(KeyType::Record(key.clone()), level) {
+                    let result = db.list_keys(KeyType::Record(key.clone()), level);
+                    trace_result!("list_keys", key, result);
+
+                    match result {
                         Ok(list) => send_response!(sender, Ok(list)),
                         Err(e) => send_response!(sender, Err(e)),
                     }
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::ListKeys(key)]);
+                        write_log!(log_batch, sender, vec![LogItem::ListKeys(key)]);
+                    }
+                }
+                // Handle ListTables action
+                DatabaseAction::ListTables(sender, key, level) => {
+                    let result = db.list_tables(KeyType::Table(key.clone()), level);
+                    trace_result!("list_tables", key, result);
+                    send_response!(sender, result);
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, vec![LogItem::ListKeys(key)]);
                     }
                 }
                 // Trigger HookManager
                 DatabaseAction::Trigger(sender, key, value) => {
-                    match db.trigger(
+                    let result = db.trigger(
                         KeyType::Record(key.clone()),
                         ValueType::RecordPointer(value.clone()),
-                    ) {
+                    );
+                    trace_result!("trigger", key, result);
+
+                    match result {
                         Ok(_) => send_response!(sender, Ok(())),
                         Err(e) => send_response!(sender, Err(e)),
                     }
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::Trigger(key, value)]);
+                        write_log!(log_batch, sender, vec![LogItem::Trigger(key, value)]);
                     }
                 }
                 // Set hook
                 DatabaseAction::HookSet(sender, prefix, link) => {
+                    send_response!(sender, set_hook(&db, prefix.clone(), link.clone(), None, Headers::new()));
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, vec![LogItem::SetHook(prefix, link)]);
+                    }
+                }
+                // Set hook with HTTP method
+                DatabaseAction::HookSetWithMethod(sender, prefix, link, method) => {
+                    send_response!(sender, set_hook(&db, prefix.clone(), link.clone(), Some(method.clone()), Headers::new()));
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, vec![LogItem::SetHookWithMethod(prefix, link, method.to_string())]);
+                    }
+                }
+                // Set hook with static headers, they are logged with their values so replay can restore them
+                DatabaseAction::HookSetWithHeaders(sender, prefix, link, method, headers) => {
+                    let result = set_hook(&db, prefix.clone(), link.clone(), method.clone(), headers.clone());
+                    send_response!(sender, result);
+
+                    if let Some(sender) = &db.logger_sender {
+                        let method = method.unwrap_or(Method::POST).to_string();
+                        write_log!(log_batch, sender, vec![LogItem::SetHookWithHeaders(prefix, link, method, headers)]);
+                    }
+                }
+                // Set events of hook, they are logged only if they are set so replay gives the same filter
+                DatabaseAction::HookSetEvents(sender, prefix, link, events) => {
+                    let hook_sender = match &db.hook_sender {
+                        Some(hook_sender) => hook_sender,
+                        None => {
+                            hook_inactive!(sender);
+                            continue;
+                        }
+                    };
+
+                    let (tx, rx) = get_channel();
+                    let action = HookManagerAction::SetEvents(tx, prefix.clone(), link.clone(), events.clone());
+                    hook_send!(sender, hook_sender, action);
+
+                    match rx.recv() {
+                        Ok(HookManagerResponse::Ok) => send_response!(sender, Ok(())),
+                        Ok(HookManagerResponse::Error(e)) if e == "Not found" => {
+                            send_response!(sender, Err(ErrorKind::NotFound("Hook is not found".to_string())));
+                            continue;
+                        }
+                        Ok(_) => {
+                            send_response!(sender, Err(ErrorKind::InvalidKey("Invalid hook events".to_string())));
+                            continue;
+                        }
+                        Err(e) => {
+                            hook_receive_failed!(sender, e);
+                            continue;
+                        }
+                    }
+
+                    if let Some(sender) = &db.logger_sender {
+                        let events = events.iter().map(|x| x.to_string()).collect();
+                        write_log!(log_batch, sender, vec![LogItem::SetHookEvents(prefix, link, events)]);
+                    }
+                }
+                // Get links for specific hook
+                DatabaseAction::HookGet(sender, prefix) => {
                     match &db.hook_sender {
                         Some(hook_sender) => {
                             let (tx, rx) = get_channel();
-                            let action = HookManagerAction::Set(tx, prefix.clone(), link.clone());
+                            let action = HookManagerAction::Get(tx, prefix.clone());
                             hook_send!(sender, hook_sender, action);
 
                             match rx.recv() {
                                 Ok(response) => match response {
-                                    HookManagerResponse::Ok => send_response!(sender, Ok(())),
+                                    HookManagerResponse::Hook(prefix, hooks) => {
+                                        send_response!(sender, Ok((prefix, hooks)))
+                                    }
                                     _ => send_response!(
                                         sender,
-                                        Err(ErrorKind::InternalError(
-                                            "Failed to add hook".to_string()
-                                        ))
+                                        Err(ErrorKind::NotFound("Hook is not found".to_string()))
                                     ),
                                 },
                                 Err(e) => hook_receive_failed!(sender, e),
@@ -176,25 +826,26 @@ pub fn start_datastore(
                     }
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::SetHook(prefix, link)]);
+                        write_log!(log_batch, sender, vec![LogItem::GetHook(prefix)]);
                     }
                 }
-                // Get links for specific hook
-                DatabaseAction::HookGet(sender, prefix) => {
+                // List hooks
+                DatabaseAction::HookList(sender, prefix) => {
                     match &db.hook_sender {
                         Some(hook_sender) => {
                             let (tx, rx) = get_channel();
-                            let action = HookManagerAction::Get(tx, prefix.clone());
+                            let action = HookManagerAction::List(tx, prefix.clone());
+
                             hook_send!(sender, hook_sender, action);
 
                             match rx.recv() {
                                 Ok(response) => match response {
-                                    HookManagerResponse::Hook(prefix, hooks) => {
-                                        send_response!(sender, Ok((prefix, hooks)))
+                                    HookManagerResponse::HookList(list) => {
+                                        send_response!(sender, Ok(list))
                                     }
                                     _ => send_response!(
                                         sender,
-                                        Err(ErrorKind::InvalidKey("Hook is not found".to_string()))
+                                        Err(ErrorKind::NotFound("Hook is not found".to_string()))
                                     ),
                                 },
                                 Err(e) => hook_receive_failed!(sender, e),
@@ -204,26 +855,26 @@ pub fn start_datastore(
                     }
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::GetHook(prefix)]);
+                        write_log!(log_batch, sender, vec![LogItem::ListHooks(prefix)]);
                     }
                 }
-                // List hooks
-                DatabaseAction::HookList(sender, prefix) => {
+                // List hooks grouped by links
+                DatabaseAction::HookListByLink(sender) => {
                     match &db.hook_sender {
                         Some(hook_sender) => {
                             let (tx, rx) = get_channel();
-                            let action = HookManagerAction::List(tx, prefix.clone());
+                            let action = HookManagerAction::ListByLink(tx);
 
                             hook_send!(sender, hook_sender, action);
 
                             match rx.recv() {
                                 Ok(response) => match response {
-                                    HookManagerResponse::HookList(list) => {
+                                    HookManagerResponse::LinkList(list) => {
                                         send_response!(sender, Ok(list))
                                     }
                                     _ => send_response!(
                                         sender,
-                                        Err(ErrorKind::InvalidKey("Hook is not found".to_string()))
+                                        Err(ErrorKind::NotFound("Hook is not found".to_string()))
                                     ),
                                 },
                                 Err(e) => hook_receive_failed!(sender, e),
@@ -233,7 +884,7 @@ pub fn start_datastore(
                     }
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::ListHooks(prefix)]);
+                        write_log!(log_batch, sender, vec![LogItem::ListHooksByLink]);
                     }
                 }
                 // Remove existing hooks
@@ -251,7 +902,7 @@ pub fn start_datastore(
                                     HookManagerResponse::Ok => send_response!(sender, Ok(())),
                                     _ => send_response!(
                                         sender,
-                                        Err(ErrorKind::InvalidKey("Hook is not found".to_string()))
+                                        Err(ErrorKind::NotFound("Hook is not found".to_string()))
                                     ),
                                 },
                                 Err(e) => hook_receive_failed!(sender, e),
@@ -261,12 +912,13 @@ pub fn start_datastore(
                     }
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::RemHook(prefix, link)]);
+                        write_log!(log_batch, sender, vec![LogItem::RemHook(prefix, link)]);
                     }
                 }
                 // Resume logging
                 DatabaseAction::ResumeLog(sender) => {
                     if let Some(logger_sender) = &db.logger_sender {
+                        log_batch.flush(logger_sender);
                         let (tx, rx) = get_channel_for_log_write();
                         send_response_with_mutex_sender!(logger_sender, LoggerAction::Resume(tx));
 
@@ -287,6 +939,7 @@ pub fn start_datastore(
                 // Suspend logging
                 DatabaseAction::SuspendLog(sender) => {
                     if let Some(logger_sender) = &db.logger_sender {
+                        log_batch.flush(logger_sender);
                         let (tx, rx) = get_channel_for_log_write();
                         send_response_with_mutex_sender!(logger_sender, LoggerAction::Suspend(tx));
 
@@ -306,33 +959,467 @@ pub fn start_datastore(
                 }
                 // Push to a queue
                 DatabaseAction::Push(sender, key, value) => {
-                    match db.push(KeyType::Record(key.clone()), value.clone()) {
-                        Ok(_) => send_response!(sender, Ok(())),
-                        Err(e) => send_response!(sender, Err(e)),
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.push_limited(KeyType::Record(key.clone()), value.clone(), None);
+                    trace_result!("push", key, result);
+
+                    let dropped = match result {
+                        Ok(dropped) => {
+                            send_response!(sender, Ok(()));
+                            dropped
+                        }
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    };
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, queue_push_log_items(key, value, dropped));
                     }
+                }
+                // Push to a queue with capacity
+                DatabaseAction::PushBounded(sender, key, value, max_len, policy) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.push_bounded(KeyType::Record(key.clone()), value.clone(), max_len, policy);
+                    trace_result!("push", key, result);
+
+                    let dropped = match result {
+                        Ok(dropped) => {
+                            send_response!(sender, Ok(dropped));
+                            dropped
+                        }
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    };
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::Push(key, value)]);
+                        write_log!(log_batch, sender, queue_push_log_items(key, value, dropped));
                     }
                 }
                 // Pop from queue
                 DatabaseAction::Pop(sender, key) => {
-                    match db.pop(KeyType::Record(key.clone())) {
-                        Ok(value) => send_response!(sender, Ok(ValueType::RecordPointer(value))),
-                        Err(e) => send_response!(sender, Err(e)),
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.pop(KeyType::Record(key.clone()));
+                    trace_result!("pop", key, result);
+
+                    match result {
+                        Ok(Some(value)) => send_response!(sender, Ok(Some(value))),
+                        Ok(None) => {
+                            send_response!(sender, Ok(None));
+                            continue;
+                        }
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    }
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, vec![LogItem::QueuePop(key)]);
+                    }
+                }
+                // Read the front of queue
+                DatabaseAction::Peek(sender, key) => {
+                    send_response!(sender, db.peek(KeyType::Record(key)));
+                }
+                // Read the back of queue
+                DatabaseAction::PeekBack(sender, key) => {
+                    send_response!(sender, db.peek_back(KeyType::Record(key)));
+                }
+                // Pop from queue if the value matches
+                DatabaseAction::PopIf(sender, key, predicate) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.pop_front_if(KeyType::Record(key.clone()), predicate);
+                    trace_result!("pop", key, result);
+
+                    let popped = matches!(result, Ok(Some(_)));
+                    send_response!(sender, result);
+
+                    if popped {
+                        if let Some(sender) = &db.logger_sender {
+                            write_log!(log_batch, sender, vec![LogItem::QueuePop(key)]);
+                        }
+                    }
+                }
+                // Append to a record
+                DatabaseAction::Append(sender, key, suffix) => {
+                    reject_if_paused!(paused, sender);
+
+                    let record_key = KeyType::Record(key.clone());
+                    let result = db.append(record_key.clone(), suffix);
+                    trace_result!("set", key, result);
+
+                    match result {
+                        Ok(_) => send_response!(sender, Ok(())),
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    }
+
+                    // Whole value is logged, so replay does not depend on the former value
+                    if let Some(sender) = &db.logger_sender {
+                        if let Some(ValueType::RecordPointer(value)) = db.lookup(&record_key) {
+                            write_log!(log_batch, sender, vec![LogItem::SetKey(key, value.clone())]);
+                        }
                     }
+                }
+                // Increment a numeric record
+                DatabaseAction::Increment(sender, key, delta) => {
+                    reject_if_paused!(paused, sender);
+
+                    let record_key = KeyType::Record(key.clone());
+                    let result = db.increment(record_key.clone(), delta);
+                    trace_result!("set", key, result);
+
+                    let value = match result {
+                        Ok(value) => value,
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    };
+                    send_response!(sender, Ok(value));
+
+                    // Stored value is logged, so numeric record remains numeric after replay
+                    if let Some(sender) = &db.logger_sender {
+                        if let Some(item) = db.lookup(&record_key).and_then(|x| internal::record_log_item(&record_key, x)) {
+                            write_log!(log_batch, sender, vec![item]);
+                        }
+                    }
+                }
+                // Increment a sharded counter
+                DatabaseAction::ShardedIncrement(sender, key, shard_count, delta) => {
+                    reject_if_paused!(paused, sender);
+
+                    let result = db.sharded_increment(KeyType::Record(key.clone()), shard_count, delta);
+                    trace_result!("set", key, result);
+
+                    let (shard_key, value) = match result {
+                        Ok(result) => result,
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    };
+                    send_response!(sender, Ok(value));
+
+                    if let Some(sender) = &db.logger_sender {
+                        if let Some(item) = db.lookup(&shard_key).and_then(|x| internal::record_log_item(&shard_key, x)) {
+                            write_log!(log_batch, sender, vec![item]);
+                        }
+                    }
+                }
+                // Sum of a sharded counter
+                DatabaseAction::ShardedSum(sender, key) => {
+                    send_response!(sender, db.sharded_sum(KeyType::Record(key)));
+                }
+                // Count items in every queue
+                DatabaseAction::TotalQueued(sender) => {
+                    send_response!(sender, Ok(db.total_queued()));
+                }
+                // Length of a queue
+                DatabaseAction::QueueLen(sender, key) => {
+                    send_response!(sender, db.queue_len(KeyType::Record(key)));
+                }
+                // Length of more queues
+                DatabaseAction::QueueLenMulti(sender, keys) => {
+                    let lengths = keys
+                        .into_iter()
+                        .map(|key| {
+                            let result = db.queue_len(KeyType::Record(key.clone()));
+                            return (key, result);
+                        })
+                        .collect();
+                    send_response!(sender, lengths);
+                }
+                // List the most read keys
+                DatabaseAction::HotKeys(sender, n) => {
+                    send_response!(sender, Ok(db.hot_keys(n)));
+                }
+                // Forward items from queue to a link
+                DatabaseAction::QueueForward(sender, key, link, batch_size) => {
+                    reject_if_paused!(paused, sender);
+
+                    let hook_sender = match &db.hook_sender {
+                        Some(hook_sender) => hook_sender.clone(),
+                        None => {
+                            hook_inactive!(sender);
+                            continue;
+                        }
+                    };
+
+                    let values = match db.pop_batch(KeyType::Record(key.clone()), batch_size) {
+                        Ok(values) => values,
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    };
+                    let (tx, rx) = get_channel();
+                    let action = HookManagerAction::Forward(tx, key.clone(), link, values.clone());
+                    if let Err(e) = hook_sender.send(action) {
+                        tracing::error!("Failed to send to hook manager: {}", e);
+                        if let Err(e) = db.requeue(KeyType::Record(key.clone()), values) {
+                            tracing::error!("failed to requeue items: {}", e);
+                        }
+                        send_response!(
+                            sender,
+                            Err(ErrorKind::InternalError("Failed to send to hook manager".to_string()))
+                        );
+                        continue;
+                    }
+
+                    // The result is handled by `finish_forward` when the hook manager answers
+                    pending_forwards.push(internal::PendingForward {
+                        sender,
+                        key,
+                        values,
+                        receiver: rx,
+                    });
+                }
+                // Copy the whole database
+                DatabaseAction::Snapshot(sender) => {
+                    send_response!(sender, Ok(db.snapshot()));
+                }
+                // Export a table as JSON
+                DatabaseAction::ExportJson(sender, key) => {
+                    let result = db.to_json(KeyType::Table(key.clone()));
+                    trace_result!("export_json", key, result);
+                    send_response!(sender, result);
+
+                    if let Some(sender) = &db.logger_sender {
+                        write_log!(log_batch, sender, vec![LogItem::ListKeys(key)]);
+                    }
+                }
+                // Import a JSON object under a table
+                DatabaseAction::ImportJson(sender, key, value) => {
+                    reject_if_paused!(paused, sender);
+
+                    // Import is atomic, so only the applied pairs are logged
+                    let result = db.import_json(KeyType::Table(key.clone()), value);
+                    trace_result!("import_json", key, result);
+                    let pairs = match result {
+                        Ok(pairs) => {
+                            send_response!(sender, Ok(pairs.iter().filter(|(key, _)| key.is_record()).count()));
+                            pairs
+                        }
+                        Err(e) => {
+                            send_response!(sender, Err(e));
+                            continue;
+                        }
+                    };
+
+                    if let Some(sender) = &db.logger_sender {
+                        let mut items = Vec::new();
+                        for (key, value) in pairs {
+                            match value {
+                                ValueType::RecordPointer(_) | ValueType::IntPointer(_) | ValueType::BytesPointer(_) => {
+                                    items.extend(internal::record_log_item(&key, &value));
+                                }
+                                ValueType::QueuePointer(queue) => {
+                                    for item in queue {
+                                        items.push(LogItem::QueuePush(key.get_key().to_string(), item));
+                                    }
+                                }
+                                ValueType::TablePointer(_) => items.push(LogItem::CreatePath(key.get_key().to_string())),
+                            }
+                        }
+                        write_log!(log_batch, sender, items);
+                    }
+                }
+                // Replace the whole database
+                DatabaseAction::Restore(sender, snapshot) => {
+                    reject_if_paused!(paused, sender);
+                    send_response!(sender, db.restore(snapshot));
+                }
+                // Save the database into file
+                DatabaseAction::Flush(sender) => {
+                    let result = match config.persist_path() {
+                        Some(path) => db.save_to_file(path),
+                        None => Err(ErrorKind::InternalError(
+                            "Persist path is not configured".to_string(),
+                        )),
+                    };
+                    send_response!(sender, result);
+                }
+                // Pause the modifications
+                DatabaseAction::Pause(sender) => {
+                    tracing::debug!("database is paused");
+                    paused = true;
+                    send_response!(sender, Ok(()));
+                }
+                // Resume the modifications
+                DatabaseAction::Resume(sender) => {
+                    tracing::debug!("database is resumed");
+                    paused = false;
+                    send_response!(sender, Ok(()));
+                }
+                // Register a watcher
+                DatabaseAction::Watch(sender, prefix) => {
+                    db.watch(prefix, sender);
+                }
+                // Return with the counters
+                DatabaseAction::Stats(sender) => {
+                    stats.hook_executions = db.hook_sends();
+                    stats.keys = db.key_depth_histogram().values().sum();
+                    stats.total_queued = db.total_queued();
+                    send_response!(sender, stats.clone());
+                }
+                // Handle Clear actions
+                DatabaseAction::Clear(sender) => {
+                    reject_if_paused!(paused, sender);
+
+                    db.clear();
+                    tracing::debug!("database is cleared");
+                    send_response!(sender, Ok(()));
 
                     if let Some(sender) = &db.logger_sender {
-                        write_log!(sender, vec![LogItem::Pop(key)]);
+                        write_log!(log_batch, sender, vec![LogItem::ClearAll]);
                     }
                 }
+                // Return with the size estimate
+                DatabaseAction::SizeEstimate(sender) => {
+                    send_response!(sender, db.size_estimate());
+                }
+                // Stop the datastore
+                DatabaseAction::Shutdown(sender) => {
+                    tracing::debug!("datastore is shutting down");
+                    let mut result = Ok(());
+
+                    // Items of unfinished forwards must be back in the queue before the database is saved
+                    for forward in pending_forwards.drain(..) {
+                        let failed = forward.poll(true).unwrap_or_default();
+                        finish_forward(&mut db, &mut log_batch, forward, failed);
+                    }
+
+                    if let Some(logger_sender) = &db.logger_sender {
+                        log_batch.flush(logger_sender);
+
+                        // Logger handles requests in order, so every earlier async write is done when it replies
+                        let (tx, rx) = get_channel_for_log_write();
+                        send_response_with_mutex_sender!(logger_sender, LoggerAction::Write(tx, Vec::new()));
+
+                        match rx.recv() {
+                            Ok(LoggerResponse::Ok) => (),
+                            Ok(LoggerResponse::Err(e)) => result = Err(ErrorKind::LogError(e)),
+                            Err(e) => {
+                                tracing::error!("failed to receive: {}", e);
+                                result = Err(ErrorKind::LogError(e.to_string()));
+                            }
+                        }
+                    }
+
+                    if let Some(path) = config.persist_path() {
+                        if let Err(e) = db.save_to_file(path) {
+                            tracing::error!("failed to save database during shutdown: {}", e);
+                            result = result.and(Err(e));
+                        }
+                    }
+
+                    send_response!(sender, result);
+                    tracing::debug!("datastore is stopped");
+                    break;
+                }
             }
         }
+
+        for forward in pending_forwards.drain(..) {
+            let failed = forward.poll(true).unwrap_or_default();
+            finish_forward(&mut db, &mut log_batch, forward, failed);
+        }
+
+        if let Some(sender) = &db.logger_sender {
+            log_batch.flush(sender);
+        }
     });
 
     return (tx, thread);
 }
 
+/// Register a hook in the hook manager of database and wait for its answer
+fn set_hook(db: &Database, prefix: Prefix, link: Link, method: Option<Method>, headers: Headers) -> ResultWithoutResult {
+    let hook_sender = match &db.hook_sender {
+        Some(hook_sender) => hook_sender,
+        None => return Err(ErrorKind::InactiveHookManager),
+    };
+
+    let (tx, rx) = get_channel();
+    if let Err(e) = hook_sender.send(HookManagerAction::SetWithHeaders(tx, prefix, link, method, headers)) {
+        tracing::error!("Failed to send to hook manager: {}", e);
+        return Err(ErrorKind::InternalError(
+            "Failed to send to hook manager".to_string(),
+        ));
+    }
+
+    match rx.recv() {
+        Ok(HookManagerResponse::Ok) => return Ok(()),
+        Ok(_) => return Err(ErrorKind::InternalError("Failed to add hook".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to receive from hook manager: {}", e);
+            return Err(ErrorKind::InternalError(
+                "Failed to receive from hook manager".to_string(),
+            ));
+        }
+    }
+}
+
+/// Put the failed items of a `QueueForward` back into the queue, answer the caller and log the delivered items
+fn finish_forward(
+    db: &mut Database,
+    log_batch: &mut internal::LogBatch,
+    forward: internal::PendingForward,
+    failed: Vec<String>,
+) {
+    let delivered = forward.values.len() - failed.len();
+
+    if !failed.is_empty() {
+        if let Err(e) = db.requeue(KeyType::Record(forward.key.clone()), failed) {
+            tracing::error!("failed to requeue items: {}", e);
+        }
+    }
+
+    send_response!(forward.sender, Ok(delivered));
+
+    if delivered > 0 {
+        if let Some(sender) = &db.logger_sender {
+            write_log!(log_batch, sender, vec![LogItem::QueueDrain(forward.key, delivered)]);
+        }
+    }
+}
+
+/// Log items of a get, a value that is loaded by read-through loader and stored is logged as set before the get.
+/// While the database is paused the loaded value is not stored, so it is not logged either.
+fn loaded_log_items(key: &str, result: &Result<(ValueType, Source), ErrorKind>, paused: bool) -> Vec<LogItem> {
+    let mut items = Vec::new();
+
+    if let Ok((value, Source::Loader)) = result {
+        if !paused {
+            items.extend(internal::record_log_item(&KeyType::Record(key.to_string()), value));
+        }
+    }
+
+    items.push(LogItem::GetKey(key.to_string()));
+    return items;
+}
+
+/// Log items of a push, dropped items are logged as drain before the push so replay gives the same queue
+fn queue_push_log_items(key: String, value: String, dropped: usize) -> Vec<LogItem> {
+    let mut items = Vec::new();
+    if dropped > 0 {
+        items.push(LogItem::QueueDrain(key.clone(), dropped));
+    }
+    items.push(LogItem::QueuePush(key, value));
+    return items;
+}
+
 /// Return with channel for Set action
 pub fn get_channel_for_set() -> (Sender<ResultWithoutResult>, Receiver<ResultWithoutResult>) {
     return std::sync::mpsc::channel::<ResultWithoutResult>();
@@ -343,12 +1430,92 @@ pub fn get_channel_for_get() -> (Sender<ResultWithResult>, Receiver<ResultWithRe
     return std::sync::mpsc::channel::<ResultWithResult>();
 }
 
-/// Return with channel for DeleteKey and DeleteTable actions
+/// Return with channel for Push action
+pub fn get_channel_for_push() -> (Sender<ResultWithoutResult>, Receiver<ResultWithoutResult>) {
+    return std::sync::mpsc::channel::<ResultWithoutResult>();
+}
+
+/// Return with channel for PushBounded action
+pub fn get_channel_for_push_bounded() -> (Sender<ResultWithCount>, Receiver<ResultWithCount>) {
+    return std::sync::mpsc::channel::<ResultWithCount>();
+}
+
+/// Return with channel for Pop action
+pub fn get_channel_for_pop() -> (Sender<ResultWithOptional>, Receiver<ResultWithOptional>) {
+    return std::sync::mpsc::channel::<ResultWithOptional>();
+}
+
+/// Return with channel for Peek and PeekBack actions
+pub fn get_channel_for_peek() -> (Sender<ResultWithOptional>, Receiver<ResultWithOptional>) {
+    return std::sync::mpsc::channel::<ResultWithOptional>();
+}
+
+/// Return with channel for ListValues action
+pub fn get_channel_for_list_values() -> (Sender<ResultWithPairs>, Receiver<ResultWithPairs>) {
+    return std::sync::mpsc::channel::<ResultWithPairs>();
+}
+
+/// Return with channel for Increment action
+pub fn get_channel_for_increment() -> (Sender<ResultWithNumber>, Receiver<ResultWithNumber>) {
+    return std::sync::mpsc::channel::<ResultWithNumber>();
+}
+
+/// Return with channel for CountKeys action
+pub fn get_channel_for_count_keys() -> (Sender<ResultWithCount>, Receiver<ResultWithCount>) {
+    return std::sync::mpsc::channel::<ResultWithCount>();
+}
+
+/// Return with channel for Exists action
+pub fn get_channel_for_exists() -> (Sender<ResultWithBool>, Receiver<ResultWithBool>) {
+    return std::sync::mpsc::channel::<ResultWithBool>();
+}
+
+/// Return with channel for CompareAndSwap action
+pub fn get_channel_for_compare_and_swap() -> (Sender<ResultWithBool>, Receiver<ResultWithBool>) {
+    return std::sync::mpsc::channel::<ResultWithBool>();
+}
+
+/// Return with channel for GetSet action
+pub fn get_channel_for_get_set() -> (Sender<ResultWithPrevious>, Receiver<ResultWithPrevious>) {
+    return std::sync::mpsc::channel::<ResultWithPrevious>();
+}
+
+/// Return with channel for GetMeta action
+pub fn get_channel_for_get_meta() -> (Sender<ResultWithMeta>, Receiver<ResultWithMeta>) {
+    return std::sync::mpsc::channel::<ResultWithMeta>();
+}
+
+/// Return with channel for GetBatch action
+pub fn get_channel_for_get_batch() -> (Sender<ValueResults>, Receiver<ValueResults>) {
+    return std::sync::mpsc::channel::<ValueResults>();
+}
+
+/// Return with channel for GetWithSource action
+pub fn get_channel_for_get_with_source() -> (Sender<ResultWithSource>, Receiver<ResultWithSource>) {
+    return std::sync::mpsc::channel::<ResultWithSource>();
+}
+
+/// Return with channel for DeletePrefix action
+pub fn get_channel_for_delete_prefix() -> (Sender<ResultWithCount>, Receiver<ResultWithCount>) {
+    return std::sync::mpsc::channel::<ResultWithCount>();
+}
+
+/// Return with channel for DeleteKey, DeleteKeyWithPruning and DeleteTable actions
 pub fn get_channel_for_delete() -> (Sender<ResultWithoutResult>, Receiver<ResultWithoutResult>) {
     return std::sync::mpsc::channel::<ResultWithoutResult>();
 }
 
-/// Return with channel for ListKeys action
+/// Return with channel for Remove action
+pub fn get_channel_for_remove() -> (Sender<ResultWithResult>, Receiver<ResultWithResult>) {
+    return std::sync::mpsc::channel::<ResultWithResult>();
+}
+
+/// Return with channel for DeleteKeys action
+pub fn get_channel_for_delete_keys() -> (Sender<ResultWithKeyResults>, Receiver<ResultWithKeyResults>) {
+    return std::sync::mpsc::channel::<ResultWithKeyResults>();
+}
+
+/// Return with channel for ListKeys and ListTables actions
 pub fn get_channel_for_list() -> (Sender<ResultWithList>, Receiver<ResultWithList>) {
     return std::sync::mpsc::channel::<ResultWithList>();
 }
@@ -374,6 +1541,66 @@ pub fn get_channel_for_hook_list() -> (Sender<ResultWithHooks>, Receiver<ResultW
     return std::sync::mpsc::channel::<ResultWithHooks>();
 }
 
+/// Return with channel for HookListByLink action
+pub fn get_channel_for_hook_list_by_link() -> (Sender<ResultWithLinks>, Receiver<ResultWithLinks>) {
+    return std::sync::mpsc::channel::<ResultWithLinks>();
+}
+
+/// Return with channel for ShardedIncrement and ShardedSum actions
+pub fn get_channel_for_sharded_counter() -> (Sender<ResultWithNumber>, Receiver<ResultWithNumber>) {
+    return std::sync::mpsc::channel::<ResultWithNumber>();
+}
+
+/// Return with channel for TotalQueued action
+pub fn get_channel_for_total_queued() -> (Sender<ResultWithCount>, Receiver<ResultWithCount>) {
+    return std::sync::mpsc::channel::<ResultWithCount>();
+}
+
+/// Return with channel for PopIf action
+pub fn get_channel_for_pop_if() -> (Sender<ResultWithOptional>, Receiver<ResultWithOptional>) {
+    return std::sync::mpsc::channel::<ResultWithOptional>();
+}
+
+/// Return with channel for QueueLen action
+pub fn get_channel_for_queue_len() -> (Sender<ResultWithCount>, Receiver<ResultWithCount>) {
+    return std::sync::mpsc::channel::<ResultWithCount>();
+}
+
+/// Return with channel for QueueLenMulti action
+pub fn get_channel_for_queue_len_multi() -> (Sender<QueueLengths>, Receiver<QueueLengths>) {
+    return std::sync::mpsc::channel::<QueueLengths>();
+}
+
+/// Return with channel for Snapshot action
+pub fn get_channel_for_snapshot() -> (Sender<ResultWithSnapshot>, Receiver<ResultWithSnapshot>) {
+    return std::sync::mpsc::channel::<ResultWithSnapshot>();
+}
+
+/// Return with channel for ExportJson action
+pub fn get_channel_for_export_json() -> (Sender<ResultWithJson>, Receiver<ResultWithJson>) {
+    return std::sync::mpsc::channel::<ResultWithJson>();
+}
+
+/// Return with channel for Stats action
+pub fn get_channel_for_stats() -> (Sender<DatastoreStats>, Receiver<DatastoreStats>) {
+    return std::sync::mpsc::channel::<DatastoreStats>();
+}
+
+/// Return with channel for SizeEstimate action
+pub fn get_channel_for_size_estimate() -> (Sender<SizeReport>, Receiver<SizeReport>) {
+    return std::sync::mpsc::channel::<SizeReport>();
+}
+
+/// Return with channel for HotKeys action
+pub fn get_channel_for_hot_keys() -> (Sender<ResultWithHotKeys>, Receiver<ResultWithHotKeys>) {
+    return std::sync::mpsc::channel::<ResultWithHotKeys>();
+}
+
+/// Return with channel for QueueForward action
+pub fn get_channel_for_queue_forward() -> (Sender<ResultWithCount>, Receiver<ResultWithCount>) {
+    return std::sync::mpsc::channel::<ResultWithCount>();
+}
+
 macro_rules! hook_inactive {
     ($sender:expr) => {
         $sender
@@ -381,20 +1608,20 @@ macro_rules! hook_inactive {
             .unwrap_or_else(|e| tracing::error!("Error during send: {}", e))
     };
 }
-pub(self) use hook_inactive;
+pub(crate) use hook_inactive;
 
 macro_rules! hook_send {
     ($sender:expr, $hook_sender:expr, $action:expr) => {
         if let Err(e) = $hook_sender.send($action) {
             tracing::error!("Failed to send to hook manager: {}", e);
             $sender
-                .send(Err(ErrorKind::InternalError("".to_string())))
+                .send(Err(ErrorKind::InternalError("Failed to send to hook manager".to_string())))
                 .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
             continue;
         }
     };
 }
-pub(self) use hook_send;
+pub(crate) use hook_send;
 
 macro_rules! hook_receive_failed {
     ($sender:expr, $error:expr) => {{
@@ -406,7 +1633,32 @@ macro_rules! hook_receive_failed {
             .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
     }};
 }
-pub(self) use hook_receive_failed;
+pub(crate) use hook_receive_failed;
+
+macro_rules! trace_result {
+    ($op:expr, $key:expr, $result:expr) => {
+        tracing::debug!(
+            op = $op,
+            key = %$key,
+            result = if $result.is_ok() { "ok" } else { "error" },
+            "request is processed"
+        );
+    };
+}
+pub(crate) use trace_result;
+
+macro_rules! reject_if_paused {
+    ($paused:expr, $sender:expr) => {
+        if $paused {
+            tracing::trace!("request is rejected, database is paused");
+            $sender
+                .send(Err(ErrorKind::Paused))
+                .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
+            continue;
+        }
+    };
+}
+pub(crate) use reject_if_paused;
 
 macro_rules! send_response {
     ($sender:expr, $value:expr) => {{
@@ -415,7 +1667,7 @@ macro_rules! send_response {
             .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
     }};
 }
-pub(self) use send_response;
+pub(crate) use send_response;
 
 macro_rules! send_response_with_mutex_sender {
     ($sender:expr, $value:expr) => {{
@@ -424,13 +1676,11 @@ macro_rules! send_response_with_mutex_sender {
             .unwrap_or_else(|e| tracing::error!("Error during send: {}", e));
     }};
 }
-pub(self) use send_response_with_mutex_sender;
+pub(crate) use send_response_with_mutex_sender;
 
 macro_rules! write_log {
-    ($logger_sender:expr, $messages:expr) => {
-        $logger_sender
-            .send(LoggerAction::WriteAsync($messages))
-            .unwrap_or_else(|e| tracing::error!("{}", e));
+    ($log_batch:expr, $logger_sender:expr, $messages:expr) => {
+        $log_batch.push($logger_sender, $messages)
     };
 }
-pub(self) use write_log;
+pub(crate) use write_log;
@@ -1,13 +1,19 @@
 use std::sync::mpsc::Sender;
 use std::collections::BTreeMap;
+use std::time::Duration;
 
-use super::types::{Hooks, Key, Link, Prefix, Value};
+use reqwest::Method;
+
+use super::types::{Headers, Hooks, Key, Link, Prefix, Value};
 
 #[derive(Debug)]
 /// Input actions for HookManager
 pub enum HookManagerAction {
-    /// SET new hook
-    Set(Sender<HookManagerResponse>, Prefix, Link),
+    /// SET new hook, requests are sent with the method or with POST if it is `None`
+    Set(Sender<HookManagerResponse>, Prefix, Link, Option<Method>),
+
+    /// SET new hook with static headers that are sent with every request
+    SetWithHeaders(Sender<HookManagerResponse>, Prefix, Link, Option<Method>, Headers),
 
     /// Remove existing hook
     Remove(Sender<HookManagerResponse>, Prefix, Link),
@@ -18,8 +24,33 @@ pub enum HookManagerAction {
     /// List hooks
     List(Sender<HookManagerResponse>, Prefix),
 
-    /// Send data to defined hooks
-    Send(Key, Value),
+    /// List hooks grouped by links
+    ListByLink(Sender<HookManagerResponse>),
+
+    /// List hooks with the HTTP method of links
+    ListWithMethods(Sender<HookManagerResponse>, Prefix),
+
+    /// List hooks with headers, values of sensitive headers are hidden if the flag is true
+    ListWithHeaders(Sender<HookManagerResponse>, Prefix, bool),
+
+    /// Send data to defined hooks, if sender is specified the delivery report is sent back
+    Send(Option<Sender<HookManagerResponse>>, Key, Value),
+
+    /// Send an event to the hooks that are subscribed for it, value is `None` for deletes
+    SendEvent(Option<Sender<HookManagerResponse>>, Key, Option<Value>, HookEvent),
+
+    /// Set which events a hook is executed for
+    SetEvents(Sender<HookManagerResponse>, Prefix, Link, Vec<HookEvent>),
+
+    /// Send values of a key to a link, failed values are sent back
+    Forward(Sender<HookManagerResponse>, Key, Link, Vec<Value>),
+
+    /// Save the hook definitions into the configured file
+    Persist(Sender<HookManagerResponse>),
+
+    /// Remove all hooks whose prefix is under the path, e.g. after the table is deleted.
+    /// If sender is specified the result is sent back.
+    RemoveUnderPrefix(Option<Sender<HookManagerResponse>>, Prefix),
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -36,4 +67,62 @@ pub enum HookManagerResponse {
 
     /// Response for LIST
     HookList(BTreeMap<Prefix, Hooks>),
+
+    /// Response for LIST WITH METHODS
+    HookListWithMethods(BTreeMap<Prefix, Vec<(Link, Method)>>),
+
+    /// Response for LIST WITH HEADERS
+    HookListWithHeaders(BTreeMap<Prefix, Vec<(Link, Headers)>>),
+
+    /// Response for LIST BY LINK
+    LinkList(BTreeMap<Link, Vec<Prefix>>),
+
+    /// Response for FORWARD, values whose delivery has failed
+    Forwarded(Vec<Value>),
+
+    /// Response for SEND, matched prefixes and links with HTTP status code or error message
+    Delivered(Vec<(Prefix, Link, Result<u16, String>)>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Type of change that hooks are executed for
+pub enum HookEvent {
+    /// Record is written
+    Set,
+
+    /// Record or table is deleted
+    Delete,
+}
+
+impl std::fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Set => "set",
+            Self::Delete => "delete",
+        };
+        return write!(f, "{}", text);
+    }
+}
+
+impl std::str::FromStr for HookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "set" => return Ok(Self::Set),
+            "delete" => return Ok(Self::Delete),
+            _ => return Err(format!("Unknown hook event: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Strategy to calculate the delay between hook delivery attempts
+pub enum BackoffStrategy {
+    /// Wait the same time before every retry
+    Fixed(Duration),
+
+    /// Delay is growing exponentially (`base * 2^retry`) but never more than `cap`.
+    /// Random jitter is applied, so the actual delay is between the half and the full of the calculated one.
+    ExponentialJitter { base: Duration, cap: Duration },
 }
@@ -1,13 +1,195 @@
 //! Main component
 
 use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::Duration;
 
 pub mod enums;
 pub mod types;
 pub mod utilities;
 
-use enums::HookManagerResponse;
-use types::{Hooks, Prefix};
+use crate::{config::Config, logger::enums::LogItem};
+use enums::{BackoffStrategy, HookEvent, HookManagerResponse};
+use reqwest::Method;
+use types::{Headers, Hooks, Link, Prefix};
+
+/// Retry policy for failed hook deliveries
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use onlyati_datastore::hook::{RetryPolicy, enums::BackoffStrategy};
+///
+/// let policy = RetryPolicy {
+///     max_retries: 3,
+///     strategy: BackoffStrategy::ExponentialJitter {
+///         base: Duration::from_millis(100),
+///         cap: Duration::from_secs(5),
+///     },
+/// };
+///
+/// assert_eq!(true, policy.delay(0) <= Duration::from_millis(100));
+/// assert_eq!(true, policy.delay(10) <= Duration::from_secs(5));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of retries after the first failed attempt, 0 means no retry
+    pub max_retries: u32,
+
+    /// How the delay is calculated between the attempts
+    pub strategy: BackoffStrategy,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        return RetryPolicy {
+            max_retries: 0,
+            strategy: BackoffStrategy::Fixed(Duration::from_millis(500)),
+        };
+    }
+}
+
+impl RetryPolicy {
+    /// Calculate the delay before the specified retry, first retry is 0
+    pub fn delay(&self, retry: u32) -> Duration {
+        match &self.strategy {
+            BackoffStrategy::Fixed(delay) => return *delay,
+            BackoffStrategy::ExponentialJitter { base, cap } => {
+                let delay = base
+                    .saturating_mul(2u32.saturating_pow(retry))
+                    .min(*cap);
+                let half = delay / 2;
+                let jitter = fastrand::u64(0..=(delay - half).as_nanos() as u64);
+                return half + Duration::from_nanos(jitter);
+            }
+        }
+    }
+}
+
+/// Outcome of the hook deliveries of a key
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HookExecutionReport {
+    /// Number of links that matched the key
+    pub attempted: usize,
+
+    /// Number of deliveries that were answered with 2xx, after the retries
+    pub succeeded: usize,
+
+    /// Links whose delivery has failed with the error message or the HTTP status
+    pub failed: Vec<(String, String)>,
+}
+
+/// Header names whose values are hidden in `HookManager::list_with_headers` if redaction is asked
+const SENSITIVE_HEADERS: [&str; 5] = ["authorization", "proxy-authorization", "cookie", "x-api-key", "x-auth-token"];
+
+/// Header of the HMAC-SHA256 signature of the request body, see `HookManager::set_signing_secret`
+pub const SIGNATURE_HEADER: &str = "X-Datastore-Signature";
+
+/// Hook prefixes that begin with this are regular expressions, they are matched against the full key
+const REGEX_PREFIX: &str = "re:";
+
+/// Registered link of a prefix with its request settings
+#[derive(Clone, Debug)]
+struct HookTarget {
+    link: Link,
+
+    /// HTTP method of request, `None` means POST
+    method: Option<Method>,
+
+    /// Static headers that are added to every request
+    headers: Headers,
+
+    /// Events that the hook is executed for
+    events: Vec<HookEvent>,
+}
+
+/// Hook definition in the file of `HookManager::save`
+struct StoredHook {
+    link: Link,
+    method: Option<String>,
+    headers: Headers,
+    events: Vec<String>,
+}
+
+impl StoredHook {
+    /// Convert hook into JSON object
+    fn to_json(&self) -> serde_json::Value {
+        return serde_json::json!({
+            "link": self.link,
+            "method": self.method,
+            "headers": self.headers,
+            "events": self.events,
+        });
+    }
+
+    /// Parse hook from JSON object, only `link` is mandatory
+    fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let link = match value.get("link").and_then(|x| x.as_str()) {
+            Some(link) => link.to_string(),
+            None => return Err("Missing 'link' field".to_string()),
+        };
+
+        let method = match value.get("method") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(serde_json::Value::String(method)) => Some(method.clone()),
+            Some(_) => return Err(format!("Method of '{}' is not a string", link)),
+        };
+
+        let mut headers = Headers::new();
+        match value.get("headers") {
+            None | Some(serde_json::Value::Null) => (),
+            Some(serde_json::Value::Object(map)) => {
+                for (name, value) in map {
+                    let value = value.as_str().ok_or(format!("Header '{}' of '{}' is not a string", name, link))?;
+                    headers.insert(name.clone(), value.to_string());
+                }
+            }
+            Some(_) => return Err(format!("Headers of '{}' is not an object", link)),
+        }
+
+        let events = match value.get("events") {
+            None | Some(serde_json::Value::Null) => Vec::new(),
+            Some(serde_json::Value::Array(list)) => list
+                .iter()
+                .map(|x| x.as_str().map(|x| x.to_string()))
+                .collect::<Option<Vec<String>>>()
+                .ok_or(format!("Events of '{}' are not strings", link))?,
+            Some(_) => return Err(format!("Events of '{}' is not an array", link)),
+        };
+
+        return Ok(StoredHook { link, method, headers, events });
+    }
+}
+
+/// Compile the expression of a `re:` prefix, it ignores the case if case folding is enabled
+fn compile_pattern(expression: &str, case_folding: bool) -> Result<regex::Regex, regex::Error> {
+    return regex::RegexBuilder::new(expression)
+        .case_insensitive(case_folding)
+        .build();
+}
+
+/// Parse the content of file that was written by `HookManager::save`
+fn parse_stored_hooks(content: &str) -> Result<BTreeMap<Prefix, Vec<StoredHook>>, String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let map = match value {
+        serde_json::Value::Object(map) => map,
+        _ => return Err("Content is not an object".to_string()),
+    };
+
+    let mut stored = BTreeMap::new();
+    for (prefix, targets) in map {
+        let targets = match targets {
+            serde_json::Value::Array(list) => list
+                .iter()
+                .map(StoredHook::from_json)
+                .collect::<Result<Vec<StoredHook>, String>>()?,
+            _ => return Err(format!("Hooks of '{}' is not an array", prefix)),
+        };
+        stored.insert(prefix, targets);
+    }
+
+    return Ok(stored);
+}
 
 /// HookManager main structure
 /// 
@@ -19,20 +201,24 @@ use types::{Hooks, Prefix};
 /// 
 /// let mut manager = HookManager::new();
 /// 
-/// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string());
+/// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None);
 /// assert_eq!(true, result.is_ok());
 /// 
-/// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3032".to_string());
+/// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3032".to_string(), None);
 /// assert_eq!(true, result.is_ok());
 /// 
-/// let result = manager.add("/root/arpa".to_string(), "http://127.0.0.1:3031".to_string());
+/// let result = manager.add("/root/arpa".to_string(), "http://127.0.0.1:3031".to_string(), None);
 /// assert_eq!(true, result.is_ok());
 /// 
 /// let result = manager.list(&"/root".to_string());
 /// assert_eq!(2, result.len());
 /// 
-/// let result = manager.list(&"/root/stat".to_string());
+/// let result = manager.list(&"/root/status".to_string());
 /// assert_eq!(1, result.len());
+///
+/// // Only whole path segments match
+/// let result = manager.list(&"/root/stat".to_string());
+/// assert_eq!(0, result.len());
 /// 
 /// let result = manager.list(&"/root/no_exist".to_string());
 /// assert_eq!(0, result.len());
@@ -40,8 +226,35 @@ use types::{Hooks, Prefix};
 #[derive(Clone)]
 pub struct HookManager {
     /// List about hooks
-    hooks: BTreeMap<Prefix, Hooks>,
+    hooks: BTreeMap<Prefix, Vec<HookTarget>>,
     client: reqwest::Client,
+
+    /// Compiled expressions of the `re:` prefixes
+    patterns: HashMap<Prefix, regex::Regex>,
+
+    /// Maximum number of links under a single prefix
+    max_links: Option<usize>,
+
+    /// Retry policy of failed deliveries
+    retry_policy: RetryPolicy,
+
+    /// Compare prefixes and keys without case sensitivity
+    case_folding: bool,
+
+    /// Maximum time of a request, `None` means no limit
+    timeout: Option<Duration>,
+
+    /// Maximum number of deliveries in progress by `utilities::start_hook_manager`, `None` means unlimited
+    max_pending: Option<usize>,
+
+    /// Shared secret of request signatures, `None` means requests are not signed
+    signing_secret: Option<String>,
+}
+
+impl Default for HookManager {
+    fn default() -> Self {
+        return HookManager::new();
+    }
 }
 
 impl HookManager {
@@ -50,30 +263,505 @@ impl HookManager {
         return HookManager {
             hooks: BTreeMap::new(),
             client: reqwest::Client::new(),
+            patterns: HashMap::new(),
+            max_links: None,
+            retry_policy: RetryPolicy::default(),
+            case_folding: false,
+            timeout: None,
+            max_pending: None,
+            signing_secret: None,
+        };
+    }
+
+    /// Allocate new HookManager based on configuration
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::config::Builder;
+    /// use onlyati_datastore::hook::HookManager;
+    ///
+    /// let config = Builder::new().max_hooks_per_prefix(1).build();
+    /// let mut manager = HookManager::from_config(&config);
+    ///
+    /// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None);
+    /// assert_eq!(true, result.is_ok());
+    ///
+    /// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3032".to_string(), None);
+    /// assert_eq!(true, result.is_err());
+    /// ```
+    pub fn from_config(config: &Config) -> Self {
+        let mut manager = HookManager::new();
+        manager.set_max_links(config.max_hooks_per_prefix());
+        manager.set_retry_policy(config.hook_retry_policy().clone());
+        manager.set_case_folding(config.hook_case_folding());
+        if let Some(timeout) = config.hook_timeout() {
+            manager.set_timeout(timeout);
+        }
+        manager.set_max_pending(config.max_pending_hooks());
+        manager.set_signing_secret(config.hook_signing_secret().cloned());
+        return manager;
+    }
+
+    /// Set that prefixes are matched without case sensitivity in `execute_hooks` and `list`
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::hook::HookManager;
+    ///
+    /// let mut manager = HookManager::new();
+    /// manager.add("/root/Status".to_string(), "http://127.0.0.1:3031".to_string(), None).unwrap();
+    ///
+    /// assert_eq!(0, manager.list(&"/root/status".to_string()).len());
+    ///
+    /// manager.set_case_folding(true);
+    /// assert_eq!(1, manager.list(&"/root/status".to_string()).len());
+    /// ```
+    pub fn set_case_folding(&mut self, enabled: bool) {
+        tracing::trace!("case folding is set to {}", enabled);
+        self.case_folding = enabled;
+
+        // Expressions of `re:` prefixes are compiled again with the new case sensitivity
+        for (prefix, pattern) in self.patterns.iter_mut() {
+            let expression = prefix.strip_prefix(REGEX_PREFIX).unwrap_or(prefix);
+            match compile_pattern(expression, enabled) {
+                Ok(compiled) => *pattern = compiled,
+                Err(e) => tracing::error!("failed to compile '{}' expression again: {}", expression, e),
+            }
+        }
+    }
+
+    /// Check that text is the prefix or it is under the prefix path, according to the case folding setting.
+    /// Only whole segments are matched, so `/root/st` does not match `/root/status`.
+    fn starts_with(&self, text: &str, prefix: &str) -> bool {
+        let (text, prefix) = match self.case_folding {
+            true => (text.to_lowercase(), prefix.to_lowercase()),
+            false => (text.to_string(), prefix.to_string()),
+        };
+
+        match text.strip_prefix(&prefix) {
+            Some(rest) => return rest.is_empty() || rest.starts_with('/') || prefix.is_empty() || prefix.ends_with('/'),
+            None => return false,
+        }
+    }
+
+    /// Check that hook of the prefix is listed for the path: `re:` prefixes are listed if they match the path,
+    /// others if they are under the path
+    fn listed(&self, prefix: &str, path: &str) -> bool {
+        match self.patterns.get(prefix) {
+            Some(pattern) => return pattern.is_match(path),
+            None => return self.starts_with(prefix, path),
+        }
+    }
+
+    /// Check that hook of the prefix belongs to the key: `re:` prefixes have to match the full key,
+    /// others have to be a parent path of the key (or the key itself)
+    fn matches(&self, key: &str, prefix: &str) -> bool {
+        match self.patterns.get(prefix) {
+            Some(pattern) => return pattern.is_match(key),
+            None => return self.starts_with(key, prefix),
+        }
+    }
+
+    /// Set the maximum time that a request can take, timed out requests are counted as failed
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use onlyati_datastore::hook::HookManager;
+    ///
+    /// let mut manager = HookManager::new();
+    /// manager.set_timeout(Duration::from_secs(5));
+    ///
+    /// assert_eq!(Some(Duration::from_secs(5)), manager.timeout());
+    /// ```
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        tracing::trace!("request timeout is set to {:?}", timeout);
+        self.client = match reqwest::Client::builder().timeout(timeout).build() {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!("failed to build client with timeout, previous is kept: {}", e);
+                return;
+            }
         };
+        self.timeout = Some(timeout);
+    }
+
+    /// Maximum time that a request can take, `None` means no limit
+    pub fn timeout(&self) -> Option<Duration> {
+        return self.timeout;
+    }
+
+    /// Set the shared secret of request signatures, `None` disables the signing.
+    /// If it is set, the HMAC-SHA256 of the request body is sent in `X-Datastore-Signature` header
+    /// as `sha256=<hex digest>`, so receiver can verify that the request is sent by the datastore.
+    ///
+    /// # Examples
+    /// Receiver recomputes the signature over the raw body and compares it in constant time:
+    /// ```
+    /// use hmac::{Hmac, Mac};
+    /// use sha2::Sha256;
+    /// use onlyati_datastore::hook::{HookManager, utilities};
+    ///
+    /// let mut manager = HookManager::new();
+    /// manager.set_signing_secret(Some("secret".to_string()));
+    /// assert_eq!(true, manager.is_signed());
+    ///
+    /// // Header and body that arrived to the receiver
+    /// let body = br#"{"event":"set","key":"/root/status/dns1","value":"ok"}"#;
+    /// let header = utilities::sign_body("secret", body);
+    ///
+    /// let digest = hex::decode(header.strip_prefix("sha256=").unwrap()).unwrap();
+    /// let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+    /// mac.update(body);
+    /// assert_eq!(true, mac.verify_slice(&digest).is_ok());
+    /// ```
+    pub fn set_signing_secret(&mut self, secret: Option<String>) {
+        tracing::trace!("request signing is set to {}", secret.is_some());
+        self.signing_secret = secret;
+    }
+
+    /// Tells that requests are signed
+    pub fn is_signed(&self) -> bool {
+        return self.signing_secret.is_some();
+    }
+
+    /// Set the maximum number of deliveries that can be in progress in `utilities::start_hook_manager`,
+    /// further `HookManagerAction::Send` requests are dropped. `None` means unlimited.
+    pub fn set_max_pending(&mut self, limit: Option<usize>) {
+        tracing::trace!("maximum pending deliveries is set to {:?}", limit);
+        self.max_pending = limit;
+    }
+
+    /// Maximum number of deliveries that can be in progress, `None` means unlimited
+    pub fn max_pending(&self) -> Option<usize> {
+        return self.max_pending;
+    }
+
+    /// Set the retry policy of failed deliveries
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        tracing::trace!("retry policy is set to {:?}", policy);
+        self.retry_policy = policy;
     }
 
-    /// Add new hook
-    pub fn add(&mut self, prefix: String, link: String) -> Result<(), HookManagerResponse> {
+    /// Set the maximum number of links can be registered under a single prefix, `None` means unlimited
+    pub fn set_max_links(&mut self, limit: Option<usize>) {
+        tracing::trace!("maximum links per prefix is set to {:?}", limit);
+        self.max_links = limit;
+    }
+
+    /// Add new hook, requests are sent with the specified HTTP method or with POST if it is `None`
+    ///
+    /// # Examples
+    /// ```
+    /// use reqwest::Method;
+    /// use onlyati_datastore::hook::HookManager;
+    ///
+    /// let mut manager = HookManager::new();
+    ///
+    /// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), Some(Method::PUT));
+    /// assert_eq!(true, result.is_ok());
+    ///
+    /// // Link can be defined only once under a prefix, whatever the method is
+    /// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None);
+    /// assert_eq!(true, result.is_err());
+    /// ```
+    pub fn add(&mut self, prefix: String, link: String, method: Option<Method>) -> Result<(), HookManagerResponse> {
+        return self.add_with_headers(prefix, link, method, Headers::new());
+    }
+
+    /// Add new hook with static headers that are sent with every request, e.g. `Authorization`
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use onlyati_datastore::hook::HookManager;
+    ///
+    /// let mut manager = HookManager::new();
+    ///
+    /// let mut headers = BTreeMap::new();
+    /// headers.insert("Authorization".to_string(), "Bearer secret".to_string());
+    ///
+    /// let result = manager.add_with_headers("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None, headers);
+    /// assert_eq!(true, result.is_ok());
+    ///
+    /// let list = manager.list_with_headers(&"/root".to_string(), true);
+    /// let (_, headers) = &list.get("/root/status").unwrap()[0];
+    /// assert_eq!(Some(&"***".to_string()), headers.get("Authorization"));
+    /// ```
+    pub fn add_with_headers(
+        &mut self,
+        prefix: String,
+        link: String,
+        method: Option<Method>,
+        headers: Headers,
+    ) -> Result<(), HookManagerResponse> {
         tracing::trace!("hook set request is performed for '{}' prefix with '{}' link", prefix, link);
+        for (name, value) in &headers {
+            let valid = reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_ok()
+                && reqwest::header::HeaderValue::from_str(value).is_ok();
+            if !valid {
+                tracing::trace!("hook set request is failed due to '{}' header is invalid", name);
+                return Err(HookManagerResponse::Error(format!("Invalid '{}' header", name)));
+            }
+        }
+
+        let pattern = match prefix.strip_prefix(REGEX_PREFIX) {
+            Some(expression) if !self.patterns.contains_key(&prefix) => match compile_pattern(expression, self.case_folding) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    tracing::trace!("hook set request is failed due to '{}' is invalid regex", expression);
+                    return Err(HookManagerResponse::Error(format!("Invalid regex '{}': {}", expression, e)));
+                }
+            },
+            _ => None,
+        };
+
+        let target = HookTarget {
+            link: link.clone(),
+            method,
+            headers,
+            events: vec![HookEvent::Set],
+        };
+
         match self.hooks.get_mut(&prefix) {
-            Some(hooks) => match hooks.iter().position(|x| x == &link) {
+            Some(hooks) => match hooks.iter().position(|x| x.link == link) {
                 Some(_) => {
                     tracing::trace!("hook set request is failed due to '{}' with '{}' link is already exist", prefix, link);
                     return Err(HookManagerResponse::Error("Already defined".to_string()));
                 },
                 None => {
-                    hooks.push(link);
+                    if let Some(limit) = self.max_links {
+                        if hooks.len() >= limit {
+                            tracing::trace!("hook set request is failed due to '{}' has already {} link", prefix, hooks.len());
+                            return Err(HookManagerResponse::Error(format!("Limit reached, maximum {} link can be defined", limit)));
+                        }
+                    }
+
+                    hooks.push(target);
                     tracing::trace!("hook set request is done for '{}'", prefix);
                     return Ok(());
                 }
             },
             None => {
+                if self.max_links == Some(0) {
+                    tracing::trace!("hook set request is failed due to no link is allowed");
+                    return Err(HookManagerResponse::Error("Limit reached, maximum 0 link can be defined".to_string()));
+                }
+
                 tracing::trace!("hook set request is done for '{}'", prefix);
-                self.hooks.insert(prefix, vec![link]);
+                if let Some(pattern) = pattern {
+                    self.patterns.insert(prefix.clone(), pattern);
+                }
+                self.hooks.insert(prefix, vec![target]);
+                return Ok(());
+            }
+        }
+    }
+
+    /// Set which events the hook is executed for, new hooks are executed only for `HookEvent::Set`.
+    /// Use `DatabaseAction::HookSetEvents` to have it logged, so `replay_log` restores the events too.
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::hook::{HookManager, enums::HookEvent};
+    ///
+    /// let mut manager = HookManager::new();
+    /// manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None).unwrap();
+    ///
+    /// let result = manager.set_events("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), vec![HookEvent::Delete]);
+    /// assert_eq!(true, result.is_ok());
+    ///
+    /// let result = manager.set_events("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), vec![]);
+    /// assert_eq!(true, result.is_err());
+    /// ```
+    pub fn set_events(&mut self, prefix: String, link: String, events: Vec<HookEvent>) -> Result<(), HookManagerResponse> {
+        tracing::trace!("hook events request is performed for '{}' prefix with '{}' link", prefix, link);
+        if events.is_empty() {
+            return Err(HookManagerResponse::Error("At least one event must be specified".to_string()));
+        }
+
+        let target = self
+            .hooks
+            .get_mut(&prefix)
+            .and_then(|hooks| hooks.iter_mut().find(|x| x.link == link));
+
+        match target {
+            Some(target) => {
+                target.events = events;
+                tracing::trace!("hook events request is done for '{}' prefix with '{}' link", prefix, link);
                 return Ok(());
             }
+            None => {
+                tracing::trace!("hook events request is failed because no '{}' link exist under '{}'", link, prefix);
+                return Err(HookManagerResponse::Error("Not found".to_string()));
+            }
+        }
+    }
+
+    /// Read a datastore log file and apply its `SetHook`, `SetHookWithMethod`, `SetHookWithHeaders`, `SetHookEvents`,
+    /// `RemHook` and `RemHooksUnder` lines, so the hook registrations can be rebuilt.
+    /// Other lines are skipped. Lines that failed originally (e.g. already defined hook) fail again, they are skipped too.
+    /// Return with the number of applied lines.
+    ///
+    /// # Arguments
+    /// 1. `path` - Path of the log file
+    pub fn replay_log(&mut self, path: &str) -> Result<usize, HookManagerResponse> {
+        tracing::trace!("hook replay request is performed for '{}'", path);
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            HookManagerResponse::Error(format!("Failed to read log file: {}", e))
+        })?;
+
+        let mut counter: usize = 0;
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let item = line.parse::<LogItem>().map_err(|e| {
+                tracing::error!("failed to parse line {} of log: {}", index + 1, e);
+                HookManagerResponse::Error(format!("Line {}: {}", index + 1, e))
+            })?;
+
+            let result = match item {
+                LogItem::SetHook(prefix, link) => self.add(prefix, link, None),
+                LogItem::SetHookWithMethod(prefix, link, method) => match Method::from_bytes(method.as_bytes()) {
+                    Ok(method) => self.add(prefix, link, Some(method)),
+                    Err(_) => Err(HookManagerResponse::Error(format!("Invalid '{}' method", method))),
+                },
+                LogItem::SetHookWithHeaders(prefix, link, method, headers) => match Method::from_bytes(method.as_bytes()) {
+                    Ok(method) => self.add_with_headers(prefix, link, Some(method), headers),
+                    Err(_) => Err(HookManagerResponse::Error(format!("Invalid '{}' method", method))),
+                },
+                LogItem::SetHookEvents(prefix, link, events) => {
+                    match events.iter().map(|x| x.parse::<HookEvent>()).collect::<Result<Vec<HookEvent>, String>>() {
+                        Ok(events) => self.set_events(prefix, link, events),
+                        Err(e) => Err(HookManagerResponse::Error(e)),
+                    }
+                }
+                LogItem::RemHook(prefix, link) => self.remove(prefix, link),
+                LogItem::RemHooksUnder(prefix) => {
+                    self.remove_under_prefix(&prefix);
+                    Ok(())
+                }
+                _ => continue,
+            };
+
+            match result {
+                Ok(_) => counter += 1,
+                Err(e) => tracing::trace!("line {} is skipped: {:?}", index + 1, e),
+            }
+        }
+
+        tracing::trace!("hook replay request is done, {} line applied", counter);
+        return Ok(counter);
+    }
+
+    /// Save the hook definitions (links with their method, headers and events) into a file as JSON.
+    /// The file is written next to the target then renamed, so a crash during write does not destroy the former file.
+    /// Header values are written without redaction, so the file has to be protected like the configuration.
+    ///
+    /// # Arguments
+    /// 1. `path` - Path of the file
+    ///
+    /// # Examples
+    /// ```
+    /// use std::path::Path;
+    /// use onlyati_datastore::hook::HookManager;
+    ///
+    /// let path = Path::new("/tmp/datastore-doc-hooks.json");
+    ///
+    /// let mut manager = HookManager::new();
+    /// manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None).unwrap();
+    /// manager.save(path).expect("Failed to save");
+    ///
+    /// let mut other = HookManager::new();
+    /// assert_eq!(Ok(1), other.load(path));
+    /// assert_eq!(Some(vec!["http://127.0.0.1:3031".to_string()]), other.get(&"/root/status".to_string()));
+    /// ```
+    pub fn save(&self, path: &Path) -> Result<(), HookManagerResponse> {
+        tracing::trace!("hook save request is performed to '{}'", path.display());
+        let stored: serde_json::Map<String, serde_json::Value> = self
+            .hooks
+            .iter()
+            .map(|(prefix, targets)| {
+                let targets = targets
+                    .iter()
+                    .map(|target| StoredHook {
+                        link: target.link.clone(),
+                        method: target.method.as_ref().map(|x| x.to_string()),
+                        headers: target.headers.clone(),
+                        events: target.events.iter().map(|x| x.to_string()).collect(),
+                    })
+                    .map(|x| x.to_json())
+                    .collect();
+                (prefix.clone(), serde_json::Value::Array(targets))
+            })
+            .collect();
+
+        let content = serde_json::to_string_pretty(&stored).map_err(|e| {
+            HookManagerResponse::Error(format!("Failed to serialize hooks: {}", e))
+        })?;
+
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, content).map_err(|e| {
+            HookManagerResponse::Error(format!("Failed to write '{}': {}", temp_path.display(), e))
+        })?;
+        std::fs::rename(&temp_path, path).map_err(|e| {
+            HookManagerResponse::Error(format!("Failed to replace '{}': {}", path.display(), e))
+        })?;
+
+        tracing::trace!("hook save request is done to '{}'", path.display());
+        return Ok(());
+    }
+
+    /// Replace the hook definitions with the file that was written by `save`.
+    /// Hooks are validated like by `add_with_headers`, if any of them is wrong the current hooks are kept.
+    /// Return with the number of loaded links.
+    ///
+    /// # Arguments
+    /// 1. `path` - Path of the file
+    pub fn load(&mut self, path: &Path) -> Result<usize, HookManagerResponse> {
+        tracing::trace!("hook load request is performed from '{}'", path.display());
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            HookManagerResponse::Error(format!("Failed to read '{}': {}", path.display(), e))
+        })?;
+        let stored = parse_stored_hooks(&content).map_err(|e| {
+            HookManagerResponse::Error(format!("Failed to parse '{}': {}", path.display(), e))
+        })?;
+
+        let mut loaded = self.clone();
+        loaded.hooks.clear();
+        loaded.patterns.clear();
+
+        let mut counter: usize = 0;
+        for (prefix, targets) in stored {
+            for target in targets {
+                let method = match target.method {
+                    Some(method) => match Method::from_bytes(method.as_bytes()) {
+                        Ok(method) => Some(method),
+                        Err(_) => return Err(HookManagerResponse::Error(format!("Invalid '{}' method", method))),
+                    },
+                    None => None,
+                };
+                loaded.add_with_headers(prefix.clone(), target.link.clone(), method, target.headers)?;
+
+                if !target.events.is_empty() {
+                    let events = target
+                        .events
+                        .iter()
+                        .map(|x| x.parse::<HookEvent>())
+                        .collect::<Result<Vec<HookEvent>, String>>()
+                        .map_err(HookManagerResponse::Error)?;
+                    loaded.set_events(prefix.clone(), target.link, events)?;
+                }
+                counter += 1;
+            }
         }
+
+        self.hooks = loaded.hooks;
+        self.patterns = loaded.patterns;
+        tracing::trace!("hook load request is done from '{}', {} link loaded", path.display(), counter);
+        return Ok(counter);
     }
 
     /// Delete existing hook
@@ -81,7 +769,7 @@ impl HookManager {
         tracing::trace!("hook set request is performed for '{}' prefix with '{}' link", prefix, link);
         match self.hooks.get_mut(&prefix) {
             Some(hooks) => {
-                match hooks.iter().position(|x| x == &link) {
+                match hooks.iter().position(|x| x.link == link) {
                     Some(index) => hooks.remove(index),
                     None => {
                         tracing::trace!("hook set request is failed because no '{}' link exist", link);
@@ -89,8 +777,9 @@ impl HookManager {
                     },
                 };
 
-                if hooks.len() == 0 {
+                if hooks.is_empty() {
                     self.hooks.remove(&prefix);
+                    self.patterns.remove(&prefix);
                 }
 
                 tracing::trace!("hook set request is done for '{}' prefix with '{}' link", prefix, link);
@@ -103,13 +792,45 @@ impl HookManager {
         }
     }
 
+    /// Delete all hooks whose prefix is under the path, only whole path segments are matched.
+    /// Hooks with `re:` prefix are kept. Return with the number of removed prefixes.
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::hook::HookManager;
+    ///
+    /// let mut manager = HookManager::new();
+    /// manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None).unwrap();
+    /// manager.add("/root/status/sub1".to_string(), "http://127.0.0.1:3031".to_string(), None).unwrap();
+    /// manager.add("/root/network".to_string(), "http://127.0.0.1:3031".to_string(), None).unwrap();
+    ///
+    /// assert_eq!(2, manager.remove_under_prefix("/root/status"));
+    /// assert_eq!(1, manager.list(&"/root".to_string()).len());
+    /// ```
+    pub fn remove_under_prefix(&mut self, path: &str) -> usize {
+        tracing::trace!("hook remove request is performed under '{}' path", path);
+        let removed: Vec<Prefix> = self
+            .hooks
+            .keys()
+            .filter(|prefix| !self.patterns.contains_key(*prefix) && self.starts_with(prefix, path))
+            .cloned()
+            .collect();
+
+        for prefix in &removed {
+            self.hooks.remove(prefix);
+        }
+
+        tracing::trace!("hook remove request is done, {} prefix is removed under '{}'", removed.len(), path);
+        return removed.len();
+    }
+
     /// Check that hook exist
     pub fn get(&self, prefix: &String) -> Option<Hooks> {
         tracing::trace!("hook get request is performed for '{}' prefix", prefix);
         match self.hooks.get(prefix) {
             Some(hooks) => {
                 tracing::trace!("hook get request is done for '{}' prefix", prefix);
-                return Some(hooks.clone());
+                return Some(hooks.iter().map(|x| x.link.clone()).collect());
             },
             None => {
                 tracing::trace!("hook get request is failed due to no '{}' prefix exist", prefix);
@@ -118,20 +839,215 @@ impl HookManager {
         }
     }
 
-    /// List hooks for specified paths
+    /// List hooks for specified paths, only whole path segments are matched.
+    /// Hooks with `re:` prefix are listed if their expression matches the path.
     pub fn list(&self, key: &String) -> BTreeMap<Prefix, Hooks> {
         tracing::trace!("hook list request is performed for '{}' prefix", key);
         let selected_hooks: BTreeMap<Prefix, Hooks> = self
             .hooks
             .iter()
-            .filter(|x| x.0.starts_with(key))
-            .map(|x| (x.0.clone(), x.1.clone()))
+            .filter(|x| self.listed(x.0, key))
+            .map(|x| (x.0.clone(), x.1.iter().map(|target| target.link.clone()).collect()))
             .collect();
         tracing::trace!("hook list request is done and found {} record", selected_hooks.len());
         return selected_hooks;
     }
 
-    /// Pass a key and send POST request if key match with any defined prefix
+    /// List hooks for specified paths with the HTTP method of links, POST is listed if it was not specified
+    ///
+    /// # Examples
+    /// ```
+    /// use reqwest::Method;
+    /// use onlyati_datastore::hook::HookManager;
+    ///
+    /// let mut manager = HookManager::new();
+    /// manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), Some(Method::PUT)).unwrap();
+    /// manager.add("/root/status".to_string(), "http://127.0.0.1:3032".to_string(), None).unwrap();
+    ///
+    /// let list = manager.list_with_methods(&"/root".to_string());
+    /// assert_eq!(
+    ///     &vec![("http://127.0.0.1:3031".to_string(), Method::PUT), ("http://127.0.0.1:3032".to_string(), Method::POST)],
+    ///     list.get("/root/status").unwrap()
+    /// );
+    /// ```
+    pub fn list_with_methods(&self, key: &String) -> BTreeMap<Prefix, Vec<(Link, Method)>> {
+        tracing::trace!("hook list with methods request is performed for '{}' prefix", key);
+        let selected_hooks: BTreeMap<Prefix, Vec<(Link, Method)>> = self
+            .hooks
+            .iter()
+            .filter(|x| self.listed(x.0, key))
+            .map(|(prefix, targets)| {
+                let links = targets
+                    .iter()
+                    .map(|target| (target.link.clone(), target.method.clone().unwrap_or(Method::POST)))
+                    .collect();
+                (prefix.clone(), links)
+            })
+            .collect();
+        tracing::trace!("hook list with methods request is done and found {} record", selected_hooks.len());
+        return selected_hooks;
+    }
+
+    /// List hooks for specified paths with the headers of links, so they can be audited.
+    /// If `redact` is true, values of sensitive headers (e.g. `Authorization`) are replaced with `***`.
+    pub fn list_with_headers(&self, key: &String, redact: bool) -> BTreeMap<Prefix, Vec<(Link, Headers)>> {
+        tracing::trace!("hook list with headers request is performed for '{}' prefix", key);
+        let selected_hooks: BTreeMap<Prefix, Vec<(Link, Headers)>> = self
+            .hooks
+            .iter()
+            .filter(|x| self.listed(x.0, key))
+            .map(|(prefix, targets)| {
+                let links = targets
+                    .iter()
+                    .map(|target| {
+                        let headers = target
+                            .headers
+                            .iter()
+                            .map(|(name, value)| {
+                                match redact && SENSITIVE_HEADERS.contains(&name.to_lowercase().as_str()) {
+                                    true => (name.clone(), "***".to_string()),
+                                    false => (name.clone(), value.clone()),
+                                }
+                            })
+                            .collect();
+                        (target.link.clone(), headers)
+                    })
+                    .collect();
+                (prefix.clone(), links)
+            })
+            .collect();
+        tracing::trace!("hook list with headers request is done and found {} record", selected_hooks.len());
+        return selected_hooks;
+    }
+
+    /// List hooks grouped by their target link, so it is visible which prefixes a link is subscribed for
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::hook::HookManager;
+    ///
+    /// let mut manager = HookManager::new();
+    ///
+    /// manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None).unwrap();
+    /// manager.add("/root/arpa".to_string(), "http://127.0.0.1:3031".to_string(), None).unwrap();
+    /// manager.add("/root/arpa".to_string(), "http://127.0.0.1:3032".to_string(), None).unwrap();
+    ///
+    /// let result = manager.list_by_link();
+    /// assert_eq!(2, result.len());
+    /// assert_eq!(2, result.get("http://127.0.0.1:3031").unwrap().len());
+    /// ```
+    pub fn list_by_link(&self) -> BTreeMap<Link, Vec<Prefix>> {
+        tracing::trace!("hook list by link request is performed");
+        let mut links: BTreeMap<Link, Vec<Prefix>> = BTreeMap::new();
+
+        for (prefix, hooks) in &self.hooks {
+            for target in hooks {
+                links
+                    .entry(target.link.clone())
+                    .or_default()
+                    .push(prefix.clone());
+            }
+        }
+
+        tracing::trace!("hook list by link request is done and found {} link", links.len());
+        return links;
+    }
+
+    /// Send every value of a key to the link as a POST request, one by one.
+    /// Delivery stops at the first failure (it was not answered with 2xx), so the order is kept.
+    /// Return with the failed and the not sent values, in the original order.
+    pub async fn forward(&self, link: &String, key: &String, values: Vec<String>) -> Vec<String> {
+        tracing::debug!("forward {} value of '{}' to '{}'", values.len(), key, link);
+        let mut failed = Vec::new();
+
+        for value in values {
+            if !failed.is_empty() {
+                failed.push(value);
+                continue;
+            }
+
+            let mut body = HashMap::new();
+            body.insert("key", key);
+            body.insert("value", &value);
+
+            match self.client.post(link).json(&body).send().await {
+                Ok(resp) if resp.status().is_success() => tracing::trace!("{:?}", resp),
+                Ok(resp) => {
+                    tracing::error!("Error: forward to '{}' is answered with {}", link, resp.status());
+                    failed.push(value);
+                }
+                Err(e) => {
+                    tracing::error!("Error: forward to '{}' failed: {}", link, e);
+                    failed.push(value);
+                }
+            }
+        }
+
+        tracing::trace!("forward is done, {} value failed", failed.len());
+        return failed;
+    }
+
+    /// Send request to the link with the specified method, it is retried according to the retry policy.
+    /// Return with the status code of the last response or with the error message if no response.
+    async fn send_with_retry(&self, target: &HookTarget, body: &serde_json::Value) -> Result<u16, String> {
+        let mut retry: u32 = 0;
+        let link = &target.link;
+        let method = target.method.clone().unwrap_or(Method::POST);
+
+        // Signature has to be calculated on the same bytes that are sent
+        let body = serde_json::to_vec(body).map_err(|e| e.to_string())?;
+        let signature = self
+            .signing_secret
+            .as_ref()
+            .map(|secret| utilities::sign_body(secret, &body));
+
+        loop {
+            tracing::trace!("send {} request to '{}' link", method, link);
+            let mut request = self
+                .client
+                .request(method.clone(), link)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.clone());
+            for (name, value) in &target.headers {
+                request = request.header(name, value);
+            }
+            if let Some(signature) = &signature {
+                request = request.header(SIGNATURE_HEADER, signature);
+            }
+
+            let result = match request.send().await {
+                Ok(resp) if resp.status().is_success() => {
+                    tracing::trace!("{:?}", resp);
+                    return Ok(resp.status().as_u16());
+                }
+                Ok(resp) => {
+                    tracing::error!("Error: HTTP request with hook but: {}", resp.status());
+                    Ok(resp.status().as_u16())
+                }
+                Err(e) if e.is_timeout() => {
+                    tracing::error!("Error: HTTP request with hook is timed out: {}", e);
+                    Err(e.to_string())
+                }
+                Err(e) => {
+                    tracing::error!("Error: HTTP request with hook but: {}", e);
+                    Err(e.to_string())
+                }
+            };
+
+            if retry >= self.retry_policy.max_retries {
+                return result;
+            }
+
+            let delay = self.retry_policy.delay(retry);
+            tracing::trace!("retry request to '{}' link after {:?}", link, delay);
+            tokio::time::sleep(delay).await;
+            retry += 1;
+        }
+    }
+
+    /// Pass a key and send request if key match with any defined prefix, POST is used if the hook has no method.
+    /// Failed deliveries are retried according to the retry policy, set `max_retries` to 0 for fire-and-forget.
+    /// Return with the number of attempted and succeeded (answered with 2xx) deliveries and with the failed links.
     /// 
     /// # Examples
     /// ```
@@ -142,10 +1058,10 @@ impl HookManager {
     /// let mut manager = HookManager::new();
     /// 
     /// // Normaly you have to specify address where the HTTP POST request can be sent
-    /// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string());
+    /// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None);
     /// assert_eq!(true, result.is_ok());
     /// 
-    /// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3032".to_string());
+    /// let result = manager.add("/root/status".to_string(), "http://127.0.0.1:3032".to_string(), None);
     /// assert_eq!(true, result.is_ok());
     /// 
     /// let rt = tokio::runtime::Builder::new_current_thread()
@@ -153,40 +1069,129 @@ impl HookManager {
     ///     .build()
     ///     .unwrap();
     /// rt.block_on(async move {
-    ///     let counter = manager.execute_hooks(&"/root/status/dns1".to_string(), &"okay".to_string()).await;
-    ///     assert_eq!(Some(2), counter);
+    ///     // Nothing listens on the links, so every delivery is failed
+    ///     let report = manager.execute_hooks(&"/root/status/dns1".to_string(), &"okay".to_string()).await;
+    ///     assert_eq!(2, report.attempted);
+    ///     assert_eq!(0, report.succeeded);
+    ///     assert_eq!("http://127.0.0.1:3031", report.failed[0].0);
     /// 
-    ///     let counter = manager.execute_hooks(&"/root/no_exist".to_string(), &"okay".to_string()).await;
-    ///     assert_eq!(None, counter);
+    ///     let report = manager.execute_hooks(&"/root/no_exist".to_string(), &"okay".to_string()).await;
+    ///     assert_eq!(0, report.attempted);
     /// });
     /// 
     /// ```
-    pub async fn execute_hooks(&self, key: &String, value: &String) -> Option<i32> {
-        let mut body = HashMap::new();
-        body.insert("key", key);
-        body.insert("value", value);
-        tracing::debug!("check hooks for {}", key);
+    pub async fn execute_hooks(&self, key: &String, value: &String) -> HookExecutionReport {
+        let deliveries = self.execute_hooks_with_report(key, value).await;
 
-        let mut counter = 0;
-
-        for (prefix, links) in &self.hooks {
-            if key.starts_with(prefix) {
-                for link in links {
-                    tracing::trace!("send POST request to '{}' link", link);
-                    counter += 1;
-                    match self.client.post(link).json(&body).send().await {
-                        Err(e) => tracing::error!("Error: HTTP request with hook but: {}", e),
-                        Ok(resp) => tracing::trace!("{:?}", resp),
-                    };
-                }
+        let mut report = HookExecutionReport {
+            attempted: deliveries.len(),
+            ..Default::default()
+        };
+        for (_, link, result) in deliveries {
+            match result {
+                Ok(status) if (200..300).contains(&status) => report.succeeded += 1,
+                Ok(status) => report.failed.push((link, format!("HTTP status {}", status))),
+                Err(e) => report.failed.push((link, e)),
             }
         }
 
-        tracing::trace!("sent {} request for '{}' key", counter, key);
+        return report;
+    }
+
+    /// Same as `execute_hooks` but return only with the number of succeeded deliveries, or `None` if no prefix matched
+    pub async fn execute_hooks_count(&self, key: &String, value: &String) -> Option<i32> {
+        let report = self.execute_hooks(key, value).await;
 
-        match counter {
+        match report.attempted {
             0 => return None,
-            i => return Some(i),
+            _ => return Some(report.succeeded as i32),
         }
     }
+
+    /// Pass a key and send request if key match with any defined prefix, POST is used if the hook has no method.
+    /// Requests of the matched links are sent in parallel.
+    /// Return with the matched prefixes and links with the HTTP status code or the error message.
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::hook::HookManager;
+    ///
+    /// let mut manager = HookManager::new();
+    /// manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None).unwrap();
+    ///
+    /// let rt = tokio::runtime::Builder::new_current_thread()
+    ///     .enable_all()
+    ///     .build()
+    ///     .unwrap();
+    /// rt.block_on(async move {
+    ///     let report = manager.execute_hooks_with_report(&"/root/status/dns1".to_string(), &"okay".to_string()).await;
+    ///     assert_eq!(1, report.len());
+    ///     assert_eq!("/root/status", report[0].0);
+    ///     assert_eq!("http://127.0.0.1:3031", report[0].1);
+    /// });
+    /// ```
+    pub async fn execute_hooks_with_report(
+        &self,
+        key: &String,
+        value: &String,
+    ) -> Vec<(Prefix, Link, Result<u16, String>)> {
+        return self.execute_event_with_report(key, Some(value), HookEvent::Set).await;
+    }
+
+    /// Pass a key with an event and send request to the hooks whose prefix match and subscribed for the event.
+    /// Body contains `event`, `timestamp` (RFC3339) and the matched hook `prefix` beside `key` and `value`,
+    /// the value is null for deletes.
+    /// Return with the same report like `execute_hooks_with_report`.
+    ///
+    /// # Examples
+    /// ```
+    /// use onlyati_datastore::hook::{HookManager, enums::HookEvent};
+    ///
+    /// let mut manager = HookManager::new();
+    /// manager.add("/root/status".to_string(), "http://127.0.0.1:3031".to_string(), None).unwrap();
+    ///
+    /// let rt = tokio::runtime::Builder::new_current_thread()
+    ///     .enable_all()
+    ///     .build()
+    ///     .unwrap();
+    /// rt.block_on(async move {
+    ///     // Hooks are executed only on set by default
+    ///     let report = manager.execute_event_with_report(&"/root/status/dns1".to_string(), None, HookEvent::Delete).await;
+    ///     assert_eq!(0, report.len());
+    /// });
+    /// ```
+    pub async fn execute_event_with_report(
+        &self,
+        key: &String,
+        value: Option<&String>,
+        event: HookEvent,
+    ) -> Vec<(Prefix, Link, Result<u16, String>)> {
+        let timestamp = chrono::Utc::now().to_rfc3339();
+        tracing::debug!("check hooks for {} on {}", key, event);
+
+        // Requests are sent in parallel, report keeps the order of prefixes and links
+        let deliveries = self
+            .hooks
+            .iter()
+            .filter(|(prefix, _)| self.matches(key, prefix))
+            .flat_map(|(prefix, links)| links.iter().map(move |target| (prefix, target)))
+            .filter(|(_, target)| target.events.contains(&event))
+            .map(|(prefix, target)| {
+                let body = serde_json::json!({
+                    "key": key,
+                    "value": value,
+                    "event": event.to_string(),
+                    "timestamp": timestamp,
+                    "prefix": prefix,
+                });
+                async move {
+                    let result = self.send_with_retry(target, &body).await;
+                    (prefix.clone(), target.link.clone(), result)
+                }
+            });
+        let report = futures::future::join_all(deliveries).await;
+
+        tracing::trace!("sent {} request for '{}' key", report.len(), key);
+        return report;
+    }
 }
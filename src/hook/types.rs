@@ -1,5 +1,8 @@
+use std::collections::BTreeMap;
+
 pub type Prefix = String;
 pub type Key = String;
 pub type Link = String;
 pub type Value = String;
 pub type Hooks = Vec<Link>;
+pub type Headers = BTreeMap<String, String>;
@@ -1,7 +1,15 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
 use std::thread::JoinHandle;
 
-use super::enums::{HookManagerAction, HookManagerResponse};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::config::Config;
+
+use super::enums::{HookEvent, HookManagerAction, HookManagerResponse};
+use super::types::{Key, Value};
 use super::HookManager;
 
 /// Start a HookManager on a single tokio thread
@@ -14,7 +22,7 @@ use super::HookManager;
 /// let (sender, _) = utilities::start_hook_manager();
 ///
 /// let (tx, rx) = utilities::get_channel();
-/// let action = HookManagerAction::Set(tx, "/root/stats".to_string(), "http://127.0.0.1:3031".to_string());
+/// let action = HookManagerAction::Set(tx, "/root/stats".to_string(), "http://127.0.0.1:3031".to_string(), None);
 ///
 /// sender.send(action).expect("Failed to send request");
 ///
@@ -23,20 +31,62 @@ use super::HookManager;
 ///
 /// ```
 pub fn start_hook_manager() -> (Sender<HookManagerAction>, JoinHandle<()>) {
+    return start_hook_manager_with_config(&Config::default());
+}
+
+/// Start a HookManager on a single tokio thread, based on configuration
+///
+/// # Examples
+/// ```
+/// use onlyati_datastore::config::Builder;
+/// use onlyati_datastore::hook::utilities;
+/// use onlyati_datastore::hook::enums::{HookManagerAction, HookManagerResponse};
+///
+/// let config = Builder::new().max_hooks_per_prefix(5).build();
+/// let (sender, _) = utilities::start_hook_manager_with_config(&config);
+///
+/// let (tx, rx) = utilities::get_channel();
+/// let action = HookManagerAction::Set(tx, "/root/stats".to_string(), "http://127.0.0.1:3031".to_string(), None);
+///
+/// sender.send(action).expect("Failed to send request");
+///
+/// let response = rx.recv().expect("Failed to receive");
+/// assert_eq!(HookManagerResponse::Ok, response);
+/// ```
+pub fn start_hook_manager_with_config(
+    config: &Config,
+) -> (Sender<HookManagerAction>, JoinHandle<()>) {
     let (tx, rx) = channel::<HookManagerAction>();
-    let mut manager = HookManager::new();
+    let mut manager = HookManager::from_config(config);
+    let persist_path = config.hook_persist_path().cloned();
+
+    if let Some(path) = &persist_path {
+        if path.exists() {
+            tracing::debug!("load hooks from '{}'", path.display());
+            if let Err(e) = manager.load(path) {
+                tracing::error!("failed to load persisted hooks, start without hooks: {:?}", e);
+            }
+        }
+    }
 
     let thread = std::thread::spawn(move || {
         let rt = tokio::runtime::Builder::new_multi_thread()
             .enable_all()
             .build()
             .expect("Failed to allocate runtime for HookManager");
+        let pending = Arc::new(AtomicUsize::new(0));
 
         loop {
             match rx.recv() {
                 Ok(request) => match request {
-                    HookManagerAction::Set(sender, prefix, target) => {
-                        match manager.add(prefix, target) {
+                    HookManagerAction::Set(sender, prefix, target, method) => {
+                        match manager.add(prefix, target, method) {
+                            Ok(_) => send_response!(sender, HookManagerResponse::Ok),
+                            Err(e) => send_response!(sender, e),
+                        }
+                    }
+                    HookManagerAction::SetWithHeaders(sender, prefix, target, method, headers) => {
+                        match manager.add_with_headers(prefix, target, method, headers) {
                             Ok(_) => send_response!(sender, HookManagerResponse::Ok),
                             Err(e) => send_response!(sender, e),
                         }
@@ -62,12 +112,62 @@ pub fn start_hook_manager() -> (Sender<HookManagerAction>, JoinHandle<()>) {
                             HookManagerResponse::HookList(manager.list(&prefix))
                         );
                     }
-                    HookManagerAction::Send(test_key, value) => {
+                    HookManagerAction::ListWithMethods(sender, prefix) => {
+                        send_response!(
+                            sender,
+                            HookManagerResponse::HookListWithMethods(manager.list_with_methods(&prefix))
+                        );
+                    }
+                    HookManagerAction::ListWithHeaders(sender, prefix, redact) => {
+                        send_response!(
+                            sender,
+                            HookManagerResponse::HookListWithHeaders(manager.list_with_headers(&prefix, redact))
+                        );
+                    }
+                    HookManagerAction::ListByLink(sender) => {
+                        send_response!(
+                            sender,
+                            HookManagerResponse::LinkList(manager.list_by_link())
+                        );
+                    }
+                    HookManagerAction::SetEvents(sender, prefix, target, events) => {
+                        match manager.set_events(prefix, target, events) {
+                            Ok(_) => send_response!(sender, HookManagerResponse::Ok),
+                            Err(e) => send_response!(sender, e),
+                        }
+                    }
+                    HookManagerAction::Send(sender, test_key, value) => {
+                        send_event(&rt, &manager, &pending, sender, test_key, Some(value), HookEvent::Set);
+                    }
+                    HookManagerAction::SendEvent(sender, test_key, value, event) => {
+                        send_event(&rt, &manager, &pending, sender, test_key, value, event);
+                    }
+                    HookManagerAction::Forward(sender, key, link, values) => {
                         let manager = manager.clone();
                         rt.spawn(async move {
-                            manager.execute_hooks(&test_key, &value).await;
+                            let failed = manager.forward(&link, &key, values).await;
+                            send_response!(sender, HookManagerResponse::Forwarded(failed));
                         });
                     }
+                    HookManagerAction::RemoveUnderPrefix(sender, prefix) => {
+                        manager.remove_under_prefix(&prefix);
+                        if let Some(sender) = sender {
+                            send_response!(sender, HookManagerResponse::Ok);
+                        }
+                    }
+                    HookManagerAction::Persist(sender) => {
+                        let result = match &persist_path {
+                            Some(path) => manager.save(path),
+                            None => Err(HookManagerResponse::Error(
+                                "Persist path is not configured".to_string(),
+                            )),
+                        };
+
+                        match result {
+                            Ok(_) => send_response!(sender, HookManagerResponse::Ok),
+                            Err(e) => send_response!(sender, e),
+                        }
+                    }
                 },
                 Err(e) => panic!("Hook manager failed: {}", e),
             }
@@ -77,6 +177,42 @@ pub fn start_hook_manager() -> (Sender<HookManagerAction>, JoinHandle<()>) {
     return (tx, thread);
 }
 
+/// Execute the hooks of an event on the runtime without waiting, if the limit of pending deliveries
+/// is reached the event is dropped
+fn send_event(
+    rt: &tokio::runtime::Runtime,
+    manager: &HookManager,
+    pending: &Arc<AtomicUsize>,
+    sender: Option<Sender<HookManagerResponse>>,
+    key: Key,
+    value: Option<Value>,
+    event: HookEvent,
+) {
+    if let Some(limit) = manager.max_pending() {
+        if pending.load(Ordering::SeqCst) >= limit {
+            tracing::error!("delivery of '{}' is dropped, {} delivery is in progress", key, limit);
+            if let Some(sender) = sender {
+                send_response!(
+                    sender,
+                    HookManagerResponse::Error("Too many pending deliveries".to_string())
+                );
+            }
+            return;
+        }
+    }
+
+    pending.fetch_add(1, Ordering::SeqCst);
+    let pending = pending.clone();
+    let manager = manager.clone();
+    rt.spawn(async move {
+        let report = manager.execute_event_with_report(&key, value.as_ref(), event).await;
+        pending.fetch_sub(1, Ordering::SeqCst);
+        if let Some(sender) = sender {
+            send_response!(sender, HookManagerResponse::Delivered(report));
+        }
+    });
+}
+
 /// Get channel for HookManager response
 pub fn get_channel() -> (Sender<HookManagerResponse>, Receiver<HookManagerResponse>) {
     return channel::<HookManagerResponse>();
@@ -89,4 +225,21 @@ macro_rules! send_response {
             .unwrap_or_else(|e| tracing::error!("Error during send: {}", e))
     };
 }
-pub(self) use send_response;
+pub(crate) use send_response;
+
+/// Calculate the value of `X-Datastore-Signature` header: HMAC-SHA256 of the body with the secret,
+/// in `sha256=<hex digest>` format
+///
+/// # Examples
+/// ```
+/// use onlyati_datastore::hook::utilities;
+///
+/// let signature = utilities::sign_body("secret", b"{}");
+/// assert_eq!(true, signature.starts_with("sha256="));
+/// assert_eq!(7 + 64, signature.len());
+/// ```
+pub fn sign_body(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts key of any size");
+    mac.update(body);
+    return format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+}
@@ -92,7 +92,9 @@
 //! assert_eq!(true, full_list.len() == 4);
 //! ```
 #![allow(dead_code)]
+#![allow(clippy::needless_return)]
 
+pub mod config;
 pub mod datastore;
 pub mod hook;
 pub mod logger;
@@ -1,44 +1,423 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::mpsc::Sender;
+use std::time::Duration;
 
 /// Item for every action in datastore
 #[derive(Clone, Debug)]
 pub enum LogItem {
     SetKey(String, String),
+    /// Numeric record is set, replay restores it as number
+    SetInt(String, i64),
+
+    /// Binary record is set, its value is written as base64
+    SetBytes(String, Vec<u8>),
     GetKey(String),
     RemKey(String),
     RemPath(String),
+    CreatePath(String),
+    /// Everything is removed whose path begins with the prefix
+    RemPrefix(String),
+    /// Record is moved to a new key
+    RenameKey(String, String),
+
+    /// Queue is moved to a new key
+    RenameQueue(String, String),
+
+    /// Table is moved with its subtree to a new key
+    RenamePath(String, String),
     ListKeys(String),
     Trigger(String, String),
     SetHook(String, String),
+    /// Hook is set with the HTTP method that its requests are sent with
+    SetHookWithMethod(String, String, String),
+    /// Hook is set with HTTP method and static headers, header values are written without redaction
+    SetHookWithHeaders(String, String, String, BTreeMap<String, String>),
+    /// Events of an existing hook are set, e.g. `set` and `delete`
+    SetHookEvents(String, String, Vec<String>),
     GetHook(String),
     RemHook(String, String),
+    /// Every hook is removed whose prefix is under the deleted table
+    RemHooksUnder(String),
     ListHooks(String),
+    ListHooksByLink,
     HookExecute(String, Vec<String>),
-    Push(String, String),
-    Pop(String),
+    /// Value is pushed to the end of queue
+    QueuePush(String, String),
+
+    /// Value is popped from the front of queue
+    QueuePop(String),
+
+    /// Specified number of values are removed from the front of queue
+    QueueDrain(String, usize),
+
+    /// Every table, record and queue is removed
+    ClearAll,
 }
 
 impl std::fmt::Display for LogItem {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let text = match self {
-            Self::SetKey(key, value) => format!("SetKey [ '{}', '{}' ]", key, value),
-            Self::GetKey(key) => format!("GetKey [ '{}' ]", key),
-            Self::RemKey(key) => format!("RemKey [ '{}' ]", key),
-            Self::RemPath(key) => format!("RemPath [ '{}' ]", key),
-            Self::ListKeys(key) => format!("ListKeys [ '{}' ]", key),
-            Self::Trigger(key, value) => format!("Trigger [ '{}', '{}' ]", key, value),
-            Self::SetHook(prefix, link) => format!("SetHook [ '{}', '{}' ]", prefix, link),
-            Self::GetHook(prefix) => format!("GetHook [ '{}' ]", prefix),
-            Self::RemHook(prefix, link) => format!("RemHook [ '{}', '{}' ]", prefix, link),
-            Self::ListHooks(prefix) => format!("ListHooks [ '{}' ]", prefix),
-            Self::HookExecute(prefix, links) => format!("HookExecute [ '{}', '{:?}' ]", prefix, links),
-            Self::Push(key, value) => format!("Push [ '{}', '{}' ]", key, value),
-            Self::Pop(key) => format!("Pop [ '{}' ]", key),
+            Self::SetKey(key, value) => format!("SetKey [ '{}', '{}' ]", escape(key), escape(value)),
+            Self::SetInt(key, value) => format!("SetInt [ '{}', '{}' ]", escape(key), value),
+            Self::SetBytes(key, value) => format!("SetBytes [ '{}', '{}' ]", escape(key), STANDARD.encode(value)),
+            Self::GetKey(key) => format!("GetKey [ '{}' ]", escape(key)),
+            Self::RemKey(key) => format!("RemKey [ '{}' ]", escape(key)),
+            Self::RemPath(key) => format!("RemPath [ '{}' ]", escape(key)),
+            Self::RemPrefix(key) => format!("RemPrefix [ '{}' ]", escape(key)),
+            Self::CreatePath(key) => format!("CreatePath [ '{}' ]", escape(key)),
+            Self::RenameKey(from, to) => format!("RenameKey [ '{}', '{}' ]", escape(from), escape(to)),
+            Self::RenameQueue(from, to) => format!("RenameQueue [ '{}', '{}' ]", escape(from), escape(to)),
+            Self::RenamePath(from, to) => format!("RenamePath [ '{}', '{}' ]", escape(from), escape(to)),
+            Self::ListKeys(key) => format!("ListKeys [ '{}' ]", escape(key)),
+            Self::Trigger(key, value) => format!("Trigger [ '{}', '{}' ]", escape(key), escape(value)),
+            Self::SetHook(prefix, link) => format!("SetHook [ '{}', '{}' ]", escape(prefix), escape(link)),
+            Self::SetHookWithMethod(prefix, link, method) => {
+                format!("SetHookWithMethod [ '{}', '{}', '{}' ]", escape(prefix), escape(link), escape(method))
+            }
+            Self::SetHookWithHeaders(prefix, link, method, headers) => {
+                let mut args = vec![escape(prefix), escape(link), escape(method)];
+                args.extend(headers.iter().map(|(name, value)| escape(&format!("{}: {}", name, value))));
+                format!("SetHookWithHeaders [ '{}' ]", args.join("', '"))
+            }
+            Self::SetHookEvents(prefix, link, events) => {
+                let mut args = vec![escape(prefix), escape(link)];
+                args.extend(events.iter().map(|x| escape(x)));
+                format!("SetHookEvents [ '{}' ]", args.join("', '"))
+            }
+            Self::GetHook(prefix) => format!("GetHook [ '{}' ]", escape(prefix)),
+            Self::RemHook(prefix, link) => format!("RemHook [ '{}', '{}' ]", escape(prefix), escape(link)),
+            Self::RemHooksUnder(prefix) => format!("RemHooksUnder [ '{}' ]", escape(prefix)),
+            Self::ListHooks(prefix) => format!("ListHooks [ '{}' ]", escape(prefix)),
+            Self::ListHooksByLink => "ListHooksByLink".to_string(),
+            Self::HookExecute(prefix, links) => {
+                format!("HookExecute [ '{}', '{}' ]", escape(prefix), escape(&format!("{:?}", links)))
+            }
+            Self::QueuePush(key, value) => format!("QueuePush [ '{}', '{}' ]", escape(key), escape(value)),
+            Self::QueuePop(key) => format!("QueuePop [ '{}' ]", escape(key)),
+            Self::QueueDrain(key, count) => format!("QueueDrain [ '{}', '{}' ]", escape(key), count),
+            Self::ClearAll => "ClearAll".to_string(),
         };
         return write!(f, "{}", text);
     }
 }
 
+impl LogItem {
+    /// Name of the action, it is the same that is written in text format
+    pub fn action(&self) -> &'static str {
+        match self {
+            Self::SetKey(..) => return "SetKey",
+            Self::SetInt(..) => return "SetInt",
+            Self::SetBytes(..) => return "SetBytes",
+            Self::GetKey(..) => return "GetKey",
+            Self::RemKey(..) => return "RemKey",
+            Self::RemPath(..) => return "RemPath",
+            Self::RemPrefix(..) => return "RemPrefix",
+            Self::CreatePath(..) => return "CreatePath",
+            Self::RenameKey(..) => return "RenameKey",
+            Self::RenameQueue(..) => return "RenameQueue",
+            Self::RenamePath(..) => return "RenamePath",
+            Self::ListKeys(..) => return "ListKeys",
+            Self::Trigger(..) => return "Trigger",
+            Self::SetHook(..) => return "SetHook",
+            Self::SetHookWithMethod(..) => return "SetHookWithMethod",
+            Self::SetHookWithHeaders(..) => return "SetHookWithHeaders",
+            Self::SetHookEvents(..) => return "SetHookEvents",
+            Self::GetHook(..) => return "GetHook",
+            Self::RemHook(..) => return "RemHook",
+            Self::RemHooksUnder(..) => return "RemHooksUnder",
+            Self::ListHooks(..) => return "ListHooks",
+            Self::ListHooksByLink => return "ListHooksByLink",
+            Self::HookExecute(..) => return "HookExecute",
+            Self::QueuePush(..) => return "QueuePush",
+            Self::QueuePop(..) => return "QueuePop",
+            Self::QueueDrain(..) => return "QueueDrain",
+            Self::ClearAll => return "ClearAll",
+        }
+    }
+
+    /// Action specific fields by name, e.g. `key` and `value` of `SetKey` or `prefix` and `link` of `SetHook`
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::logger::enums::LogItem;
+    ///
+    /// let item = LogItem::SetHook("/root/status".to_string(), "http://127.0.0.1:3031".to_string());
+    /// let fields = item.fields();
+    ///
+    /// assert_eq!("SetHook", item.action());
+    /// assert_eq!(Some(&serde_json::json!("/root/status")), fields.get("prefix"));
+    /// assert_eq!(Some(&serde_json::json!("http://127.0.0.1:3031")), fields.get("link"));
+    /// ```
+    pub fn fields(&self) -> serde_json::Map<String, serde_json::Value> {
+        let fields = match self {
+            Self::SetKey(key, value) | Self::Trigger(key, value) | Self::QueuePush(key, value) => {
+                serde_json::json!({ "key": key, "value": value })
+            }
+            Self::SetInt(key, value) => serde_json::json!({ "key": key, "value": value }),
+            Self::SetBytes(key, value) => serde_json::json!({ "key": key, "value": STANDARD.encode(value) }),
+            Self::GetKey(key)
+            | Self::RemKey(key)
+            | Self::RemPath(key)
+            | Self::RemPrefix(key)
+            | Self::CreatePath(key)
+            | Self::ListKeys(key)
+            | Self::QueuePop(key) => serde_json::json!({ "key": key }),
+            Self::RenameKey(from, to) | Self::RenameQueue(from, to) | Self::RenamePath(from, to) => {
+                serde_json::json!({ "key": from, "to": to })
+            }
+            Self::SetHook(prefix, link) | Self::RemHook(prefix, link) => {
+                serde_json::json!({ "prefix": prefix, "link": link })
+            }
+            Self::SetHookWithMethod(prefix, link, method) => {
+                serde_json::json!({ "prefix": prefix, "link": link, "method": method })
+            }
+            Self::SetHookWithHeaders(prefix, link, method, headers) => {
+                serde_json::json!({ "prefix": prefix, "link": link, "method": method, "headers": headers })
+            }
+            Self::SetHookEvents(prefix, link, events) => {
+                serde_json::json!({ "prefix": prefix, "link": link, "events": events })
+            }
+            Self::GetHook(prefix) | Self::RemHooksUnder(prefix) | Self::ListHooks(prefix) => {
+                serde_json::json!({ "prefix": prefix })
+            }
+            Self::ListHooksByLink | Self::ClearAll => serde_json::json!({}),
+            Self::HookExecute(prefix, links) => serde_json::json!({ "prefix": prefix, "links": links }),
+            Self::QueueDrain(key, count) => serde_json::json!({ "key": key, "count": count }),
+        };
+
+        match fields {
+            serde_json::Value::Object(map) => return map,
+            _ => return serde_json::Map::new(),
+        }
+    }
+
+    /// Build item from a line that is written in `LogFormat::Json`
+    fn from_json(line: &str) -> Result<Self, String> {
+        let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(line)
+            .map_err(|e| format!("Invalid JSON log line: {}", e))?;
+
+        let text = |name: &str| -> Result<String, String> {
+            return object
+                .get(name)
+                .and_then(|x| x.as_str())
+                .map(|x| x.to_string())
+                .ok_or(format!("Missing '{}' field in log line: {}", name, line));
+        };
+
+        let item = match text("action")?.as_str() {
+            "SetKey" => Self::SetKey(text("key")?, text("value")?),
+            "SetInt" => {
+                let value = object
+                    .get("value")
+                    .and_then(|x| x.as_i64())
+                    .ok_or(format!("Missing 'value' field in log line: {}", line))?;
+                Self::SetInt(text("key")?, value)
+            }
+            "SetBytes" => Self::SetBytes(text("key")?, decode_bytes(&text("value")?, line)?),
+            "GetKey" => Self::GetKey(text("key")?),
+            "RemKey" => Self::RemKey(text("key")?),
+            "RemPath" => Self::RemPath(text("key")?),
+            "RemPrefix" => Self::RemPrefix(text("key")?),
+            "CreatePath" => Self::CreatePath(text("key")?),
+            "RenameKey" => Self::RenameKey(text("key")?, text("to")?),
+            "RenameQueue" => Self::RenameQueue(text("key")?, text("to")?),
+            "RenamePath" => Self::RenamePath(text("key")?, text("to")?),
+            "ListKeys" => Self::ListKeys(text("key")?),
+            "Trigger" => Self::Trigger(text("key")?, text("value")?),
+            "SetHook" => Self::SetHook(text("prefix")?, text("link")?),
+            "SetHookWithMethod" => Self::SetHookWithMethod(text("prefix")?, text("link")?, text("method")?),
+            "SetHookWithHeaders" => {
+                let headers = object
+                    .get("headers")
+                    .and_then(|x| x.as_object())
+                    .ok_or(format!("Missing 'headers' field in log line: {}", line))?
+                    .iter()
+                    .map(|(name, value)| value.as_str().map(|value| (name.clone(), value.to_string())))
+                    .collect::<Option<BTreeMap<String, String>>>()
+                    .ok_or(format!("Invalid header in log line: {}", line))?;
+                Self::SetHookWithHeaders(text("prefix")?, text("link")?, text("method")?, headers)
+            }
+            "SetHookEvents" => {
+                let events = object
+                    .get("events")
+                    .and_then(|x| x.as_array())
+                    .ok_or(format!("Missing 'events' field in log line: {}", line))?
+                    .iter()
+                    .map(|x| x.as_str().map(|x| x.to_string()))
+                    .collect::<Option<Vec<String>>>()
+                    .ok_or(format!("Invalid event in log line: {}", line))?;
+                Self::SetHookEvents(text("prefix")?, text("link")?, events)
+            }
+            "GetHook" => Self::GetHook(text("prefix")?),
+            "RemHook" => Self::RemHook(text("prefix")?, text("link")?),
+            "RemHooksUnder" => Self::RemHooksUnder(text("prefix")?),
+            "ListHooks" => Self::ListHooks(text("prefix")?),
+            "ListHooksByLink" => Self::ListHooksByLink,
+            "HookExecute" => {
+                let links = object
+                    .get("links")
+                    .and_then(|x| x.as_array())
+                    .map(|x| x.iter().filter_map(|x| x.as_str().map(|x| x.to_string())).collect())
+                    .unwrap_or_default();
+                Self::HookExecute(text("prefix")?, links)
+            }
+            "QueuePush" => Self::QueuePush(text("key")?, text("value")?),
+            "QueuePop" => Self::QueuePop(text("key")?),
+            "QueueDrain" => {
+                let count = object
+                    .get("count")
+                    .and_then(|x| x.as_u64())
+                    .ok_or(format!("Missing 'count' field in log line: {}", line))?;
+                Self::QueueDrain(text("key")?, count as usize)
+            }
+            "ClearAll" => Self::ClearAll,
+            _ => return Err(format!("Unknown item in log line: {}", line)),
+        };
+
+        return Ok(item);
+    }
+}
+
+impl std::str::FromStr for LogItem {
+    type Err = String;
+
+    /// Parse a log line that is written by the logger, the leading timestamp is skipped.
+    /// Both `LogFormat::Text` and `LogFormat::Json` lines are accepted.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim_end();
+        if line.starts_with('{') {
+            return Self::from_json(line);
+        }
+
+        // Item name is the word before the first argument list, or the last word if there is no argument
+        let (name, args) = match line.find(" [ ") {
+            Some(index) => {
+                let name_start = line[..index].rfind(' ').map(|i| i + 1).unwrap_or(0);
+                (&line[name_start..index], Some(&line[index + 1..]))
+            }
+            None => (line.rsplit(' ').next().unwrap_or(line), None),
+        };
+
+        let args: Vec<String> = match args {
+            Some(args) => unescape_args(args).ok_or(format!("Invalid arguments in log line: {}", line))?,
+            None => Vec::new(),
+        };
+
+        let arg = |index: usize| -> Result<String, String> {
+            return args
+                .get(index)
+                .cloned()
+                .ok_or(format!("Missing argument in log line: {}", line));
+        };
+
+        let item = match name {
+            "SetKey" => Self::SetKey(arg(0)?, arg(1)?),
+            "SetInt" => {
+                let value = arg(1)?
+                    .parse::<i64>()
+                    .map_err(|_| format!("Invalid number in log line: {}", line))?;
+                Self::SetInt(arg(0)?, value)
+            }
+            "SetBytes" => Self::SetBytes(arg(0)?, decode_bytes(&arg(1)?, line)?),
+            "GetKey" => Self::GetKey(arg(0)?),
+            "RemKey" => Self::RemKey(arg(0)?),
+            "RemPath" => Self::RemPath(arg(0)?),
+            "RemPrefix" => Self::RemPrefix(arg(0)?),
+            "CreatePath" => Self::CreatePath(arg(0)?),
+            "RenameKey" => Self::RenameKey(arg(0)?, arg(1)?),
+            "RenameQueue" => Self::RenameQueue(arg(0)?, arg(1)?),
+            "RenamePath" => Self::RenamePath(arg(0)?, arg(1)?),
+            "ListKeys" => Self::ListKeys(arg(0)?),
+            "Trigger" => Self::Trigger(arg(0)?, arg(1)?),
+            "SetHook" => Self::SetHook(arg(0)?, arg(1)?),
+            "SetHookWithMethod" => Self::SetHookWithMethod(arg(0)?, arg(1)?, arg(2)?),
+            // Headers are written as `name: value` arguments after the method
+            "SetHookWithHeaders" => {
+                let headers = args
+                    .iter()
+                    .skip(3)
+                    .map(|x| x.split_once(": ").map(|(name, value)| (name.to_string(), value.to_string())))
+                    .collect::<Option<BTreeMap<String, String>>>()
+                    .ok_or(format!("Invalid header in log line: {}", line))?;
+                Self::SetHookWithHeaders(arg(0)?, arg(1)?, arg(2)?, headers)
+            }
+            // Events are written as arguments after the link
+            "SetHookEvents" => Self::SetHookEvents(arg(0)?, arg(1)?, args.iter().skip(2).cloned().collect()),
+            "GetHook" => Self::GetHook(arg(0)?),
+            "RemHook" => Self::RemHook(arg(0)?, arg(1)?),
+            "RemHooksUnder" => Self::RemHooksUnder(arg(0)?),
+            "ListHooks" => Self::ListHooks(arg(0)?),
+            "ListHooksByLink" => Self::ListHooksByLink,
+            // Links are written in debug format, they are not restored
+            "HookExecute" => Self::HookExecute(arg(0)?, Vec::new()),
+            "QueuePush" => Self::QueuePush(arg(0)?, arg(1)?),
+            "QueuePop" => Self::QueuePop(arg(0)?),
+            "QueueDrain" => {
+                let count = arg(1)?
+                    .parse::<usize>()
+                    .map_err(|e| format!("Invalid count in log line: {}", e))?;
+                Self::QueueDrain(arg(0)?, count)
+            }
+            "ClearAll" => Self::ClearAll,
+            _ => return Err(format!("Unknown item in log line: {}", line)),
+        };
+
+        return Ok(item);
+    }
+}
+
+/// Decode the base64 value of `SetBytes` item
+fn decode_bytes(value: &str, line: &str) -> Result<Vec<u8>, String> {
+    return STANDARD
+        .decode(value)
+        .map_err(|e| format!("Invalid base64 value in log line: {}: {}", e, line));
+}
+
+/// Escape an argument of `LogFormat::Text` line, so it can be written between apostrophes in one line
+fn escape(arg: &str) -> String {
+    let mut escaped = String::with_capacity(arg.len());
+    for c in arg.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    return escaped;
+}
+
+/// Parse the `[ 'arg1', 'arg2' ]` argument list of a `LogFormat::Text` line and unescape the arguments
+fn unescape_args(args: &str) -> Option<Vec<String>> {
+    let mut rest = args.strip_prefix("[ ")?;
+    let mut result = Vec::new();
+
+    loop {
+        let mut chars = rest.strip_prefix('\'')?.char_indices();
+        let mut arg = String::new();
+        let end = loop {
+            match chars.next()? {
+                (_, '\\') => match chars.next()?.1 {
+                    'n' => arg.push('\n'),
+                    'r' => arg.push('\r'),
+                    c => arg.push(c),
+                },
+                (index, '\'') => break index + 2,
+                (_, c) => arg.push(c),
+            }
+        };
+        result.push(arg);
+
+        rest = &rest[end..];
+        if rest == " ]" {
+            return Some(result);
+        }
+        rest = rest.strip_prefix(", ")?;
+    }
+}
+
 /// Represent state of logger
 #[derive(PartialEq)]
 pub enum LogState {
@@ -52,6 +431,90 @@ pub enum LogState {
     Suspended,
 }
 
+/// How the log file is rotated
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LogRotation {
+    /// Every line is written into the same file
+    #[default]
+    None,
+
+    /// File is renamed to `path.N` when it would exceed the size in bytes, then a new file is opened
+    Size(u64),
+
+    /// Lines are written into `path.YYYY-MM-DD` file according to the current UTC date
+    Daily,
+}
+
+/// Format of the lines in log file
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LogFormat {
+    /// `{timestamp} {action} [ '{argument}', ... ]` lines, backslash, apostrophe and line breaks of the
+    /// arguments are escaped by backslash (`\\`, `\'`, `\n`, `\r`)
+    #[default]
+    Text,
+
+    /// One JSON object per line with `timestamp`, `action` and the fields of `LogItem::fields`
+    Json,
+}
+
+/// When the written lines are flushed from memory buffer into the log file.
+/// File is always flushed when it is stopped, suspended or rotated.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FlushPolicy {
+    /// Lines are flushed only when the file is stopped, suspended or rotated
+    #[default]
+    Buffered,
+
+    /// Every line is flushed immediately
+    EveryWrite,
+
+    /// File is flushed after every N lines
+    EveryN(usize),
+
+    /// File is flushed by the first write after the interval elapsed since the last flush
+    Interval(Duration),
+}
+
+/// Selects which items are written, items are identified by `LogItem::action` (e.g. `SetKey`)
+///
+/// # Example
+/// ```
+/// use onlyati_datastore::logger::enums::{LogFilter, LogItem};
+///
+/// let filter = LogFilter::mutations();
+/// assert_eq!(true, filter.allows(&LogItem::SetKey("/root/status".to_string(), "ok".to_string())));
+/// assert_eq!(false, filter.allows(&LogItem::GetKey("/root/status".to_string())));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum LogFilter {
+    /// Every item is written
+    #[default]
+    All,
+
+    /// Only the listed actions are written
+    Allow(BTreeSet<String>),
+
+    /// Every action is written except the listed ones
+    Deny(BTreeSet<String>),
+}
+
+impl LogFilter {
+    /// Filter that skips the reads (`GetKey`, `ListKeys`, `GetHook`, `ListHooks` and `ListHooksByLink`)
+    pub fn mutations() -> Self {
+        let reads = ["GetKey", "ListKeys", "GetHook", "ListHooks", "ListHooksByLink"];
+        return LogFilter::Deny(reads.iter().map(|x| x.to_string()).collect());
+    }
+
+    /// Tells that the item has to be written
+    pub fn allows(&self, item: &LogItem) -> bool {
+        match self {
+            Self::All => return true,
+            Self::Allow(actions) => return actions.contains(item.action()),
+            Self::Deny(actions) => return !actions.contains(item.action()),
+        }
+    }
+}
+
 /// Types that can be sent back by using the `start_logger` utility
 #[derive(PartialEq, Debug)]
 pub enum LoggerResponse {
@@ -73,6 +536,9 @@ pub enum LoggerAction {
     /// Write request
     Write(Sender<LoggerResponse>, Vec<LogItem>),
     WriteAsync(Vec<LogItem>),
+
+    /// Write the buffered messages, close the log file and stop the logger thread
+    Stop(Sender<LoggerResponse>),
 }
 
 impl std::fmt::Display for LoggerAction {
@@ -82,6 +548,7 @@ impl std::fmt::Display for LoggerAction {
             Self::Suspend(_) => "Suspend".to_string(),
             Self::Write(_, item) => format!("Write [ '{:?}' ]", item),
             Self::WriteAsync(item) => format!("Write [ '{:?}' ]", item),
+            Self::Stop(_) => "Stop".to_string(),
         };
         return write!(f, "{}", text);
     }
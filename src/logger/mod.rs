@@ -1,15 +1,20 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use flate2::{write::GzEncoder, Compression};
 use std::{
     fs::File,
     io::{BufWriter, Write},
     path::Path,
+    time::Instant,
 };
 
-use self::enums::{LogItem, LogState};
+use self::enums::{FlushPolicy, LogFilter, LogFormat, LogItem, LogRotation, LogState};
 
 pub mod enums;
 pub mod utilities;
 
+/// Default format of timestamps, RFC3339 (e.g. `2023-05-20T10:00:00.123456789+00:00`)
+pub const DEFAULT_TIMESTAMP_FORMAT: &str = "%+";
+
 /// Logger manager main structure
 /// 
 /// There are 3 state fo logger:
@@ -43,6 +48,36 @@ pub struct LoggerManager {
     pub(crate) state: LogState,
     file: Option<BufWriter<File>>,
     buffer: Vec<(DateTime<Utc>, LogItem)>,
+
+    /// How the log file is rotated
+    rotation: LogRotation,
+
+    /// Size of the current log file, `None` until it is read during the first start
+    file_size: Option<u64>,
+
+    /// Date of the open file in daily rotation
+    file_date: Option<NaiveDate>,
+
+    /// Format of the written lines
+    format: LogFormat,
+
+    /// Items that are written, others are dropped
+    filter: LogFilter,
+
+    /// When the lines are flushed into the file
+    flush_policy: FlushPolicy,
+
+    /// Number of lines written since the last flush
+    unflushed: usize,
+
+    /// Time of the last flush
+    last_flush: Instant,
+
+    /// Compress the files that are rotated out
+    compress: bool,
+
+    /// `chrono` format of the timestamps in the lines
+    timestamp_format: String,
 }
 
 impl LoggerManager {
@@ -54,24 +89,280 @@ impl LoggerManager {
             state: LogState::Close,
             file: None,
             buffer: Vec::new(),
+            rotation: LogRotation::None,
+            file_size: None,
+            file_date: None,
+            format: LogFormat::Text,
+            filter: LogFilter::All,
+            flush_policy: FlushPolicy::Buffered,
+            unflushed: 0,
+            last_flush: Instant::now(),
+            compress: false,
+            timestamp_format: DEFAULT_TIMESTAMP_FORMAT.to_string(),
         };
     }
 
+    /// Allocate new logger with the specified rotation mode, see `LogRotation`
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::logger::{LoggerManager, enums::{LogItem, LogRotation}};
+    ///
+    /// let path = "/tmp/datastore-log-daily-doc".to_string();
+    ///
+    /// let mut logger = LoggerManager::with_rotation(path.clone(), LogRotation::Daily);
+    /// logger.start().expect("Failed to start logger");
+    /// logger.write(LogItem::SetKey("/root/status".to_string(), "ok".to_string())).expect("Failed to write");
+    /// logger.stop().expect("Failed to stop logger");
+    ///
+    /// let today = chrono::Utc::now().format("%Y-%m-%d");
+    /// assert_eq!(true, std::path::Path::new(&format!("{}.{}", path, today)).exists());
+    /// ```
+    pub fn with_rotation(path: String, rotation: LogRotation) -> Self {
+        let mut logger = LoggerManager::new(path);
+        logger.rotation = rotation;
+        return logger;
+    }
+
+    /// Allocate new logger whose file is rotated when it would exceed the size.
+    /// The current file is renamed to `path.N`, where N is the first free number from 1, then a new file is opened.
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::logger::{LoggerManager, enums::LogItem};
+    ///
+    /// let path = "/tmp/datastore-log-rotate-doc".to_string();
+    /// let _ = std::fs::remove_file(&path);
+    /// let _ = std::fs::remove_file(format!("{}.1", path));
+    ///
+    /// let mut logger = LoggerManager::with_max_file_size(path.clone(), 64);
+    /// logger.start().expect("Failed to start logger");
+    /// logger.write(LogItem::SetKey("/root/status".to_string(), "ok".to_string())).expect("Failed to write");
+    /// logger.write(LogItem::SetKey("/root/status".to_string(), "ok".to_string())).expect("Failed to write");
+    /// logger.stop().expect("Failed to stop logger");
+    ///
+    /// assert_eq!(true, std::path::Path::new(&format!("{}.1", path)).exists());
+    /// ```
+    pub fn with_max_file_size(path: String, max_file_size: u64) -> Self {
+        return LoggerManager::with_rotation(path, LogRotation::Size(max_file_size));
+    }
+
+    /// Set the format of the further written lines, default is `LogFormat::Text`
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::logger::{LoggerManager, enums::{LogFormat, LogItem}};
+    ///
+    /// let path = "/tmp/datastore-log-json-doc".to_string();
+    /// let _ = std::fs::remove_file(&path);
+    ///
+    /// let mut logger = LoggerManager::new(path.clone());
+    /// logger.set_format(LogFormat::Json);
+    /// logger.start().expect("Failed to start logger");
+    /// logger.write(LogItem::SetKey("/root/status".to_string(), "ok".to_string())).expect("Failed to write");
+    /// logger.stop().expect("Failed to stop logger");
+    ///
+    /// let content = std::fs::read_to_string(&path).unwrap();
+    /// let line: serde_json::Value = serde_json::from_str(content.trim_end()).unwrap();
+    /// assert_eq!("SetKey", line["action"]);
+    /// assert_eq!("/root/status", line["key"]);
+    /// ```
+    pub fn set_format(&mut self, format: LogFormat) {
+        tracing::trace!("log format is set to {:?}", format);
+        self.format = format;
+    }
+
+    /// Set which items are written, the others are dropped by `write` without error. Default is `LogFilter::All`.
+    pub fn set_filter(&mut self, filter: LogFilter) {
+        tracing::trace!("log filter is set to {:?}", filter);
+        self.filter = filter;
+    }
+
+    /// Set that the files rotated out are compressed into `.gz` files on a background thread,
+    /// e.g. `path.1` becomes `path.1.gz`. Default is false.
+    pub fn set_compression(&mut self, enabled: bool) {
+        tracing::trace!("compression of rotated log files is set to {}", enabled);
+        self.compress = enabled;
+    }
+
+    /// Set when the written lines are flushed into the file, default is `FlushPolicy::Buffered`
+    pub fn set_flush_policy(&mut self, policy: FlushPolicy) {
+        tracing::trace!("log flush policy is set to {:?}", policy);
+        self.flush_policy = policy;
+    }
+
+    /// Write the buffered lines into the log file, it does nothing if the file is not open
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::logger::{LoggerManager, enums::LogItem};
+    ///
+    /// let path = "/tmp/datastore-log-flush-doc".to_string();
+    /// let _ = std::fs::remove_file(&path);
+    ///
+    /// let mut logger = LoggerManager::new(path.clone());
+    /// logger.start().expect("Failed to start logger");
+    /// logger.write(LogItem::SetKey("/root/status".to_string(), "ok".to_string())).expect("Failed to write");
+    /// logger.flush().expect("Failed to flush");
+    ///
+    /// assert_eq!(1, LoggerManager::replay(&path).unwrap().len());
+    /// ```
+    pub fn flush(&mut self) -> Result<(), String> {
+        if let Some(file) = &mut self.file {
+            if let Err(e) = file.flush() {
+                tracing::error!("failed to flush log file: {}", e);
+                return Err(format!("Failed to flush log file: {}", e));
+            }
+            tracing::trace!("flushed {} lines", self.unflushed);
+        }
+
+        self.unflushed = 0;
+        self.last_flush = Instant::now();
+        return Ok(());
+    }
+
+    /// Set the `chrono` format of the timestamps (see `chrono::format::strftime`), default is RFC3339.
+    /// Formats whose timestamps contain space or log delimiter (`[`, `]`, `'`, `{`) are rejected,
+    /// else the lines cannot be parsed back.
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::logger::LoggerManager;
+    ///
+    /// let mut logger = LoggerManager::new("/tmp/datastore-log-format-doc".to_string());
+    /// assert_eq!(true, logger.set_timestamp_format("%Y-%m-%dT%H:%M:%S%.3fZ".to_string()).is_ok());
+    /// assert_eq!(true, logger.set_timestamp_format("%Y-%Q".to_string()).is_err());
+    /// assert_eq!(true, logger.set_timestamp_format("%Y-%m-%d %H:%M:%S".to_string()).is_err());
+    /// ```
+    pub fn set_timestamp_format(&mut self, format: String) -> Result<(), String> {
+        Self::check_timestamp_format(&format)?;
+
+        tracing::trace!("log timestamp format is set to '{}'", format);
+        self.timestamp_format = format;
+        return Ok(());
+    }
+
+    /// Check that the timestamp format is valid and its output does not contain space or log delimiter
+    pub(crate) fn check_timestamp_format(format: &str) -> Result<(), String> {
+        let invalid = chrono::format::StrftimeItems::new(format).any(|x| x == chrono::format::Item::Error);
+        if invalid {
+            return Err(format!("Invalid timestamp format: {}", format));
+        }
+
+        // Padding and some specifiers (e.g. `%e`, `%c`) write spaces, so the output is checked
+        let sample = Utc::now().format(format).to_string();
+        let delimited = sample.starts_with('{')
+            || sample.chars().any(|c| c.is_whitespace() || c == '[' || c == ']' || c == '\'');
+        if delimited {
+            return Err(format!("Timestamp format writes space or delimiter: {}", format));
+        }
+
+        return Ok(());
+    }
+
+    /// Format a line according to the log format, with line break
+    fn format_line(&self, time: &DateTime<Utc>, item: &LogItem) -> String {
+        let timestamp = time.format(&self.timestamp_format);
+        match self.format {
+            LogFormat::Text => return format!("{} {}\n", timestamp, item),
+            LogFormat::Json => {
+                let mut object = serde_json::Map::new();
+                object.insert("timestamp".to_string(), serde_json::json!(timestamp.to_string()));
+                object.insert("action".to_string(), serde_json::json!(item.action()));
+                object.extend(item.fields());
+                return format!("{}\n", serde_json::Value::Object(object));
+            }
+        }
+    }
+
+    /// Path of the file that is written now, in daily rotation it contains the date
+    fn active_path(&self, date: NaiveDate) -> String {
+        match self.rotation {
+            LogRotation::Daily => return format!("{}.{}", self.path, date.format("%Y-%m-%d")),
+            _ => return self.path.clone(),
+        }
+    }
+
+    /// Read a log file that is written by the logger and parse its lines. Read entries
+    /// (`GetKey`, `ListKeys` and hook queries) are skipped, so the result can be passed
+    /// to `Database::apply_log` to rebuild the state of database.
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::logger::{LoggerManager, enums::LogItem};
+    ///
+    /// let path = "/tmp/datastore-log-replay-doc".to_string();
+    /// let _ = std::fs::remove_file(&path);
+    ///
+    /// let mut logger = LoggerManager::new(path.clone());
+    /// logger.start().expect("Failed to start logger");
+    /// logger.write(LogItem::SetKey("/root/status".to_string(), "ok".to_string())).expect("Failed to write");
+    /// logger.write(LogItem::GetKey("/root/status".to_string())).expect("Failed to write");
+    /// logger.stop().expect("Failed to stop logger");
+    ///
+    /// let items = LoggerManager::replay(&path).expect("Failed to replay");
+    /// assert_eq!(1, items.len());
+    /// ```
+    pub fn replay(path: &str) -> Result<Vec<LogItem>, String> {
+        tracing::trace!("replay log file '{}'", path);
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                tracing::error!("failed to read log file: {}", e);
+                return Err(format!("failed to read log file: {}", e));
+            }
+        };
+
+        let mut items = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let item = line
+                .parse::<LogItem>()
+                .map_err(|e| format!("Line {}: {}", index + 1, e))?;
+
+            match item {
+                LogItem::GetKey(_)
+                | LogItem::ListKeys(_)
+                | LogItem::GetHook(_)
+                | LogItem::ListHooks(_)
+                | LogItem::ListHooksByLink => continue,
+                item => items.push(item),
+            }
+        }
+
+        tracing::trace!("replay log file is done, {} items found", items.len());
+        return Ok(items);
+    }
+
     /// Open a buffer for the specified file
     /// After it, every write request will be directly written to file
     pub fn start(&mut self) -> Result<(), String> {
         tracing::trace!("opening file for write");
+        let today = Utc::now().date_naive();
         match File::options()
             .create(true)
-            .write(true)
             .read(true)
             .append(true)
-            .open(Path::new(&self.path))
+            .open(Path::new(&self.active_path(today)))
         {
             Ok(file) => {
                 tracing::trace!("log file is open");
+                if self.file_size.is_none() {
+                    self.file_size = Some(file.metadata().map(|x| x.len()).unwrap_or(0));
+                }
+                let previous_date = self.file_date.replace(today);
                 self.file = Some(BufWriter::new(file));
                 self.state = LogState::Open;
+
+                // File of the former day is not written anymore
+                if let (LogRotation::Daily, Some(date)) = (self.rotation, previous_date) {
+                    if date != today && self.compress {
+                        Self::compress_in_background(self.active_path(date));
+                    }
+                }
                 return Ok(());
             }
             Err(e) => {
@@ -81,17 +372,111 @@ impl LoggerManager {
         };
     }
 
+    /// Return with the last `n` lines of the current log file, oldest first.
+    /// Open file is flushed before, so buffered lines are not missed.
+    ///
+    /// # Example
+    /// ```
+    /// use onlyati_datastore::logger::{LoggerManager, enums::LogItem};
+    ///
+    /// let path = "/tmp/datastore-log-tail-doc".to_string();
+    /// let _ = std::fs::remove_file(&path);
+    ///
+    /// let mut logger = LoggerManager::new(path);
+    /// logger.start().expect("Failed to start logger");
+    /// logger.write(LogItem::SetKey("/root/status".to_string(), "ok".to_string())).expect("Failed to write");
+    /// logger.write(LogItem::RemKey("/root/status".to_string())).expect("Failed to write");
+    ///
+    /// let lines = logger.tail(1).expect("Failed to read");
+    /// assert_eq!(1, lines.len());
+    /// assert_eq!(true, lines[0].ends_with("RemKey [ '/root/status' ]"));
+    /// ```
+    pub fn tail(&mut self, n: usize) -> Result<Vec<String>, String> {
+        tracing::trace!("read last {} lines of log", n);
+        let content = self.read_current()?;
+        let lines: Vec<&str> = content.lines().filter(|x| !x.trim().is_empty()).collect();
+        let start = lines.len().saturating_sub(n);
+
+        return Ok(lines[start..].iter().map(|x| x.to_string()).collect());
+    }
+
+    /// Return with the lines of the current log file that are written at or after the time, with their timestamp.
+    /// Open file is flushed before, so buffered lines are not missed.
+    pub fn read_since(&mut self, ts: DateTime<Utc>) -> Result<Vec<(DateTime<Utc>, String)>, String> {
+        tracing::trace!("read log lines since {}", ts);
+        let content = self.read_current()?;
+
+        let mut lines = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let time = self.line_timestamp(line).ok_or(format!("Line {}: invalid timestamp", index + 1))?;
+            if time >= ts {
+                lines.push((time, line.to_string()));
+            }
+        }
+
+        tracing::trace!("{} log lines found since {}", lines.len(), ts);
+        return Ok(lines);
+    }
+
+    /// Flush the open file and read the content of the current log file
+    fn read_current(&mut self) -> Result<String, String> {
+        self.flush()?;
+
+        let path = self.active_path(self.file_date.unwrap_or(Utc::now().date_naive()));
+        return std::fs::read_to_string(&path).map_err(|e| {
+            tracing::error!("failed to read log file: {}", e);
+            format!("failed to read log file: {}", e)
+        });
+    }
+
+    /// Parse timestamp of a line, it is the `timestamp` field in JSON and the text before the action in text format
+    fn line_timestamp(&self, line: &str) -> Option<DateTime<Utc>> {
+        if line.starts_with('{') {
+            let object: serde_json::Value = serde_json::from_str(line).ok()?;
+            return self.parse_timestamp(object.get("timestamp")?.as_str()?);
+        }
+
+        // Action is the word before the first argument list, or the last word if there is no argument
+        let end = match line.find(" [ ") {
+            Some(index) => line[..index].rfind(' ')?,
+            None => line.trim_end().rfind(' ')?,
+        };
+        return self.parse_timestamp(&line[..end]);
+    }
+
+    /// Parse timestamp with the configured format, RFC3339 and the former `chrono` default format are accepted too
+    fn parse_timestamp(&self, text: &str) -> Option<DateTime<Utc>> {
+        if let Ok(time) = DateTime::parse_from_str(text, &self.timestamp_format) {
+            return Some(time.with_timezone(&Utc));
+        }
+        if let Ok(time) = chrono::NaiveDateTime::parse_from_str(text, &self.timestamp_format) {
+            return Some(time.and_utc());
+        }
+        if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+            return Some(time.with_timezone(&Utc));
+        }
+
+        let time = chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f UTC").ok()?;
+        return Some(time.and_utc());
+    }
+
     /// Close the buffer for the specified file
     /// After it, every write request is going to be failed and not buffered.
     pub fn stop(&mut self) -> Result<(), String> {
-        match &mut self.file {
-            Some(_) => {
-                self.file = None;
-                tracing::trace!("closed the log file");
-                return Ok(());
-            }
-            None => return Err(String::from("Logger manager does not run")),
+        if self.file.is_none() {
+            return Err(String::from("Logger manager does not run"));
+        }
+
+        let result = self.flush();
+        self.file = None;
+        if result.is_ok() {
+            tracing::trace!("closed the log file");
         }
+        return result;
     }
 
     /// Close the log file and do not write more into it.
@@ -99,12 +484,11 @@ impl LoggerManager {
     /// They will be written if the logging has resumed.
     pub fn suspend(&mut self) -> Result<(), String> {
         tracing::trace!("suspend the logging");
-        if self.file.is_some() {
-            self.file = None;
-        }
+        let result = self.flush();
+        self.file = None;
         self.state = LogState::Suspended;
 
-        return Ok(());
+        return result;
     }
 
     /// Resume the logging means that those message which were buffered during suspended status will be written first.
@@ -115,28 +499,20 @@ impl LoggerManager {
             return Err(String::from("Only possible resume from LogState::Suspend"));
         }
 
-        if let Err(e) = self.start() {
-            return Err(e);
-        }
+        self.start()?;
 
         tracing::trace!("writing {} lines after resume", self.buffer.len());
 
-        for item in &self.buffer {
-            if let Some(file) = &mut self.file {
-                let line = format!("{} {}\n", item.0, item.1);
-                if let Err(e) = file.write_all(line.as_bytes()) {
-                    tracing::error!("failed to write log after a resume: {}", e);
-                    return Err(format!("Failed to write log after a resume: {}", e));
-                }
+        for item in std::mem::take(&mut self.buffer) {
+            let line = self.format_line(&item.0, &item.1);
+            if let Err(e) = self.write_line(&line) {
+                tracing::error!("failed to write log after a resume: {}", e);
+                return Err(format!("Failed to write log after a resume: {}", e));
             }
         }
 
-        self.buffer = Vec::new();
+        self.stop()?;
 
-        if let Err(e) = self.stop() {
-            return Err(e);
-        }
-        
         tracing::trace!("logging has resumed");
         return Ok(());
     }
@@ -144,6 +520,10 @@ impl LoggerManager {
     /// Make a write reqest
     pub fn write(&mut self, item: LogItem) -> Result<(), String> {
         tracing::trace!("write log record");
+        if !self.filter.allows(&item) {
+            tracing::trace!("write is skipped due to filter");
+            return Ok(());
+        }
         let now = Utc::now();
 
         match &mut self.state {
@@ -154,25 +534,22 @@ impl LoggerManager {
             }
             // Regular write to a file
             LogState::Open => {
-                match &mut self.file {
-                    Some(file) => {
-                        let line = format!("{} {}\n", now, item);
-                        match file.write_all(line.as_bytes()) {
-                            Ok(_) => {
-                                tracing::trace!("write is done");
-                                return Ok(());
-                            },
-                            Err(e) => {
-                                tracing::error!("error during log writing: {}", e);
-                                return Err(format!("error during log writing: {}", e));
-                            }
-                        }
-                    }
-                    None => {
-                        tracing::error!("wanted to write log while logging was not started");
-                        return Err(String::from(
-                            "wanted to write log while logging was not started",
-                        ));
+                if self.file.is_none() {
+                    tracing::error!("wanted to write log while logging was not started");
+                    return Err(String::from(
+                        "wanted to write log while logging was not started",
+                    ));
+                }
+
+                let line = self.format_line(&now, &item);
+                match self.write_line(&line) {
+                    Ok(_) => {
+                        tracing::trace!("write is done");
+                        return Ok(());
+                    },
+                    Err(e) => {
+                        tracing::error!("error during log writing: {}", e);
+                        return Err(format!("error during log writing: {}", e));
                     }
                 }
             }
@@ -184,4 +561,95 @@ impl LoggerManager {
             }
         }
     }
+
+    /// Write a line into the open file, the file is rotated before if the line would exceed the size limit
+    /// or the date has changed since the file is opened
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        let size = self.file_size.unwrap_or(0);
+        match self.rotation {
+            LogRotation::Size(limit) if size > 0 && size + line.len() as u64 > limit => self.rotate()?,
+            LogRotation::Daily if self.file_date != Some(Utc::now().date_naive()) => {
+                self.flush()?;
+                tracing::debug!("log file is rotated due to date change");
+                self.start()?;
+            }
+            _ => (),
+        }
+
+        match &mut self.file {
+            Some(file) => {
+                file.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+                self.file_size = Some(self.file_size.unwrap_or(0) + line.len() as u64);
+                self.unflushed += 1;
+            }
+            None => return Err(String::from("log file is not open")),
+        }
+
+        let flush = match self.flush_policy {
+            FlushPolicy::Buffered => false,
+            FlushPolicy::EveryWrite => true,
+            FlushPolicy::EveryN(count) => self.unflushed >= count,
+            FlushPolicy::Interval(interval) => self.last_flush.elapsed() >= interval,
+        };
+        if flush {
+            self.flush()?;
+        }
+        return Ok(());
+    }
+
+    /// Close the current file, rename it to the first free `path.N` name and open a new file
+    fn rotate(&mut self) -> Result<(), String> {
+        self.flush()?;
+        self.file = None;
+
+        // Compressed files take their number too
+        let mut index: u32 = 1;
+        while Path::new(&format!("{}.{}", self.path, index)).exists()
+            || Path::new(&format!("{}.{}.gz", self.path, index)).exists()
+        {
+            index += 1;
+        }
+
+        let rotated = format!("{}.{}", self.path, index);
+        std::fs::rename(&self.path, &rotated).map_err(|e| format!("failed to rotate to '{}': {}", rotated, e))?;
+        tracing::debug!("log file is rotated to '{}'", rotated);
+
+        if self.compress {
+            Self::compress_in_background(rotated);
+        }
+
+        self.file_size = Some(0);
+        return self.start();
+    }
+
+    /// Compress the file to `path.gz` on a new thread and remove the original one.
+    /// If the compression fails, the original file is kept and the partial `.gz` file is removed.
+    fn compress_in_background(path: String) {
+        std::thread::spawn(move || {
+            let target = format!("{}.gz", path);
+            match Self::gzip(&path, &target) {
+                Ok(_) => {
+                    if let Err(e) = std::fs::remove_file(&path) {
+                        tracing::error!("failed to remove '{}' after compression: {}", path, e);
+                    }
+                    tracing::debug!("log file is compressed to '{}'", target);
+                }
+                Err(e) => {
+                    tracing::error!("failed to compress '{}': {}", path, e);
+                    let _ = std::fs::remove_file(&target);
+                }
+            }
+        });
+    }
+
+    /// Write the gzip compressed content of source file into the target file
+    fn gzip(source: &str, target: &str) -> std::io::Result<()> {
+        let mut input = File::open(source)?;
+        let output = File::create(target)?;
+
+        let mut encoder = GzEncoder::new(output, Compression::default());
+        std::io::copy(&mut input, &mut encoder)?;
+        encoder.finish()?.sync_all()?;
+        return Ok(());
+    }
 }
@@ -3,6 +3,7 @@ use std::{
     thread::JoinHandle,
 };
 
+use crate::config::Config;
 use crate::logger::enums::LogState;
 
 use super::{
@@ -10,13 +11,31 @@ use super::{
     LoggerManager,
 };
 
-pub fn start_logger(path: &String) -> (Sender<LoggerAction>, JoinHandle<()>) {
+pub fn start_logger(path: &str) -> (Sender<LoggerAction>, JoinHandle<()>) {
+    return start_logger_with_config(path, &Config::default());
+}
+
+/// Start a logger thread based on configuration, e.g. the log file is rotated according to `log_rotation`
+pub fn start_logger_with_config(path: &str, config: &Config) -> (Sender<LoggerAction>, JoinHandle<()>) {
     let (tx, rx) = mpsc::channel::<LoggerAction>();
 
-    let path = path.clone();
+    let path = path.to_string();
+    let rotation = config.log_rotation();
+    let format = config.log_format();
+    let filter = config.log_filter().clone();
+    let flush_policy = config.log_flush_policy();
+    let compression = config.log_compression();
+    let timestamp_format = config.log_timestamp_format().to_string();
 
     let thread = std::thread::spawn(move || {
-        let mut logger = LoggerManager::new(path);
+        let mut logger = LoggerManager::with_rotation(path, rotation);
+        logger.set_format(format);
+        logger.set_filter(filter);
+        logger.set_flush_policy(flush_policy);
+        logger.set_compression(compression);
+        if let Err(e) = logger.set_timestamp_format(timestamp_format) {
+            tracing::error!("default timestamp format is kept: {}", e);
+        }
 
         while let Ok(request) = rx.recv() {
             tracing::trace!("request has come: {}", request);
@@ -76,6 +95,21 @@ pub fn start_logger(path: &String) -> (Sender<LoggerAction>, JoinHandle<()>) {
                         }
                     }
                 }
+                LoggerAction::Stop(sender) => {
+                    // Every earlier request is already processed, only the buffer has to be written
+                    let result = match logger.state {
+                        LogState::Suspended => logger.resume(),
+                        _ => Ok(()),
+                    };
+
+                    match result {
+                        Ok(_) => send_response!(sender, LoggerResponse::Ok),
+                        Err(e) => send_response!(sender, LoggerResponse::Err(e)),
+                    }
+
+                    tracing::debug!("logger is stopped");
+                    break;
+                }
             }
         }
     });
@@ -94,4 +128,4 @@ macro_rules! send_response {
             .unwrap_or_else(|e| tracing::error!("Error during send: {}", e))
     };
 }
-pub(self) use send_response;
+pub(crate) use send_response;
@@ -1,10 +1,19 @@
 #[cfg(test)]
 mod tests {
-    use std::{io::prelude::*, sync::mpsc::channel};
+    use std::{
+        collections::HashMap,
+        io::prelude::*,
+        sync::{mpsc::channel, Arc, Mutex},
+    };
 
     use crate::{
         datastore::{
-            enums::{error::ErrorKind, pair::KeyType, pair::ValueType, DatabaseAction, ListType},
+            client::DatastoreClient,
+            enums::{
+                error::ErrorKind, pair::KeyType, pair::ValueType, ChangeKind, DatabaseAction,
+                DropPolicy, InsertMode, KeyValidation, ListType, MergePolicy, Source, TxnOp,
+            },
+            types::ChangeEvent,
             utilities::{self, start_datastore},
             Database,
         },
@@ -87,7 +96,7 @@ mod tests {
         match rx.recv().expect("Failed to receive message") {
             Ok(_) => panic!("This key should not exist"),
             Err(e) => match e {
-                ErrorKind::InvalidKey(msg) => assert_eq!("Specified key does not exist", msg),
+                ErrorKind::NotFound(msg) => assert_eq!("Specified key does not exist", msg),
                 e => panic!("This is not a correct panic: {}", e),
             },
         }
@@ -116,7 +125,7 @@ mod tests {
         // Error #2
         match db.get(KeyType::Record("/root/status".to_string())) {
             Err(e) => match e {
-                ErrorKind::InvalidKey(msg) => {
+                ErrorKind::NotFound(msg) => {
                     assert_eq!("Specified key does not exist", msg)
                 }
                 _ => panic!("Should have returned InvalidKey instead {:?}", e),
@@ -160,7 +169,7 @@ mod tests {
         // Error #6
         match db.delete_key(KeyType::Record("/root/asd".to_string())) {
             Err(e) => match e {
-                ErrorKind::InvalidKey(msg) => {
+                ErrorKind::NotFound(msg) => {
                     assert_eq!("Specified key does not exist", msg)
                 }
                 _ => panic!("Should have returned InvalidKey instead {:?}", e),
@@ -171,7 +180,7 @@ mod tests {
         // Error #7
         match db.delete_table(KeyType::Table("/root/asd".to_string())) {
             Err(e) => match e {
-                ErrorKind::InvalidKey(msg) => {
+                ErrorKind::NotFound(msg) => {
                     assert_eq!("Specified key does not exist", msg)
                 }
                 _ => panic!("Should have returned InvalidKey instead {:?}", e),
@@ -181,13 +190,8 @@ mod tests {
 
         // Error #8
         match db.pop(KeyType::Record("/root/asd".to_string())) {
-            Err(e) => match e {
-                ErrorKind::InvalidKey(msg) => {
-                    assert_eq!("Specified key does not exist", msg)
-                }
-                _ => panic!("Should have returned InvalidKey instead {:?}", e),
-            },
-            Ok(_) => panic!("Returned with Ok but it should have with Err"),
+            Ok(value) => assert_eq!(None, value),
+            Err(e) => panic!("Missing queue should have returned None instead {:?}", e),
         }
 
         return Ok(());
@@ -276,21 +280,21 @@ mod tests {
         // Check listing
         match db.list_keys(KeyType::Record("/root".to_string()), ListType::All) {
             Ok(table) => {
-                assert_eq!(true, table.len() >= 1);
+                assert_eq!(false, table.is_empty());
             }
             Err(e) => panic!("{}", e),
         }
 
         match db.list_keys(KeyType::Record("/root/network".to_string()), ListType::All) {
             Ok(table) => {
-                assert_eq!(true, table.len() >= 1);
+                assert_eq!(false, table.is_empty());
             }
             Err(e) => panic!("{}", e),
         }
 
         match db.list_keys(KeyType::Record("/root".to_string()), ListType::OneLevel) {
             Ok(table) => {
-                assert_eq!(true, table.len() >= 1);
+                assert_eq!(false, table.is_empty());
             }
             Err(e) => panic!("{}", e),
         }
@@ -337,13 +341,13 @@ mod tests {
         assert_eq!(true, response.is_ok());
 
         let response = db.pop(KeyType::Record("/root/tickets/open".to_string())).expect("Pop should work");
-        assert_eq!("SINC100".to_string(), response);
+        assert_eq!(Some("SINC100".to_string()), response);
 
         let response = db.pop(KeyType::Record("/root/tickets/open".to_string())).expect("Pop should work");
-        assert_eq!("SINC101".to_string(), response);
+        assert_eq!(Some("SINC101".to_string()), response);
 
-        let response = db.pop(KeyType::Record("/root/tickets/open".to_string()));
-        assert_eq!(true, response.is_err());
+        let response = db.pop(KeyType::Record("/root/tickets/open".to_string())).expect("Pop should work");
+        assert_eq!(None, response);
 
         // Test earlier gets again
         let value = db.get(KeyType::Record("/root/tickets".to_string())).expect("Failed to fetch key after queue actions");
@@ -360,35 +364,38 @@ mod tests {
 
         let result = manager.add(
             "/root/status".to_string(),
-            "http://127.0.0.1:3031".to_string(),
+            "http://127.0.0.1:3047".to_string(),
+            None,
         );
         assert_eq!(true, result.is_ok());
 
         let result = manager.add(
             "/root/status".to_string(),
             "http://127.0.0.1:3032".to_string(),
+            None,
         );
         assert_eq!(true, result.is_ok());
 
         let result = manager.add(
             "/root/arpa".to_string(),
-            "http://127.0.0.1:3031".to_string(),
+            "http://127.0.0.1:3047".to_string(),
+            None,
         );
         assert_eq!(true, result.is_ok());
 
         let result = manager.list(&"/root".to_string());
         assert_eq!(2, result.len());
 
-        let result = manager.list(&"/root/stat".to_string());
+        let result = manager.list(&"/root/status".to_string());
         assert_eq!(1, result.len());
 
         let result = manager.list(&"/root/no_exist".to_string());
         assert_eq!(0, result.len());
 
         // Start a dummy TCP listenere for testing
-        std::thread::spawn(|| {
-            let listener = std::net::TcpListener::bind("127.0.0.1:3031")
-                .expect("Failed to listen on 127.0.0.1:3031");
+        let listener = std::net::TcpListener::bind("127.0.0.1:3047")
+            .expect("Failed to listen on 127.0.0.1:3047");
+        std::thread::spawn(move || {
             println!("Start to listen");
             while let Ok(stream) = listener.accept() {
                 let mut stream = stream.0;
@@ -429,17 +436,18 @@ mod tests {
             .unwrap();
         rt.block_on(async move {
             let counter = manager
-                .execute_hooks(&"/root/status/dns1".to_string(), &"okay".to_string())
+                .execute_hooks_count(&"/root/status/dns1".to_string(), &"okay".to_string())
                 .await;
-            assert_eq!(Some(2), counter);
+            // Nothing listens on the second link
+            assert_eq!(Some(1), counter);
 
             let counter = manager
-                .execute_hooks(&"/root/no_exist".to_string(), &"okay".to_string())
+                .execute_hooks_count(&"/root/no_exist".to_string(), &"okay".to_string())
                 .await;
             assert_eq!(None, counter);
 
             let counter = manager
-                .execute_hooks(
+                .execute_hooks_count(
                     &"/root/arpa/server1".to_string(),
                     &"This is the value".to_string(),
                 )
@@ -450,4 +458,3008 @@ mod tests {
             tokio::time::sleep(tokio::time::Duration::new(1, 0)).await;
         });
     }
+
+    #[test]
+    fn test_total_queued() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        assert_eq!(0, db.total_queued());
+
+        let queues = vec![
+            ("/root/tickets/open", 3),
+            ("/root/tickets/closed", 1),
+            ("/root/jobs", 2),
+            ("/root/agent/server1/tasks/pending", 4),
+        ];
+
+        for (key, count) in &queues {
+            for i in 0..*count {
+                db.push(KeyType::Record(key.to_string()), format!("item{}", i))
+                    .expect("Failed to push");
+            }
+        }
+
+        // Records does not count
+        db.insert(
+            KeyType::Record("/root/tickets/open".to_string()),
+            ValueType::RecordPointer("not a queue".to_string()),
+        )
+        .expect("Failed to insert");
+
+        assert_eq!(10, db.total_queued());
+
+        db.pop(KeyType::Record("/root/jobs".to_string()))
+            .expect("Failed to pop");
+        assert_eq!(9, db.total_queued());
+
+        // Check on the built-in server
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+        for (key, count) in &queues {
+            for i in 0..*count {
+                let (tx, rx) = channel();
+                let action = DatabaseAction::Push(tx, key.to_string(), format!("item{}", i));
+                sender.send(action).expect("Failed to send the request");
+                rx.recv().expect("Failed to receive").expect("Failed to push");
+            }
+        }
+
+        let (tx, rx) = utilities::get_channel_for_total_queued();
+        sender
+            .send(DatabaseAction::TotalQueued(tx))
+            .expect("Failed to send the request");
+        let count = rx.recv().expect("Failed to receive").expect("Failed to count");
+        assert_eq!(10, count);
+    }
+
+    #[test]
+    fn test_hot_keys() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+
+        let keys = vec![
+            ("/root/status/sub1", 5),
+            ("/root/status/sub2", 1),
+            ("/root/network/dns", 3),
+            ("/root/network/www", 0),
+        ];
+
+        for (key, _) in &keys {
+            db.insert(
+                KeyType::Record(key.to_string()),
+                ValueType::RecordPointer("OK".to_string()),
+            )
+            .expect("Failed to insert");
+        }
+
+        // Counters are disabled by default
+        db.get(KeyType::Record("/root/status/sub1".to_string()))
+            .expect("Failed to get");
+        assert_eq!(0, db.hot_keys(10).len());
+
+        db.enable_access_counters();
+        for (key, count) in &keys {
+            for _ in 0..*count {
+                db.get(KeyType::Record(key.to_string()))
+                    .expect("Failed to get");
+            }
+        }
+
+        // Missing keys are not counted
+        let _ = db.get(KeyType::Record("/root/not/exist".to_string()));
+
+        let hot_keys = db.hot_keys(10);
+        assert_eq!(
+            vec![
+                (KeyType::Record("/root/status/sub1".to_string()), 5),
+                (KeyType::Record("/root/network/dns".to_string()), 3),
+                (KeyType::Record("/root/status/sub2".to_string()), 1),
+            ],
+            hot_keys
+        );
+
+        let hot_keys = db.hot_keys(1);
+        assert_eq!(
+            vec![(KeyType::Record("/root/status/sub1".to_string()), 5)],
+            hot_keys
+        );
+
+        db.disable_access_counters();
+        assert_eq!(0, db.hot_keys(10).len());
+
+        // Check on the built-in server
+        let config = crate::config::Builder::new().access_counters(true).build();
+        let (sender, _) = utilities::start_datastore_with_config("root".to_string(), None, None, &config);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/status".to_string(), "ok".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        for _ in 0..2 {
+            let (tx, rx) = utilities::get_channel_for_get();
+            let action = DatabaseAction::Get(tx, "/root/status".to_string());
+            sender.send(action).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to get");
+        }
+
+        let (tx, rx) = utilities::get_channel_for_hot_keys();
+        sender
+            .send(DatabaseAction::HotKeys(tx, 5))
+            .expect("Failed to send the request");
+        let hot_keys = rx.recv().expect("Failed to receive").expect("Failed to list");
+        assert_eq!(
+            vec![(KeyType::Record("/root/status".to_string()), 2)],
+            hot_keys
+        );
+    }
+
+    #[test]
+    fn test_rename_prefix() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+
+        let records = vec![
+            "/root/v1",
+            "/root/v1/status",
+            "/root/v1/network/dns",
+            "/root/v1/network/www",
+            "/root/v10/status",
+            "/root/other/status",
+        ];
+
+        for key in &records {
+            db.insert(
+                KeyType::Record(key.to_string()),
+                ValueType::RecordPointer(format!("{} value", key)),
+            )
+            .expect("Failed to insert");
+        }
+        db.push(KeyType::Record("/root/v1/jobs".to_string()), "job1".to_string())
+            .expect("Failed to push");
+
+        // Overlapping prefixes are rejected
+        assert_eq!(true, db.rename_prefix("/root/v1", "/root/v1/new").is_err());
+        assert_eq!(true, db.rename_prefix("/root/v1", "/root").is_err());
+        assert_eq!(true, db.rename_prefix("/root/v1", "/root/v1").is_err());
+
+        // Clobbering existing data is rejected and nothing is moved
+        db.insert(
+            KeyType::Record("/root/v2/network/dns".to_string()),
+            ValueType::RecordPointer("existing".to_string()),
+        )
+        .expect("Failed to insert");
+        assert_eq!(true, db.rename_prefix("/root/v1", "/root/v2").is_err());
+        assert_eq!(true, db.get(KeyType::Record("/root/v1/status".to_string())).is_ok());
+        db.delete_table(KeyType::Table("/root/v2".to_string()))
+            .expect("Failed to delete table");
+
+        // Destination does not exist before
+        let moved = db
+            .rename_prefix("/root/v1", "/root/v2/migrated")
+            .expect("Failed to rename");
+        assert_eq!(5, moved);
+
+        for key in ["/root/v1", "/root/v1/status", "/root/v1/network/dns"] {
+            assert_eq!(true, db.get(KeyType::Record(key.to_string())).is_err());
+        }
+
+        for key in ["/root/v1", "/root/v1/status", "/root/v1/network/dns", "/root/v1/network/www"] {
+            let new_key = key.replace("/root/v1", "/root/v2/migrated");
+            let value = db
+                .get(KeyType::Record(new_key))
+                .expect("Renamed key not found");
+            assert_eq!(ValueType::RecordPointer(format!("{} value", key)), value);
+        }
+
+        let job = db
+            .pop(KeyType::Record("/root/v2/migrated/jobs".to_string()))
+            .expect("Failed to pop");
+        assert_eq!(Some("job1".to_string()), job);
+
+        // Similar prefix by characters is not touched
+        let value = db
+            .get(KeyType::Record("/root/v10/status".to_string()))
+            .expect("Key not found");
+        assert_eq!(ValueType::RecordPointer("/root/v10/status value".to_string()), value);
+
+        // Nothing to move
+        let moved = db.rename_prefix("/root/v1", "/root/v3").expect("Failed to rename");
+        assert_eq!(0, moved);
+
+        // Destination that is not a valid new key is rejected before the source is removed
+        db.set_key_validation(KeyValidation::Restricted);
+        db.insert(KeyType::record("/root/v4/a"), ValueType::RecordPointer("a".to_string())).expect("Failed to insert");
+        db.create_table(KeyType::table("/root/v4/empty")).expect("Failed to create table");
+        assert_eq!(true, matches!(db.rename_prefix("/root/v4", "/root/v 5"), Err(ErrorKind::InvalidKey(_))));
+        assert_eq!(ValueType::RecordPointer("a".to_string()), db.get(KeyType::record("/root/v4/a")).expect("Key not found"));
+
+        // Empty tables are moved too
+        assert_eq!(1, db.rename_prefix("/root/v4", "/root/v5").expect("Failed to rename"));
+        assert_eq!(true, db.exists(KeyType::table("/root/v5/empty")).expect("Failed to check"));
+        assert_eq!(false, db.exists(KeyType::table("/root/v4")).expect("Failed to check"));
+    }
+
+    #[test]
+    fn test_snapshot_size_estimate() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+
+        let empty = r#"{"name":"root","root":{"records":{},"ints":{},"bytes":{},"queues":{},"tables":{}}}"#;
+        assert_eq!(empty.len(), db.snapshot_size_estimate());
+
+        db.insert(
+            KeyType::Record("/root/status".to_string()),
+            ValueType::RecordPointer("say \"hi\"".to_string()),
+        )
+        .expect("Failed to insert");
+        db.push(KeyType::Record("/root/jobs".to_string()), "job1".to_string())
+            .expect("Failed to push");
+        db.push(KeyType::Record("/root/jobs".to_string()), "job2".to_string())
+            .expect("Failed to push");
+        db.insert(
+            KeyType::Record("/root/net/eth0".to_string()),
+            ValueType::RecordPointer("up".to_string()),
+        )
+        .expect("Failed to insert");
+
+        let expected = concat!(
+            r#"{"name":"root","root":{"#,
+            r#""records":{"status":"say \"hi\""},"ints":{},"bytes":{},"#,
+            r#""queues":{"jobs":["job1","job2"]},"#,
+            r#""tables":{"net":{"records":{"eth0":"up"},"ints":{},"bytes":{},"queues":{},"tables":{}}}"#,
+            r#"}}"#,
+        );
+        assert_eq!(expected.len(), db.snapshot_size_estimate());
+    }
+
+    /// Create the databases for merge tests
+    fn create_merge_databases() -> (Database, Database) {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        db.insert(
+            KeyType::Record("/root/status/server1".to_string()),
+            ValueType::RecordPointer("ok".to_string()),
+        )
+        .expect("Failed to insert");
+        db.insert(
+            KeyType::Record("/root/status/server2".to_string()),
+            ValueType::RecordPointer("ok".to_string()),
+        )
+        .expect("Failed to insert");
+
+        let mut other = Database::new("remote".to_string()).expect("Failed to allocate database");
+        other
+            .insert(
+                KeyType::Record("/remote/status/server2".to_string()),
+                ValueType::RecordPointer("nok".to_string()),
+            )
+            .expect("Failed to insert");
+        other
+            .insert(
+                KeyType::Record("/remote/status/server3".to_string()),
+                ValueType::RecordPointer("nok".to_string()),
+            )
+            .expect("Failed to insert");
+        other
+            .push(KeyType::Record("/remote/tickets".to_string()), "SINC100".to_string())
+            .expect("Failed to push");
+
+        return (db, other);
+    }
+
+    #[test]
+    fn test_merge_keep_existing() {
+        let (mut db, other) = create_merge_databases();
+
+        let merged = db
+            .merge(other, MergePolicy::KeepExisting)
+            .expect("Failed to merge");
+        assert_eq!(2, merged);
+
+        let value = db
+            .get(KeyType::Record("/root/status/server2".to_string()))
+            .expect("Key not found");
+        assert_eq!(ValueType::RecordPointer("ok".to_string()), value);
+
+        let value = db
+            .get(KeyType::Record("/root/status/server3".to_string()))
+            .expect("Key not found");
+        assert_eq!(ValueType::RecordPointer("nok".to_string()), value);
+
+        let ticket = db
+            .pop(KeyType::Record("/root/tickets".to_string()))
+            .expect("Failed to pop");
+        assert_eq!(Some("SINC100".to_string()), ticket);
+    }
+
+    #[test]
+    fn test_merge_overwrite() {
+        let (mut db, other) = create_merge_databases();
+
+        let merged = db
+            .merge(other, MergePolicy::Overwrite)
+            .expect("Failed to merge");
+        assert_eq!(3, merged);
+
+        let value = db
+            .get(KeyType::Record("/root/status/server2".to_string()))
+            .expect("Key not found");
+        assert_eq!(ValueType::RecordPointer("nok".to_string()), value);
+
+        let value = db
+            .get(KeyType::Record("/root/status/server1".to_string()))
+            .expect("Key not found");
+        assert_eq!(ValueType::RecordPointer("ok".to_string()), value);
+    }
+
+    #[test]
+    fn test_merge_error() {
+        let (mut db, other) = create_merge_databases();
+
+        let result = db.merge(other, MergePolicy::Error);
+        assert_eq!(true, result.is_err());
+
+        // Nothing is merged
+        let result = db.get(KeyType::Record("/root/status/server3".to_string()));
+        assert_eq!(true, result.is_err());
+
+        let value = db
+            .get(KeyType::Record("/root/status/server2".to_string()))
+            .expect("Key not found");
+        assert_eq!(ValueType::RecordPointer("ok".to_string()), value);
+
+        // Without conflict it works
+        let (mut db, _) = create_merge_databases();
+        let mut other = Database::new("root".to_string()).expect("Failed to allocate database");
+        other
+            .insert(
+                KeyType::Record("/root/network/dns".to_string()),
+                ValueType::RecordPointer("ok".to_string()),
+            )
+            .expect("Failed to insert");
+
+        let merged = db.merge(other, MergePolicy::Error).expect("Failed to merge");
+        assert_eq!(1, merged);
+
+        // Key that is not valid as new key rejects the whole merge
+        let (mut db, _) = create_merge_databases();
+        db.set_key_validation(KeyValidation::Restricted);
+        let mut other = Database::new("root".to_string()).expect("Failed to allocate database");
+        other.insert(KeyType::record("/root/a"), ValueType::RecordPointer("a".to_string())).expect("Failed to insert");
+        other.insert(KeyType::record("/root/b c"), ValueType::RecordPointer("bc".to_string())).expect("Failed to insert");
+
+        let result = db.merge(other, MergePolicy::Overwrite);
+        assert_eq!(true, matches!(result, Err(ErrorKind::InvalidKey(_))));
+        assert_eq!(true, db.get(KeyType::record("/root/a")).is_err());
+    }
+
+    #[test]
+    fn test_root_iter() {
+        /// Custom walk that goes down only till the specified depth
+        fn walk(
+            entries: Vec<(&KeyType, &ValueType)>,
+            prefix: String,
+            depth: usize,
+            result: &mut Vec<String>,
+        ) {
+            for (key, value) in entries {
+                let path = format!("{}/{}", prefix, key.get_key());
+                match value {
+                    ValueType::TablePointer(table) => {
+                        if depth > 1 {
+                            walk(table.iter().collect(), path, depth - 1, result);
+                        }
+                    }
+                    _ => result.push(path),
+                }
+            }
+        }
+
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        assert_eq!(0, db.root_iter().count());
+
+        let keys = vec![
+            "/root/node_name",
+            "/root/status/sub1",
+            "/root/status/sub2",
+            "/root/network/dns/primary",
+            "/root/network/dns/secondary/backup",
+        ];
+
+        for key in &keys {
+            db.insert(
+                KeyType::Record(key.to_string()),
+                ValueType::RecordPointer("ok".to_string()),
+            )
+            .expect("Failed to insert");
+        }
+
+        let mut result = Vec::new();
+        walk(db.root_iter().collect(), "/root".to_string(), 1, &mut result);
+        assert_eq!(vec!["/root/node_name".to_string()], result);
+
+        let mut result = Vec::new();
+        walk(db.root_iter().collect(), "/root".to_string(), 2, &mut result);
+        assert_eq!(3, result.len());
+
+        let mut result = Vec::new();
+        walk(db.root_iter().collect(), "/root".to_string(), 3, &mut result);
+        assert_eq!(4, result.len());
+        assert_eq!(true, result.contains(&"/root/network/dns/primary".to_string()));
+        assert_eq!(false, result.contains(&"/root/network/dns/secondary/backup".to_string()));
+    }
+
+    #[test]
+    fn test_insert_modes() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        let key = KeyType::Record("/root/status".to_string());
+
+        // Upsert creates and updates
+        db.insert_with_mode(key.clone(), ValueType::RecordPointer("1".to_string()), InsertMode::Upsert)
+            .expect("Upsert should create");
+        db.insert_with_mode(key.clone(), ValueType::RecordPointer("2".to_string()), InsertMode::Upsert)
+            .expect("Upsert should update");
+        assert_eq!(ValueType::RecordPointer("2".to_string()), db.get(key.clone()).unwrap());
+
+        // CreateOnly fails on existing key
+        let result = db.insert_with_mode(key.clone(), ValueType::RecordPointer("3".to_string()), InsertMode::CreateOnly);
+        match result {
+            Err(ErrorKind::InvalidKey(msg)) => assert_eq!("Specified key already exists", msg),
+            _ => panic!("CreateOnly should have failed"),
+        }
+        assert_eq!(ValueType::RecordPointer("2".to_string()), db.get(key.clone()).unwrap());
+
+        let new_key = KeyType::Record("/root/network".to_string());
+        db.insert_with_mode(new_key.clone(), ValueType::RecordPointer("ok".to_string()), InsertMode::CreateOnly)
+            .expect("CreateOnly should create");
+
+        // UpdateOnly fails on missing key
+        let missing_key = KeyType::Record("/root/missing".to_string());
+        let result = db.insert_with_mode(missing_key.clone(), ValueType::RecordPointer("ok".to_string()), InsertMode::UpdateOnly);
+        match result {
+            Err(ErrorKind::NotFound(msg)) => assert_eq!("Specified key does not exist", msg),
+            _ => panic!("UpdateOnly should have failed"),
+        }
+        assert_eq!(true, db.get(missing_key).is_err());
+
+        db.insert_with_mode(new_key.clone(), ValueType::RecordPointer("nok".to_string()), InsertMode::UpdateOnly)
+            .expect("UpdateOnly should update");
+        assert_eq!(ValueType::RecordPointer("nok".to_string()), db.get(new_key).unwrap());
+
+        // Check on the built-in server
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::SetWithMode(tx, "/root/status".to_string(), "ok".to_string(), InsertMode::UpdateOnly);
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_err());
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::SetWithMode(tx, "/root/status".to_string(), "ok".to_string(), InsertMode::CreateOnly);
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_ok());
+    }
+
+    #[test]
+    fn test_key_depth_histogram() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        assert_eq!(0, db.key_depth_histogram().len());
+
+        let keys = vec![
+            "/root/node_name",
+            "/root/location",
+            "/root/status/sub1",
+            "/root/status/sub2",
+            "/root/status/sub3",
+            "/root/network/dns/primary",
+            "/root/network/dns/secondary/backup",
+        ];
+
+        for key in &keys {
+            db.insert(
+                KeyType::Record(key.to_string()),
+                ValueType::RecordPointer("ok".to_string()),
+            )
+            .expect("Failed to insert");
+        }
+
+        // Queues are not counted
+        db.push(KeyType::Record("/root/tickets".to_string()), "SINC100".to_string())
+            .expect("Failed to push");
+
+        let histogram = db.key_depth_histogram();
+        let expected: std::collections::BTreeMap<usize, usize> =
+            [(1, 2), (2, 3), (3, 1), (4, 1)].into_iter().collect();
+        assert_eq!(expected, histogram);
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/status".to_string(), "ok".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set the value");
+
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::Pause(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to pause");
+
+        // Modifications are rejected
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/status".to_string(), "nok".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(true, matches!(rx.recv().expect("Failed to receive"), Err(ErrorKind::Paused)));
+
+        let (tx, rx) = utilities::get_channel_for_delete();
+        let action = DatabaseAction::DeleteKey(tx, "/root/status".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(true, matches!(rx.recv().expect("Failed to receive"), Err(ErrorKind::Paused)));
+
+        // Reads are still served
+        let (tx, rx) = utilities::get_channel_for_get();
+        let action = DatabaseAction::Get(tx, "/root/status".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(
+            ValueType::RecordPointer("ok".to_string()),
+            rx.recv().expect("Failed to receive").expect("Failed to get the value")
+        );
+
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::Resume(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to resume");
+
+        // Modifications are allowed again
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/status".to_string(), "nok".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set the value");
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        let action = DatabaseAction::Get(tx, "/root/status".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(
+            ValueType::RecordPointer("nok".to_string()),
+            rx.recv().expect("Failed to receive").expect("Failed to get the value")
+        );
+    }
+
+    /// Subscriber that collects the fields of every event
+    struct FieldCollector {
+        events: Arc<Mutex<Vec<HashMap<String, String>>>>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl<'a> tracing::field::Visit for FieldVisitor<'a> {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl tracing::Subscriber for FieldCollector {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            return true;
+        }
+
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            return tracing::span::Id::from_u64(1);
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut fields = HashMap::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.events.lock().unwrap().push(fields);
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[test]
+    fn test_structured_tracing() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let collector = FieldCollector {
+            events: events.clone(),
+        };
+
+        tracing::subscriber::with_default(collector, || {
+            let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+            db.insert(
+                KeyType::Record("/root/status".to_string()),
+                ValueType::RecordPointer("ok".to_string()),
+            )
+            .expect("Failed to insert");
+            db.get(KeyType::Record("/root/status".to_string()))
+                .expect("Failed to get");
+            let _ = db.get(KeyType::Record("/root/missing".to_string()));
+            db.delete_key(KeyType::Record("/root/status".to_string()))
+                .expect("Failed to delete");
+        });
+
+        let events = events.lock().unwrap();
+        let structured: Vec<(&str, &str, &str)> = events
+            .iter()
+            .filter(|fields| fields.contains_key("op"))
+            .map(|fields| {
+                (
+                    fields["op"].as_str(),
+                    fields["key"].as_str(),
+                    fields["result"].as_str(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("set", "/root/status", "ok"),
+                ("get", "/root/status", "ok"),
+                ("get", "/root/missing", "error"),
+                ("delete_key", "/root/status", "ok"),
+            ],
+            structured
+        );
+    }
+
+    #[test]
+    fn test_read_through_loader() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        let calls = Arc::new(Mutex::new(0));
+        let loader_calls = calls.clone();
+        db.set_loader(move |key| {
+            *loader_calls.lock().unwrap() += 1;
+            if key.starts_with("/root/cache/") {
+                return Some(format!("value of {}", key));
+            }
+            return None;
+        });
+
+        // Miss is loaded and stored
+        let key = KeyType::Record("/root/cache/item1".to_string());
+        assert_eq!(
+            ValueType::RecordPointer("value of /root/cache/item1".to_string()),
+            db.get_or_load(key.clone()).expect("Failed to load")
+        );
+        assert_eq!(
+            ValueType::RecordPointer("value of /root/cache/item1".to_string()),
+            db.get(key.clone()).expect("Value is not stored")
+        );
+
+        // Second read is served from database
+        db.get_or_load(key).expect("Failed to get");
+        assert_eq!(1, *calls.lock().unwrap());
+
+        // Loader without value
+        let result = db.get_or_load(KeyType::Record("/root/other".to_string()));
+        assert_eq!(true, result.is_err());
+        assert_eq!(2, *calls.lock().unwrap());
+
+        db.clear_loader();
+        let result = db.get_or_load(KeyType::Record("/root/cache/item2".to_string()));
+        assert_eq!(true, result.is_err());
+
+        // Check on the built-in server
+        let config = crate::config::Builder::new()
+            .read_through_loader(|key| Some(key.to_uppercase()))
+            .build();
+        let (sender, _) = utilities::start_datastore_with_config("root".to_string(), None, None, &config);
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        let action = DatabaseAction::Get(tx, "/root/status".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(
+            ValueType::RecordPointer("/ROOT/STATUS".to_string()),
+            rx.recv().expect("Failed to receive").expect("Failed to get the value")
+        );
+    }
+
+    #[test]
+    fn test_read_through_loader_log() {
+        let path = "/tmp/datastore-log22.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+
+        let config = crate::config::Builder::new()
+            .logger_path(path.clone())
+            .read_through_loader(|key| Some(key.to_uppercase()))
+            .build();
+        let (sender, handles) = config.start("root".to_string());
+        let client = DatastoreClient::new(sender.clone());
+
+        assert_eq!(ValueType::RecordPointer("/ROOT/STATUS".to_string()), client.get("/root/status".to_string()).expect("Failed to get"));
+
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::GetBatch(tx, vec!["/root/batch".to_string()])).expect("Failed to send the request");
+        let mut results = rx.recv().expect("Failed to receive");
+        assert_eq!(ValueType::RecordPointer("/ROOT/BATCH".to_string()), results.remove(0).1.expect("Failed to get"));
+
+        // While paused the loaded value is returned but not stored
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::Pause(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to pause");
+
+        assert_eq!(ValueType::RecordPointer("/ROOT/PAUSED".to_string()), client.get("/root/paused".to_string()).expect("Failed to get"));
+        assert_eq!(false, client.exists(KeyType::record("/root/paused")).expect("Failed to check"));
+
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::GetBatch(tx, vec!["/root/paused-batch".to_string()])).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive")[0].1.is_ok());
+        assert_eq!(false, client.exists(KeyType::record("/root/paused-batch")).expect("Failed to check"));
+
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::Resume(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to resume");
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Shutdown(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to shutdown");
+        handles.datastore.join().expect("Failed to join");
+
+        let (logger_sender, logger) = handles.logger.expect("Logger is not started");
+        drop(logger_sender);
+        logger.join().expect("Failed to join");
+
+        // Replay has the loaded value without loader
+        let mut db = Database::new("root".to_string()).unwrap();
+        db.replay_log(&path).expect("Failed to replay");
+        assert_eq!(ValueType::RecordPointer("/ROOT/STATUS".to_string()), db.get(KeyType::record("/root/status")).expect("Key not found"));
+        assert_eq!(true, db.get(KeyType::record("/root/paused")).is_err());
+        assert_eq!(ValueType::RecordPointer("/ROOT/BATCH".to_string()), db.get(KeyType::record("/root/batch")).expect("Key not found"));
+        assert_eq!(true, db.get(KeyType::record("/root/paused-batch")).is_err());
+    }
+
+    #[test]
+    fn test_delete_keys() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        db.insert(
+            KeyType::Record("/root/status/sub1".to_string()),
+            ValueType::RecordPointer("OK".to_string()),
+        )
+        .expect("Failed to insert");
+        db.insert(
+            KeyType::Record("/root/status/sub2".to_string()),
+            ValueType::RecordPointer("OK".to_string()),
+        )
+        .expect("Failed to insert");
+
+        let results = db.delete_keys(vec![
+            KeyType::Record("/root/status/sub1".to_string()),
+            KeyType::Record("/root/status/missing".to_string()),
+            KeyType::Record("/root/status/sub2".to_string()),
+            KeyType::Record("/other/status".to_string()),
+        ]);
+
+        assert_eq!(4, results.len());
+        assert_eq!(KeyType::Record("/root/status/sub1".to_string()), results[0].0);
+        assert_eq!(true, results[0].1.is_ok());
+        assert_eq!(true, matches!(results[1].1, Err(ErrorKind::NotFound(_))));
+        assert_eq!(true, results[2].1.is_ok());
+        assert_eq!(true, matches!(results[3].1, Err(ErrorKind::InvalidKey(_))));
+
+        assert_eq!(true, db.get(KeyType::Record("/root/status/sub1".to_string())).is_err());
+        assert_eq!(true, db.get(KeyType::Record("/root/status/sub2".to_string())).is_err());
+
+        // Check on the built-in server
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/status".to_string(), "ok".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set the value");
+
+        let (tx, rx) = utilities::get_channel_for_delete_keys();
+        let action = DatabaseAction::DeleteKeys(
+            tx,
+            vec!["/root/missing".to_string(), "/root/status".to_string()],
+        );
+        sender.send(action).expect("Failed to send the request");
+        let results = rx.recv().expect("Failed to receive").expect("Failed to delete keys");
+        assert_eq!(true, results[0].1.is_err());
+        assert_eq!(true, results[1].1.is_ok());
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        db.insert(
+            KeyType::Record("/root/status/sub1".to_string()),
+            ValueType::RecordPointer("OK".to_string()),
+        )
+        .expect("Failed to insert");
+        db.push(KeyType::Record("/root/jobs".to_string()), "backup".to_string())
+            .expect("Failed to push");
+        assert_eq!(true, db.verify().is_ok());
+
+        // Break the tree on purpose
+        db.place(
+            KeyType::Record("/root/status/sub2".to_string()),
+            ValueType::TablePointer(crate::datastore::types::Table::new()),
+        )
+        .expect("Failed to place");
+        db.place(
+            KeyType::Queue("/root/status/events".to_string()),
+            ValueType::RecordPointer("not a queue".to_string()),
+        )
+        .expect("Failed to place");
+        db.place(
+            KeyType::Queue("/root/status/empty".to_string()),
+            ValueType::QueuePointer(std::collections::VecDeque::new()),
+        )
+        .expect("Failed to place");
+        db.place(
+            KeyType::Table("/root/network".to_string()),
+            ValueType::RecordPointer("not a table".to_string()),
+        )
+        .expect("Failed to place");
+
+        let violations = db.verify().expect_err("Violations are not detected");
+        assert_eq!(3, violations.len());
+        assert_eq!(
+            true,
+            violations.contains(&"record '/root/status/sub2' does not point to a record".to_string())
+        );
+        assert_eq!(
+            true,
+            violations.contains(&"queue '/root/status/events' does not point to a queue".to_string())
+        );
+        assert_eq!(
+            true,
+            violations.contains(&"table '/root/network' does not point to a table".to_string())
+        );
+    }
+
+    #[test]
+    fn test_queue_len_multi() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        for (key, count) in [("/root/jobs/build", 3), ("/root/jobs/test", 1), ("/root/mail", 2)] {
+            for i in 0..count {
+                let (tx, rx) = utilities::get_channel_for_set();
+                let action = DatabaseAction::Push(tx, key.to_string(), format!("item{}", i));
+                sender.send(action).expect("Failed to send the request");
+                rx.recv().expect("Failed to receive").expect("Failed to push");
+            }
+        }
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/status".to_string(), "ok".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set the value");
+
+        let (tx, rx) = utilities::get_channel_for_queue_len_multi();
+        let action = DatabaseAction::QueueLenMulti(
+            tx,
+            vec![
+                "/root/jobs/build".to_string(),
+                "/root/status".to_string(),
+                "/root/jobs/test".to_string(),
+                "/root/mail".to_string(),
+                "/root/missing".to_string(),
+            ],
+        );
+        sender.send(action).expect("Failed to send the request");
+        let lengths = rx.recv().expect("Failed to receive");
+
+        assert_eq!(5, lengths.len());
+        assert_eq!("/root/jobs/build", lengths[0].0);
+        assert_eq!(3, *lengths[0].1.as_ref().unwrap());
+        assert_eq!("/root/status", lengths[1].0);
+        assert_eq!(true, lengths[1].1.is_err());
+        assert_eq!(1, *lengths[2].1.as_ref().unwrap());
+        assert_eq!(2, *lengths[3].1.as_ref().unwrap());
+        assert_eq!(true, lengths[4].1.is_err());
+    }
+
+    #[test]
+    fn test_insert_versioned() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        let key = KeyType::Record("/root/config/limit".to_string());
+
+        // Fresh insert
+        assert_eq!(0, db.version(key.clone()).unwrap());
+        let version = db
+            .insert_versioned(key.clone(), ValueType::RecordPointer("10".to_string()), 0)
+            .expect("Failed to insert");
+        assert_eq!(1, version);
+
+        // Matching version
+        let version = db
+            .insert_versioned(key.clone(), ValueType::RecordPointer("20".to_string()), version)
+            .expect("Failed to update");
+        assert_eq!(2, version);
+        assert_eq!(ValueType::RecordPointer("20".to_string()), db.get(key.clone()).unwrap());
+
+        // Stale version
+        let result = db.insert_versioned(key.clone(), ValueType::RecordPointer("30".to_string()), 1);
+        assert_eq!(true, matches!(result, Err(ErrorKind::VersionConflict(2))));
+        assert_eq!(ValueType::RecordPointer("20".to_string()), db.get(key.clone()).unwrap());
+
+        // Plain insert also increases the version
+        db.insert(key.clone(), ValueType::RecordPointer("40".to_string()))
+            .expect("Failed to insert");
+        assert_eq!(3, db.version(key.clone()).unwrap());
+        let result = db.insert_versioned(key.clone(), ValueType::RecordPointer("50".to_string()), 2);
+        assert_eq!(true, matches!(result, Err(ErrorKind::VersionConflict(3))));
+
+        // Version does not go backward after delete
+        db.delete_key(key.clone()).expect("Failed to delete");
+        assert_eq!(0, db.version(key.clone()).unwrap());
+        let version = db
+            .insert_versioned(key.clone(), ValueType::RecordPointer("60".to_string()), 0)
+            .expect("Failed to insert");
+        assert_eq!(4, version);
+
+        // Record that was written without version
+        let key = KeyType::Record("/root/config/name".to_string());
+        db.insert(key.clone(), ValueType::RecordPointer("test".to_string()))
+            .expect("Failed to insert");
+        assert_eq!(1, db.version(key.clone()).unwrap());
+        let version = db
+            .insert_versioned(key.clone(), ValueType::RecordPointer("prod".to_string()), 1)
+            .expect("Failed to update");
+        assert_eq!(2, version);
+    }
+
+    #[test]
+    fn test_create_table() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+
+        // Route does not exist yet
+        let result = db.list_keys(KeyType::Record("/root/network/dns".to_string()), ListType::All);
+        assert_eq!(true, result.is_err());
+
+        db.create_table(KeyType::Table("/root/network/dns".to_string()))
+            .expect("Failed to create table");
+
+        let list = db
+            .list_keys(KeyType::Record("/root/network/dns".to_string()), ListType::All)
+            .expect("Failed to list");
+        assert_eq!(0, list.len());
+
+        // Already exists
+        let result = db.create_table(KeyType::Table("/root/network/dns".to_string()));
+        assert_eq!(true, result.is_err());
+
+        db.insert(
+            KeyType::Record("/root/network/www".to_string()),
+            ValueType::RecordPointer("ok".to_string()),
+        )
+        .expect("Failed to insert");
+        let result = db.create_table(KeyType::Table("/root/network/www".to_string()));
+        assert_eq!(true, result.is_err());
+
+        let result = db.create_table(KeyType::Record("/root/other".to_string()));
+        assert_eq!(true, result.is_err());
+        assert_eq!(true, db.verify().is_ok());
+
+        // Check on the built-in server
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::CreateTable(tx, "/root/empty".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to create table");
+
+        let (tx, rx) = utilities::get_channel_for_list();
+        let action = DatabaseAction::ListKeys(tx, "/root/empty".to_string(), ListType::OneLevel);
+        sender.send(action).expect("Failed to send the request");
+        let list = rx.recv().expect("Failed to receive").expect("Failed to list");
+        assert_eq!(0, list.len());
+    }
+
+    #[test]
+    fn test_max_list_results() {
+        let config = crate::config::Builder::new().max_list_results(3).build();
+        let (sender, _) = utilities::start_datastore_with_config("root".to_string(), None, None, &config);
+
+        for i in 0..5 {
+            let (tx, rx) = utilities::get_channel_for_set();
+            let table = if i < 2 { "small" } else { "large" };
+            let action = DatabaseAction::Set(tx, format!("/root/{}/key{}", table, i), "ok".to_string());
+            sender.send(action).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to set the value");
+        }
+
+        let (tx, rx) = utilities::get_channel_for_list();
+        let action = DatabaseAction::ListKeys(tx, "/root/large".to_string(), ListType::All);
+        sender.send(action).expect("Failed to send the request");
+        let list = rx.recv().expect("Failed to receive").expect("Failed to list");
+        assert_eq!(3, list.len());
+
+        let (tx, rx) = utilities::get_channel_for_list();
+        let action = DatabaseAction::ListKeys(tx, "/root".to_string(), ListType::All);
+        sender.send(action).expect("Failed to send the request");
+        match rx.recv().expect("Failed to receive") {
+            Err(ErrorKind::ResultTooLarge { count, limit }) => {
+                assert_eq!(5, count);
+                assert_eq!(3, limit);
+            }
+            _ => panic!("Limit is not applied"),
+        }
+
+        // Other listing actions have the same limit
+        let client = DatastoreClient::new(sender.clone());
+        assert_eq!(3, client.list_values("/root/large".to_string(), ListType::All).expect("Failed to list").len());
+        let result = client.list_values("/root".to_string(), ListType::All);
+        assert_eq!(true, matches!(result, Err(ErrorKind::ResultTooLarge { count: 5, limit: 3 })));
+
+        for i in 0..3 {
+            client.create_table(format!("/root/large/sub{}", i)).expect("Failed to create table");
+        }
+        let result = client.list_tables("/root".to_string(), ListType::All);
+        assert_eq!(true, matches!(result, Err(ErrorKind::ResultTooLarge { count: 5, limit: 3 })));
+        assert_eq!(2, client.list_tables("/root".to_string(), ListType::OneLevel).expect("Failed to list").len());
+
+        let (tx, rx) = utilities::get_channel_for_export_json();
+        sender.send(DatabaseAction::ExportJson(tx, "/root/large".to_string())).expect("Failed to send the request");
+        let result = rx.recv().expect("Failed to receive");
+        assert_eq!(true, matches!(result, Err(ErrorKind::ResultTooLarge { count: 6, limit: 3 })));
+
+        let (tx, rx) = utilities::get_channel_for_export_json();
+        sender.send(DatabaseAction::ExportJson(tx, "/root/small".to_string())).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_ok());
+
+        // Unlimited by default
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        for i in 0..5 {
+            db.insert(
+                KeyType::Record(format!("/root/key{}", i)),
+                ValueType::RecordPointer("ok".to_string()),
+            )
+            .expect("Failed to insert");
+        }
+        let list = db
+            .list_keys(KeyType::Record("/root".to_string()), ListType::All)
+            .expect("Failed to list");
+        assert_eq!(5, list.len());
+    }
+
+    #[test]
+    fn test_pop_front_if() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        let key = KeyType::Record("/root/jobs".to_string());
+        db.push(key.clone(), "build:1".to_string()).expect("Failed to push");
+        db.push(key.clone(), "test:1".to_string()).expect("Failed to push");
+
+        // Front does not match, it remains
+        let value = db.pop_front_if(key.clone(), |x| x.starts_with("test:")).expect("Failed to pop");
+        assert_eq!(None, value);
+        assert_eq!(2, db.queue_len(key.clone()).unwrap());
+
+        let value = db.pop_front_if(key.clone(), |x| x.starts_with("build:")).expect("Failed to pop");
+        assert_eq!(Some("build:1".to_string()), value);
+
+        let value = db.pop_front_if(key.clone(), |x| x.starts_with("test:")).expect("Failed to pop");
+        assert_eq!(Some("test:1".to_string()), value);
+
+        // Queue is removed once it is empty
+        assert_eq!(true, db.pop_front_if(key.clone(), |_| true).is_err());
+
+        // Check on the built-in server
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Push(tx, "/root/jobs".to_string(), "deploy:1".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to push");
+
+        let (tx, rx) = utilities::get_channel_for_pop_if();
+        let action = DatabaseAction::PopIf(tx, "/root/jobs".to_string(), Box::new(|x| x.ends_with(":2")));
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(None, rx.recv().expect("Failed to receive").expect("Failed to pop"));
+
+        let (tx, rx) = utilities::get_channel_for_pop_if();
+        let action = DatabaseAction::PopIf(tx, "/root/jobs".to_string(), Box::new(|x| x.ends_with(":1")));
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(
+            Some("deploy:1".to_string()),
+            rx.recv().expect("Failed to receive").expect("Failed to pop")
+        );
+    }
+
+    #[test]
+    fn test_get_with_source() {
+        let config = crate::config::Builder::new()
+            .read_through_loader(|key| Some(format!("loaded {}", key)))
+            .build();
+        let (sender, _) = utilities::start_datastore_with_config("root".to_string(), None, None, &config);
+
+        for expected in [Source::Loader, Source::Cache] {
+            let (tx, rx) = utilities::get_channel_for_get_with_source();
+            let action = DatabaseAction::GetWithSource(tx, "/root/status".to_string());
+            sender.send(action).expect("Failed to send the request");
+            let (value, source) = rx.recv().expect("Failed to receive").expect("Failed to get");
+            assert_eq!(ValueType::RecordPointer("loaded /root/status".to_string()), value);
+            assert_eq!(expected, source);
+        }
+
+        // Without loader the value is coming from database only
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+        let (tx, rx) = utilities::get_channel_for_get_with_source();
+        let action = DatabaseAction::GetWithSource(tx, "/root/status".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_err());
+    }
+
+    #[test]
+    fn test_sharded_counter() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_sharded_counter();
+        let action = DatabaseAction::ShardedSum(tx, "/root/visits".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(0, rx.recv().expect("Failed to receive").expect("Failed to sum"));
+
+        let mut threads = Vec::new();
+        for _ in 0..4 {
+            let sender = sender.clone();
+            threads.push(std::thread::spawn(move || {
+                for _ in 0..25 {
+                    let (tx, rx) = utilities::get_channel_for_sharded_counter();
+                    let action = DatabaseAction::ShardedIncrement(tx, "/root/visits".to_string(), 8, 2);
+                    sender.send(action).expect("Failed to send the request");
+                    rx.recv().expect("Failed to receive").expect("Failed to increment");
+                }
+            }));
+        }
+        for thread in threads {
+            thread.join().expect("Failed to join thread");
+        }
+
+        let (tx, rx) = utilities::get_channel_for_sharded_counter();
+        let action = DatabaseAction::ShardedSum(tx, "/root/visits".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(200, rx.recv().expect("Failed to receive").expect("Failed to sum"));
+
+        // Increments are distributed on shards
+        let (tx, rx) = utilities::get_channel_for_list();
+        let action = DatabaseAction::ListKeys(tx, "/root/visits".to_string(), ListType::OneLevel);
+        sender.send(action).expect("Failed to send the request");
+        let shards = rx.recv().expect("Failed to receive").expect("Failed to list");
+        assert_eq!(true, shards.len() > 1);
+        assert_eq!(true, shards.len() <= 8);
+
+        let (tx, rx) = utilities::get_channel_for_sharded_counter();
+        let action = DatabaseAction::ShardedIncrement(tx, "/root/visits".to_string(), 0, 1);
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_err());
+
+        // Sum of the shards overflows
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        for i in 0..2 {
+            db.insert(
+                KeyType::Record(format!("/root/big/shard{}", i)),
+                ValueType::RecordPointer(i64::MAX.to_string()),
+            )
+            .expect("Failed to insert");
+        }
+        let result = db.sharded_sum(KeyType::Record("/root/big".to_string()));
+        assert_eq!(true, matches!(result, Err(ErrorKind::TypeError(_))));
+
+        // Numeric shard is counted, sibling records that are not shards are skipped
+        db.insert(KeyType::record("/root/hits/shard0"), ValueType::IntPointer(5)).expect("Failed to insert");
+        db.increment(KeyType::record("/root/hits/shard0"), 2).expect("Failed to increment");
+        db.insert(KeyType::record("/root/hits/shard1"), ValueType::RecordPointer("3".to_string())).expect("Failed to insert");
+        db.insert(KeyType::record("/root/hits/shardname"), ValueType::RecordPointer("visits".to_string())).expect("Failed to insert");
+        db.insert(KeyType::record("/root/hits/shard"), ValueType::RecordPointer("none".to_string())).expect("Failed to insert");
+        assert_eq!(10, db.sharded_sum(KeyType::record("/root/hits")).expect("Failed to sum"));
+
+        // Shard that is not a number is a type error like for increment
+        db.insert(KeyType::record("/root/hits/shard2"), ValueType::RecordPointer("x".to_string())).expect("Failed to insert");
+        let result = db.sharded_sum(KeyType::record("/root/hits"));
+        assert_eq!(true, matches!(result, Err(ErrorKind::TypeError(_))));
+    }
+
+    #[test]
+    fn test_get_or_insert() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/existing".to_string(), "old".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set the value");
+
+        // Existing value is returned
+        let (tx, rx) = utilities::get_channel_for_get();
+        let action = DatabaseAction::GetOrInsert(tx, "/root/existing".to_string(), "default".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(
+            ValueType::RecordPointer("old".to_string()),
+            rx.recv().expect("Failed to receive").expect("Failed to get")
+        );
+
+        // Missing value is created
+        let (tx, rx) = utilities::get_channel_for_get();
+        let action = DatabaseAction::GetOrInsert(tx, "/root/new".to_string(), "default".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(
+            ValueType::RecordPointer("default".to_string()),
+            rx.recv().expect("Failed to receive").expect("Failed to get")
+        );
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        let action = DatabaseAction::Get(tx, "/root/new".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(
+            ValueType::RecordPointer("default".to_string()),
+            rx.recv().expect("Failed to receive").expect("Failed to get")
+        );
+
+        // Invalid key
+        let (tx, rx) = utilities::get_channel_for_get();
+        let action = DatabaseAction::GetOrInsert(tx, "/other/new".to_string(), "default".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_err());
+    }
+
+    #[test]
+    fn test_copy_prefix() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        let template = [
+            ("/root/template/name", "default"),
+            ("/root/template/limits/cpu", "2"),
+            ("/root/template/limits/memory", "4G"),
+            ("/root/template/network/dns/primary", "10.0.0.1"),
+        ];
+        for (key, value) in template {
+            db.insert(
+                KeyType::Record(key.to_string()),
+                ValueType::RecordPointer(value.to_string()),
+            )
+            .expect("Failed to insert");
+        }
+        db.insert(
+            KeyType::Record("/root/templates/other".to_string()),
+            ValueType::RecordPointer("not copied".to_string()),
+        )
+        .expect("Failed to insert");
+
+        let copied = db.copy_prefix("/root/template", "/root/tenants/acme").expect("Failed to copy");
+        assert_eq!(4, copied);
+
+        for (key, value) in template {
+            let new_key = key.replace("/root/template", "/root/tenants/acme");
+            assert_eq!(
+                ValueType::RecordPointer(value.to_string()),
+                db.get(KeyType::Record(new_key)).expect("Key is not copied")
+            );
+
+            // Template remains
+            assert_eq!(
+                ValueType::RecordPointer(value.to_string()),
+                db.get(KeyType::Record(key.to_string())).expect("Template is changed")
+            );
+        }
+
+        let list = db
+            .list_keys(KeyType::Record("/root/tenants/acme".to_string()), ListType::All)
+            .expect("Failed to list");
+        assert_eq!(4, list.len());
+
+        // Overlapping prefixes and existing destination
+        assert_eq!(true, db.copy_prefix("/root/template", "/root/template/copy").is_err());
+        assert_eq!(true, db.copy_prefix("/root/template/limits", "/root/template").is_err());
+        assert_eq!(true, db.copy_prefix("/root/template", "/root/tenants/acme").is_err());
+    }
+
+    #[test]
+    fn test_queue_push_pop_actions() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/status".to_string(), "ok".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        // Record and queue cannot share a key
+        let (tx, rx) = utilities::get_channel_for_push();
+        let action = DatabaseAction::Push(tx, "/root/status".to_string(), "item".to_string());
+        sender.send(action).expect("Failed to send the request");
+        let result = rx.recv().expect("Failed to receive");
+        assert_eq!(true, matches!(result, Err(ErrorKind::InvalidKey(_))));
+
+        for item in ["first", "second"] {
+            let (tx, rx) = utilities::get_channel_for_push();
+            let action = DatabaseAction::Push(tx, "/root/jobs".to_string(), item.to_string());
+            sender.send(action).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to push");
+        }
+
+        for item in ["first", "second"] {
+            let (tx, rx) = utilities::get_channel_for_pop();
+            sender.send(DatabaseAction::Pop(tx, "/root/jobs".to_string())).expect("Failed to send the request");
+            let value = rx.recv().expect("Failed to receive").expect("Failed to pop");
+            assert_eq!(Some(item.to_string()), value);
+        }
+
+        let (tx, rx) = utilities::get_channel_for_pop();
+        sender.send(DatabaseAction::Pop(tx, "/root/jobs".to_string())).expect("Failed to send the request");
+        assert_eq!(None, rx.recv().expect("Failed to receive").expect("Failed to pop"));
+    }
+
+    #[test]
+    fn test_queue_peek() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        for item in ["first", "second", "third"] {
+            let (tx, rx) = utilities::get_channel_for_push();
+            let action = DatabaseAction::Push(tx, "/root/queues/jobs".to_string(), item.to_string());
+            sender.send(action).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to push");
+        }
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/queues/status".to_string(), "ok".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        let peek = |action: fn(_, String) -> DatabaseAction, key: &str| {
+            let (tx, rx) = utilities::get_channel_for_peek();
+            sender.send(action(tx, key.to_string())).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+
+        // Peek does not remove the item
+        for _ in 0..2 {
+            let front = peek(DatabaseAction::Peek, "/root/queues/jobs").expect("Failed to peek");
+            assert_eq!(Some("first".to_string()), front);
+        }
+        let back = peek(DatabaseAction::PeekBack, "/root/queues/jobs").expect("Failed to peek");
+        assert_eq!(Some("third".to_string()), back);
+
+        assert_eq!(None, peek(DatabaseAction::Peek, "/root/queues/missing").expect("Failed to peek"));
+
+        let result = peek(DatabaseAction::Peek, "/root/queues/status");
+        assert_eq!(true, matches!(result, Err(ErrorKind::InvalidKey(_))));
+        let result = peek(DatabaseAction::PeekBack, "/root/queues");
+        assert_eq!(true, matches!(result, Err(ErrorKind::InvalidKey(_))));
+
+        let (tx, rx) = utilities::get_channel_for_pop();
+        sender.send(DatabaseAction::Pop(tx, "/root/queues/jobs".to_string())).expect("Failed to send the request");
+        let value = rx.recv().expect("Failed to receive").expect("Failed to pop");
+        assert_eq!(Some("first".to_string()), value);
+    }
+
+    #[test]
+    fn test_queue_len() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        for i in 0..3 {
+            let (tx, rx) = utilities::get_channel_for_push();
+            let action = DatabaseAction::Push(tx, "/root/work".to_string(), format!("item{}", i));
+            sender.send(action).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to push");
+        }
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/status".to_string(), "ok".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        let queue_len = |key: &str| {
+            let (tx, rx) = utilities::get_channel_for_queue_len();
+            sender.send(DatabaseAction::QueueLen(tx, key.to_string())).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+
+        assert_eq!(3, queue_len("/root/work").expect("Failed to get length"));
+
+        let result = queue_len("/root/status");
+        assert_eq!(true, matches!(result, Err(ErrorKind::NotFound(_))));
+        let result = queue_len("/root/missing");
+        assert_eq!(true, matches!(result, Err(ErrorKind::NotFound(_))));
+    }
+
+    #[test]
+    fn test_bounded_queue() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let push_bounded = |key: &str, value: &str, max_len: usize, policy: DropPolicy| {
+            let (tx, rx) = utilities::get_channel_for_push_bounded();
+            let action = DatabaseAction::PushBounded(tx, key.to_string(), value.to_string(), max_len, policy);
+            sender.send(action).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+        let push = |key: &str, value: &str| {
+            let (tx, rx) = utilities::get_channel_for_push();
+            sender.send(DatabaseAction::Push(tx, key.to_string(), value.to_string())).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+        let pop_all = |key: &str| {
+            let mut items = Vec::new();
+            loop {
+                let (tx, rx) = utilities::get_channel_for_pop();
+                sender.send(DatabaseAction::Pop(tx, key.to_string())).expect("Failed to send the request");
+                match rx.recv().expect("Failed to receive") {
+                    Ok(Some(value)) => items.push(value),
+                    _ => break,
+                }
+            }
+            return items;
+        };
+
+        // Reject new items
+        assert_eq!(0, push_bounded("/root/reject", "1", 2, DropPolicy::RejectNew).unwrap());
+        assert_eq!(0, push_bounded("/root/reject", "2", 2, DropPolicy::RejectNew).unwrap());
+        let result = push_bounded("/root/reject", "3", 2, DropPolicy::RejectNew);
+        assert_eq!(true, matches!(result, Err(ErrorKind::QueueFull)));
+
+        // Stored capacity is honored by plain push too
+        assert_eq!(true, matches!(push("/root/reject", "4"), Err(ErrorKind::QueueFull)));
+        assert_eq!(vec!["1".to_string(), "2".to_string()], pop_all("/root/reject"));
+
+        // Drop oldest items
+        for i in 1..=3 {
+            push_bounded("/root/drop", &i.to_string(), 3, DropPolicy::DropOldest).unwrap();
+        }
+        assert_eq!(1, push_bounded("/root/drop", "4", 3, DropPolicy::DropOldest).unwrap());
+        push("/root/drop", "5").expect("Failed to push");
+        assert_eq!(vec!["3".to_string(), "4".to_string(), "5".to_string()], pop_all("/root/drop"));
+
+        // Queue was emptied and removed, new queue with the same key is unbounded
+        for i in 1..=5 {
+            push("/root/drop", &i.to_string()).expect("Failed to push");
+        }
+
+        // Limit of bounded push applies to the existing unbounded queue for that push only
+        assert_eq!(3, push_bounded("/root/drop", "6", 3, DropPolicy::DropOldest).unwrap());
+        let result = push_bounded("/root/drop", "7", 3, DropPolicy::RejectNew);
+        assert_eq!(true, matches!(result, Err(ErrorKind::QueueFull)));
+        push("/root/drop", "8").expect("Failed to push");
+        assert_eq!(
+            vec!["4".to_string(), "5".to_string(), "6".to_string(), "8".to_string()],
+            pop_all("/root/drop")
+        );
+
+        let result = push_bounded("/root/zero", "1", 0, DropPolicy::DropOldest);
+        assert_eq!(true, matches!(result, Err(ErrorKind::QueueFull)));
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        // Several clients try to take the lock at the same time, only one can win
+        let mut handlers = Vec::new();
+        for i in 0..8 {
+            let sender = sender.clone();
+            handlers.push(std::thread::spawn(move || {
+                let (tx, rx) = utilities::get_channel_for_compare_and_swap();
+                let action = DatabaseAction::CompareAndSwap(tx, "/root/lock".to_string(), None, format!("node{}", i));
+                sender.send(action).expect("Failed to send the request");
+                return rx.recv().expect("Failed to receive").expect("Failed to swap");
+            }));
+        }
+        let winners = handlers
+            .into_iter()
+            .map(|handler| handler.join().expect("Thread failed"))
+            .filter(|swapped| *swapped)
+            .count();
+        assert_eq!(1, winners);
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/lock".to_string())).expect("Failed to send the request");
+        let owner = match rx.recv().expect("Failed to receive").expect("Failed to get") {
+            ValueType::RecordPointer(owner) => owner,
+            _ => panic!("Record is expected"),
+        };
+
+        let cas = |expected: Option<&str>, new: &str| {
+            let (tx, rx) = utilities::get_channel_for_compare_and_swap();
+            let action = DatabaseAction::CompareAndSwap(
+                tx,
+                "/root/lock".to_string(),
+                expected.map(|e| e.to_string()),
+                new.to_string(),
+            );
+            sender.send(action).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+
+        assert_eq!(false, cas(Some("nobody"), "free").unwrap());
+        assert_eq!(true, cas(Some(&owner), "free").unwrap());
+        assert_eq!(false, cas(Some(&owner), "free").unwrap());
+
+        let (tx, rx) = utilities::get_channel_for_compare_and_swap();
+        let action = DatabaseAction::CompareAndSwap(tx, "/other/lock".to_string(), None, "x".to_string());
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_err());
+    }
+
+    #[test]
+    fn test_get_set() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let get_set = |key: &str, value: &str| {
+            let (tx, rx) = utilities::get_channel_for_get_set();
+            sender.send(DatabaseAction::GetSet(tx, key.to_string(), value.to_string())).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+
+        assert_eq!(None, get_set("/root/cluster/leader", "node1").expect("Failed to swap"));
+        assert_eq!(
+            Some(ValueType::RecordPointer("node1".to_string())),
+            get_set("/root/cluster/leader", "node2").expect("Failed to swap")
+        );
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/cluster/leader".to_string())).expect("Failed to send the request");
+        assert_eq!(
+            ValueType::RecordPointer("node2".to_string()),
+            rx.recv().expect("Failed to receive").expect("Failed to get")
+        );
+
+        assert_eq!(true, get_set("/other/leader", "node1").is_err());
+    }
+
+    #[test]
+    fn test_exists() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx, "/root/status/sub1".to_string(), "ok".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        let (tx, rx) = utilities::get_channel_for_push();
+        sender.send(DatabaseAction::Push(tx, "/root/jobs".to_string(), "job1".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to push");
+
+        let exists = |key: KeyType| {
+            let (tx, rx) = utilities::get_channel_for_exists();
+            sender.send(DatabaseAction::Exists(tx, key)).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+
+        assert_eq!(true, exists(KeyType::Record("/root/status/sub1".to_string())).unwrap());
+        assert_eq!(true, exists(KeyType::Table("/root/status".to_string())).unwrap());
+        assert_eq!(true, exists(KeyType::Queue("/root/jobs".to_string())).unwrap());
+        assert_eq!(false, exists(KeyType::Record("/root/status/sub2".to_string())).unwrap());
+        assert_eq!(false, exists(KeyType::Record("/root/missing/sub1".to_string())).unwrap());
+
+        // Type mismatch
+        let result = exists(KeyType::Record("/root/status".to_string()));
+        assert_eq!(true, matches!(result, Err(ErrorKind::InvalidKey(_))));
+        let result = exists(KeyType::Record("/root/jobs".to_string()));
+        assert_eq!(true, matches!(result, Err(ErrorKind::InvalidKey(_))));
+
+        assert_eq!(true, exists(KeyType::Record("/other/status".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_set_batch() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let pairs = (0..1000)
+            .map(|i| (format!("/root/bulk/key{}", i), format!("value{}", i)))
+            .collect();
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::SetBatch(tx, pairs)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set batch");
+
+        let (tx, rx) = utilities::get_channel_for_list();
+        let action = DatabaseAction::ListKeys(tx, "/root/bulk".to_string(), ListType::All);
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(1000, rx.recv().expect("Failed to receive").expect("Failed to list").len());
+
+        // Invalid key is reported with its index and nothing is inserted
+        let pairs = vec![
+            ("/root/other/key1".to_string(), "value1".to_string()),
+            ("/wrong/key2".to_string(), "value2".to_string()),
+        ];
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::SetBatch(tx, pairs)).expect("Failed to send the request");
+        let result = rx.recv().expect("Failed to receive");
+        assert_eq!(true, matches!(result, Err(ErrorKind::BatchFailed { index: 1, .. })));
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/other/key1".to_string())).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_err());
+    }
+
+    #[test]
+    fn test_get_batch() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let pairs = (0..3).map(|i| (format!("/root/cache/key{}", i), format!("value{}", i))).collect();
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::SetBatch(tx, pairs)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set batch");
+
+        let keys = vec![
+            "/root/cache/key0".to_string(),
+            "/root/cache/missing".to_string(),
+            "/root/cache/key2".to_string(),
+            "/other/key".to_string(),
+        ];
+        let (tx, rx) = utilities::get_channel_for_get_batch();
+        sender.send(DatabaseAction::GetBatch(tx, keys.clone())).expect("Failed to send the request");
+        let results = rx.recv().expect("Failed to receive");
+
+        assert_eq!(keys, results.iter().map(|(key, _)| key.clone()).collect::<Vec<String>>());
+        assert_eq!(ValueType::RecordPointer("value0".to_string()), *results[0].1.as_ref().unwrap());
+        assert_eq!(true, results[1].1.is_err());
+        assert_eq!(ValueType::RecordPointer("value2".to_string()), *results[2].1.as_ref().unwrap());
+        assert_eq!(true, results[3].1.is_err());
+    }
+
+    #[test]
+    fn test_rename() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let pairs = vec![
+            ("/root/app/config/port".to_string(), "8080".to_string()),
+            ("/root/app/config/tls/cert".to_string(), "cert.pem".to_string()),
+            ("/root/app/name".to_string(), "demo".to_string()),
+        ];
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::SetBatch(tx, pairs)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set batch");
+
+        let (tx, rx) = utilities::get_channel_for_push();
+        sender.send(DatabaseAction::Push(tx, "/root/app/jobs".to_string(), "job1".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to push");
+
+        let rename = |from: KeyType, to: KeyType| {
+            let (tx, rx) = utilities::get_channel_for_set();
+            sender.send(DatabaseAction::Rename(tx, from, to)).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+        let get = |key: &str| {
+            let (tx, rx) = utilities::get_channel_for_get();
+            sender.send(DatabaseAction::Get(tx, key.to_string())).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+
+        // Record is moved, missing tables are created
+        rename(
+            KeyType::Record("/root/app/name".to_string()),
+            KeyType::Record("/root/meta/app/name".to_string()),
+        )
+        .expect("Failed to rename");
+        assert_eq!(true, get("/root/app/name").is_err());
+        assert_eq!(ValueType::RecordPointer("demo".to_string()), get("/root/meta/app/name").unwrap());
+
+        // Queue is moved
+        rename(KeyType::Queue("/root/app/jobs".to_string()), KeyType::Queue("/root/app/tasks".to_string()))
+            .expect("Failed to rename");
+        let (tx, rx) = utilities::get_channel_for_pop();
+        sender.send(DatabaseAction::Pop(tx, "/root/app/tasks".to_string())).expect("Failed to send the request");
+        assert_eq!(Some("job1".to_string()), rx.recv().unwrap().unwrap());
+
+        // Table is moved with its subtree
+        rename(KeyType::Table("/root/app".to_string()), KeyType::Table("/root/apps/demo".to_string()))
+            .expect("Failed to rename");
+        assert_eq!(true, get("/root/app/config/port").is_err());
+        assert_eq!(ValueType::RecordPointer("8080".to_string()), get("/root/apps/demo/config/port").unwrap());
+        assert_eq!(ValueType::RecordPointer("cert.pem".to_string()), get("/root/apps/demo/config/tls/cert").unwrap());
+
+        // Failed renames leave the source untouched
+        let failures = vec![
+            (KeyType::Table("/root/apps".to_string()), KeyType::Table("/root/apps/demo/nested".to_string())),
+            (KeyType::Record("/root/apps/demo/config/port".to_string()), KeyType::Record("/root/apps/demo/config/tls/cert".to_string())),
+            (KeyType::Record("/root/apps/demo/config/port".to_string()), KeyType::Record("/other/port".to_string())),
+            (KeyType::Record("/root/apps/demo/config/port".to_string()), KeyType::Table("/root/port".to_string())),
+            (KeyType::Record("/root/missing".to_string()), KeyType::Record("/root/new".to_string())),
+        ];
+        for (from, to) in failures {
+            assert_eq!(true, rename(from, to).is_err());
+        }
+        assert_eq!(ValueType::RecordPointer("8080".to_string()), get("/root/apps/demo/config/port").unwrap());
+        assert_eq!(ValueType::RecordPointer("cert.pem".to_string()), get("/root/apps/demo/config/tls/cert").unwrap());
+    }
+
+    #[test]
+    fn test_rename_state() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        let record = |key: &str| KeyType::Record(key.to_string());
+
+        db.insert_versioned(record("/root/app/port"), ValueType::RecordPointer("80".to_string()), 0)
+            .expect("Failed to insert");
+        db.insert_versioned(record("/root/app/port"), ValueType::RecordPointer("8080".to_string()), 1)
+            .expect("Failed to insert");
+        db.push_bounded(record("/root/app/jobs"), "job1".to_string(), 1, DropPolicy::RejectNew)
+            .expect("Failed to push");
+
+        // Record keeps its version, queue keeps its capacity
+        db.rename(KeyType::Record("/root/app/port".to_string()), KeyType::Record("/root/app/listen".to_string()))
+            .expect("Failed to rename");
+        assert_eq!(2, db.version(record("/root/app/listen")).expect("Failed to get version"));
+
+        db.rename(KeyType::Queue("/root/app/jobs".to_string()), KeyType::Queue("/root/app/tasks".to_string()))
+            .expect("Failed to rename");
+        let result = db.push(record("/root/app/tasks"), "job2".to_string());
+        assert_eq!(true, matches!(result, Err(ErrorKind::QueueFull)));
+
+        // Table moves the state of its subtree
+        db.rename(KeyType::Table("/root/app".to_string()), KeyType::Table("/root/web".to_string()))
+            .expect("Failed to rename");
+        assert_eq!(2, db.version(record("/root/web/listen")).expect("Failed to get version"));
+        let result = db.push(record("/root/web/tasks"), "job2".to_string());
+        assert_eq!(true, matches!(result, Err(ErrorKind::QueueFull)));
+
+        // Nothing is left behind for the old keys
+        db.insert(record("/root/app/listen"), ValueType::RecordPointer("new".to_string())).expect("Failed to insert");
+        assert_eq!(1, db.version(record("/root/app/listen")).expect("Failed to get version"));
+        db.push(record("/root/app/tasks"), "job1".to_string()).expect("Failed to push");
+        db.push(record("/root/app/tasks"), "job2".to_string()).expect("Failed to push");
+
+        db.rename_prefix("/root/web", "/root/api").expect("Failed to rename");
+        assert_eq!(2, db.version(record("/root/api/listen")).expect("Failed to get version"));
+        let result = db.push(record("/root/api/tasks"), "job2".to_string());
+        assert_eq!(true, matches!(result, Err(ErrorKind::QueueFull)));
+    }
+
+    #[test]
+    fn test_count_keys() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let pairs = vec![
+            ("/root/metrics/cpu".to_string(), "10".to_string()),
+            ("/root/metrics/memory".to_string(), "20".to_string()),
+            ("/root/metrics/disk/sda".to_string(), "30".to_string()),
+            ("/root/metrics/disk/sdb".to_string(), "40".to_string()),
+        ];
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::SetBatch(tx, pairs)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set batch");
+
+        let (tx, rx) = utilities::get_channel_for_push();
+        sender.send(DatabaseAction::Push(tx, "/root/metrics/events".to_string(), "e1".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to push");
+
+        let count = |key: &str, level: ListType| {
+            let (tx, rx) = utilities::get_channel_for_count_keys();
+            sender.send(DatabaseAction::CountKeys(tx, key.to_string(), level)).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+        let list = |key: &str, level: ListType| {
+            let (tx, rx) = utilities::get_channel_for_list();
+            sender.send(DatabaseAction::ListKeys(tx, key.to_string(), level)).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive").expect("Failed to list").len();
+        };
+
+        assert_eq!(3, count("/root/metrics", ListType::OneLevel).unwrap());
+        assert_eq!(5, count("/root/metrics", ListType::All).unwrap());
+        assert_eq!(list("/root/metrics", ListType::OneLevel), count("/root/metrics", ListType::OneLevel).unwrap());
+        assert_eq!(list("/root/metrics", ListType::All), count("/root/metrics", ListType::All).unwrap());
+        assert_eq!(true, count("/root/missing", ListType::All).is_err());
+    }
+
+    #[test]
+    fn test_list_values() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let pairs = vec![
+            ("/root/export/name".to_string(), "demo".to_string()),
+            ("/root/export/config/port".to_string(), "8080".to_string()),
+        ];
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::SetBatch(tx, pairs)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set batch");
+
+        for item in ["job1", "job2"] {
+            let (tx, rx) = utilities::get_channel_for_push();
+            sender.send(DatabaseAction::Push(tx, "/root/export/jobs".to_string(), item.to_string())).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to push");
+        }
+
+        let list = |key: &str, level: ListType| {
+            let (tx, rx) = utilities::get_channel_for_list_values();
+            sender.send(DatabaseAction::ListValues(tx, key.to_string(), level)).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+
+        let pairs = list("/root/export", ListType::OneLevel).expect("Failed to list");
+        assert_eq!(2, pairs.len());
+        assert_eq!(true, pairs.contains(&(
+            KeyType::Record("/root/export/name".to_string()),
+            ValueType::RecordPointer("demo".to_string())
+        )));
+        assert_eq!(true, pairs.contains(&(
+            KeyType::Queue("/root/export/jobs".to_string()),
+            ValueType::QueuePointer(["job1".to_string(), "job2".to_string()].into())
+        )));
+
+        let pairs = list("/root/export", ListType::All).expect("Failed to list");
+        assert_eq!(3, pairs.len());
+        assert_eq!(true, pairs.contains(&(
+            KeyType::Record("/root/export/config/port".to_string()),
+            ValueType::RecordPointer("8080".to_string())
+        )));
+
+        assert_eq!(true, list("/root/missing", ListType::All).is_err());
+    }
+
+    #[test]
+    fn test_increment() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let mut handlers = Vec::new();
+        for _ in 0..4 {
+            let sender = sender.clone();
+            handlers.push(std::thread::spawn(move || {
+                for _ in 0..50 {
+                    let (tx, rx) = utilities::get_channel_for_increment();
+                    sender.send(DatabaseAction::Increment(tx, "/root/counter".to_string(), 1)).expect("Failed to send the request");
+                    rx.recv().expect("Failed to receive").expect("Failed to increment");
+                }
+            }));
+        }
+        for handler in handlers {
+            handler.join().expect("Thread failed");
+        }
+
+        let (tx, rx) = utilities::get_channel_for_increment();
+        sender.send(DatabaseAction::Increment(tx, "/root/counter".to_string(), -10)).expect("Failed to send the request");
+        assert_eq!(190, rx.recv().expect("Failed to receive").expect("Failed to increment"));
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx, "/root/name".to_string(), "demo".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        let (tx, rx) = utilities::get_channel_for_increment();
+        sender.send(DatabaseAction::Increment(tx, "/root/name".to_string(), 1)).expect("Failed to send the request");
+        let result = rx.recv().expect("Failed to receive");
+        assert_eq!(true, matches!(result, Err(ErrorKind::TypeError(_))));
+    }
+
+    #[test]
+    fn test_append() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let append = |key: &str, suffix: &str| {
+            let (tx, rx) = utilities::get_channel_for_set();
+            sender.send(DatabaseAction::Append(tx, key.to_string(), suffix.to_string())).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+
+        for line in ["line1\n", "line2\n", "line3\n"] {
+            append("/root/logs/app", line).expect("Failed to append");
+        }
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/logs/app".to_string())).expect("Failed to send the request");
+        assert_eq!(
+            ValueType::RecordPointer("line1\nline2\nline3\n".to_string()),
+            rx.recv().expect("Failed to receive").expect("Failed to get")
+        );
+
+        let (tx, rx) = utilities::get_channel_for_push();
+        sender.send(DatabaseAction::Push(tx, "/root/jobs".to_string(), "job1".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to push");
+
+        assert_eq!(true, matches!(append("/root/logs", "x"), Err(ErrorKind::InvalidKey(_))));
+        assert_eq!(true, matches!(append("/root/jobs", "x"), Err(ErrorKind::InvalidKey(_))));
+    }
+
+    #[test]
+    fn test_ttl() {
+        let config = crate::config::Builder::new().ttl_sweep_interval(std::time::Duration::from_millis(100)).build();
+        let (sender, _) = utilities::start_datastore_with_config("root".to_string(), None, None, &config);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::SetWithTtl(tx, "/root/cache/session".to_string(), "user1".to_string(), 1);
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::SetWithTtl(tx, "/root/cache/renewed".to_string(), "user2".to_string(), 1);
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        // Plain set removes the expiry time
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/cache/renewed".to_string(), "user3".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        let count = || {
+            let (tx, rx) = utilities::get_channel_for_count_keys();
+            sender.send(DatabaseAction::CountKeys(tx, "/root/cache".to_string(), ListType::All)).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive").expect("Failed to count");
+        };
+        let get = |key: &str| {
+            let (tx, rx) = utilities::get_channel_for_get();
+            sender.send(DatabaseAction::Get(tx, key.to_string())).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+
+        assert_eq!(ValueType::RecordPointer("user1".to_string()), get("/root/cache/session").unwrap());
+        assert_eq!(2, count());
+
+        // Sweeper removes the expired record without any request
+        std::thread::sleep(std::time::Duration::from_millis(1500));
+        assert_eq!(1, count());
+        assert_eq!(true, get("/root/cache/session").is_err());
+        assert_eq!(ValueType::RecordPointer("user3".to_string()), get("/root/cache/renewed").unwrap());
+    }
+
+    #[test]
+    fn test_ttl_lazy_expiration() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        let key = KeyType::Record("/root/token".to_string());
+
+        db.insert_with_ttl(key.clone(), ValueType::RecordPointer("abc".to_string()), std::time::Duration::from_millis(50))
+            .expect("Failed to insert");
+        assert_eq!(true, db.exists(key.clone()).unwrap());
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(true, db.get(key.clone()).is_err());
+        assert_eq!(false, db.exists(key.clone()).unwrap());
+        assert_eq!(1, db.increment(key.clone(), 1).unwrap());
+
+        // Insert removed the expiry time
+        assert_eq!(0, db.remove_expired().len());
+        assert_eq!(ValueType::RecordPointer("1".to_string()), db.get(key).unwrap());
+    }
+
+    #[test]
+    fn test_ttl_keys_in_sync() {
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        let record = |key: &str| KeyType::Record(key.to_string());
+        let ttl = std::time::Duration::from_millis(50);
+
+        // Too large time to live is rejected before anything is written
+        let result = db.insert_with_ttl(record("/root/forever"), ValueType::RecordPointer("x".to_string()), std::time::Duration::MAX);
+        assert_eq!(true, matches!(result, Err(ErrorKind::InvalidKey(_))));
+        assert_eq!(false, db.exists(record("/root/forever")).unwrap());
+
+        db.insert_with_ttl(record("/root/token"), ValueType::RecordPointer("abc".to_string()), ttl).expect("Failed to insert");
+        db.insert_with_ttl(record("/root/session/user"), ValueType::RecordPointer("joe".to_string()), ttl).expect("Failed to insert");
+        db.insert_with_ttl(record("/root/cache/item"), ValueType::RecordPointer("1".to_string()), ttl).expect("Failed to insert");
+
+        // Expiry time follows the record
+        db.rename(record("/root/token"), record("/root/auth/token")).expect("Failed to rename");
+        db.copy_prefix("/root/session", "/root/backup").expect("Failed to copy");
+
+        // Deleted table does not leave expiry time behind for a new record on the same key
+        db.delete_table(KeyType::Table("/root/cache".to_string())).expect("Failed to delete");
+        db.insert(record("/root/cache/item"), ValueType::RecordPointer("2".to_string())).expect("Failed to insert");
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let mut removed = db.remove_expired();
+        removed.sort();
+        assert_eq!(vec![record("/root/auth/token"), record("/root/backup/user"), record("/root/session/user")], removed);
+        assert_eq!(ValueType::RecordPointer("2".to_string()), db.get(record("/root/cache/item")).unwrap());
+    }
+
+    #[test]
+    fn test_record_meta() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let set = |key: &str, value: &str| {
+            let (tx, rx) = utilities::get_channel_for_set();
+            sender.send(DatabaseAction::Set(tx, key.to_string(), value.to_string())).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to set");
+        };
+        let get_meta = |key: &str| {
+            let (tx, rx) = utilities::get_channel_for_get_meta();
+            sender.send(DatabaseAction::GetMeta(tx, key.to_string())).expect("Failed to send the request");
+            return rx.recv().expect("Failed to receive");
+        };
+
+        set("/root/app/status", "starting");
+        let first = get_meta("/root/app/status").expect("Failed to get meta");
+        assert_eq!(first.created_at, first.updated_at);
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        set("/root/app/status", "running");
+        let second = get_meta("/root/app/status").expect("Failed to get meta");
+        assert_eq!(first.created_at, second.created_at);
+        assert_eq!(true, second.updated_at > first.updated_at);
+
+        // Metadata is moved by rename
+        let (tx, rx) = utilities::get_channel_for_set();
+        let action = DatabaseAction::Rename(tx, KeyType::Table("/root/app".to_string()), KeyType::Table("/root/service".to_string()));
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to rename");
+        assert_eq!(second, get_meta("/root/service/status").expect("Failed to get meta"));
+        assert_eq!(true, get_meta("/root/app/status").is_err());
+
+        // Re-created record has new creation time
+        let (tx, rx) = utilities::get_channel_for_delete();
+        sender.send(DatabaseAction::DeleteKey(tx, "/root/service/status".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to delete");
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        set("/root/service/status", "restarted");
+        let third = get_meta("/root/service/status").expect("Failed to get meta");
+        assert_eq!(true, third.created_at > second.created_at);
+
+        // Values are compared without metadata
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        db.insert(KeyType::Record("/root/a".to_string()), ValueType::RecordPointer("x".to_string())).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        db.insert(KeyType::Record("/root/b".to_string()), ValueType::RecordPointer("x".to_string())).unwrap();
+        assert_eq!(
+            db.get(KeyType::Record("/root/a".to_string())).unwrap(),
+            db.get(KeyType::Record("/root/b".to_string())).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let pairs = vec![
+            ("/root/status".to_string(), "ok".to_string()),
+            ("/root/network/dns".to_string(), "up".to_string()),
+            ("/root/network".to_string(), "record beside table".to_string()),
+        ];
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::SetBatch(tx, pairs)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set batch");
+
+        for item in ["job1", "job2"] {
+            let (tx, rx) = utilities::get_channel_for_push();
+            sender.send(DatabaseAction::Push(tx, "/root/network/jobs".to_string(), item.to_string())).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to push");
+        }
+
+        let (tx, rx) = utilities::get_channel_for_snapshot();
+        sender.send(DatabaseAction::Snapshot(tx)).expect("Failed to send the request");
+        let snapshot = rx.recv().expect("Failed to receive").expect("Failed to create snapshot");
+
+        // Snapshot survives serialization
+        let json = snapshot.to_json();
+        let snapshot = crate::datastore::types::DatabaseSnapshot::from_json(&json).expect("Failed to deserialize");
+        assert_eq!(Some(&"record beside table".to_string()), snapshot.root.records.get("network"));
+        assert_eq!(
+            Some(&vec!["job1".to_string(), "job2".to_string()]),
+            snapshot.root.tables["network"].queues.get("jobs")
+        );
+
+        // Restore drops the later changes
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx, "/root/status".to_string(), "changed".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Restore(tx, snapshot.clone())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to restore");
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/status".to_string())).expect("Failed to send the request");
+        assert_eq!(ValueType::RecordPointer("ok".to_string()), rx.recv().unwrap().unwrap());
+
+        let (tx, rx) = utilities::get_channel_for_pop();
+        sender.send(DatabaseAction::Pop(tx, "/root/network/jobs".to_string())).expect("Failed to send the request");
+        assert_eq!(Some("job1".to_string()), rx.recv().unwrap().unwrap());
+
+        // Snapshot of other database cannot be restored
+        let mut other = Database::new("other".to_string()).expect("Failed to allocate database");
+        let result = other.restore(snapshot);
+        assert_eq!(true, matches!(result, Err(ErrorKind::InvalidRoot(_))));
+    }
+
+    #[test]
+    fn test_persist() {
+        let path = std::path::PathBuf::from("/tmp/datastore-persist.json");
+        if path.exists() {
+            std::fs::remove_file(&path).expect("Failed to delete persisted file");
+        }
+
+        let config = crate::config::Builder::new().persist_path(path.clone()).build();
+
+        {
+            let (sender, handler) = utilities::start_datastore_with_config("root".to_string(), None, None, &config);
+
+            let (tx, rx) = utilities::get_channel_for_set();
+            sender.send(DatabaseAction::Set(tx, "/root/network/dns".to_string(), "up".to_string())).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to set");
+
+            for item in ["job1", "job2", "it's 'quoted'"] {
+                let (tx, rx) = utilities::get_channel_for_push();
+                sender.send(DatabaseAction::Push(tx, "/root/jobs".to_string(), item.to_string())).expect("Failed to send the request");
+                rx.recv().expect("Failed to receive").expect("Failed to push");
+            }
+
+            let (tx, rx) = utilities::get_channel_for_set();
+            sender.send(DatabaseAction::Flush(tx)).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to flush");
+
+            drop(sender);
+            handler.join().expect("Thread failed");
+        }
+
+        // New datastore loads the saved state
+        let (sender, _) = utilities::start_datastore_with_config("root".to_string(), None, None, &config);
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/network/dns".to_string())).expect("Failed to send the request");
+        assert_eq!(ValueType::RecordPointer("up".to_string()), rx.recv().unwrap().unwrap());
+
+        for item in ["job1", "job2", "it's 'quoted'"] {
+            let (tx, rx) = utilities::get_channel_for_pop();
+            sender.send(DatabaseAction::Pop(tx, "/root/jobs".to_string())).expect("Failed to send the request");
+            assert_eq!(Some(item.to_string()), rx.recv().unwrap().unwrap());
+        }
+
+        // Flush without path
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Flush(tx)).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_err());
+
+        // Broken file does not stop the datastore, it starts empty
+        std::fs::write(&path, "{ broken").expect("Failed to write persisted file");
+        let (sender, _) = utilities::start_datastore_with_config("root".to_string(), None, None, &config);
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/network/dns".to_string())).expect("Failed to send the request");
+        assert_eq!(true, matches!(rx.recv().expect("Failed to receive"), Err(ErrorKind::NotFound(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_pairs() {
+        let key = KeyType::Queue("/root/jobs".to_string());
+        let text = serde_json::to_string(&key).expect("Failed to serialize");
+        let parsed: KeyType = serde_json::from_str(&text).expect("Failed to deserialize");
+        assert_eq!(true, parsed.is_queue());
+        assert_eq!("/root/jobs", parsed.get_key());
+
+        let value = ValueType::QueuePointer(std::collections::VecDeque::from(vec!["a".to_string(), "b".to_string()]));
+        let text = serde_json::to_string(&value).expect("Failed to serialize");
+        let parsed: ValueType = serde_json::from_str(&text).expect("Failed to deserialize");
+        assert_eq!(value, parsed);
+
+        let error = ErrorKind::BatchFailed { index: 2, error: Box::new(ErrorKind::QueueFull) };
+        let text = serde_json::to_string(&error).expect("Failed to serialize");
+        let parsed: ErrorKind = serde_json::from_str(&text).expect("Failed to deserialize");
+        assert_eq!(true, matches!(parsed, ErrorKind::BatchFailed { index: 2, .. }));
+
+        // Table keys are strings, so JSON can be used
+        let mut sub = crate::datastore::types::Table::new();
+        sub.insert(KeyType::Record("count".to_string()), ValueType::IntPointer(5));
+        let mut table = crate::datastore::types::Table::new();
+        table.insert(KeyType::Record("status".to_string()), ValueType::RecordPointer("ok".to_string()));
+        table.insert(KeyType::Table("status".to_string()), ValueType::TablePointer(sub));
+        let text = serde_json::to_string(&table).expect("Failed to serialize");
+        let parsed: crate::datastore::types::Table = serde_json::from_str(&text).expect("Failed to deserialize");
+        assert_eq!(table, parsed);
+    }
+
+    #[test]
+    fn test_export_json() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx.clone(), "/root/status/sub1".to_string(), "ok".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Set(tx.clone(), "/root/status".to_string(), "degraded".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Set(tx.clone(), "/root/network/dns".to_string(), "up".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Push(tx.clone(), "/root/jobs".to_string(), "job1".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Push(tx.clone(), "/root/jobs".to_string(), "job2".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Push(tx.clone(), "/root/network".to_string(), "event".to_string())).expect("Failed to send the request");
+        drop(tx);
+        while let Ok(result) = rx.recv() {
+            result.expect("Failed to modify");
+        }
+
+        let (tx, rx) = utilities::get_channel_for_export_json();
+        sender.send(DatabaseAction::ExportJson(tx, "/root".to_string())).expect("Failed to send the request");
+        let json = rx.recv().expect("Failed to receive").expect("Failed to export");
+        let expected = serde_json::json!({
+            "status": "degraded",
+            "status/": { "sub1": "ok" },
+            "network/": { "dns": "up" },
+            "network[]": ["event"],
+            "jobs": ["job1", "job2"],
+        });
+        assert_eq!(expected, json);
+
+        let (tx, rx) = utilities::get_channel_for_export_json();
+        sender.send(DatabaseAction::ExportJson(tx, "/root/network".to_string())).expect("Failed to send the request");
+        assert_eq!(serde_json::json!({ "dns": "up" }), rx.recv().unwrap().unwrap());
+
+        let (tx, rx) = utilities::get_channel_for_export_json();
+        sender.send(DatabaseAction::ExportJson(tx, "/root/missing".to_string())).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_import_json() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let json = serde_json::json!({
+            "status": "degraded",
+            "status/": { "sub1": "ok", "enabled": true },
+            "network/": { "port": 53, "key": { "$bytes": "AQID" } },
+            "network[]": ["event"],
+            "jobs": ["job1", "job2"],
+            "empty": {},
+        });
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::ImportJson(tx, "/root".to_string(), json.clone())).expect("Failed to send the request");
+        assert_eq!(5, rx.recv().expect("Failed to receive").expect("Failed to import"));
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/status/enabled".to_string())).expect("Failed to send the request");
+        assert_eq!(ValueType::RecordPointer("true".to_string()), rx.recv().unwrap().unwrap());
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/network/port".to_string())).expect("Failed to send the request");
+        assert_eq!(ValueType::IntPointer(53), rx.recv().unwrap().unwrap());
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/network/key".to_string())).expect("Failed to send the request");
+        assert_eq!(ValueType::BytesPointer(vec![1, 2, 3]), rx.recv().unwrap().unwrap());
+
+        let (tx, rx) = utilities::get_channel_for_export_json();
+        sender.send(DatabaseAction::ExportJson(tx, "/root".to_string())).expect("Failed to send the request");
+        let mut expected = json.clone();
+        expected["status/"]["enabled"] = serde_json::json!("true");
+        assert_eq!(expected, rx.recv().unwrap().unwrap());
+
+        // Wrong types are rejected before any change
+        for value in [
+            serde_json::json!({ "new": "value", "bad": [1, 2] }),
+            serde_json::json!({ "new": "value", "bad": [["nested"]] }),
+            serde_json::json!({ "new": "value", "bad": null }),
+            serde_json::json!({ "new": "value", "bad": { "$bytes": "not base64!" } }),
+            serde_json::json!("value"),
+        ] {
+            let (tx, rx) = channel();
+            sender.send(DatabaseAction::ImportJson(tx, "/root".to_string(), value)).expect("Failed to send the request");
+            let result = rx.recv().expect("Failed to receive");
+            assert_eq!(true, matches!(result, Err(ErrorKind::TypeError(_))));
+        }
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx, "/root/new".to_string())).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().unwrap().is_err());
+
+        // Value that fails during write does not leave the others behind
+        let mut db = Database::new("root".to_string()).unwrap();
+        db.set_max_value_bytes(Some(8));
+        let json = serde_json::json!({ "a": "short", "b": "too long value", "jobs": ["job1"] });
+        assert_eq!(true, db.from_json(KeyType::Table("/root".to_string()), json).is_err());
+        assert_eq!(true, db.get(KeyType::Record("/root/a".to_string())).is_err());
+        assert_eq!(true, db.queue_len(KeyType::Record("/root/jobs".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let path = std::path::PathBuf::from("/tmp/datastore-shutdown.json");
+        if path.exists() {
+            std::fs::remove_file(&path).expect("Failed to delete persisted file");
+        }
+        let log_path = "/tmp/datastore-log10.txt".to_string();
+        if std::path::Path::new(&log_path).exists() {
+            std::fs::remove_file(&log_path).expect("Failed to delete temp log");
+        }
+
+        let config = crate::config::Builder::new()
+            .persist_path(path.clone())
+            .log_batch(100, std::time::Duration::from_secs(60))
+            .build();
+        let (logger_sender, _) = crate::logger::utilities::start_logger(&log_path);
+        let (sender, handler) = utilities::start_datastore_with_config("root".to_string(), None, Some(logger_sender), &config);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx, "/root/status".to_string(), "ok".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Shutdown(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to shutdown");
+        handler.join().expect("Thread failed");
+
+        // Batched log item and the file are written before the reply
+        let content = std::fs::read_to_string(&log_path).expect("Failed to read log");
+        assert_eq!(true, content.contains("SetKey [ '/root/status', 'ok' ]"));
+        assert_eq!(true, path.exists());
+
+        // Thread is stopped, further requests are not accepted
+        let (tx, _) = utilities::get_channel_for_set();
+        assert_eq!(true, sender.send(DatabaseAction::Set(tx, "/root/status".to_string(), "nok".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_stats() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx.clone(), "/root/status/sub1".to_string(), "ok".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Set(tx.clone(), "/root/status/sub2".to_string(), "ok".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::SetBatch(tx.clone(), vec![
+            ("/root/network/dns".to_string(), "up".to_string()),
+            ("/root/network/dhcp".to_string(), "up".to_string()),
+        ])).expect("Failed to send the request");
+        sender.send(DatabaseAction::DeleteKey(tx.clone(), "/root/status/sub1".to_string())).expect("Failed to send the request");
+        drop(tx);
+        while rx.recv().is_ok() {}
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx.clone(), "/root/status/sub2".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Get(tx.clone(), "/root/status/sub1".to_string())).expect("Failed to send the request");
+        drop(tx);
+        while rx.recv().is_ok() {}
+
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::ListKeys(tx, "/root".to_string(), ListType::All)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to list");
+
+        let (tx, rx) = utilities::get_channel_for_push();
+        sender.send(DatabaseAction::Push(tx.clone(), "/root/jobs".to_string(), "job1".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Push(tx.clone(), "/root/network/events".to_string(), "event1".to_string())).expect("Failed to send the request");
+        drop(tx);
+        while rx.recv().is_ok() {}
+
+        let (tx, rx) = utilities::get_channel_for_stats();
+        sender.send(DatabaseAction::Stats(tx)).expect("Failed to send the request");
+        let stats = rx.recv().expect("Failed to receive");
+        assert_eq!(2, stats.total_queued);
+        assert_eq!(4, stats.sets);
+        assert_eq!(2, stats.gets);
+        assert_eq!(1, stats.deletes);
+        assert_eq!(1, stats.lists);
+        assert_eq!(0, stats.hook_executions);
+        assert_eq!(3, stats.keys);
+    }
+
+    #[test]
+    fn test_shared_database() {
+        let db = crate::datastore::shared::SharedDatabase::new("root".to_string()).expect("Failed to allocate");
+
+        // Writers and readers work from more threads on the same database
+        let mut threads = Vec::new();
+        for i in 0..4 {
+            let db = db.clone();
+            threads.push(std::thread::spawn(move || {
+                for j in 0..50 {
+                    let key = KeyType::Record(format!("/root/shared/worker{}/key{}", i, j));
+                    db.insert(key.clone(), ValueType::RecordPointer(j.to_string())).expect("Failed to insert");
+                    assert_eq!(ValueType::RecordPointer(j.to_string()), db.get(key).expect("Failed to get"));
+                    db.increment(KeyType::Record("/root/counter".to_string()), 1).expect("Failed to increment");
+                }
+            }));
+        }
+        for thread in threads {
+            thread.join().expect("Thread failed");
+        }
+
+        assert_eq!(ValueType::RecordPointer("200".to_string()), db.get(KeyType::Record("/root/counter".to_string())).expect("Failed to get"));
+        assert_eq!(200, db.count_keys(KeyType::Record("/root/shared".to_string()), ListType::All).expect("Failed to count"));
+
+        // Reads hold the lock together, it would block forever with exclusive lock
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+        let (other_db, other_barrier) = (db.clone(), barrier.clone());
+        let reader = std::thread::spawn(move || other_db.read(|_| other_barrier.wait()));
+        db.read(|_| barrier.wait());
+        reader.join().expect("Thread failed");
+
+        db.delete_table(KeyType::Table("/root/shared".to_string())).expect("Failed to delete");
+        assert_eq!(false, db.exists(KeyType::Record("/root/shared/worker0/key0".to_string())).expect("Failed to check"));
+    }
+
+    #[test]
+    fn test_watch() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (watch_tx, watch_rx) = channel();
+        sender.send(DatabaseAction::Watch(watch_tx, "/root/status".to_string())).expect("Failed to send the request");
+        let (gone_tx, gone_rx) = channel();
+        sender.send(DatabaseAction::Watch(gone_tx, "/root".to_string())).expect("Failed to send the request");
+        drop(gone_rx);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx.clone(), "/root/status/sub1".to_string(), "ok".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Set(tx.clone(), "/root/statusx/sub1".to_string(), "ok".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Set(tx.clone(), "/root/network/dns".to_string(), "up".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::DeleteKey(tx.clone(), "/root/status/sub1".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::DeleteKey(tx.clone(), "/root/status/sub1".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::Set(tx.clone(), "/root/status/sub2".to_string(), "ok".to_string())).expect("Failed to send the request");
+        sender.send(DatabaseAction::DeleteTable(tx.clone(), "/root/status".to_string())).expect("Failed to send the request");
+        drop(tx);
+        while rx.recv().is_ok() {}
+
+        let events: Vec<ChangeEvent> = watch_rx.try_iter().collect();
+        let expected = vec![
+            ChangeEvent { key: "/root/status/sub1".to_string(), kind: ChangeKind::Set, value: Some("ok".to_string()) },
+            ChangeEvent { key: "/root/status/sub1".to_string(), kind: ChangeKind::Delete, value: None },
+            ChangeEvent { key: "/root/status/sub2".to_string(), kind: ChangeKind::Set, value: Some("ok".to_string()) },
+            ChangeEvent { key: "/root/status".to_string(), kind: ChangeKind::Delete, value: None },
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn test_transaction() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx, "/root/account/alice".to_string(), "100".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set");
+
+        let (watch_tx, watch_rx) = channel();
+        sender.send(DatabaseAction::Watch(watch_tx, "/root/account".to_string())).expect("Failed to send the request");
+
+        // Failed compare rolls back the earlier operations
+        let (tx, rx) = utilities::get_channel_for_set();
+        let ops = vec![
+            TxnOp::Set("/root/account/bob".to_string(), "50".to_string()),
+            TxnOp::DeleteKey("/root/account/alice".to_string()),
+            TxnOp::CompareAndSwap("/root/account/alice".to_string(), Some("100".to_string()), "50".to_string()),
+        ];
+        sender.send(DatabaseAction::Transaction(tx, ops)).expect("Failed to send the request");
+        match rx.recv().expect("Failed to receive") {
+            Err(ErrorKind::BatchFailed { index, error }) => {
+                assert_eq!(2, index);
+                assert_eq!(true, matches!(*error, ErrorKind::CompareFailed(_)));
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx.clone(), "/root/account/alice".to_string())).expect("Failed to send the request");
+        assert_eq!(ValueType::RecordPointer("100".to_string()), rx.recv().expect("Failed to receive").expect("Key is rolled back"));
+        sender.send(DatabaseAction::Get(tx, "/root/account/bob".to_string())).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_err());
+        assert_eq!(0, watch_rx.try_iter().count());
+
+        // Successful transaction applies everything
+        let (tx, rx) = utilities::get_channel_for_set();
+        let ops = vec![
+            TxnOp::CompareAndSwap("/root/account/alice".to_string(), Some("100".to_string()), "50".to_string()),
+            TxnOp::Set("/root/account/bob".to_string(), "50".to_string()),
+            TxnOp::DeleteTable("/root/pending".to_string()),
+        ];
+        sender.send(DatabaseAction::Transaction(tx, ops.clone())).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_err());
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Transaction(tx, ops[..2].to_vec())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed transaction");
+
+        let (tx, rx) = utilities::get_channel_for_get();
+        sender.send(DatabaseAction::Get(tx.clone(), "/root/account/alice".to_string())).expect("Failed to send the request");
+        assert_eq!(ValueType::RecordPointer("50".to_string()), rx.recv().expect("Failed to receive").expect("Key not found"));
+        sender.send(DatabaseAction::Get(tx, "/root/account/bob".to_string())).expect("Failed to send the request");
+        assert_eq!(ValueType::RecordPointer("50".to_string()), rx.recv().expect("Failed to receive").expect("Key not found"));
+        assert_eq!(2, watch_rx.try_iter().count());
+    }
+
+    #[test]
+    fn test_client() {
+        let (sender, handle) = start_datastore("root".to_string(), None, None);
+        let client = DatastoreClient::new(sender);
+
+        client.set("/root/status/sub1".to_string(), "ok".to_string()).expect("Failed to set");
+        client.set("/root/status/sub2".to_string(), "nok".to_string()).expect("Failed to set");
+        assert_eq!(ValueType::RecordPointer("ok".to_string()), client.get("/root/status/sub1".to_string()).expect("Key not found"));
+        assert_eq!(true, client.get("/root/status/sub3".to_string()).is_err());
+        assert_eq!(true, client.exists(KeyType::Record("/root/status/sub2".to_string())).expect("Failed to check"));
+        assert_eq!(2, client.list("/root/status".to_string(), ListType::OneLevel).expect("Failed to list").len());
+
+        assert_eq!(5, client.increment("/root/counter".to_string(), 5).expect("Failed to increment"));
+        client.push("/root/queue".to_string(), "job1".to_string()).expect("Failed to push");
+        assert_eq!(Some("job1".to_string()), client.pop("/root/queue".to_string()).expect("Failed to pop"));
+
+        client.delete_table("/root/status".to_string()).expect("Failed to delete");
+        assert_eq!(true, client.get("/root/status/sub1".to_string()).is_err());
+        assert_eq!(3, client.stats().expect("Failed to get stats").sets);
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        client.sender().send(DatabaseAction::Shutdown(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to shutdown");
+        handle.join().expect("Failed to join");
+
+        match client.set("/root/status".to_string(), "ok".to_string()) {
+            Err(ErrorKind::InternalError(_)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_config_start() {
+        let path = "/tmp/datastore-log19.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+
+        let config = crate::config::Builder::new().hook_manager(true).logger_path(path.clone()).build();
+        let (sender, handles) = config.start("root".to_string());
+        let client = DatastoreClient::new(sender);
+
+        let (tx, rx) = utilities::get_channel_for_hook_set();
+        client.sender().send(DatabaseAction::HookSet(tx, "/root/status".to_string(), "http://127.0.0.1:3057".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Hook manager is not wired");
+
+        client.set("/root/network/dns".to_string(), "up".to_string()).expect("Failed to set");
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        client.sender().send(DatabaseAction::Shutdown(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to shutdown");
+        handles.datastore.join().expect("Failed to join");
+
+        let (logger_sender, logger) = handles.logger.expect("Logger is not started");
+        drop(logger_sender);
+        logger.join().expect("Failed to join");
+
+        let content = std::fs::read_to_string(&path).expect("Failed to read log");
+        assert_eq!(true, content.contains("/root/network/dns"));
+
+        let config = crate::config::Builder::new().build();
+        let (_, handles) = config.start("root".to_string());
+        assert_eq!(true, handles.hook_manager.is_none());
+        assert_eq!(true, handles.logger.is_none());
+    }
+
+    #[test]
+    fn test_key_validation() {
+        let mut db = Database::new("root".to_string()).unwrap();
+        let value = ValueType::RecordPointer("ok".to_string());
+
+        // Lenient is the default, empty segments are skipped
+        db.insert(KeyType::Record("/root//status".to_string()), value.clone()).expect("Failed to insert");
+        assert_eq!(value, db.get(KeyType::Record("/root/status".to_string())).expect("Key not found"));
+
+        db.set_key_validation(KeyValidation::NoEmptySegments);
+        match db.insert(KeyType::Record("/root//status".to_string()), value.clone()) {
+            Err(ErrorKind::InvalidKey(message)) => assert_eq!(true, message.contains("'/root'")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert_eq!(true, db.insert(KeyType::Record("/root/status/".to_string()), value.clone()).is_err());
+        assert_eq!(true, db.push(KeyType::Queue("/root//jobs".to_string()), "job1".to_string()).is_err());
+        assert_eq!(true, db.create_table(KeyType::Table("/root/network//dns".to_string())).is_err());
+        db.insert(KeyType::Record("/root/network/dns server".to_string()), value.clone()).expect("Failed to insert");
+
+        db.set_key_validation(KeyValidation::Restricted);
+        match db.insert(KeyType::Record("/root/network/dns server".to_string()), value.clone()) {
+            Err(ErrorKind::InvalidKey(message)) => assert_eq!(true, message.contains("'dns server'")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert_eq!(true, db.insert(KeyType::Record("/root/status\n".to_string()), value.clone()).is_err());
+        db.insert(KeyType::Record("/root/network/dns-1.local:53".to_string()), value.clone()).expect("Failed to insert");
+
+        // Existing keys can be still read and deleted, but not renamed to invalid key
+        assert_eq!(value, db.get(KeyType::Record("/root/network/dns server".to_string())).expect("Key not found"));
+        let result = db.rename(
+            KeyType::Record("/root/network/dns-1.local:53".to_string()),
+            KeyType::Record("/root/network/dns 1".to_string()),
+        );
+        assert_eq!(true, matches!(result, Err(ErrorKind::InvalidKey(_))));
+        assert_eq!(true, db.get(KeyType::Record("/root/network/dns-1.local:53".to_string())).is_ok());
+        db.delete_key(KeyType::Record("/root/network/dns server".to_string())).expect("Failed to delete");
+
+        let config = crate::config::Builder::new().key_validation(KeyValidation::Restricted).build();
+        let mut db = Database::from_config("root".to_string(), &config).unwrap();
+        assert_eq!(true, db.insert(KeyType::Record("/root/dns server".to_string()), value).is_err());
+    }
+
+    #[test]
+    fn test_remove() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+        let client = DatastoreClient::new(sender.clone());
+
+        client.set("/root/jobs/job1".to_string(), "backup".to_string()).expect("Failed to set");
+
+        let (watch_tx, watch_rx) = channel();
+        client.watch("/root/jobs".to_string(), watch_tx).expect("Failed to watch");
+
+        let (tx, rx) = utilities::get_channel_for_remove();
+        sender.send(DatabaseAction::Remove(tx.clone(), "/root/jobs/job1".to_string())).expect("Failed to send the request");
+        assert_eq!(ValueType::RecordPointer("backup".to_string()), rx.recv().expect("Failed to receive").expect("Failed to remove"));
+
+        sender.send(DatabaseAction::Remove(tx, "/root/jobs/job1".to_string())).expect("Failed to send the request");
+        assert_eq!(true, matches!(rx.recv().expect("Failed to receive"), Err(ErrorKind::NotFound(_))));
+
+        assert_eq!(true, client.get("/root/jobs/job1".to_string()).is_err());
+        assert_eq!(1, watch_rx.try_iter().count());
+        assert_eq!(2, client.stats().expect("Failed to get stats").deletes);
+
+        // Record and queue on the same path are removed by their own type
+        let mut db = Database::new("root".to_string()).unwrap();
+        db.push(KeyType::record("/root/x"), "item".to_string()).expect("Failed to push");
+        db.insert(KeyType::record("/root/x"), ValueType::RecordPointer("rec".to_string())).expect("Failed to insert");
+
+        let removed = db.remove(KeyType::queue("/root/x")).expect("Failed to remove");
+        assert_eq!(ValueType::QueuePointer(["item".to_string()].into_iter().collect()), removed);
+        assert_eq!(ValueType::RecordPointer("rec".to_string()), db.get(KeyType::record("/root/x")).expect("Key not found"));
+        assert_eq!(true, matches!(db.remove(KeyType::queue("/root/x")), Err(ErrorKind::NotFound(_))));
+        db.delete_key(KeyType::record("/root/x")).expect("Failed to delete");
+        assert_eq!(true, db.get(KeyType::record("/root/x")).is_err());
+
+        // Expired record is not found, like for get
+        let ttl = std::time::Duration::from_millis(10);
+        db.insert_with_ttl(KeyType::record("/root/temp"), ValueType::RecordPointer("old".to_string()), ttl).expect("Failed to insert");
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert_eq!(true, matches!(db.remove(KeyType::record("/root/temp")), Err(ErrorKind::NotFound(_))));
+        assert_eq!(vec![KeyType::record("/root/temp")], db.remove_expired());
+    }
+
+    #[test]
+    fn test_delete_key_pruning() {
+        let mut db = Database::new("root".to_string()).unwrap();
+        let value = ValueType::RecordPointer("ok".to_string());
+        db.insert(KeyType::Record("/root/a/b/c/d".to_string()), value.clone()).unwrap();
+        db.insert(KeyType::Record("/root/a/x".to_string()), value.clone()).unwrap();
+        db.insert(KeyType::Record("/root/top".to_string()), value.clone()).unwrap();
+
+        let pruned = db.delete_key_pruning(KeyType::Record("/root/a/b/c/d".to_string())).expect("Failed to delete");
+        assert_eq!(vec!["/root/a/b/c".to_string(), "/root/a/b".to_string()], pruned);
+        assert_eq!(false, db.exists(KeyType::Table("/root/a/b".to_string())).unwrap());
+        assert_eq!(true, db.exists(KeyType::Table("/root/a".to_string())).unwrap());
+
+        // Root table is kept even if it becomes empty
+        db.delete_key(KeyType::Record("/root/top".to_string())).unwrap();
+        let pruned = db.delete_key_pruning(KeyType::Record("/root/a/x".to_string())).expect("Failed to delete");
+        assert_eq!(vec!["/root/a".to_string()], pruned);
+        assert_eq!(0, db.list_keys(KeyType::Record("/root".to_string()), ListType::All).unwrap().len());
+        assert_eq!(true, matches!(db.delete_key_pruning(KeyType::Record("/root/a/x".to_string())), Err(ErrorKind::NotFound(_))));
+
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+        let client = DatastoreClient::new(sender.clone());
+        client.set("/root/tmp/job/step".to_string(), "ok".to_string()).expect("Failed to set");
+        client.set("/root/keep/job/step".to_string(), "ok".to_string()).expect("Failed to set");
+
+        let (tx, rx) = utilities::get_channel_for_delete();
+        sender.send(DatabaseAction::DeleteKeyWithPruning(tx.clone(), "/root/tmp/job/step".to_string(), true)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to delete");
+        sender.send(DatabaseAction::DeleteKeyWithPruning(tx, "/root/keep/job/step".to_string(), false)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to delete");
+
+        assert_eq!(false, client.exists(KeyType::Table("/root/tmp".to_string())).expect("Failed to check"));
+        assert_eq!(true, client.exists(KeyType::Table("/root/keep/job".to_string())).expect("Failed to check"));
+    }
+
+    #[test]
+    fn test_case_insensitive_keys() {
+        let config = crate::config::Builder::new().case_insensitive_keys(true).build();
+        let mut db = Database::from_config("Root".to_string(), &config).unwrap();
+        let value = ValueType::RecordPointer("ok".to_string());
+
+        db.insert(KeyType::Record("/Root/Status/DNS".to_string()), value.clone()).expect("Failed to insert");
+        db.insert(KeyType::Record("/root/status/dns".to_string()), ValueType::RecordPointer("up".to_string())).expect("Failed to insert");
+        assert_eq!(ValueType::RecordPointer("up".to_string()), db.get(KeyType::Record("/ROOT/STATUS/DNS".to_string())).expect("Key not found"));
+        assert_eq!(1, db.count_keys(KeyType::Record("/root".to_string()), ListType::All).expect("Failed to count"));
+
+        let list = db.list_keys(KeyType::Record("/Root/Status".to_string()), ListType::All).expect("Failed to list");
+        assert_eq!(vec![KeyType::Record("/root/status/dns".to_string())], list);
+
+        db.push(KeyType::Record("/root/Jobs".to_string()), "job1".to_string()).expect("Failed to push");
+        assert_eq!(1, db.queue_len(KeyType::Record("/root/jobs".to_string())).expect("Failed to get length"));
+
+        db.delete_key(KeyType::Record("/root/Status/Dns".to_string())).expect("Failed to delete");
+        assert_eq!(true, matches!(db.get(KeyType::Record("/root/status/dns".to_string())), Err(ErrorKind::NotFound(_))));
+        db.delete_table(KeyType::Table("/ROOT/STATUS".to_string())).expect("Failed to delete");
+
+        // Batch and pruning delete fold the keys too
+        let pairs = vec![
+            (KeyType::Record("/Root/Network/DNS".to_string()), value.clone()),
+            (KeyType::Record("/root/network/dhcp".to_string()), value.clone()),
+        ];
+        db.insert_many(pairs).expect("Failed to insert");
+        db.delete_key(KeyType::Record("/root/network/dhcp".to_string())).expect("Failed to delete");
+        let pruned = db.delete_key_pruning(KeyType::Record("/ROOT/NETWORK/dns".to_string())).expect("Failed to delete");
+        assert_eq!(vec!["/root/network".to_string()], pruned);
+
+        // Keys are kept as they are by default
+        let mut db = Database::new("root".to_string()).unwrap();
+        db.insert(KeyType::Record("/root/Status".to_string()), value.clone()).expect("Failed to insert");
+        db.insert(KeyType::Record("/root/status".to_string()), value.clone()).expect("Failed to insert");
+        assert_eq!(2, db.count_keys(KeyType::Record("/root".to_string()), ListType::All).expect("Failed to count"));
+    }
+
+    #[test]
+    fn test_size_estimate() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+        let client = DatastoreClient::new(sender.clone());
+
+        let empty = client.size_estimate().expect("Failed to estimate");
+        assert_eq!(crate::datastore::types::SizeReport::default(), empty);
+
+        client.set("/root/status/sub1".to_string(), "ok".to_string()).expect("Failed to set");
+        client.set("/root/status/sub2".to_string(), "nok".to_string()).expect("Failed to set");
+        client.push("/root/jobs".to_string(), "job1".to_string()).expect("Failed to push");
+        client.push("/root/jobs".to_string(), "job22".to_string()).expect("Failed to push");
+
+        let (tx, rx) = utilities::get_channel_for_size_estimate();
+        sender.send(DatabaseAction::SizeEstimate(tx)).expect("Failed to send the request");
+        let report = rx.recv().expect("Failed to receive");
+        assert_eq!(2, report.records);
+        assert_eq!(1, report.tables);
+        assert_eq!(1, report.queues);
+        assert_eq!(2, report.queue_items);
+        assert_eq!("sub1".len() + "sub2".len() + "status".len() + "jobs".len(), report.key_bytes);
+        assert_eq!("ok".len() + "nok".len() + "job1".len() + "job22".len(), report.value_bytes);
+        assert_eq!(true, report.total_bytes > report.key_bytes + report.value_bytes);
+    }
+
+    #[test]
+    fn test_clear() {
+        let path = "/tmp/datastore-log20.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+
+        let config = crate::config::Builder::new().logger_path(path.clone()).build();
+        let (sender, handles) = config.start("root".to_string());
+        let client = DatastoreClient::new(sender.clone());
+
+        client.set("/root/status/sub1".to_string(), "ok".to_string()).expect("Failed to set");
+        client.push("/root/jobs".to_string(), "job1".to_string()).expect("Failed to push");
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Clear(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to clear");
+
+        assert_eq!(0, client.list("/root".to_string(), ListType::All).expect("Failed to list").len());
+        assert_eq!(true, client.get("/root/status/sub1".to_string()).is_err());
+
+        // Database can be used after clear
+        client.set("/root/network".to_string(), "up".to_string()).expect("Failed to set");
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Shutdown(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to shutdown");
+        handles.datastore.join().expect("Failed to join");
+
+        let (logger_sender, logger) = handles.logger.expect("Logger is not started");
+        drop(logger_sender);
+        logger.join().expect("Failed to join");
+
+        let content = std::fs::read_to_string(&path).expect("Failed to read log");
+        assert_eq!(true, content.contains("ClearAll"));
+
+        // Replay wipes the state that was written before the clear
+        let mut db = Database::new("root".to_string()).unwrap();
+        db.replay_log(&path).expect("Failed to replay");
+        assert_eq!(true, db.get(KeyType::Record("/root/status/sub1".to_string())).is_err());
+        assert_eq!(None, db.pop(KeyType::Record("/root/jobs".to_string())).expect("Failed to pop"));
+        assert_eq!(ValueType::RecordPointer("up".to_string()), db.get(KeyType::Record("/root/network".to_string())).expect("Key not found"));
+    }
+
+    #[test]
+    fn test_list_tables() {
+        let (sender, _) = start_datastore("root".to_string(), None, None);
+        let client = DatastoreClient::new(sender.clone());
+
+        client.set("/root/status/sub1".to_string(), "ok".to_string()).expect("Failed to set");
+        client.set("/root/status/server/dns".to_string(), "ok".to_string()).expect("Failed to set");
+        client.create_table("/root/network".to_string()).expect("Failed to create table");
+        client.push("/root/jobs".to_string(), "job1".to_string()).expect("Failed to push");
+
+        let (tx, rx) = utilities::get_channel_for_list();
+        sender.send(DatabaseAction::ListTables(tx, "/root".to_string(), ListType::OneLevel)).expect("Failed to send the request");
+        let mut list = rx.recv().expect("Failed to receive").expect("Failed to list");
+        list.sort_by(|a, b| a.get_key().cmp(b.get_key()));
+        assert_eq!(
+            vec![
+                KeyType::Table("/root/network".to_string()),
+                KeyType::Table("/root/status".to_string()),
+            ],
+            list
+        );
+
+        let list = client.list_tables("/root".to_string(), ListType::All).expect("Failed to list");
+        assert_eq!(3, list.len());
+        assert_eq!(true, list.contains(&KeyType::Table("/root/status/server".to_string())));
+
+        // Child comes right after its parent
+        let parent = list.iter().position(|x| x.get_key() == "/root/status").unwrap();
+        assert_eq!(KeyType::Table("/root/status/server".to_string()), list[parent + 1]);
+
+        let list = client.list_tables("/root/network".to_string(), ListType::All).expect("Failed to list");
+        assert_eq!(0, list.len());
+
+        let result = client.list_tables("/root/missing".to_string(), ListType::All);
+        assert_eq!(true, matches!(result, Err(ErrorKind::NotFound(_))));
+    }
+
+    #[test]
+    fn test_key_constructors() {
+        let mut db = Database::new("root".to_string()).unwrap();
+
+        db.insert("/root/status/sub1".into(), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+        db.insert(KeyType::from("/root/status/sub2".to_string()), ValueType::RecordPointer("nok".to_string())).expect("Failed to insert");
+        db.push(KeyType::record("/root/jobs"), "job1".to_string()).expect("Failed to push");
+
+        assert_eq!(ValueType::RecordPointer("ok".to_string()), db.get(KeyType::record("/root/status/sub1")).expect("Key not found"));
+        assert_eq!(true, KeyType::from("/root/status/sub2").is_record());
+        assert_eq!(true, KeyType::table("/root/status").is_table());
+        assert_eq!(true, KeyType::queue("/root/jobs").is_queue());
+
+        let list = db.list_keys("/root".into(), ListType::All).expect("Failed to list");
+        assert_eq!(true, list.contains(&KeyType::queue("/root/jobs")));
+        assert_eq!(true, list.contains(&KeyType::record("/root/status/sub1")));
+
+        db.delete_table(KeyType::table("/root/status")).expect("Failed to delete table");
+        assert_eq!(true, db.get("/root/status/sub2".into()).is_err());
+    }
+
+    #[test]
+    fn test_value_display() {
+        let mut db = Database::new("root".to_string()).unwrap();
+
+        db.insert(KeyType::record("/root/status/sub1"), ValueType::RecordPointer("ok".to_string())).expect("Failed to insert");
+        db.insert(KeyType::record("/root/status/sub2"), ValueType::RecordPointer("nok".to_string())).expect("Failed to insert");
+        db.push(KeyType::record("/root/jobs"), "job1".to_string()).expect("Failed to push");
+        db.push(KeyType::record("/root/jobs"), "job2".to_string()).expect("Failed to push");
+
+        let value = db.get(KeyType::record("/root/status/sub1")).expect("Key not found");
+        assert_eq!("ok", value.to_string());
+        assert_eq!(Some("ok"), value.as_record());
+        assert_eq!(None, value.as_queue());
+
+        let pairs = db.list_pairs(KeyType::record("/root"), ListType::OneLevel).expect("Failed to list");
+        let (_, queue) = pairs.iter().find(|(key, _)| key.is_queue()).expect("Queue not found");
+        assert_eq!("[job1, job2]", queue.to_string());
+        assert_eq!(2, queue.as_queue().expect("Not a queue").len());
+        assert_eq!(None, queue.as_record());
+
+        let empty = ValueType::QueuePointer(std::collections::VecDeque::new());
+        assert_eq!("[]", empty.to_string());
+
+        let mut table = crate::datastore::types::Table::new();
+        table.insert(KeyType::record("sub1"), ValueType::RecordPointer("ok".to_string()));
+        table.insert(KeyType::queue("jobs"), empty.clone());
+        assert_eq!("<table: 2 entries>", ValueType::TablePointer(table).to_string());
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let mut db = Database::new("root".to_string()).unwrap();
+
+        db.insert(KeyType::record("/root/stats/errors"), ValueType::IntPointer(41)).expect("Failed to insert");
+        db.insert(KeyType::record("/root/stats/blob"), ValueType::BytesPointer(vec![0x00, 0xff, 0x10])).expect("Failed to insert");
+        db.insert(KeyType::record("/root/stats/name"), ValueType::RecordPointer("41".to_string())).expect("Failed to insert");
+
+        let value = db.get(KeyType::record("/root/stats/errors")).expect("Key not found");
+        assert_eq!(true, value.is_int());
+        assert_eq!(true, value.belongs_to_record());
+        assert_eq!(Some(41), value.as_int());
+        assert_eq!("41", value.get_value());
+        assert_eq!(false, ValueType::RecordPointer("41".to_string()) == value);
+
+        let value = db.get(KeyType::record("/root/stats/blob")).expect("Key not found");
+        assert_eq!(true, value.is_bytes());
+        assert_eq!(Some(&[0x00, 0xff, 0x10][..]), value.as_bytes());
+        assert_eq!(1, db.version(KeyType::record("/root/stats/blob")).expect("Failed to get version"));
+
+        // Increment keeps the numeric record numeric, binary record is refused
+        assert_eq!(42, db.increment(KeyType::record("/root/stats/errors"), 1).expect("Failed to increment"));
+        assert_eq!(ValueType::IntPointer(42), db.get(KeyType::record("/root/stats/errors")).expect("Key not found"));
+        let result = db.increment(KeyType::record("/root/stats/blob"), 1);
+        assert_eq!(true, matches!(result, Err(ErrorKind::TypeError(_))));
+        let result = db.append(KeyType::record("/root/stats/blob"), "x".to_string());
+        assert_eq!(true, matches!(result, Err(ErrorKind::TypeError(_))));
+
+        assert_eq!(true, db.verify().is_ok());
+        assert_eq!(3, db.size_estimate().records);
+        assert_eq!(Some(&3), db.key_depth_histogram().get(&2));
+
+        let json = db.to_json(KeyType::table("/root/stats")).expect("Failed to export");
+        assert_eq!(serde_json::json!(42), json["errors"]);
+        assert_eq!(serde_json::json!({ "$bytes": "AP8Q" }), json["blob"]);
+        assert_eq!(serde_json::json!("41"), json["name"]);
+
+        // JSON export is imported with the same types
+        let mut imported = Database::new("root".to_string()).unwrap();
+        imported.from_json(KeyType::table("/root/stats"), json).expect("Failed to import");
+        assert_eq!(ValueType::IntPointer(42), imported.get(KeyType::record("/root/stats/errors")).expect("Key not found"));
+        assert_eq!(ValueType::BytesPointer(vec![0x00, 0xff, 0x10]), imported.get(KeyType::record("/root/stats/blob")).expect("Key not found"));
+        assert_eq!(ValueType::RecordPointer("41".to_string()), imported.get(KeyType::record("/root/stats/name")).expect("Key not found"));
+
+        // Snapshot keeps the types
+        let snapshot = db.snapshot();
+        let mut restored = Database::new("root".to_string()).unwrap();
+        restored.restore(snapshot).expect("Failed to restore");
+        assert_eq!(ValueType::IntPointer(42), restored.get(KeyType::record("/root/stats/errors")).expect("Key not found"));
+        assert_eq!(ValueType::BytesPointer(vec![0x00, 0xff, 0x10]), restored.get(KeyType::record("/root/stats/blob")).expect("Key not found"));
+        assert_eq!(ValueType::RecordPointer("41".to_string()), restored.get(KeyType::record("/root/stats/name")).expect("Key not found"));
+
+        // Compare and swap matches the number by its text and keeps it numeric
+        let key = KeyType::record("/root/stats/errors");
+        assert_eq!(false, db.compare_and_swap(key.clone(), Some("41".to_string()), "50".to_string()).unwrap());
+        assert_eq!(true, db.compare_and_swap(key.clone(), Some("42".to_string()), "50".to_string()).unwrap());
+        assert_eq!(ValueType::IntPointer(50), db.get(key.clone()).expect("Key not found"));
+        assert_eq!(true, db.compare_and_swap(key.clone(), Some("50".to_string()), "none".to_string()).unwrap());
+        assert_eq!(ValueType::RecordPointer("none".to_string()), db.get(key).expect("Key not found"));
+    }
+
+    #[test]
+    fn test_delete_prefix() {
+        let path = "/tmp/datastore-log21.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+
+        let config = crate::config::Builder::new().logger_path(path.clone()).build();
+        let (sender, handles) = config.start("root".to_string());
+        let client = DatastoreClient::new(sender.clone());
+
+        client.set("/root/session/a1/user".to_string(), "joe".to_string()).expect("Failed to set");
+        client.set("/root/session/a1/ttl".to_string(), "60".to_string()).expect("Failed to set");
+        client.set("/root/session/b2".to_string(), "empty".to_string()).expect("Failed to set");
+        client.set("/root/sessions".to_string(), "2".to_string()).expect("Failed to set");
+        client.push("/root/session-queue".to_string(), "job1".to_string()).expect("Failed to push");
+        client.set("/root/status".to_string(), "ok".to_string()).expect("Failed to set");
+
+        let (watch_tx, watch_rx) = std::sync::mpsc::channel();
+        client.watch("/root/session".to_string(), watch_tx).expect("Failed to watch");
+
+        // Content of the table, the table itself remains
+        let (tx, rx) = utilities::get_channel_for_delete_prefix();
+        sender.send(DatabaseAction::DeletePrefix(tx, "/root/session/".to_string())).expect("Failed to send the request");
+        assert_eq!(4, rx.recv().expect("Failed to receive").expect("Failed to delete prefix"));
+        assert_eq!(true, client.exists(KeyType::table("/root/session")).expect("Failed to check"));
+        assert_eq!(true, client.get("/root/session/b2".to_string()).is_err());
+        assert_eq!(true, client.get("/root/sessions".to_string()).is_ok());
+
+        let mut events: Vec<String> = watch_rx.try_iter().map(|x| x.key).collect();
+        events.sort();
+        assert_eq!(vec!["/root/session/a1", "/root/session/b2"], events);
+
+        // Partial name matches more keys
+        assert_eq!(3, client.delete_prefix("/root/sess".to_string()).expect("Failed to delete prefix"));
+        assert_eq!(0, client.delete_prefix("/root/sess".to_string()).expect("Failed to delete prefix"));
+        assert_eq!(0, client.delete_prefix("/root/missing/table/".to_string()).expect("Failed to delete prefix"));
+        assert_eq!(vec![KeyType::record("/root/status")], client.list("/root".to_string(), ListType::All).expect("Failed to list"));
+
+        let result = client.delete_prefix("/ro".to_string());
+        assert_eq!(true, matches!(result, Err(ErrorKind::InvalidKey(_))));
+
+        client.set("/root/session/c3".to_string(), "new".to_string()).expect("Failed to set");
+
+        let (tx, rx) = utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Shutdown(tx)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to shutdown");
+        handles.datastore.join().expect("Failed to join");
+
+        let (logger_sender, logger) = handles.logger.expect("Logger is not started");
+        drop(logger_sender);
+        logger.join().expect("Failed to join");
+
+        // Replay applies the same removals
+        let mut db = Database::new("root".to_string()).unwrap();
+        db.replay_log(&path).expect("Failed to replay");
+        assert_eq!(true, db.get(KeyType::record("/root/sessions")).is_err());
+        assert_eq!(true, db.get(KeyType::record("/root/session/a1/user")).is_err());
+        assert_eq!(ValueType::RecordPointer("new".to_string()), db.get(KeyType::record("/root/session/c3")).expect("Key not found"));
+        assert_eq!(ValueType::RecordPointer("ok".to_string()), db.get(KeyType::record("/root/status")).expect("Key not found"));
+    }
+
+    #[test]
+    fn test_max_value_bytes() {
+        let config = crate::config::Builder::new().max_value_bytes(8).build();
+        assert_eq!(Some(8), config.max_value_bytes());
+        let mut db = Database::from_config("root".to_string(), &config).unwrap();
+
+        db.insert(KeyType::record("/root/status"), ValueType::RecordPointer("12345678".to_string())).expect("Failed to insert");
+        let result = db.insert(KeyType::record("/root/status"), ValueType::RecordPointer("123456789".to_string()));
+        assert_eq!(true, matches!(result, Err(ErrorKind::ValueTooLarge { size: 9, max: 8 })));
+        assert_eq!(ValueType::RecordPointer("12345678".to_string()), db.get(KeyType::record("/root/status")).expect("Key not found"));
+
+        let result = db.insert(KeyType::record("/root/blob"), ValueType::BytesPointer(vec![0; 9]));
+        assert_eq!(true, matches!(result, Err(ErrorKind::ValueTooLarge { size: 9, max: 8 })));
+        db.insert(KeyType::record("/root/counter"), ValueType::IntPointer(i64::MAX)).expect("Failed to insert");
+
+        // Too large item alone does not create the path
+        let result = db.push(KeyType::record("/root/new/jobs"), "123456789".to_string());
+        assert_eq!(true, matches!(result, Err(ErrorKind::ValueTooLarge { .. })));
+        assert_eq!(false, db.exists(KeyType::table("/root/new")).expect("Failed to check"));
+
+        // Queue is limited by the size of all items
+        db.push(KeyType::record("/root/jobs"), "job1".to_string()).expect("Failed to push");
+        db.push(KeyType::record("/root/jobs"), "job2".to_string()).expect("Failed to push");
+        let result = db.push(KeyType::record("/root/jobs"), "j".to_string());
+        assert_eq!(true, matches!(result, Err(ErrorKind::ValueTooLarge { size: 9, max: 8 })));
+        assert_eq!(2, db.queue_len(KeyType::record("/root/jobs")).expect("Failed to get length"));
+
+        // Dropped items make room
+        let dropped = db.push_bounded(KeyType::record("/root/jobs"), "job3".to_string(), 2, DropPolicy::DropOldest).expect("Failed to push");
+        assert_eq!(1, dropped);
+
+        let result = db.insert_many(vec![
+            (KeyType::record("/root/a"), ValueType::RecordPointer("ok".to_string())),
+            (KeyType::record("/root/b"), ValueType::RecordPointer("too long value".to_string())),
+        ]);
+        assert_eq!(true, matches!(result, Err(ErrorKind::BatchFailed { index: 1, .. })));
+        assert_eq!(true, db.get(KeyType::record("/root/a")).is_err());
+
+        // Unlimited by default
+        let mut db = Database::new("root".to_string()).unwrap();
+        db.insert(KeyType::record("/root/status"), ValueType::RecordPointer("x".repeat(1024))).expect("Failed to insert");
+        db.set_max_value_bytes(Some(1));
+        db.set_max_value_bytes(None);
+        db.insert(KeyType::record("/root/status"), ValueType::RecordPointer("x".repeat(2048))).expect("Failed to insert");
+    }
 }
@@ -1,18 +1,69 @@
 #[cfg(test)]
 mod tests {
-    use std::io::prelude::*;
+    use std::{
+        io::{prelude::*, BufReader},
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
 
     use crate::{
+        config::Builder,
         datastore::{
-            enums::DatabaseAction,
+            enums::{error::ErrorKind, DatabaseAction},
             utilities::{
-                get_channel_for_hook_get, get_channel_for_hook_list, get_channel_for_hook_remove,
-                get_channel_for_hook_set,
+                get_channel_for_hook_get, get_channel_for_hook_list,
+                get_channel_for_hook_list_by_link, get_channel_for_hook_remove,
+                get_channel_for_hook_set, get_channel_for_queue_forward,
             },
         },
-        hook::{utilities, HookManager},
+        hook::{
+            enums::{BackoffStrategy, HookEvent, HookManagerAction, HookManagerResponse},
+            utilities, HookManager, RetryPolicy,
+        },
     };
 
+    /// Start a dummy HTTP server that answers every request with the specified status line,
+    /// received request bodies are collected into the returned vector
+    fn start_dummy_listener(port: u16, status: &'static str) -> Arc<Mutex<Vec<String>>> {
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+            .expect("Failed to listen for dummy listener");
+
+        let thread_bodies = bodies.clone();
+        std::thread::spawn(move || {
+            while let Ok((mut stream, _)) = listener.accept() {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+
+                let mut body = vec![0; content_length];
+                let _ = reader.read_exact(&mut body);
+                thread_bodies
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&body).to_string());
+
+                let response = format!(
+                    "{}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        return bodies;
+    }
+
     #[test]
     fn test_hook_manager() {
         let mut manager = HookManager::new();
@@ -20,30 +71,35 @@ mod tests {
         let result = manager.add(
             "/root/status".to_string(),
             "http://127.0.0.1:3031".to_string(),
+            None,
         );
         assert_eq!(true, result.is_ok());
 
         let result = manager.add(
             "/root/status".to_string(),
             "http://127.0.0.1:3032".to_string(),
+            None,
         );
         assert_eq!(true, result.is_ok());
 
         let result = manager.add(
             "/root/status".to_string(),
             "http://127.0.0.1:3032".to_string(),
+            None,
         );
         assert_eq!(true, result.is_err());
 
         let result = manager.add(
             "/root/status".to_string(),
             "http://127.0.0.1:3033".to_string(),
+            None,
         );
         assert_eq!(true, result.is_ok());
 
         let result = manager.add(
             "/root/arpa".to_string(),
             "http://127.0.0.1:3031".to_string(),
+            None,
         );
         assert_eq!(true, result.is_ok());
 
@@ -56,16 +112,16 @@ mod tests {
         let result = manager.list(&"/root".to_string());
         assert_eq!(2, result.len());
 
-        let result = manager.list(&"/root/stat".to_string());
+        let result = manager.list(&"/root/status".to_string());
         assert_eq!(1, result.len());
 
         let result = manager.list(&"/root/no_exist".to_string());
         assert_eq!(0, result.len());
 
         // Start a dummy TCP listenere for testing
-        std::thread::spawn(|| {
-            let listener = std::net::TcpListener::bind("127.0.0.1:3031")
-                .expect("Failed to listen on 127.0.0.1:3031");
+        let listener = std::net::TcpListener::bind("127.0.0.1:3031")
+            .expect("Failed to listen on 127.0.0.1:3031");
+        std::thread::spawn(move || {
             println!("Start to listen");
             while let Ok(stream) = listener.accept() {
                 let mut stream = stream.0;
@@ -106,23 +162,32 @@ mod tests {
             .unwrap();
         rt.block_on(async move {
             let counter = manager
-                .execute_hooks(&"/root/status/dns1".to_string(), &"okay".to_string())
+                .execute_hooks_count(&"/root/status/dns1".to_string(), &"okay".to_string())
                 .await;
-            assert_eq!(Some(2), counter);
+            // Nothing listens on the second link
+            assert_eq!(Some(1), counter);
 
             let counter = manager
-                .execute_hooks(&"/root/no_exist".to_string(), &"okay".to_string())
+                .execute_hooks_count(&"/root/no_exist".to_string(), &"okay".to_string())
                 .await;
             assert_eq!(None, counter);
 
             let counter = manager
-                .execute_hooks(
+                .execute_hooks_count(
                     &"/root/arpa/server1".to_string(),
                     &"This is the value".to_string(),
                 )
                 .await;
             assert_eq!(Some(1), counter);
 
+            let report = manager
+                .execute_hooks(&"/root/status/dns1".to_string(), &"okay".to_string())
+                .await;
+            assert_eq!(2, report.attempted);
+            assert_eq!(1, report.succeeded);
+            assert_eq!(1, report.failed.len());
+            assert_eq!("http://127.0.0.1:3032", report.failed[0].0);
+
             // Wait some time until request are received
             tokio::time::sleep(tokio::time::Duration::new(1, 0)).await;
         });
@@ -201,8 +266,8 @@ mod tests {
             .expect("Failed to received response")
             .expect("Bad request");
         assert_eq!(2, result.len());
-        assert_eq!(true, result.contains_key(&"/root/status".to_string()));
-        assert_eq!(true, result.contains_key(&"/root/arpa".to_string()));
+        assert_eq!(true, result.contains_key("/root/status"));
+        assert_eq!(true, result.contains_key("/root/arpa"));
 
         // Test remove
         let (tx, rx) = get_channel_for_hook_remove();
@@ -213,8 +278,7 @@ mod tests {
         );
         sender.send(action).expect("Failed to send hook request");
 
-        let _result = rx
-            .recv()
+        rx.recv()
             .expect("Failed to received response")
             .expect("Bad request");
 
@@ -229,8 +293,8 @@ mod tests {
             .expect("Bad request");
         println!("{:?}", result);
         assert_eq!(1, result.len());
-        assert_eq!(true, result.contains_key(&"/root/status".to_string()));
-        assert_eq!(false, result.contains_key(&"/root/arpa".to_string()));
+        assert_eq!(true, result.contains_key("/root/status"));
+        assert_eq!(false, result.contains_key("/root/arpa"));
 
         // Test remove again
         let (tx, rx) = get_channel_for_hook_remove();
@@ -241,8 +305,7 @@ mod tests {
         );
         sender.send(action).expect("Failed to send hook request");
 
-        let _result = rx
-            .recv()
+        rx.recv()
             .expect("Failed to received response")
             .expect("Bad request");
 
@@ -261,4 +324,1083 @@ mod tests {
         assert_eq!(1, result.1.len());
         assert_eq!(list_etalon, result.1);
     }
+
+    #[test]
+    fn test_hook_list_by_link() {
+        let mut manager = HookManager::new();
+
+        let hooks = vec![
+            ("/root/status", "http://127.0.0.1:3031"),
+            ("/root/arpa", "http://127.0.0.1:3031"),
+            ("/root/network", "http://127.0.0.1:3031"),
+            ("/root/status", "http://127.0.0.1:3032"),
+            ("/root/network", "http://127.0.0.1:3033"),
+        ];
+
+        for (prefix, link) in hooks {
+            manager
+                .add(prefix.to_string(), link.to_string(), None)
+                .expect("Failed to add hook");
+        }
+
+        let result = manager.list_by_link();
+        assert_eq!(3, result.len());
+
+        let prefixes = result
+            .get("http://127.0.0.1:3031")
+            .expect("Link should exist");
+        assert_eq!(
+            vec![
+                "/root/arpa".to_string(),
+                "/root/network".to_string(),
+                "/root/status".to_string()
+            ],
+            *prefixes
+        );
+
+        let prefixes = result
+            .get("http://127.0.0.1:3032")
+            .expect("Link should exist");
+        assert_eq!(vec!["/root/status".to_string()], *prefixes);
+
+        // Same through the datastore
+        let (sender, _) = utilities::start_hook_manager();
+        let (sender, _) =
+            crate::datastore::utilities::start_datastore("root".to_string(), Some(sender), None);
+
+        for (prefix, link) in [
+            ("/root/status", "http://127.0.0.1:3031"),
+            ("/root/arpa", "http://127.0.0.1:3031"),
+        ] {
+            let (tx, rx) = get_channel_for_hook_set();
+            let action = DatabaseAction::HookSet(tx, prefix.to_string(), link.to_string());
+            sender.send(action).expect("Failed to send hook request");
+            rx.recv()
+                .expect("Failed to received response")
+                .expect("Bad request");
+        }
+
+        let (tx, rx) = get_channel_for_hook_list_by_link();
+        let action = DatabaseAction::HookListByLink(tx);
+        sender.send(action).expect("Failed to send hook request");
+
+        let result = rx
+            .recv()
+            .expect("Failed to received response")
+            .expect("Bad request");
+        assert_eq!(1, result.len());
+        assert_eq!(2, result.get("http://127.0.0.1:3031").unwrap().len());
+    }
+
+    #[test]
+    fn test_hook_limit() {
+        let config = Builder::new().max_hooks_per_prefix(3).build();
+        let mut manager = HookManager::from_config(&config);
+
+        for port in 3031..3034 {
+            let result = manager.add(
+                "/root/status".to_string(),
+                format!("http://127.0.0.1:{}", port),
+                None,
+            );
+            assert_eq!(true, result.is_ok());
+        }
+
+        let result = manager.add(
+            "/root/status".to_string(),
+            "http://127.0.0.1:3034".to_string(),
+            None,
+        );
+        assert_eq!(true, result.is_err());
+
+        // Other prefix is not affected
+        let result = manager.add(
+            "/root/arpa".to_string(),
+            "http://127.0.0.1:3034".to_string(),
+            None,
+        );
+        assert_eq!(true, result.is_ok());
+
+        // After a remove, there is place again
+        let result = manager.remove(
+            "/root/status".to_string(),
+            "http://127.0.0.1:3031".to_string(),
+        );
+        assert_eq!(true, result.is_ok());
+
+        let result = manager.add(
+            "/root/status".to_string(),
+            "http://127.0.0.1:3034".to_string(),
+            None,
+        );
+        assert_eq!(true, result.is_ok());
+
+        let result = manager.get(&"/root/status".to_string()).expect("Hook should exist");
+        assert_eq!(3, result.len());
+    }
+
+    #[test]
+    fn test_queue_forward() {
+        let bodies = start_dummy_listener(3041, "HTTP/1.1 200 OK");
+
+        let path = "/tmp/datastore-queue-forward.txt".to_string();
+        if std::path::Path::new(&path).exists() {
+            std::fs::remove_file(&path).expect("Failed to delete temp log");
+        }
+        let (logger_sender, _) = crate::logger::utilities::start_logger(&path);
+
+        let (sender, _) = utilities::start_hook_manager();
+        let (sender, _) = crate::datastore::utilities::start_datastore(
+            "root".to_string(),
+            Some(sender),
+            Some(logger_sender),
+        );
+
+        for i in 0..5 {
+            let (tx, rx) = std::sync::mpsc::channel();
+            let action = DatabaseAction::Push(tx, "/root/outbox".to_string(), format!("msg{}", i));
+            sender.send(action).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to push");
+        }
+
+        // Deliver the first 3 items
+        let (tx, rx) = get_channel_for_queue_forward();
+        let action = DatabaseAction::QueueForward(
+            tx,
+            "/root/outbox".to_string(),
+            "http://127.0.0.1:3041".to_string(),
+            3,
+        );
+        sender.send(action).expect("Failed to send the request");
+        let delivered = rx.recv().expect("Failed to receive").expect("Failed to forward");
+        assert_eq!(3, delivered);
+
+        let bodies = bodies.lock().unwrap().clone();
+        assert_eq!(3, bodies.len());
+        for (i, body) in bodies.iter().enumerate() {
+            assert_eq!(true, body.contains(&format!("msg{}", i)));
+        }
+
+        // Nobody listen here, so items must be put back to the queue
+        let (tx, rx) = get_channel_for_queue_forward();
+        let action = DatabaseAction::QueueForward(
+            tx,
+            "/root/outbox".to_string(),
+            "http://127.0.0.1:3049".to_string(),
+            10,
+        );
+        sender.send(action).expect("Failed to send the request");
+        let delivered = rx.recv().expect("Failed to receive").expect("Failed to forward");
+        assert_eq!(0, delivered);
+
+        for i in 3..5 {
+            let (tx, rx) = std::sync::mpsc::channel();
+            let action = DatabaseAction::Pop(tx, "/root/outbox".to_string());
+            sender.send(action).expect("Failed to send the request");
+            let value = rx.recv().expect("Failed to receive").expect("Failed to pop");
+            assert_eq!(Some(format!("msg{}", i)), value);
+        }
+
+        // Queue is empty now
+        let (tx, rx) = get_channel_for_queue_forward();
+        let action = DatabaseAction::QueueForward(
+            tx,
+            "/root/outbox".to_string(),
+            "http://127.0.0.1:3041".to_string(),
+            10,
+        );
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(true, rx.recv().expect("Failed to receive").is_err());
+
+        // Delivered items are logged as one drain
+        std::thread::sleep(std::time::Duration::new(1, 0));
+        let content = std::fs::read_to_string(&path).expect("Failed to read log");
+        let drains: Vec<&str> = content.lines().filter(|line| line.contains("QueueDrain")).collect();
+        assert_eq!(1, drains.len());
+        assert_eq!(true, drains[0].ends_with("QueueDrain [ '/root/outbox', '3' ]"));
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let policy = RetryPolicy {
+            max_retries: 8,
+            strategy: BackoffStrategy::ExponentialJitter {
+                base: Duration::from_millis(100),
+                cap: Duration::from_secs(5),
+            },
+        };
+
+        let first = policy.delay(0);
+        assert_eq!(true, first >= Duration::from_millis(50));
+        assert_eq!(true, first <= Duration::from_millis(100));
+
+        // Below the cap every delay is at least the maximum of the previous one
+        let mut previous = first;
+        for retry in 1..6 {
+            let delay = policy.delay(retry);
+            assert_eq!(true, delay >= previous);
+            assert_eq!(true, delay <= Duration::from_secs(5));
+            previous = delay;
+        }
+
+        // It remains on the cap
+        assert_eq!(true, policy.delay(30) >= Duration::from_millis(2500));
+        assert_eq!(true, policy.delay(30) <= Duration::from_secs(5));
+
+        let policy = RetryPolicy {
+            max_retries: 3,
+            strategy: BackoffStrategy::Fixed(Duration::from_millis(200)),
+        };
+        for retry in 0..3 {
+            assert_eq!(Duration::from_millis(200), policy.delay(retry));
+        }
+    }
+
+    #[test]
+    fn test_retry_with_paused_clock() {
+        let bodies = start_dummy_listener(3042, "HTTP/1.1 500 Internal Server Error");
+
+        let config = Builder::new()
+            .hook_retry_policy(RetryPolicy {
+                max_retries: 3,
+                strategy: BackoffStrategy::ExponentialJitter {
+                    base: Duration::from_secs(1),
+                    cap: Duration::from_secs(60),
+                },
+            })
+            .build();
+        let mut manager = HookManager::from_config(&config);
+        manager
+            .add("/root/status".to_string(), "http://127.0.0.1:3042".to_string(), None)
+            .expect("Failed to add hook");
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()
+            .unwrap();
+        rt.block_on(async move {
+            let start = tokio::time::Instant::now();
+            let counter = manager
+                .execute_hooks_count(&"/root/status/dns1".to_string(), &"okay".to_string())
+                .await;
+            // Every attempt is answered with 500
+            assert_eq!(Some(0), counter);
+
+            // Sleeps are at least 0.5 + 1 + 2 seconds on the paused clock
+            assert_eq!(true, start.elapsed() >= Duration::from_millis(3500));
+        });
+
+        assert_eq!(4, bodies.lock().unwrap().len());
+    }
+
+    #[test]
+    fn test_hook_case_folding() {
+        let bodies = start_dummy_listener(3043, "HTTP/1.1 200 OK");
+
+        let mut manager = HookManager::new();
+        manager
+            .add("/root/Status".to_string(), "http://127.0.0.1:3043".to_string(), None)
+            .expect("Failed to add hook");
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // Case sensitive by default
+        assert_eq!(0, manager.list(&"/root/status".to_string()).len());
+        let counter = rt.block_on(manager.execute_hooks_count(&"/root/status/dns1".to_string(), &"ok".to_string()));
+        assert_eq!(None, counter);
+
+        let config = Builder::new().hook_case_folding(true).build();
+        let mut manager = HookManager::from_config(&config);
+        manager
+            .add("/root/Status".to_string(), "http://127.0.0.1:3043".to_string(), None)
+            .expect("Failed to add hook");
+
+        assert_eq!(1, manager.list(&"/ROOT/status".to_string()).len());
+        let counter = rt.block_on(manager.execute_hooks_count(&"/root/status/dns1".to_string(), &"ok".to_string()));
+        assert_eq!(Some(1), counter);
+
+        // Expressions ignore the case too, also if folding is changed after they are added
+        let mut patterns = HookManager::new();
+        patterns
+            .add("re:^/root/Net.*$".to_string(), "http://127.0.0.1:3043".to_string(), None)
+            .expect("Failed to add hook");
+        assert_eq!(0, patterns.list(&"/root/network".to_string()).len());
+        patterns.set_case_folding(true);
+        assert_eq!(1, patterns.list(&"/root/network".to_string()).len());
+        patterns.set_case_folding(false);
+        assert_eq!(0, patterns.list(&"/root/network".to_string()).len());
+
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(1, bodies.len());
+        assert_eq!(true, bodies[0].contains("/root/status/dns1"));
+    }
+
+    #[test]
+    fn test_hook_delivery_report() {
+        let _bodies = start_dummy_listener(3044, "HTTP/1.1 202 Accepted");
+
+        let (sender, _) = utilities::start_hook_manager();
+        for (prefix, link) in [
+            ("/root/status", "http://127.0.0.1:3044"),
+            ("/root/status", "http://127.0.0.1:3049"),
+            ("/root/network", "http://127.0.0.1:3044"),
+        ] {
+            let (tx, rx) = utilities::get_channel();
+            let action = HookManagerAction::Set(tx, prefix.to_string(), link.to_string(), None);
+            sender.send(action).expect("Failed to send the request");
+            assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+        }
+
+        let (tx, rx) = utilities::get_channel();
+        let action = HookManagerAction::Send(
+            Some(tx),
+            "/root/status/dns1".to_string(),
+            "okay".to_string(),
+        );
+        sender.send(action).expect("Failed to send the request");
+
+        let report = match rx.recv().expect("Failed to receive") {
+            HookManagerResponse::Delivered(report) => report,
+            other => panic!("Unexpected response: {:?}", other),
+        };
+
+        assert_eq!(2, report.len());
+        assert_eq!("/root/status", report[0].0);
+        assert_eq!("http://127.0.0.1:3044", report[0].1);
+        assert_eq!(Ok(202), report[0].2);
+        assert_eq!("/root/status", report[1].0);
+        assert_eq!("http://127.0.0.1:3049", report[1].1);
+        assert_eq!(true, report[1].2.is_err());
+    }
+
+    #[test]
+    fn test_hook_replay() {
+        let path = "/tmp/datastore-hook-replay.txt".to_string();
+        if std::path::Path::new(&path).exists() {
+            std::fs::remove_file(&path).expect("Failed to delete temp log");
+        }
+        let (logger_sender, _) = crate::logger::utilities::start_logger(&path);
+
+        let (hook_sender, _) = utilities::start_hook_manager();
+        let (sender, _) = crate::datastore::utilities::start_datastore(
+            "root".to_string(),
+            Some(hook_sender),
+            Some(logger_sender),
+        );
+
+        let hooks = [
+            ("/root/status", "http://127.0.0.1:3031"),
+            ("/root/status", "http://127.0.0.1:3032"),
+            ("/root/arpa", "http://127.0.0.1:3031"),
+        ];
+        for (prefix, link) in hooks {
+            let (tx, rx) = get_channel_for_hook_set();
+            let action = DatabaseAction::HookSet(tx, prefix.to_string(), link.to_string());
+            sender.send(action).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to set hook");
+        }
+
+        let (tx, rx) = get_channel_for_hook_remove();
+        let action = DatabaseAction::HookRemove(
+            tx,
+            "/root/status".to_string(),
+            "http://127.0.0.1:3031".to_string(),
+        );
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to remove hook");
+
+        let (tx, rx) = get_channel_for_hook_set();
+        let action = DatabaseAction::HookSetWithMethod(
+            tx,
+            "/root/network".to_string(),
+            "http://127.0.0.1:3033".to_string(),
+            reqwest::Method::PUT,
+        );
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set hook");
+
+        let mut headers = std::collections::BTreeMap::new();
+        headers.insert("Authorization".to_string(), "Bearer it's secret".to_string());
+        let (tx, rx) = get_channel_for_hook_set();
+        let action = DatabaseAction::HookSetWithHeaders(
+            tx,
+            "/root/network".to_string(),
+            "http://127.0.0.1:3034".to_string(),
+            None,
+            headers.clone(),
+        );
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set hook");
+
+        // Event filter is logged, filter of missing hook is rejected and not logged
+        let (tx, rx) = get_channel_for_hook_set();
+        let action = DatabaseAction::HookSetEvents(tx, "/root/arpa".to_string(), "http://127.0.0.1:3031".to_string(), vec![HookEvent::Delete]);
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set events");
+
+        let (tx, rx) = get_channel_for_hook_set();
+        let action = DatabaseAction::HookSetEvents(tx, "/root/arpa".to_string(), "http://127.0.0.1:3039".to_string(), vec![HookEvent::Delete]);
+        sender.send(action).expect("Failed to send the request");
+        assert_eq!(true, matches!(rx.recv().expect("Failed to receive"), Err(ErrorKind::NotFound(_))));
+
+        // Non-hook entries are in the log too
+        let (tx, rx) = crate::datastore::utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/status/dns".to_string(), "ok".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set value");
+
+        std::thread::sleep(std::time::Duration::new(1, 0));
+
+        let mut manager = HookManager::new();
+        let count = manager.replay_log(&path).expect("Failed to replay");
+        assert_eq!(7, count);
+
+        // Hook with delete event only is not executed for set
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let counter = rt.block_on(manager.execute_hooks_count(&"/root/arpa/x".to_string(), &"ok".to_string()));
+        assert_eq!(None, counter);
+
+        // Method and headers are restored too
+        let list = manager.list_with_methods(&"/root/network".to_string());
+        assert_eq!(
+            &vec![
+                ("http://127.0.0.1:3033".to_string(), reqwest::Method::PUT),
+                ("http://127.0.0.1:3034".to_string(), reqwest::Method::POST),
+            ],
+            list.get("/root/network").unwrap()
+        );
+        let list = manager.list_with_headers(&"/root/network".to_string(), false);
+        assert_eq!(&headers, &list.get("/root/network").unwrap()[1].1);
+
+        let list = manager.list(&"/root".to_string());
+        assert_eq!(3, list.len());
+        assert_eq!(
+            &vec!["http://127.0.0.1:3032".to_string()],
+            list.get("/root/status").unwrap()
+        );
+        assert_eq!(
+            &vec!["http://127.0.0.1:3031".to_string()],
+            list.get("/root/arpa").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hook_method() {
+        // Collect the request lines instead of the bodies
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 3045))
+            .expect("Failed to listen for dummy listener");
+        let request_lines = Arc::new(Mutex::new(Vec::new()));
+        let thread_lines = request_lines.clone();
+        std::thread::spawn(move || {
+            while let Ok((mut stream, _)) = listener.accept() {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                let _ = reader.read_line(&mut request_line);
+                thread_lines.lock().unwrap().push(request_line.trim_end().to_string());
+
+                let response = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let mut manager = HookManager::new();
+        manager
+            .add("/root/status".to_string(), "http://127.0.0.1:3045/default".to_string(), None)
+            .expect("Failed to add hook");
+        manager
+            .add("/root/status".to_string(), "http://127.0.0.1:3045/put".to_string(), Some(reqwest::Method::PUT))
+            .expect("Failed to add hook");
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let report = rt.block_on(manager.execute_hooks_with_report(&"/root/status/dns".to_string(), &"ok".to_string()));
+        assert_eq!(2, report.len());
+
+        // Method can be set through the datastore too
+        let (hook_sender, _) = utilities::start_hook_manager();
+        let (sender, _) = crate::datastore::utilities::start_datastore("root".to_string(), Some(hook_sender), None);
+
+        let (tx, rx) = get_channel_for_hook_set();
+        let action = DatabaseAction::HookSetWithMethod(
+            tx,
+            "/root/network".to_string(),
+            "http://127.0.0.1:3045/patch".to_string(),
+            reqwest::Method::PATCH,
+        );
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set hook");
+
+        let (tx, rx) = crate::datastore::utilities::get_channel_for_set();
+        let action = DatabaseAction::Set(tx, "/root/network/dns".to_string(), "up".to_string());
+        sender.send(action).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set value");
+
+        std::thread::sleep(Duration::from_secs(1));
+
+        let lines = request_lines.lock().unwrap();
+        assert_eq!(3, lines.len());
+        assert_eq!(true, lines.contains(&"POST /default HTTP/1.1".to_string()));
+        assert_eq!(true, lines.contains(&"PUT /put HTTP/1.1".to_string()));
+        assert_eq!(true, lines.contains(&"PATCH /patch HTTP/1.1".to_string()));
+    }
+
+    #[test]
+    fn test_hook_timeout() {
+        // Listener accepts the connection but never answers
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 3046))
+            .expect("Failed to listen for dummy listener");
+        std::thread::spawn(move || {
+            let mut streams = Vec::new();
+            while let Ok((stream, _)) = listener.accept() {
+                streams.push(stream);
+            }
+        });
+
+        let config = Builder::new().hook_timeout(Duration::from_millis(200)).build();
+        let mut manager = HookManager::from_config(&config);
+        assert_eq!(Some(Duration::from_millis(200)), manager.timeout());
+        manager
+            .add("/root/status".to_string(), "http://127.0.0.1:3046".to_string(), None)
+            .expect("Failed to add hook");
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let start = std::time::Instant::now();
+        let report = rt.block_on(manager.execute_hooks_with_report(&"/root/status/dns".to_string(), &"ok".to_string()));
+
+        assert_eq!(1, report.len());
+        assert_eq!(true, report[0].2.is_err());
+        assert_eq!(true, start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_hook_headers() {
+        // Collect the header lines of requests
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 3048))
+            .expect("Failed to listen for dummy listener");
+        let header_lines = Arc::new(Mutex::new(Vec::new()));
+        let thread_lines = header_lines.clone();
+        std::thread::spawn(move || {
+            while let Ok((mut stream, _)) = listener.accept() {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    thread_lines.lock().unwrap().push(line.trim_end().to_lowercase());
+                }
+
+                let response = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let (sender, _) = utilities::start_hook_manager();
+
+        let mut headers = std::collections::BTreeMap::new();
+        headers.insert("Authorization".to_string(), "Bearer secret".to_string());
+        headers.insert("X-Source".to_string(), "datastore".to_string());
+
+        let (tx, rx) = utilities::get_channel();
+        let action = HookManagerAction::SetWithHeaders(
+            tx,
+            "/root/status".to_string(),
+            "http://127.0.0.1:3048".to_string(),
+            None,
+            headers.clone(),
+        );
+        sender.send(action).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        // Invalid header is rejected
+        let mut invalid = std::collections::BTreeMap::new();
+        invalid.insert("Bad Header".to_string(), "value".to_string());
+        let (tx, rx) = utilities::get_channel();
+        let action = HookManagerAction::SetWithHeaders(
+            tx,
+            "/root/network".to_string(),
+            "http://127.0.0.1:3048".to_string(),
+            None,
+            invalid,
+        );
+        sender.send(action).expect("Failed to send request");
+        assert_ne!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        // Listing with and without redaction
+        let (tx, rx) = utilities::get_channel();
+        sender.send(HookManagerAction::ListWithHeaders(tx, "/root".to_string(), false)).expect("Failed to send request");
+        match rx.recv().expect("Failed to receive") {
+            HookManagerResponse::HookListWithHeaders(list) => {
+                assert_eq!(1, list.len());
+                assert_eq!(&vec![("http://127.0.0.1:3048".to_string(), headers.clone())], list.get("/root/status").unwrap());
+            }
+            response => panic!("Unexpected response: {:?}", response),
+        }
+
+        let (tx, rx) = utilities::get_channel();
+        sender.send(HookManagerAction::ListWithHeaders(tx, "/root".to_string(), true)).expect("Failed to send request");
+        match rx.recv().expect("Failed to receive") {
+            HookManagerResponse::HookListWithHeaders(list) => {
+                let (_, listed) = &list.get("/root/status").unwrap()[0];
+                assert_eq!(Some(&"***".to_string()), listed.get("Authorization"));
+                assert_eq!(Some(&"datastore".to_string()), listed.get("X-Source"));
+            }
+            response => panic!("Unexpected response: {:?}", response),
+        }
+
+        // Headers are sent with the request
+        let (tx, rx) = utilities::get_channel();
+        sender.send(HookManagerAction::Send(Some(tx), "/root/status/dns".to_string(), "ok".to_string())).expect("Failed to send request");
+        match rx.recv().expect("Failed to receive") {
+            HookManagerResponse::Delivered(report) => assert_eq!(Ok(200), report[0].2),
+            response => panic!("Unexpected response: {:?}", response),
+        }
+
+        let lines = header_lines.lock().unwrap();
+        assert_eq!(true, lines.contains(&"authorization: bearer secret".to_string()));
+        assert_eq!(true, lines.contains(&"x-source: datastore".to_string()));
+    }
+
+    #[test]
+    fn test_hook_parallel_delivery() {
+        // Every connection is answered after a delay on its own thread
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 3050))
+            .expect("Failed to listen for dummy listener");
+        std::thread::spawn(move || {
+            while let Ok((mut stream, _)) = listener.accept() {
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                            break;
+                        }
+                    }
+
+                    std::thread::sleep(Duration::from_millis(500));
+                    let response = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                    let _ = stream.write_all(response.as_bytes());
+                });
+            }
+        });
+
+        let mut manager = HookManager::new();
+        for index in 0..4 {
+            manager
+                .add("/root/status".to_string(), format!("http://127.0.0.1:3050/{}", index), None)
+                .expect("Failed to add hook");
+        }
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let start = std::time::Instant::now();
+        let counter = rt.block_on(manager.execute_hooks_count(&"/root/status/dns".to_string(), &"ok".to_string()));
+
+        assert_eq!(Some(4), counter);
+        assert_eq!(true, start.elapsed() < Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_hook_pending_limit() {
+        // Slow endpoint, every connection is answered after a second
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 3051))
+            .expect("Failed to listen for dummy listener");
+        std::thread::spawn(move || {
+            while let Ok((mut stream, _)) = listener.accept() {
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                            break;
+                        }
+                    }
+
+                    std::thread::sleep(Duration::from_secs(1));
+                    let response = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                    let _ = stream.write_all(response.as_bytes());
+                });
+            }
+        });
+
+        let config = Builder::new().max_pending_hooks(1).build();
+        let (hook_sender, _) = utilities::start_hook_manager_with_config(&config);
+
+        let (tx, rx) = utilities::get_channel();
+        let action = HookManagerAction::Set(tx, "/root/status".to_string(), "http://127.0.0.1:3051".to_string(), None);
+        hook_sender.send(action).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        // Database does not wait for the delivery
+        let (sender, _) = crate::datastore::utilities::start_datastore("root".to_string(), Some(hook_sender.clone()), None);
+        let start = std::time::Instant::now();
+        let (tx, rx) = crate::datastore::utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx, "/root/status/dns".to_string(), "ok".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set value");
+        assert_eq!(true, start.elapsed() < Duration::from_millis(500));
+
+        // First delivery is still in progress, so the next one is dropped
+        let (tx, rx) = utilities::get_channel();
+        hook_sender.send(HookManagerAction::Send(Some(tx), "/root/status/dns".to_string(), "nok".to_string())).expect("Failed to send request");
+        assert_eq!(
+            HookManagerResponse::Error("Too many pending deliveries".to_string()),
+            rx.recv().expect("Failed to receive")
+        );
+
+        // Slot is freed after the delivery
+        std::thread::sleep(Duration::from_millis(1500));
+        let (tx, rx) = utilities::get_channel();
+        hook_sender.send(HookManagerAction::Send(Some(tx), "/root/status/dns".to_string(), "ok".to_string())).expect("Failed to send request");
+        match rx.recv().expect("Failed to receive") {
+            HookManagerResponse::Delivered(report) => assert_eq!(Ok(200), report[0].2),
+            response => panic!("Unexpected response: {:?}", response),
+        }
+    }
+
+    #[test]
+    fn test_auto_hooks() {
+        let bodies = start_dummy_listener(3052, "HTTP/1.1 200 OK");
+
+        let set_and_trigger = |config: &crate::config::Config| {
+            let (hook_sender, _) = utilities::start_hook_manager();
+            let (tx, rx) = utilities::get_channel();
+            let action = HookManagerAction::Set(tx, "/root/status".to_string(), "http://127.0.0.1:3052".to_string(), None);
+            hook_sender.send(action).expect("Failed to send request");
+            assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+            let (sender, _) = crate::datastore::utilities::start_datastore_with_config("root".to_string(), Some(hook_sender), None, config);
+
+            let (tx, rx) = crate::datastore::utilities::get_channel_for_set();
+            sender.send(DatabaseAction::Set(tx, "/root/status/dns".to_string(), "set".to_string())).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to set value");
+
+            let (tx, rx) = crate::datastore::utilities::get_channel_for_set();
+            sender.send(DatabaseAction::Trigger(tx, "/root/status/dns".to_string(), "trigger".to_string())).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to trigger");
+
+            std::thread::sleep(Duration::from_millis(500));
+        };
+
+        // Enabled by default
+        set_and_trigger(&Builder::new().build());
+        assert_eq!(2, bodies.lock().unwrap().len());
+
+        // Only the trigger is delivered
+        bodies.lock().unwrap().clear();
+        set_and_trigger(&Builder::new().auto_hooks(false).build());
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(1, bodies.len());
+        assert_eq!(true, bodies[0].contains("trigger"));
+    }
+
+    #[test]
+    fn test_auto_hooks_case_insensitive_transaction() {
+        let bodies = start_dummy_listener(3058, "HTTP/1.1 200 OK");
+
+        let (hook_sender, _) = utilities::start_hook_manager();
+        let (tx, rx) = utilities::get_channel();
+        let action = HookManagerAction::Set(tx, "/root/status".to_string(), "http://127.0.0.1:3058".to_string(), None);
+        hook_sender.send(action).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        let config = Builder::new().case_insensitive_keys(true).build();
+        let (sender, _) = crate::datastore::utilities::start_datastore_with_config("root".to_string(), Some(hook_sender), None, &config);
+
+        // Transaction write alerts with the same key like a direct write
+        let (tx, rx) = crate::datastore::utilities::get_channel_for_set();
+        let ops = vec![crate::datastore::enums::TxnOp::Set("/Root/Status/DNS".to_string(), "txn".to_string())];
+        sender.send(DatabaseAction::Transaction(tx, ops)).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to commit");
+
+        let (tx, rx) = crate::datastore::utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx, "/Root/Status/DNS".to_string(), "set".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set value");
+
+        std::thread::sleep(Duration::from_millis(500));
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(2, bodies.len());
+        assert_eq!(true, bodies.iter().all(|x| x.contains("/root/status/dns") && !x.contains("/Root")));
+    }
+
+    #[test]
+    fn test_hook_delete_event() {
+        let bodies = start_dummy_listener(3053, "HTTP/1.1 200 OK");
+
+        let (hook_sender, _) = utilities::start_hook_manager();
+        let (tx, rx) = utilities::get_channel();
+        let action = HookManagerAction::Set(tx, "/root/status".to_string(), "http://127.0.0.1:3053".to_string(), None);
+        hook_sender.send(action).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        // Empty event list is rejected
+        let (tx, rx) = utilities::get_channel();
+        let action = HookManagerAction::SetEvents(tx, "/root/status".to_string(), "http://127.0.0.1:3053".to_string(), Vec::new());
+        hook_sender.send(action).expect("Failed to send request");
+        assert_eq!(true, matches!(rx.recv().expect("Failed to receive"), HookManagerResponse::Error(_)));
+
+        let (tx, rx) = utilities::get_channel();
+        let action = HookManagerAction::SetEvents(tx, "/root/status".to_string(), "http://127.0.0.1:3053".to_string(), vec![HookEvent::Delete]);
+        hook_sender.send(action).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        let (sender, _) = crate::datastore::utilities::start_datastore("root".to_string(), Some(hook_sender), None);
+
+        let (tx, rx) = crate::datastore::utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx, "/root/status/dns".to_string(), "ok".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set value");
+
+        let (tx, rx) = crate::datastore::utilities::get_channel_for_delete();
+        sender.send(DatabaseAction::DeleteKey(tx, "/root/status/dns".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to delete key");
+
+        std::thread::sleep(Duration::from_millis(500));
+
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(1, bodies.len());
+        assert_eq!(true, bodies[0].contains("\"event\":\"delete\""));
+        assert_eq!(true, bodies[0].contains("\"value\":null"));
+
+        let body: serde_json::Value = serde_json::from_str(&bodies[0]).expect("Body is not JSON");
+        assert_eq!("/root/status/dns", body["key"]);
+        assert_eq!("/root/status", body["prefix"]);
+        let timestamp = body["timestamp"].as_str().expect("Missing timestamp");
+        assert_eq!(true, chrono::DateTime::parse_from_rfc3339(timestamp).is_ok());
+    }
+
+    #[test]
+    fn test_hook_prefix_matching() {
+        let mut manager = HookManager::new();
+        let link = "http://127.0.0.1:3054".to_string();
+
+        let result = manager.add("/root/st".to_string(), link.clone(), None);
+        assert_eq!(true, result.is_ok());
+
+        let result = manager.add("re:^/root/.*/dns[0-9]$".to_string(), link.clone(), None);
+        assert_eq!(true, result.is_ok());
+
+        let result = manager.add("re:[".to_string(), link.clone(), None);
+        match result {
+            Err(HookManagerResponse::Error(message)) => assert_eq!(true, message.starts_with("Invalid regex")),
+            _ => panic!("Invalid regex is accepted"),
+        }
+        assert_eq!(None, manager.get(&"re:[".to_string()));
+        assert_eq!(Some(vec![link.clone()]), manager.get(&"re:^/root/.*/dns[0-9]$".to_string()));
+
+        // Partial segment does not match, the pattern lists the hook for matching key
+        assert_eq!(0, manager.list(&"/root/status".to_string()).len());
+        assert_eq!(1, manager.list(&"/root/st".to_string()).len());
+        assert_eq!(1, manager.list(&"/root/status/dns1".to_string()).len());
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let report = manager.execute_hooks_with_report(&"/root/status/dns1".to_string(), &"ok".to_string()).await;
+            assert_eq!(1, report.len());
+            assert_eq!("re:^/root/.*/dns[0-9]$", report[0].0);
+
+            let report = manager.execute_hooks_with_report(&"/root/st/dns".to_string(), &"ok".to_string()).await;
+            assert_eq!(1, report.len());
+            assert_eq!("/root/st", report[0].0);
+        });
+
+        // Pattern is dropped with the last link
+        let result = manager.remove("re:^/root/.*/dns[0-9]$".to_string(), link.clone());
+        assert_eq!(true, result.is_ok());
+        assert_eq!(0, manager.list(&"/root/status/dns1".to_string()).len());
+    }
+
+    #[test]
+    fn test_hook_persist() {
+        let path = std::path::PathBuf::from("/tmp/datastore-hooks.json");
+        let _ = std::fs::remove_file(&path);
+        let config = Builder::new().hook_persist_path(path.clone()).build();
+        let link = "http://127.0.0.1:3055".to_string();
+
+        // Nothing to save without path
+        let (sender, _) = utilities::start_hook_manager();
+        let (tx, rx) = utilities::get_channel();
+        sender.send(HookManagerAction::Persist(tx)).expect("Failed to send request");
+        assert_eq!(true, matches!(rx.recv().expect("Failed to receive"), HookManagerResponse::Error(_)));
+
+        let (sender, _) = utilities::start_hook_manager_with_config(&config);
+
+        let mut headers = std::collections::BTreeMap::new();
+        headers.insert("X-Api-Key".to_string(), "secret".to_string());
+        let (tx, rx) = utilities::get_channel();
+        let action = HookManagerAction::SetWithHeaders(tx, "/root/status".to_string(), link.clone(), Some(reqwest::Method::PUT), headers);
+        sender.send(action).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        let (tx, rx) = utilities::get_channel();
+        let action = HookManagerAction::SetEvents(tx, "/root/status".to_string(), link.clone(), vec![HookEvent::Delete]);
+        sender.send(action).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        let (tx, rx) = utilities::get_channel();
+        sender.send(HookManagerAction::Persist(tx)).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        // New manager loads the hooks on boot
+        let (sender, _) = utilities::start_hook_manager_with_config(&config);
+        let (tx, rx) = utilities::get_channel();
+        sender.send(HookManagerAction::ListWithHeaders(tx, "/root".to_string(), false)).expect("Failed to send request");
+        match rx.recv().expect("Failed to receive") {
+            HookManagerResponse::HookListWithHeaders(list) => {
+                let (loaded_link, headers) = &list.get("/root/status").expect("Hook is not loaded")[0];
+                assert_eq!(&link, loaded_link);
+                assert_eq!(Some(&"secret".to_string()), headers.get("X-Api-Key"));
+            }
+            other => panic!("Unexpected response: {:?}", other),
+        }
+
+        // Events are kept, so set is not delivered
+        let (tx, rx) = utilities::get_channel();
+        sender.send(HookManagerAction::Send(Some(tx), "/root/status/dns".to_string(), "ok".to_string())).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::Delivered(Vec::new()), rx.recv().expect("Failed to receive"));
+
+        // Broken file does not stop the hook manager, it starts without hooks
+        std::fs::write(&path, "{ broken").expect("Failed to write persisted file");
+        let (sender, _) = utilities::start_hook_manager_with_config(&config);
+        let (tx, rx) = utilities::get_channel();
+        sender.send(HookManagerAction::List(tx, "/root".to_string())).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::HookList(std::collections::BTreeMap::new()), rx.recv().expect("Failed to receive"));
+    }
+
+    #[test]
+    fn test_hook_signature() {
+        // Collect the signature header and the body of requests
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 3056))
+            .expect("Failed to listen for dummy listener");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let thread_requests = requests.clone();
+        std::thread::spawn(move || {
+            while let Ok((mut stream, _)) = listener.accept() {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                let mut signature = None;
+
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+
+                    let lower = line.to_lowercase();
+                    if let Some(value) = lower.strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                    if let Some(value) = lower.strip_prefix("x-datastore-signature:") {
+                        signature = Some(value.trim().to_string());
+                    }
+                }
+
+                let mut body = vec![0; content_length];
+                let _ = reader.read_exact(&mut body);
+                thread_requests.lock().unwrap().push((signature, body));
+
+                let response = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let mut manager = HookManager::new();
+        manager
+            .add("/root/status".to_string(), "http://127.0.0.1:3056".to_string(), None)
+            .expect("Failed to add hook");
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // Not signed by default
+        assert_eq!(false, manager.is_signed());
+        let counter = rt.block_on(manager.execute_hooks_count(&"/root/status/dns1".to_string(), &"ok".to_string()));
+        assert_eq!(Some(1), counter);
+
+        let config = Builder::new().hook_signing_secret("secret".to_string()).build();
+        assert_eq!(true, format!("{:?}", config).contains("hook_signing_secret: true"));
+        manager.set_signing_secret(config.hook_signing_secret().cloned());
+        let counter = rt.block_on(manager.execute_hooks_count(&"/root/status/dns1".to_string(), &"ok".to_string()));
+        assert_eq!(Some(1), counter);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(2, requests.len());
+        assert_eq!(None, requests[0].0);
+
+        let (signature, body) = &requests[1];
+        assert_eq!(Some(utilities::sign_body("secret", body)), *signature);
+        assert_ne!(Some(utilities::sign_body("other", body)), *signature);
+        assert_eq!(true, serde_json::from_slice::<serde_json::Value>(body).is_ok());
+    }
+
+    #[test]
+    fn test_hook_remove_under_prefix() {
+        let path = "/tmp/datastore-log24.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+        let (logger_sender, _) = crate::logger::utilities::start_logger(&path);
+
+        let (hook_sender, _) = utilities::start_hook_manager();
+        let (sender, _) = crate::datastore::utilities::start_datastore(
+            "root".to_string(),
+            Some(hook_sender.clone()),
+            Some(logger_sender),
+        );
+        for prefix in ["/root/status", "/root/status/sub1", "/root/statusx", "/root/network", "re:^/root/status/"] {
+            let (tx, rx) = get_channel_for_hook_set();
+            let action = DatabaseAction::HookSet(tx, prefix.to_string(), "http://127.0.0.1:3049".to_string());
+            sender.send(action).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to set hook");
+        }
+
+        let (tx, rx) = crate::datastore::utilities::get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx, "/root/status/sub1/dns".to_string(), "ok".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set value");
+
+        let (tx, rx) = crate::datastore::utilities::get_channel_for_delete();
+        sender.send(DatabaseAction::DeleteTable(tx, "/root/status".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to delete table");
+
+        // Removal is sent before the response, hook manager processes the actions in order
+        let (tx, rx) = utilities::get_channel();
+        hook_sender.send(HookManagerAction::List(tx, "/root".to_string())).expect("Failed to send request");
+        match rx.recv().expect("Failed to receive") {
+            HookManagerResponse::HookList(list) => {
+                assert_eq!(vec!["/root/network", "/root/statusx"], list.keys().collect::<Vec<&String>>());
+            }
+            other => panic!("Unexpected response: {:?}", other),
+        }
+
+        // Removal is logged, so replay does not restore the removed hooks
+        std::thread::sleep(std::time::Duration::new(1, 0));
+        let mut manager = HookManager::new();
+        assert_eq!(6, manager.replay_log(&path).expect("Failed to replay"));
+        let list = manager.list(&"/root".to_string());
+        assert_eq!(vec!["/root/network", "/root/statusx"], list.keys().collect::<Vec<&String>>());
+
+        let (tx, rx) = utilities::get_channel();
+        hook_sender.send(HookManagerAction::Get(tx, "re:^/root/status/".to_string())).expect("Failed to send request");
+        assert_eq!(true, matches!(rx.recv().expect("Failed to receive"), HookManagerResponse::Hook(..)));
+
+        let (tx, rx) = utilities::get_channel();
+        hook_sender.send(HookManagerAction::RemoveUnderPrefix(Some(tx), "/root".to_string())).expect("Failed to send request");
+        assert_eq!(HookManagerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        let (tx, rx) = utilities::get_channel();
+        hook_sender.send(HookManagerAction::Get(tx, "/root/network".to_string())).expect("Failed to send request");
+        assert_eq!(true, matches!(rx.recv().expect("Failed to receive"), HookManagerResponse::Error(_)));
+    }
 }
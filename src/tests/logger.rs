@@ -1,13 +1,15 @@
 #[cfg(test)]
 mod test {
+    use std::io::Read;
     use std::path::Path;
     use std::sync::mpsc::channel;
+    use std::time::Duration;
 
     use crate::{logger::{
-        enums::{LogItem, LoggerAction, LoggerResponse},
-        utilities::{get_channel_for_log_write, start_logger},
+        enums::{FlushPolicy, LogFilter, LogFormat, LogItem, LogRotation, LoggerAction, LoggerResponse},
+        utilities::{get_channel_for_log_write, start_logger, start_logger_with_config},
         LoggerManager,
-    }, datastore::{utilities::{start_datastore, get_channel_for_set}, enums::DatabaseAction}};
+    }, datastore::{utilities::{start_datastore, start_datastore_with_config, get_channel_for_set}, enums::{DatabaseAction, DropPolicy, pair::{KeyType, ValueType}}, Database}, config::Builder};
 
     #[test]
     fn test_log1() {
@@ -103,7 +105,7 @@ mod test {
             std::fs::remove_file(path).expect("Failed to delete temp log");
         }
 
-        let (sender, _) = start_logger(&"/tmp/datastore-log3.txt".to_string());
+        let (sender, _) = start_logger("/tmp/datastore-log3.txt");
 
         let action = LoggerAction::WriteAsync(vec![LogItem::SetKey(
             "/root/test1".to_string(),
@@ -197,4 +199,689 @@ mod test {
 
         assert_eq!(count + 1, count2);
     }
+
+    #[test]
+    fn test_log_buffer_across_threads() {
+        let path = "/tmp/datastore-log5.txt".to_string();
+        {
+            let path = Path::new(&path);
+            if path.exists() {
+                std::fs::remove_file(path).expect("Failed to delete temp log");
+            }
+        }
+
+        let (sender, _) = start_logger(&path);
+
+        // Suspend the logger, so every item is buffered in memory
+        let (tx, rx) = get_channel_for_log_write();
+        sender.send(LoggerAction::Suspend(tx)).expect("Failed to send the request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive reply"));
+
+        // Items are created on other threads and outlive their scope in the buffer
+        let mut threads = Vec::new();
+        for i in 0..3 {
+            let sender = sender.clone();
+            threads.push(std::thread::spawn(move || {
+                let key = format!("/root/thread/{}", i);
+                let (tx, rx) = get_channel_for_log_write();
+                let action = LoggerAction::Write(tx, vec![LogItem::SetKey(key, "done".to_string())]);
+                sender.send(action).expect("Failed to send the request");
+                assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive reply"));
+            }));
+        }
+
+        for thread in threads {
+            thread.join().expect("Failed to join thread");
+        }
+
+        assert_eq!(false, Path::new(&path).exists());
+
+        // Resume and check that every buffered item is written
+        let (tx, rx) = get_channel_for_log_write();
+        sender.send(LoggerAction::Resume(tx)).expect("Failed to send the request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive reply"));
+
+        let content = std::fs::read_to_string(path.clone()).expect("Failed to open file for line counting");
+        assert_eq!(3, content.lines().count());
+        for i in 0..3 {
+            assert_eq!(true, content.contains(&format!("SetKey [ '/root/thread/{}', 'done' ]", i)));
+        }
+    }
+
+    #[test]
+    fn test_log_queue_replay() {
+        let path = "/tmp/datastore-log6.txt".to_string();
+        {
+            let path = Path::new(&path);
+            if path.exists() {
+                std::fs::remove_file(path).expect("Failed to delete temp log");
+            }
+        }
+
+        let (logger_sender, _) = start_logger(&path);
+        let (sender, _) = start_datastore("root".to_string(), None, Some(logger_sender));
+
+        for value in ["first", "second", "third"] {
+            let (tx, rx) = channel();
+            let action = DatabaseAction::Push(tx, "/root/queue".to_string(), value.to_string());
+            sender.send(action).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to push");
+        }
+
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::Pop(tx, "/root/queue".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to pop");
+
+        // Pop of missing queue is not logged
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::Pop(tx, "/root/missing".to_string())).expect("Failed to send the request");
+        assert_eq!(None, rx.recv().expect("Failed to receive").expect("Failed to pop"));
+
+        std::thread::sleep(std::time::Duration::new(1, 0)); // Wait some time that the async write will be finished
+
+        let content = std::fs::read_to_string(path.clone()).expect("Failed to open file for line counting");
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(4, lines.len());
+        assert_eq!(true, lines[0].ends_with("QueuePush [ '/root/queue', 'first' ]"));
+        assert_eq!(true, lines[3].ends_with("QueuePop [ '/root/queue' ]"));
+
+        // Replay the log on a new database
+        let mut replica = Database::new("root".to_string()).expect("Failed to allocate database");
+        for line in lines {
+            let parts: Vec<&str> = line.split('\'').collect();
+            let key = KeyType::Record(parts[1].to_string());
+            if line.contains("QueuePush [") {
+                replica.push(key, parts[3].to_string()).expect("Failed to replay push");
+            } else if line.contains("QueuePop [") {
+                replica.pop(key).expect("Failed to replay pop");
+            }
+        }
+
+        for _ in 0..2 {
+            let (tx, rx) = channel();
+            sender.send(DatabaseAction::Pop(tx, "/root/queue".to_string())).expect("Failed to send the request");
+            let value = rx.recv().expect("Failed to receive").expect("Failed to pop");
+            let replayed = replica.pop(KeyType::Record("/root/queue".to_string())).expect("Failed to pop");
+            assert_eq!(value, replayed);
+        }
+    }
+
+    #[test]
+    fn test_log_batch() {
+        let set_keys = |sender: &std::sync::mpsc::Sender<DatabaseAction>| {
+            for i in 0..5 {
+                let (tx, rx) = get_channel_for_set();
+                let action = DatabaseAction::Set(tx, format!("/root/batch/{}", i), "ok".to_string());
+                sender.send(action).expect("Failed to send the request");
+                rx.recv().expect("Failed to receive").expect("Failed to set the value");
+            }
+        };
+
+        // By default every item is sent immediately
+        let (logger_sender, logger_receiver) = channel();
+        let (sender, _) = start_datastore("root".to_string(), None, Some(logger_sender));
+        set_keys(&sender);
+
+        let mut sends = 0;
+        while let Ok(LoggerAction::WriteAsync(items)) = logger_receiver.recv_timeout(Duration::from_millis(200)) {
+            assert_eq!(1, items.len());
+            sends += 1;
+        }
+        assert_eq!(5, sends);
+
+        // Items are collected up to 3 items
+        let config = Builder::new().log_batch(3, Duration::from_millis(300)).build();
+        let (logger_sender, logger_receiver) = channel();
+        let (sender, _) = start_datastore_with_config("root".to_string(), None, Some(logger_sender), &config);
+        set_keys(&sender);
+
+        let mut sizes = Vec::new();
+        while let Ok(LoggerAction::WriteAsync(items)) = logger_receiver.recv_timeout(Duration::from_secs(1)) {
+            sizes.push(items.len());
+        }
+
+        // Second batch is sent after the window is elapsed
+        assert_eq!(vec![3, 2], sizes);
+    }
+
+    #[test]
+    fn test_log_stop() {
+        let path = "/tmp/datastore-log7.txt".to_string();
+        {
+            let path = Path::new(&path);
+            if path.exists() {
+                std::fs::remove_file(path).expect("Failed to delete temp log");
+            }
+        }
+
+        let (sender, thread) = start_logger(&path);
+
+        for i in 0..3 {
+            let action = LoggerAction::WriteAsync(vec![LogItem::SetKey(format!("/root/test{}", i), "ok".to_string())]);
+            sender.send(action).expect("Failed to send the request");
+        }
+
+        // Buffered items are written at stop too
+        let (tx, rx) = get_channel_for_log_write();
+        sender.send(LoggerAction::Suspend(tx)).expect("Failed to send the request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive reply"));
+
+        let action = LoggerAction::WriteAsync(vec![LogItem::RemKey("/root/test0".to_string())]);
+        sender.send(action).expect("Failed to send the request");
+
+        let (tx, rx) = get_channel_for_log_write();
+        sender.send(LoggerAction::Stop(tx)).expect("Failed to send the request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive reply"));
+
+        thread.join().expect("Logger thread did not exit");
+
+        let content = std::fs::read_to_string(path.clone()).expect("Failed to open file for line counting");
+        assert_eq!(4, content.lines().count());
+        assert_eq!(true, content.lines().last().unwrap().ends_with("RemKey [ '/root/test0' ]"));
+
+        // Logger does not accept more request
+        let action = LoggerAction::WriteAsync(vec![LogItem::GetKey("/root/test1".to_string())]);
+        assert_eq!(true, sender.send(action).is_err());
+    }
+
+    #[test]
+    fn test_log_line_replay() {
+        let path = "/tmp/datastore-log8.txt".to_string();
+        {
+            let path = Path::new(&path);
+            if path.exists() {
+                std::fs::remove_file(path).expect("Failed to delete temp log");
+            }
+        }
+
+        let (logger_sender, _) = start_logger(&path);
+        let (sender, _) = start_datastore("root".to_string(), None, Some(logger_sender));
+
+        let send = |action: DatabaseAction| sender.send(action).expect("Failed to send the request");
+        let (tx, rx) = channel();
+        send(DatabaseAction::Set(tx.clone(), "/root/status/sub1".to_string(), "ok".to_string()));
+        send(DatabaseAction::Set(tx.clone(), "/root/status/sub2".to_string(), "nok".to_string()));
+        send(DatabaseAction::Set(tx.clone(), "/root/network/dns".to_string(), "up".to_string()));
+        send(DatabaseAction::DeleteKey(tx.clone(), "/root/status/sub1".to_string()));
+        send(DatabaseAction::DeleteTable(tx.clone(), "/root/network".to_string()));
+        send(DatabaseAction::CreateTable(tx.clone(), "/root/empty".to_string()));
+        send(DatabaseAction::Push(tx.clone(), "/root/queue".to_string(), "first".to_string()));
+        send(DatabaseAction::Push(tx.clone(), "/root/queue".to_string(), "second".to_string()));
+        send(DatabaseAction::DeleteKey(tx.clone(), "/root/missing".to_string()));
+        send(DatabaseAction::Set(tx.clone(), "/root/old/name".to_string(), "demo".to_string()));
+        send(DatabaseAction::Rename(
+            tx.clone(),
+            KeyType::Table("/root/old".to_string()),
+            KeyType::Table("/root/new".to_string()),
+        ));
+        drop(tx);
+        while rx.recv().is_ok() {}
+
+        let (tx, rx) = channel();
+        send(DatabaseAction::Pop(tx, "/root/queue".to_string()));
+        rx.recv().expect("Failed to receive").expect("Failed to pop");
+
+        std::thread::sleep(std::time::Duration::new(1, 0)); // Wait some time that the async write will be finished
+
+        // Apply lines one by one
+        let content = std::fs::read_to_string(path.clone()).expect("Failed to read log");
+        let mut stream = Database::new("root".to_string()).expect("Failed to allocate database");
+        for line in content.lines() {
+            stream.apply_log_line(line).expect("Failed to apply line");
+        }
+
+        // Replay the whole file
+        let mut batch = Database::new("root".to_string()).expect("Failed to allocate database");
+        let count = batch.replay_log(&path).expect("Failed to replay");
+        assert_eq!(content.lines().count(), count);
+
+        let mut stream_snapshot = Vec::new();
+        stream.write_snapshot(&mut stream_snapshot).expect("Failed to create snapshot");
+        let mut batch_snapshot = Vec::new();
+        batch.write_snapshot(&mut batch_snapshot).expect("Failed to create snapshot");
+        assert_eq!(stream_snapshot, batch_snapshot);
+
+        // Parse the file then apply the items
+        let items = LoggerManager::replay(&path).expect("Failed to replay");
+        assert_eq!(false, items.iter().any(|x| matches!(x, LogItem::GetKey(_) | LogItem::ListKeys(_))));
+        let mut applied = Database::new("root".to_string()).expect("Failed to allocate database");
+        applied.apply_log(items).expect("Failed to apply");
+
+        let mut applied_snapshot = Vec::new();
+        applied.write_snapshot(&mut applied_snapshot).expect("Failed to create snapshot");
+        assert_eq!(batch_snapshot, applied_snapshot);
+        assert_eq!(true, LoggerManager::replay("/tmp/datastore-log-missing.txt").is_err());
+
+        assert_eq!(
+            ValueType::RecordPointer("nok".to_string()),
+            batch.get(KeyType::Record("/root/status/sub2".to_string())).unwrap()
+        );
+        assert_eq!(true, batch.get(KeyType::Record("/root/status/sub1".to_string())).is_err());
+        assert_eq!(true, batch.get(KeyType::Record("/root/network/dns".to_string())).is_err());
+        assert_eq!(true, batch.get(KeyType::Record("/root/old/name".to_string())).is_err());
+        assert_eq!(
+            ValueType::RecordPointer("demo".to_string()),
+            batch.get(KeyType::Record("/root/new/name".to_string())).unwrap()
+        );
+        assert_eq!(1, batch.queue_len(KeyType::Record("/root/queue".to_string())).unwrap());
+        assert_eq!(Some("second".to_string()), batch.pop(KeyType::Record("/root/queue".to_string())).unwrap());
+
+        // Broken line
+        let result = batch.apply_log_line("2023-05-20 10:00:00 UTC Unknown [ 'x' ]");
+        assert_eq!(true, result.is_err());
+    }
+
+    #[test]
+    fn test_log_text_escape() {
+        let path = "/tmp/datastore-log23.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+
+        let value = "it's ', ' and ] with\nnew line \\ and ' ]".to_string();
+        let items = vec![
+            LogItem::SetKey("/root/status".to_string(), value.clone()),
+            LogItem::QueuePush("/root/jobs".to_string(), "', '".to_string()),
+            LogItem::SetKey("/root/a']".to_string(), "]".to_string()),
+            LogItem::RenameKey("/root/a']".to_string(), "/root/b".to_string()),
+        ];
+
+        // Every argument is in one line and it is parsed back as it was
+        for item in &items {
+            let line = item.to_string();
+            assert_eq!(1, line.lines().count());
+            let parsed: LogItem = line.parse().expect("Failed to parse line");
+            assert_eq!(format!("{:?}", item), format!("{:?}", parsed));
+        }
+
+        let (logger_sender, logger) = start_logger(&path);
+        let (tx, rx) = get_channel_for_log_write();
+        logger_sender.send(LoggerAction::Write(tx, items.clone())).expect("Failed to send the request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive"));
+        drop(logger_sender);
+        logger.join().expect("Failed to join");
+
+        let content = std::fs::read_to_string(&path).expect("Failed to read log");
+        assert_eq!(4, content.lines().count());
+
+        let replayed = LoggerManager::replay(&path).expect("Failed to replay");
+        assert_eq!(format!("{:?}", items), format!("{:?}", replayed));
+
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        db.replay_log(&path).expect("Failed to replay");
+        assert_eq!(ValueType::RecordPointer(value), db.get(KeyType::Record("/root/status".to_string())).unwrap());
+        assert_eq!(ValueType::RecordPointer("]".to_string()), db.get(KeyType::Record("/root/b".to_string())).unwrap());
+        assert_eq!(Some("', '".to_string()), db.pop(KeyType::Record("/root/jobs".to_string())).unwrap());
+    }
+
+    #[test]
+    fn test_log_bounded_queue_replay() {
+        let path = "/tmp/datastore-log9.txt".to_string();
+        {
+            let path = Path::new(&path);
+            if path.exists() {
+                std::fs::remove_file(path).expect("Failed to delete temp log");
+            }
+        }
+
+        let (logger_sender, _) = start_logger(&path);
+        let (sender, _) = start_datastore("root".to_string(), None, Some(logger_sender));
+
+        for value in ["first", "second", "third", "fourth"] {
+            let (tx, rx) = channel();
+            let action = DatabaseAction::PushBounded(tx, "/root/queue".to_string(), value.to_string(), 2, DropPolicy::DropOldest);
+            sender.send(action).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to push");
+        }
+
+        std::thread::sleep(std::time::Duration::new(1, 0)); // Wait some time that the async write will be finished
+
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        db.replay_log(&path).expect("Failed to replay");
+
+        assert_eq!(2, db.queue_len(KeyType::Record("/root/queue".to_string())).unwrap());
+        assert_eq!(Some("third".to_string()), db.pop(KeyType::Record("/root/queue".to_string())).unwrap());
+        assert_eq!(Some("fourth".to_string()), db.pop(KeyType::Record("/root/queue".to_string())).unwrap());
+    }
+
+    #[test]
+    fn test_log_rotate_size() {
+        let path = "/tmp/datastore-log11.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+        for index in 1..10 {
+            let _ = std::fs::remove_file(format!("{}.{}", path, index));
+        }
+
+        let config = Builder::new().log_max_file_size(200).build();
+        let (sender, _) = start_logger_with_config(&path, &config);
+
+        for i in 0..10 {
+            let (tx, rx) = get_channel_for_log_write();
+            let items = vec![LogItem::SetKey(format!("/root/rotate/{}", i), "ok".to_string())];
+            sender.send(LoggerAction::Write(tx, items)).expect("Failed to send request");
+            assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive"));
+        }
+
+        // Every file is under the limit and no line is lost
+        let mut files = vec![path.clone()];
+        let mut index = 1;
+        while Path::new(&format!("{}.{}", path, index)).exists() {
+            files.push(format!("{}.{}", path, index));
+            index += 1;
+        }
+        assert_eq!(true, files.len() > 2);
+
+        let mut lines = 0;
+        for file in &files {
+            assert_eq!(true, std::fs::metadata(file).unwrap().len() <= 200);
+            lines += LoggerManager::replay(file).expect("Failed to replay").len();
+        }
+        assert_eq!(10, lines);
+    }
+
+    #[test]
+    fn test_log_rotate_daily() {
+        let path = "/tmp/datastore-log12.txt".to_string();
+        let dated = format!("{}.{}", path, chrono::Utc::now().format("%Y-%m-%d"));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&dated);
+
+        let config = Builder::new().log_rotation(LogRotation::Daily).build();
+        let (sender, _) = start_logger_with_config(&path, &config);
+
+        let (tx, rx) = get_channel_for_log_write();
+        let items = vec![LogItem::SetKey("/root/daily/1".to_string(), "ok".to_string())];
+        sender.send(LoggerAction::Write(tx, items)).expect("Failed to send request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        // Buffered lines are written into the file of the current date too
+        let (tx, rx) = get_channel_for_log_write();
+        sender.send(LoggerAction::Suspend(tx)).expect("Failed to send request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        let (tx, rx) = get_channel_for_log_write();
+        let items = vec![LogItem::SetKey("/root/daily/2".to_string(), "ok".to_string())];
+        sender.send(LoggerAction::Write(tx, items)).expect("Failed to send request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        let (tx, rx) = get_channel_for_log_write();
+        sender.send(LoggerAction::Resume(tx)).expect("Failed to send request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        assert_eq!(false, Path::new(&path).exists());
+        assert_eq!(2, LoggerManager::replay(&dated).expect("Failed to replay").len());
+    }
+
+    #[test]
+    fn test_log_json_format() {
+        let path = "/tmp/datastore-log13.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+
+        let config = Builder::new().log_format(LogFormat::Json).build();
+        let (sender, _) = start_logger_with_config(&path, &config);
+
+        let items = vec![
+            LogItem::SetKey("/root/status".to_string(), "ok".to_string()),
+            LogItem::SetHook("/root".to_string(), "http://127.0.0.1:3031".to_string()),
+            LogItem::QueueDrain("/root/queue".to_string(), 2),
+            LogItem::GetKey("/root/status".to_string()),
+            LogItem::SetHookWithHeaders(
+                "/root".to_string(),
+                "http://127.0.0.1:3031".to_string(),
+                "PUT".to_string(),
+                [("Authorization".to_string(), "Bearer token".to_string())].into_iter().collect(),
+            ),
+            LogItem::SetHookEvents("/root".to_string(), "http://127.0.0.1:3031".to_string(), vec!["set".to_string(), "delete".to_string()]),
+        ];
+        let (tx, rx) = get_channel_for_log_write();
+        sender.send(LoggerAction::Write(tx, items)).expect("Failed to send request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        let content = std::fs::read_to_string(&path).expect("Failed to read log");
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|x| serde_json::from_str(x).expect("Line is not JSON"))
+            .collect();
+        assert_eq!(6, lines.len());
+        assert_eq!("SetKey", lines[0]["action"]);
+        assert_eq!("ok", lines[0]["value"]);
+        assert_eq!(true, chrono::DateTime::parse_from_rfc3339(lines[0]["timestamp"].as_str().unwrap()).is_ok());
+        assert_eq!("http://127.0.0.1:3031", lines[1]["link"]);
+        assert_eq!(2, lines[2]["count"]);
+
+        // JSON lines can be replayed too
+        let items = LoggerManager::replay(&path).expect("Failed to replay");
+        assert_eq!(5, items.len());
+        assert_eq!("QueueDrain [ '/root/queue', '2' ]", items[2].to_string());
+        assert_eq!(
+            "SetHookWithHeaders [ '/root', 'http://127.0.0.1:3031', 'PUT', 'Authorization: Bearer token' ]",
+            items[3].to_string()
+        );
+        assert_eq!("SetHookEvents [ '/root', 'http://127.0.0.1:3031', 'set', 'delete' ]", items[4].to_string());
+    }
+
+    #[test]
+    fn test_log_read_back() {
+        for format in [LogFormat::Text, LogFormat::Json] {
+            let path = "/tmp/datastore-log14.txt".to_string();
+            let _ = std::fs::remove_file(&path);
+
+            let mut manager = LoggerManager::new(path);
+            manager.set_format(format);
+            manager.start().expect("Failed to start logger");
+            manager.write(LogItem::SetKey("/root/status".to_string(), "ok".to_string())).expect("Failed to write");
+            manager.write(LogItem::GetKey("/root/status".to_string())).expect("Failed to write");
+
+            std::thread::sleep(Duration::from_millis(20));
+            let since = chrono::Utc::now();
+            manager.write(LogItem::RemKey("/root/status".to_string())).expect("Failed to write");
+
+            // Lines in the write buffer are read too
+            let lines = manager.tail(2).expect("Failed to read");
+            assert_eq!(2, lines.len());
+            assert_eq!(true, lines[0].contains("GetKey"));
+            assert_eq!(3, manager.tail(10).expect("Failed to read").len());
+
+            let lines = manager.read_since(since).expect("Failed to read");
+            assert_eq!(1, lines.len());
+            assert_eq!(true, lines[0].0 >= since);
+            assert_eq!(true, lines[0].1.contains("RemKey"));
+
+            manager.stop().expect("Failed to stop logger");
+        }
+    }
+
+    #[test]
+    fn test_log_filter() {
+        // Datastore does not send the reads
+        let config = Builder::new().log_filter(LogFilter::mutations()).build();
+        let (logger_sender, logger_receiver) = channel();
+        let (sender, _) = start_datastore_with_config("root".to_string(), None, Some(logger_sender), &config);
+
+        let (tx, rx) = get_channel_for_set();
+        sender.send(DatabaseAction::Set(tx, "/root/status".to_string(), "ok".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to set the value");
+
+        let (tx, rx) = channel();
+        sender.send(DatabaseAction::Get(tx, "/root/status".to_string())).expect("Failed to send the request");
+        rx.recv().expect("Failed to receive").expect("Failed to get the value");
+
+        let mut actions = Vec::new();
+        while let Ok(LoggerAction::WriteAsync(items)) = logger_receiver.recv_timeout(Duration::from_millis(200)) {
+            actions.extend(items.iter().map(|x| x.action()));
+        }
+        assert_eq!(vec!["SetKey"], actions);
+
+        // Logger drops the not allowed items
+        let path = "/tmp/datastore-log15.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+
+        let mut manager = LoggerManager::new(path.clone());
+        manager.set_filter(LogFilter::Allow(["RemKey".to_string()].into_iter().collect()));
+        manager.start().expect("Failed to start logger");
+        manager.write(LogItem::SetKey("/root/status".to_string(), "ok".to_string())).expect("Failed to write");
+        manager.write(LogItem::RemKey("/root/status".to_string())).expect("Failed to write");
+        manager.stop().expect("Failed to stop logger");
+
+        let items = LoggerManager::replay(&path).expect("Failed to replay");
+        assert_eq!(1, items.len());
+        assert_eq!("RemKey", items[0].action());
+    }
+
+    #[test]
+    fn test_log_flush_policy() {
+        let path = "/tmp/datastore-log16.txt".to_string();
+        let lines_on_disk = || std::fs::read_to_string(&path).unwrap_or_default().lines().count();
+        let item = || LogItem::SetKey("/root/status".to_string(), "ok".to_string());
+
+        // Lines are written after every second write
+        let _ = std::fs::remove_file(&path);
+        let mut manager = LoggerManager::new(path.clone());
+        manager.set_flush_policy(FlushPolicy::EveryN(2));
+        manager.start().expect("Failed to start logger");
+
+        manager.write(item()).expect("Failed to write");
+        assert_eq!(0, lines_on_disk());
+        manager.write(item()).expect("Failed to write");
+        assert_eq!(2, lines_on_disk());
+        manager.write(item()).expect("Failed to write");
+        assert_eq!(2, lines_on_disk());
+
+        // Suspend flushes the remaining lines
+        manager.suspend().expect("Failed to suspend");
+        assert_eq!(3, lines_on_disk());
+
+        // Every line is written immediately
+        let _ = std::fs::remove_file(&path);
+        let mut manager = LoggerManager::new(path.clone());
+        manager.set_flush_policy(FlushPolicy::EveryWrite);
+        manager.start().expect("Failed to start logger");
+        manager.write(item()).expect("Failed to write");
+        assert_eq!(1, lines_on_disk());
+        manager.stop().expect("Failed to stop logger");
+    }
+
+    #[test]
+    fn test_log_rotate_compression() {
+        let path = "/tmp/datastore-log17.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+        for index in 1..10 {
+            let _ = std::fs::remove_file(format!("{}.{}", path, index));
+            let _ = std::fs::remove_file(format!("{}.{}.gz", path, index));
+        }
+
+        let config = Builder::new().log_max_file_size(200).log_compression(true).build();
+        let (sender, _) = start_logger_with_config(&path, &config);
+
+        for i in 0..6 {
+            let (tx, rx) = get_channel_for_log_write();
+            let items = vec![LogItem::SetKey(format!("/root/gzip/{}", i), "ok".to_string())];
+            sender.send(LoggerAction::Write(tx, items)).expect("Failed to send request");
+            assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive"));
+        }
+
+        // Compression runs in background
+        let compressed = format!("{}.1.gz", path);
+        for _ in 0..40 {
+            if !Path::new(&format!("{}.1", path)).exists() && !Path::new(&format!("{}.2", path)).exists() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(false, Path::new(&format!("{}.1", path)).exists());
+        assert_eq!(true, Path::new(&compressed).exists());
+
+        let mut content = String::new();
+        let file = std::fs::File::open(&compressed).expect("Failed to open compressed file");
+        flate2::read::GzDecoder::new(file).read_to_string(&mut content).expect("Failed to decompress");
+        assert_eq!(true, content.contains("SetKey [ '/root/gzip/0', 'ok' ]"));
+    }
+
+    #[test]
+    fn test_log_timestamp_format() {
+        let path = "/tmp/datastore-log18.txt".to_string();
+        let _ = std::fs::remove_file(&path);
+
+        // RFC3339 by default, in direct write and after resume too
+        let mut manager = LoggerManager::new(path.clone());
+        manager.start().expect("Failed to start logger");
+        manager.write(LogItem::SetKey("/root/status".to_string(), "ok".to_string())).expect("Failed to write");
+        manager.suspend().expect("Failed to suspend");
+        manager.write(LogItem::RemKey("/root/status".to_string())).expect("Failed to write");
+        manager.resume().expect("Failed to resume");
+
+        let content = std::fs::read_to_string(&path).expect("Failed to read log");
+        for line in content.lines() {
+            let timestamp = line.split(' ').next().unwrap();
+            assert_eq!(true, chrono::DateTime::parse_from_rfc3339(timestamp).is_ok());
+        }
+        assert_eq!(2, LoggerManager::replay(&path).expect("Failed to replay").len());
+
+        // Custom format is used and parsed back
+        let _ = std::fs::remove_file(&path);
+        let config = Builder::new().log_timestamp_format("%Y%m%d-%H%M%S%.3f".to_string()).build();
+        let (sender, _) = start_logger_with_config(&path, &config);
+        let (tx, rx) = get_channel_for_log_write();
+        let items = vec![LogItem::SetKey("/root/status".to_string(), "ok".to_string())];
+        sender.send(LoggerAction::Write(tx, items)).expect("Failed to send request");
+        assert_eq!(LoggerResponse::Ok, rx.recv().expect("Failed to receive"));
+
+        let content = std::fs::read_to_string(&path).expect("Failed to read log");
+        let timestamp = content.split(' ').next().unwrap();
+        assert_eq!(true, chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%d-%H%M%S%.3f").is_ok());
+
+        // Formats that write space or delimiter are rejected
+        for format in ["%Y-%m-%d %H:%M:%S", "[%Y%m%d]", "%c", "'%s'"] {
+            let config = Builder::new().log_timestamp_format(format.to_string()).build();
+            assert_eq!(crate::logger::DEFAULT_TIMESTAMP_FORMAT, config.log_timestamp_format());
+        }
+
+        let mut manager = LoggerManager::new(path.clone());
+        manager.set_timestamp_format("%Y%m%d-%H%M%S%.3f".to_string()).expect("Invalid format");
+        let since = chrono::Utc::now() - chrono::Duration::minutes(1);
+        assert_eq!(1, manager.read_since(since).expect("Failed to read").len());
+    }
+
+    #[test]
+    fn test_log_typed_values() {
+        for (index, format) in [LogFormat::Text, LogFormat::Json].into_iter().enumerate() {
+            let path = format!("/tmp/datastore-log{}.txt", 25 + index);
+            let _ = std::fs::remove_file(&path);
+
+            let config = Builder::new().logger_path(path.clone()).log_format(format).build();
+            let (sender, handles) = config.start("root".to_string());
+            let client = crate::datastore::client::DatastoreClient::new(sender.clone());
+
+            let json = serde_json::json!({ "port": 53, "key": { "$bytes": "AP8Q" }, "name": "demo" });
+            let (tx, rx) = channel();
+            sender.send(DatabaseAction::ImportJson(tx, "/root/stats".to_string(), json)).expect("Failed to send the request");
+            assert_eq!(3, rx.recv().expect("Failed to receive").expect("Failed to import"));
+
+            assert_eq!(54, client.increment("/root/stats/port".to_string(), 1).expect("Failed to increment"));
+            let swapped = client.compare_and_swap("/root/stats/port".to_string(), Some("54".to_string()), "80".to_string());
+            assert_eq!(true, swapped.expect("Failed to swap"));
+
+            let (tx, rx) = get_channel_for_set();
+            sender.send(DatabaseAction::Shutdown(tx)).expect("Failed to send the request");
+            rx.recv().expect("Failed to receive").expect("Failed to shutdown");
+            handles.datastore.join().expect("Failed to join");
+
+            let (logger_sender, logger) = handles.logger.expect("Logger is not started");
+            drop(logger_sender);
+            logger.join().expect("Failed to join");
+
+            // Replay restores the same types
+            let items = LoggerManager::replay(&path).expect("Failed to replay");
+            assert_eq!(true, items.iter().any(|x| x.to_string() == "SetBytes [ '/root/stats/key', 'AP8Q' ]"));
+            assert_eq!(true, items.iter().any(|x| x.to_string() == "SetInt [ '/root/stats/port', '80' ]"));
+
+            let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+            db.replay_log(&path).expect("Failed to replay");
+            assert_eq!(ValueType::IntPointer(80), db.get(KeyType::record("/root/stats/port")).expect("Key not found"));
+            assert_eq!(ValueType::BytesPointer(vec![0x00, 0xff, 0x10]), db.get(KeyType::record("/root/stats/key")).expect("Key not found"));
+            assert_eq!(ValueType::RecordPointer("demo".to_string()), db.get(KeyType::record("/root/stats/name")).expect("Key not found"));
+        }
+
+        let mut db = Database::new("root".to_string()).expect("Failed to allocate database");
+        assert_eq!(true, db.apply_log_line("2023-05-20 10:00:00 UTC SetInt [ '/root/port', 'x' ]").is_err());
+        assert_eq!(true, db.apply_log_line("2023-05-20 10:00:00 UTC SetBytes [ '/root/key', '!' ]").is_err());
+    }
 }
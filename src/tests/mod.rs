@@ -1,3 +1,6 @@
+// Tests assert booleans as `assert_eq!(true, ...)` on purpose, the expected value is always on the left
+#![allow(clippy::bool_assert_comparison)]
+
 mod hook_manager;
 mod datastore;
 mod logger;
\ No newline at end of file